@@ -190,7 +190,9 @@ final class MetadataCache: ObservableObject {
                         isNullable: col.isNullable,
                         isPrimaryKey: col.isPrimaryKey,
                         ordinalPosition: col.ordinalPosition,
-                        columnDefault: col.columnDefault
+                        columnDefault: col.columnDefault,
+                        defaultKind: col.defaultKind,
+                        isGenerated: col.isGenerated
                     )
                     byTable[col.tableName, default: []].append(info)
                 }
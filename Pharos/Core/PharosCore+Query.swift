@@ -24,7 +24,8 @@ extension PharosCore {
         queryId: String? = nil,
         limit: Int32 = 1000,
         schema: String? = nil,
-        source: String? = nil
+        source: String? = nil,
+        runAsRole: String? = nil
     ) async throws -> QueryResult {
         return try await withAsyncCallback { callback, context in
             connectionId.withCString { cConn in
@@ -32,7 +33,9 @@ extension PharosCore {
                     withOptionalCString(queryId) { cQid in
                         withOptionalCString(schema) { cSchema in
                             withOptionalCString(source) { cSource in
-                                pharos_execute_query(cConn, cSql, cQid, limit, cSchema, cSource, callback, context)
+                                withOptionalCString(runAsRole) { cRole in
+                                    pharos_execute_query(cConn, cSql, cQid, limit, cSchema, cSource, cRole, callback, context)
+                                }
                             }
                         }
                     }
@@ -73,6 +76,25 @@ extension PharosCore {
         }
     }
 
+    /// The SQL `fetchMoreRows` would run for a page; throws on a negative or oversized limit/offset.
+    static func previewPaginationSQL(sql: String, limit: Int64, offset: Int64) throws -> String {
+        guard let ptr = sql.withCString({ pharos_preview_pagination_sql($0, limit, offset) }) else {
+            throw PharosCoreError.nullResult
+        }
+        defer { pharos_free_string(ptr) }
+        let json = String(cString: ptr)
+        guard let dict = try? JSONSerialization.jsonObject(with: Data(json.utf8)) as? [String: Any] else {
+            throw PharosCoreError.rustError("Unexpected result: \(json)")
+        }
+        if let errorMsg = dict["error"] as? String {
+            throw PharosCoreError.rustError(errorMsg)
+        }
+        guard let wrapped = dict["sql"] as? String else {
+            throw PharosCoreError.rustError("Unexpected result: \(json)")
+        }
+        return wrapped
+    }
+
     /// Cancel a running query.
     static func cancelQuery(connectionId: String, queryId: String) async throws -> Bool {
         let result: String = try await withAsyncCallback { callback, context in
@@ -85,6 +107,24 @@ extension PharosCore {
         return result == "true"
     }
 
+    /// Override the statement timeout for this connection until disconnect; nil restores the setting.
+    static func setSessionTimeout(connectionId: String, seconds: UInt32?) async throws -> SessionTimeout {
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                pharos_set_session_timeout(cConn, seconds.map { Int64($0) } ?? -1, callback, context)
+            }
+        }
+    }
+
+    /// The statement timeout queries on this connection run with.
+    static func getSessionTimeout(connectionId: String) async throws -> SessionTimeout {
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                pharos_get_session_timeout(cConn, callback, context)
+            }
+        }
+    }
+
     /// Validate SQL syntax.
     static func validateSQL(connectionId: String, sql: String, schema: String? = nil) async throws -> ValidationResult {
         return try await withAsyncCallback { callback, context in
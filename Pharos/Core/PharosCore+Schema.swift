@@ -5,11 +5,11 @@ import CPharosCore
 
 extension PharosCore {
 
-    /// Get schemas for a connection.
-    static func getSchemas(connectionId: String) async throws -> [SchemaInfo] {
+    /// Get schemas for a connection. System schemas are excluded unless `includeSystem`.
+    static func getSchemas(connectionId: String, includeSystem: Bool = false) async throws -> [SchemaInfo] {
         return try await withAsyncCallback { callback, context in
             connectionId.withCString { cConn in
-                pharos_get_schemas(cConn, callback, context)
+                pharos_get_schemas(cConn, includeSystem, callback, context)
             }
         }
     }
@@ -84,6 +84,33 @@ extension PharosCore {
         }
     }
 
+    /// Refresh a materialized view; returns the elapsed milliseconds.
+    static func refreshMaterializedView(connectionId: String, schema: String, view: String, concurrently: Bool) async throws -> UInt64 {
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                schema.withCString { cSchema in
+                    view.withCString { cView in
+                        pharos_refresh_materialized_view(cConn, cSchema, cView, concurrently, callback, context)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Tables and columns for autocompletion; `includeDetails` adds column types and comments.
+    static func getCompletionMetadata(connectionId: String, includeDetails: Bool = false) async throws -> [CompletionTable] {
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                pharos_get_completion_metadata(cConn, includeDetails, callback, context)
+            }
+        }
+    }
+
+    /// Drop the cached autocompletion metadata, e.g. after DDL.
+    static func clearCompletionMetadata(connectionId: String) {
+        connectionId.withCString { pharos_clear_completion_metadata($0) }
+    }
+
     /// Get functions for a schema.
     static func getSchemaFunctions(connectionId: String, schema: String) async throws -> [FunctionInfo] {
         return try await withAsyncCallback { callback, context in
@@ -94,4 +121,26 @@ extension PharosCore {
             }
         }
     }
+
+    /// Get sequences for a schema.
+    static func getSequences(connectionId: String, schema: String) async throws -> [SequenceInfo] {
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                schema.withCString { cSchema in
+                    pharos_get_sequences(cConn, cSchema, callback, context)
+                }
+            }
+        }
+    }
+
+    /// Estimate table and index bloat for a schema, largest waste first.
+    static func getBloatEstimate(connectionId: String, schema: String) async throws -> [BloatEstimate] {
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                schema.withCString { cSchema in
+                    pharos_get_bloat_estimate(cConn, cSchema, callback, context)
+                }
+            }
+        }
+    }
 }
@@ -14,4 +14,22 @@ extension PharosCore {
     static func saveSettings(_ settings: AppSettings) throws {
         try callSyncVoid(input: settings) { pharos_save_settings($0) }
     }
+
+    /// Write the keyboard shortcuts to a JSON file. Returns how many were written.
+    static func exportKeymap(filePath: String) async throws -> Int {
+        return try await withAsyncCallback { callback, context in
+            filePath.withCString { cPath in
+                pharos_export_keymap(cPath, callback, context)
+            }
+        }
+    }
+
+    /// Merge a keymap file over the current shortcuts and save them.
+    static func importKeymap(filePath: String) async throws -> KeymapImportResult {
+        return try await withAsyncCallback { callback, context in
+            filePath.withCString { cPath in
+                pharos_import_keymap(cPath, callback, context)
+            }
+        }
+    }
 }
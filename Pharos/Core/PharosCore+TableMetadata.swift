@@ -31,6 +31,19 @@ extension PharosCore {
         }
     }
 
+    /// Get user-defined triggers for a table.
+    static func getTableTriggers(connectionId: String, schema: String, table: String) async throws -> [TriggerInfo] {
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                schema.withCString { cSchema in
+                    table.withCString { cTable in
+                        pharos_get_table_triggers(cConn, cSchema, cTable, callback, context)
+                    }
+                }
+            }
+        }
+    }
+
     /// Generate reconstructed CREATE TABLE DDL (three detail variants) for a table.
     static func generateTableDDL(connectionId: String, schema: String, table: String) async throws -> TableDDL {
         return try await withAsyncCallback { callback, context in
@@ -43,4 +56,17 @@ extension PharosCore {
             }
         }
     }
+
+    /// Generate CREATE VIEW (or CREATE MATERIALIZED VIEW) DDL for a view.
+    static func generateViewDDL(connectionId: String, schema: String, view: String) async throws -> String {
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                schema.withCString { cSchema in
+                    view.withCString { cView in
+                        pharos_generate_view_ddl(cConn, cSchema, cView, callback, context)
+                    }
+                }
+            }
+        }
+    }
 }
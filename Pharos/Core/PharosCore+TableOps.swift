@@ -18,6 +18,24 @@ extension PharosCore {
         }
     }
 
+    /// Copy a table (and optionally its data) from one connection to another.
+    static func copyTableAcrossConnections(
+        sourceConnectionId: String,
+        targetConnectionId: String,
+        options: CloneTableOptions
+    ) async throws -> CopyTableResult {
+        let jsonStr = String(decoding: try JSONEncoder.pharos.encode(options), as: UTF8.self)
+        return try await withAsyncCallback { callback, context in
+            sourceConnectionId.withCString { cSource in
+                targetConnectionId.withCString { cTarget in
+                    jsonStr.withCString { cJson in
+                        pharos_copy_table_across_connections(cSource, cTarget, cJson, callback, context)
+                    }
+                }
+            }
+        }
+    }
+
     /// Export table data to a file.
     static func exportTable(connectionId: String, options: ExportTableOptions) async throws -> ExportTableResult {
         let json = try JSONEncoder.pharos.encode(options)
@@ -31,6 +49,19 @@ extension PharosCore {
         }
     }
 
+    /// Export a table as INSERT statements.
+    static func exportSql(connectionId: String, options: ExportSqlOptions) async throws -> ExportTableResult {
+        let json = try JSONEncoder.pharos.encode(options)
+        let jsonStr = String(decoding: json, as: UTF8.self)
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                jsonStr.withCString { cJson in
+                    pharos_export_sql(cConn, cJson, callback, context)
+                }
+            }
+        }
+    }
+
     /// Import CSV data into a table.
     static func importCsv(connectionId: String, options: ImportCsvOptions) async throws -> ImportCsvResult {
         let json = try JSONEncoder.pharos.encode(options)
@@ -44,6 +75,30 @@ extension PharosCore {
         }
     }
 
+    /// Try a column type change in a rolled-back transaction to surface cast errors.
+    static func previewAlterColumnType(connectionId: String, options: AlterColumnTypeOptions) async throws -> AlterColumnTypePreview {
+        let jsonStr = String(decoding: try JSONEncoder.pharos.encode(options), as: UTF8.self)
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                jsonStr.withCString { cJson in
+                    pharos_preview_alter_column_type(cConn, cJson, callback, context)
+                }
+            }
+        }
+    }
+
+    /// Change a column's type. Returns the executed statement.
+    static func alterColumnType(connectionId: String, options: AlterColumnTypeOptions) async throws -> String {
+        let jsonStr = String(decoding: try JSONEncoder.pharos.encode(options), as: UTF8.self)
+        return try await withAsyncCallback { callback, context in
+            connectionId.withCString { cConn in
+                jsonStr.withCString { cJson in
+                    pharos_alter_column_type(cConn, cJson, callback, context)
+                }
+            }
+        }
+    }
+
     /// Read live row count for an in-progress import. Returns nil if no active import.
     static func getImportProgress(connectionId: String, schema: String, table: String) -> Int64? {
         let key = "\(connectionId)|\(schema)|\(table)"
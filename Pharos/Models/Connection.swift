@@ -8,6 +8,45 @@ enum SslMode: String, Codable {
     case require
 }
 
+enum SshAuthMethod: String, Codable {
+    case privateKey
+    case password
+}
+
+struct SshTunnelConfig: Codable, Equatable {
+    var host: String
+    var port: UInt16 = 22
+    var username: String
+    var authMethod: SshAuthMethod = .privateKey
+    var privateKeyPath: String?
+    /// SSH password or key passphrase; kept in the keychain by the core.
+    var password: String = ""
+
+    init(from decoder: Decoder) throws {
+        let c = try decoder.container(keyedBy: CodingKeys.self)
+        host = try c.decode(String.self, forKey: .host)
+        port = try c.decodeIfPresent(UInt16.self, forKey: .port) ?? 22
+        username = try c.decode(String.self, forKey: .username)
+        authMethod = try c.decodeIfPresent(SshAuthMethod.self, forKey: .authMethod) ?? .privateKey
+        privateKeyPath = try c.decodeIfPresent(String.self, forKey: .privateKeyPath)
+        password = try c.decodeIfPresent(String.self, forKey: .password) ?? ""
+    }
+
+    init(host: String, port: UInt16 = 22, username: String, authMethod: SshAuthMethod = .privateKey,
+         privateKeyPath: String? = nil, password: String = "") {
+        self.host = host
+        self.port = port
+        self.username = username
+        self.authMethod = authMethod
+        self.privateKeyPath = privateKeyPath
+        self.password = password
+    }
+
+    private enum CodingKeys: String, CodingKey {
+        case host, port, username, authMethod, privateKeyPath, password
+    }
+}
+
 struct ConnectionConfig: Codable, Identifiable {
     var id: String
     var name: String
@@ -19,6 +58,14 @@ struct ConnectionConfig: Codable, Identifiable {
     var sslMode: SslMode = .prefer
     var color: String?
     var defaultSchema: String?
+    /// Never record queries run on this connection in history.
+    var disableHistory: Bool = false
+    /// Column-name globs whose values are shown as `****` in results.
+    var maskedColumns: [String] = []
+    /// Free-form labels for filtering the connection list.
+    var labels: [String] = []
+    /// Reach the server through an SSH bastion.
+    var sshTunnel: SshTunnelConfig? = nil
 
     // Custom decoder: Rust skips "password" when empty and "color" when nil,
     // so these keys may be absent in the JSON.
@@ -34,11 +81,16 @@ struct ConnectionConfig: Codable, Identifiable {
         sslMode = try c.decodeIfPresent(SslMode.self, forKey: .sslMode) ?? .prefer
         color = try c.decodeIfPresent(String.self, forKey: .color)
         defaultSchema = try c.decodeIfPresent(String.self, forKey: .defaultSchema)
+        disableHistory = try c.decodeIfPresent(Bool.self, forKey: .disableHistory) ?? false
+        maskedColumns = try c.decodeIfPresent([String].self, forKey: .maskedColumns) ?? []
+        labels = try c.decodeIfPresent([String].self, forKey: .labels) ?? []
+        sshTunnel = try c.decodeIfPresent(SshTunnelConfig.self, forKey: .sshTunnel)
     }
 
     init(id: String, name: String, host: String, port: UInt16, database: String,
          username: String, password: String = "", sslMode: SslMode = .prefer,
-         color: String? = nil, defaultSchema: String? = nil) {
+         color: String? = nil, defaultSchema: String? = nil, disableHistory: Bool = false,
+         maskedColumns: [String] = [], labels: [String] = [], sshTunnel: SshTunnelConfig? = nil) {
         self.id = id
         self.name = name
         self.host = host
@@ -49,10 +101,14 @@ struct ConnectionConfig: Codable, Identifiable {
         self.sslMode = sslMode
         self.color = color
         self.defaultSchema = defaultSchema
+        self.disableHistory = disableHistory
+        self.maskedColumns = maskedColumns
+        self.labels = labels
+        self.sshTunnel = sshTunnel
     }
 
     private enum CodingKeys: String, CodingKey {
-        case id, name, host, port, database, username, password, sslMode, color, defaultSchema
+        case id, name, host, port, database, username, password, sslMode, color, defaultSchema, disableHistory, maskedColumns, labels, sshTunnel
     }
 }
 
@@ -74,10 +130,13 @@ struct ConnectionInfo: Codable {
     let status: ConnectionStatus
     let error: String?
     let latencyMs: UInt64?
+    /// server_version, e.g. "16.2"; nil if the server didn't report one.
+    var serverVersion: String? = nil
 
     enum CodingKeys: String, CodingKey {
         case id, name, host, port, database, status, error
         case latencyMs = "latency_ms"
+        case serverVersion = "server_version"
     }
 }
 
@@ -85,9 +144,11 @@ struct TestConnectionResult: Codable {
     let success: Bool
     let latencyMs: UInt64?
     let error: String?
+    var serverVersion: String? = nil
 
     enum CodingKeys: String, CodingKey {
         case success, error
         case latencyMs = "latency_ms"
+        case serverVersion = "server_version"
     }
 }
@@ -17,9 +17,14 @@ struct QueryResult: Codable {
     let executionTimeMs: UInt64
     let hasMore: Bool
     let historyEntryId: String?
+    /// NOTICE/WARNING messages raised while the query ran; absent when there were none.
+    var notices: [String]? = nil
+    /// Changes when a re-run could see different data; nil if the server has no snapshot.
+    var snapshotId: String? = nil
 
     enum CodingKeys: String, CodingKey {
-        case columns, rows
+        case columns, rows, notices
+        case snapshotId = "snapshot_id"
         case rowCount = "row_count"
         case executionTimeMs = "execution_time_ms"
         case hasMore = "has_more"
@@ -32,14 +37,25 @@ struct ExecuteResult: Codable {
     let executionTimeMs: UInt64
     /// History entry id for this statement, so it can be associated with a workspace.
     let historyEntryId: String?
+    /// NOTICE/WARNING messages raised while the statement ran; absent when there were none.
+    var notices: [String]? = nil
 
     enum CodingKeys: String, CodingKey {
+        case notices
         case rowsAffected = "rows_affected"
         case executionTimeMs = "execution_time_ms"
         case historyEntryId = "history_entry_id"
     }
 }
 
+/// A connection's effective statement timeout.
+struct SessionTimeout: Codable {
+    /// 0 means no timeout.
+    let timeoutSeconds: UInt32
+    /// Set for this session rather than taken from the query settings.
+    let isOverride: Bool
+}
+
 struct ValidationResult: Codable {
     let valid: Bool
     let error: ValidationError?
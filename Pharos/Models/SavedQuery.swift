@@ -9,6 +9,8 @@ struct SavedQuery: Codable, Identifiable {
     var variables: String?
     let createdAt: String
     let updatedAt: String
+    /// Result grid layout JSON (column order, hidden columns, widths); nil for the default.
+    var columnLayout: String?
     // Rust uses #[serde(rename_all = "camelCase")] — Swift property names match directly
 }
 
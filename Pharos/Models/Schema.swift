@@ -21,6 +21,13 @@ enum PartitionStrategy: String, Codable {
     var badgeLabel: String { rawValue.uppercased() }
 }
 
+/// pg_class.relpersistence: unlogged tables are truncated after a crash.
+enum TablePersistence: String, Codable {
+    case logged
+    case unlogged
+    case temporary
+}
+
 struct TableInfo: Codable {
     let name: String
     let schemaName: String
@@ -34,11 +41,13 @@ struct TableInfo: Codable {
     let partitionKey: String?       // raw pg_get_partkeydef, e.g. "RANGE (created_at)"
     let partitionBound: String?     // pg_get_expr(relpartbound) or "DEFAULT"
     let partitionCount: Int64?
+    let persistence: TablePersistence
     // Rust uses #[serde(rename_all = "camelCase")] — Swift property names match directly
 
     enum CodingKeys: String, CodingKey {
         case name, schemaName, tableType, rowCountEstimate, totalSizeBytes
         case isPartitioned, isPartition, partitionStrategy, partitionKey, partitionBound, partitionCount
+        case persistence
     }
 
     init(from decoder: Decoder) throws {
@@ -57,6 +66,8 @@ struct TableInfo: Codable {
         partitionKey = try c.decodeIfPresent(String.self, forKey: .partitionKey)
         partitionBound = try c.decodeIfPresent(String.self, forKey: .partitionBound)
         partitionCount = try c.decodeIfPresent(Int64.self, forKey: .partitionCount)
+        persistence = (try c.decodeIfPresent(String.self, forKey: .persistence))
+            .flatMap(TablePersistence.init(rawValue:)) ?? .logged
     }
 
     /// Memberwise init for tests / in-code construction.
@@ -64,12 +75,14 @@ struct TableInfo: Codable {
          rowCountEstimate: Int64?, totalSizeBytes: Int64?,
          isPartitioned: Bool = false, isPartition: Bool = false,
          partitionStrategy: PartitionStrategy? = nil, partitionKey: String? = nil,
-         partitionBound: String? = nil, partitionCount: Int64? = nil) {
+         partitionBound: String? = nil, partitionCount: Int64? = nil,
+         persistence: TablePersistence = .logged) {
         self.name = name; self.schemaName = schemaName; self.tableType = tableType
         self.rowCountEstimate = rowCountEstimate; self.totalSizeBytes = totalSizeBytes
         self.isPartitioned = isPartitioned; self.isPartition = isPartition
         self.partitionStrategy = partitionStrategy; self.partitionKey = partitionKey
         self.partitionBound = partitionBound; self.partitionCount = partitionCount
+        self.persistence = persistence
     }
 }
 
@@ -78,6 +91,37 @@ struct PartitionRef: Codable {
     let name: String
 }
 
+struct InheritedTable: Codable {
+    let schemaName: String
+    let name: String
+    let isPartition: Bool
+}
+
+struct TableInheritance: Codable {
+    let parents: [InheritedTable]
+    let children: [InheritedTable]
+}
+
+struct RlsPolicyInfo: Codable {
+    let name: String
+    let command: String
+    let permissive: Bool
+    let roles: [String]
+    let usingExpression: String?
+    let withCheckExpression: String?
+}
+
+struct TableRlsPolicies: Codable {
+    let rlsEnabled: Bool
+    let rlsForced: Bool
+    let policies: [RlsPolicyInfo]
+}
+
+struct TableStorageParam: Codable {
+    let name: String
+    let value: String
+}
+
 struct AnalyzeResult: Codable {
     let hadUnanalyzed: Bool
     let permissionDeniedTables: [String]
@@ -86,6 +130,14 @@ struct AnalyzeResult: Codable {
     let tables: [TableInfo]
 }
 
+/// What a column's default does: whether a new row can leave it blank.
+enum ColumnDefaultKind: String, Codable {
+    case none
+    case sequence
+    case function
+    case literal
+}
+
 struct ColumnInfo: Codable {
     let name: String
     let dataType: String
@@ -93,6 +145,9 @@ struct ColumnInfo: Codable {
     let isPrimaryKey: Bool
     let ordinalPosition: Int32
     let columnDefault: String?
+    let defaultKind: ColumnDefaultKind
+    /// GENERATED ALWAYS AS (...) STORED; can't be inserted into.
+    var isGenerated: Bool = false
 }
 
 struct SchemaColumnInfo: Codable {
@@ -103,6 +158,9 @@ struct SchemaColumnInfo: Codable {
     let isPrimaryKey: Bool
     let ordinalPosition: Int32
     let columnDefault: String?
+    let defaultKind: ColumnDefaultKind
+    /// GENERATED ALWAYS AS (...) STORED; can't be inserted into.
+    var isGenerated: Bool = false
 }
 
 struct IndexInfo: Codable {
@@ -123,6 +181,32 @@ struct ConstraintInfo: Codable {
     let checkClause: String?
 }
 
+struct TriggerInfo: Codable {
+    let name: String
+    /// BEFORE, AFTER or INSTEAD OF.
+    let timing: String
+    let events: [String]
+    /// ROW or STATEMENT.
+    let level: String
+    let functionName: String
+    let enabled: Bool
+    let definition: String
+}
+
+/// A table and its columns for editor autocompletion.
+struct CompletionTable: Codable {
+    let schemaName: String
+    let name: String
+    let columns: [CompletionColumn]
+}
+
+/// Type and comment are only present when details were requested.
+struct CompletionColumn: Codable {
+    let name: String
+    let dataType: String?
+    let comment: String?
+}
+
 struct FunctionInfo: Codable {
     let name: String
     let schemaName: String
@@ -132,6 +216,39 @@ struct FunctionInfo: Codable {
     let language: String
 }
 
+enum BloatObjectKind: String, Codable {
+    case table
+    case index
+}
+
+struct BloatEstimate: Codable {
+    let kind: BloatObjectKind
+    let schemaName: String
+    let name: String
+    /// The table itself, or the index's table.
+    let tableName: String
+    let realSizeBytes: Int64
+    let bloatBytes: Int64
+    let bloatPercent: Double
+    /// False when stats are missing; the numbers are then only a guess.
+    let reliable: Bool
+}
+
+struct SequenceInfo: Codable {
+    let name: String
+    let schemaName: String
+    let dataType: String
+    let startValue: Int64
+    let increment: Int64
+    let minValue: Int64
+    let maxValue: Int64
+    /// nil until nextval has been called.
+    let lastValue: Int64?
+    let cycle: Bool
+    /// "table.column" for serial/identity sequences.
+    let ownedBy: String?
+}
+
 // MARK: - Table Operations
 
 enum ExportFormat: String, Codable, CaseIterable {
@@ -181,6 +298,13 @@ struct CloneTableResult: Codable {
     let rowsCopied: Int64?
 }
 
+struct CopyTableResult: Codable {
+    let success: Bool
+    let rowsCopied: Int64?
+    /// Columns created as text because their type doesn't exist on the target.
+    let columnsAsText: [String]
+}
+
 struct ExportTableOptions: Codable {
     let schemaName: String
     let tableName: String
@@ -189,6 +313,30 @@ struct ExportTableOptions: Codable {
     let nullAsEmpty: Bool
     let filePath: String
     let format: ExportFormat
+    /// Order for exports with no explicit `columns`; explicit columns keep their order.
+    var columnOrder: ExportColumnOrder = .ordinal
+    /// Match `COPY ... TO` output exactly (CSV and TSV only).
+    var copyCompatible = false
+    /// Keep generated columns in SQL INSERT exports (they'd make the INSERTs fail).
+    var includeGeneratedColumns = false
+}
+
+struct ExportSqlOptions: Codable {
+    let schemaName: String
+    let tableName: String
+    /// Empty exports every column.
+    var columns: [String] = []
+    let filePath: String
+    /// Rows per INSERT statement; 0 or 1 writes one statement per row.
+    var rowsPerInsert: UInt32 = 1
+    /// Start the file with the table's CREATE TABLE DDL.
+    var includeCreateTable = false
+    var includeGeneratedColumns = false
+}
+
+enum ExportColumnOrder: String, Codable {
+    case ordinal = "ordinal"
+    case alphabetical = "alphabetical"
 }
 
 struct ExportTableResult: Codable {
@@ -201,9 +349,41 @@ struct ImportCsvOptions: Codable {
     let tableName: String
     let filePath: String
     let hasHeaders: Bool
+    /// Field text imported as NULL; when set, empty fields import as empty strings.
+    var nullMarker: String? = nil
+    /// Strip leading/trailing whitespace from fields; the null marker is matched after trimming.
+    var trimWhitespace: Bool = false
+    /// With trimWhitespace, import whitespace-only fields as NULL instead of empty strings.
+    var trimmedEmptyAsNull: Bool = false
+    /// Encoding of fields destined for bytea columns.
+    var byteaEncoding: ByteaEncoding = .text
+    /// Insert row by row instead of streaming the file through COPY.
+    var perRowInsert: Bool = false
+}
+
+enum ByteaEncoding: String, Codable {
+    case text
+    case hex
+    case base64
 }
 
 struct ImportCsvResult: Codable {
     let success: Bool
     let rowsImported: UInt64
 }
+
+struct AlterColumnTypeOptions: Codable {
+    let schemaName: String
+    let tableName: String
+    let columnName: String
+    /// Whitelisted base type with optional modifiers and [] suffixes, e.g. "numeric(12, 2)".
+    let newType: String
+    /// Expression computing the new value from the old, for USING.
+    var usingExpr: String? = nil
+}
+
+struct AlterColumnTypePreview: Codable {
+    let sql: String
+    let success: Bool
+    let error: String?
+}
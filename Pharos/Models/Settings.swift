@@ -77,6 +77,28 @@ struct QuerySettings: Codable, Equatable {
     var notifyWhenAppInactive: Bool = true
     var notifyWhenBackgroundTab: Bool = true
     var notifyMinDurationSeconds: UInt32 = 5
+    /// Hard ceiling on rows per fetch; larger requested limits are clamped by the core.
+    var maxResultRows: UInt32 = 1_000_000
+    /// Show PostGIS geometry/geography values as GeoJSON (requires the extension).
+    var decodePostgisGeometry: Bool = false
+    /// Record executed queries in history (connections can also opt out individually).
+    var historyEnabled: Bool = true
+    /// Rows per cursor FETCH for SELECT-like queries; 0 streams the result directly.
+    var fetchSize: UInt32 = 0
+    /// Minutes without activity before a connection is closed; 0 never closes it.
+    var idleDisconnectMinutes: UInt32 = 0
+    /// Largest estimated row count a guarded UPDATE/DELETE may touch without forcing it.
+    var rowcountGuardMaxRows: UInt64 = 1000
+    /// Date/time rendering: nil keeps the defaults, "iso8601" for ISO-8601, or a strftime pattern.
+    var datetimeFormat: String? = nil
+    /// NULL text in CSV/TSV and Markdown exports; nil keeps the defaults.
+    var exportNullDisplay: String? = nil
+    /// Connection attempts (connects and tests) allowed in flight at once.
+    var maxConcurrentConnects: UInt32 = 8
+    /// Skip the per-query SET search_path when the session already uses that schema.
+    var skipRedundantSearchPath: Bool = true
+    /// Extra attempts to get a connection when the pool is exhausted.
+    var acquireRetries: UInt32 = 2
 }
 
 struct UISettings: Codable, Equatable {
@@ -98,6 +120,18 @@ struct KeyboardSettings: Codable, Equatable {
     var shortcuts: [KeyboardShortcut] = []
 }
 
+/// An imported shortcut skipped because its key combination was already taken.
+struct KeymapConflict: Codable {
+    let shortcutId: String
+    let conflictsWith: String
+    let binding: String
+}
+
+struct KeymapImportResult: Codable {
+    let imported: Int
+    let conflicts: [KeymapConflict]
+}
+
 struct ChartSettings: Codable, Equatable {
     var palette: [String] = ChartPalette.defaultHex
 }
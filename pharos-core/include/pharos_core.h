@@ -8,6 +8,11 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * How many plans are kept per (connection, query).
+ */
+#define MAX_STORED_PLANS 5
+
 /**
  * Callback invoked when an async operation completes.
  * - `context`: opaque pointer passed through from the caller (e.g. Swift continuation)
@@ -26,6 +31,11 @@ typedef void (*AsyncCallback)(void *context, const char *result_json, const char
  */
  char *pharos_load_connections(void);
 
+/**
+ * Load saved connections that carry `label` (case-insensitive). Returns JSON array.
+ */
+ char *pharos_filter_connections_by_label(const char *label);
+
 /**
  * Save a connection config. `json` is a JSON-encoded ConnectionConfig.
  * Returns NULL on success, or an error message string (caller must free).
@@ -48,6 +58,31 @@ typedef void (*AsyncCallback)(void *context, const char *result_json, const char
  */
  void pharos_connect(const char *connection_id, AsyncCallback callback, void *context);
 
+/**
+ * Connect to another database on the server of `connection_id`. The derived
+ * connection is only saved when `persist` is true. Returns JSON ConnectionInfo via callback.
+ */
+
+void pharos_connect_to_database(const char *connection_id,
+                                const char *database,
+                                bool persist,
+                                AsyncCallback callback,
+                                void *context);
+
+/**
+ * Connections closed for inactivity since the last call, as a JSON array of
+ * connection IDs. Poll this to mirror idle disconnects in the UI. Caller must free.
+ */
+ char *pharos_take_idle_disconnected(void);
+
+/**
+ * Get server capabilities. Returns JSON ServerCapabilities via callback.
+ */
+
+void pharos_get_server_capabilities(const char *connection_id,
+                                    AsyncCallback callback,
+                                    void *context);
+
 /**
  * Disconnect from PostgreSQL. Calls `callback` when done.
  */
@@ -58,6 +93,12 @@ typedef void (*AsyncCallback)(void *context, const char *result_json, const char
  */
  void pharos_test_connection(const char *json, AsyncCallback callback, void *context);
 
+/**
+ * Test every saved connection, bounded by the connection-attempt limit.
+ * Returns a JSON array of ConnectionTestReport via callback.
+ */
+ void pharos_test_all_connections(AsyncCallback callback, void *context);
+
 /**
  * Initialize the Rust runtime, SQLite database, and credential cache.
  * `app_data_dir` must be a valid UTF-8 path to the application support directory.
@@ -87,8 +128,20 @@ void pharos_free_string(char *ptr);
  */
  char *pharos_format_sql(const char *sql);
 
+/**
+ * Pretty-print a JSON/JSONB value with sorted keys. Returns NULL if the input
+ * isn't valid JSON. Caller must free.
+ */
+ char *pharos_format_json_value(const char *json);
+
+/**
+ * Validate a jsonb cell edit. Returns JSON JsonbValidation. Caller must free.
+ */
+ char *pharos_validate_jsonb(const char *json);
+
 /**
  * Execute a SQL query. Returns JSON QueryResult via callback.
+ * `run_as_role` (nullable) runs the query under `SET ROLE`.
  */
 
 void pharos_execute_query(const char *connection_id,
@@ -97,9 +150,104 @@ void pharos_execute_query(const char *connection_id,
                           int32_t limit,
                           const char *schema,
                           const char *source,
+                          const char *run_as_role,
+                          AsyncCallback callback,
+                          void *context);
+
+/**
+ * Execute a SQL query like `pharos_execute_query`, returning JSON
+ * ColumnarQueryResult (values grouped per column) via callback.
+ */
+
+void pharos_execute_query_columnar(const char *connection_id,
+                                   const char *sql,
+                                   const char *query_id,
+                                   int32_t limit,
+                                   const char *schema,
+                                   const char *source,
+                                   const char *run_as_role,
+                                   AsyncCallback callback,
+                                   void *context);
+
+/**
+ * Fetch the plan for a single statement, then execute it on the same connection.
+ * Returns JSON ExplainAndRunResult via callback.
+ */
+
+void pharos_explain_and_run(const char *connection_id,
+                            const char *sql,
+                            const char *query_id,
+                            int32_t limit,
+                            const char *schema,
+                            AsyncCallback callback,
+                            void *context);
+
+/**
+ * EXPLAIN one statement in `format` (json/text/xml/yaml); with `analyze` it
+ * runs inside a rolled-back transaction. Returns JSON ExplainResult via callback.
+ */
+
+void pharos_explain_query(const char *connection_id,
+                          const char *sql,
+                          bool analyze,
+                          const char *format,
+                          const char *schema,
+                          AsyncCallback callback,
+                          void *context);
+
+/**
+ * Run one statement and report wall-clock time alongside its
+ * pg_stat_statements counters. `query_id` (nullable) lets `pharos_cancel_query`
+ * stop it. Returns JSON QueryProfile via callback.
+ */
+
+void pharos_profile_query(const char *connection_id,
+                          const char *sql,
+                          const char *schema,
+                          const char *query_id,
                           AsyncCallback callback,
                           void *context);
 
+/**
+ * Execute a query extracting jsonb paths from named columns server-side.
+ * `paths_json` is a JSON array of JsonPathExtraction. Returns JSON QueryResult via callback.
+ */
+
+void pharos_execute_query_with_json_paths(const char *connection_id,
+                                          const char *sql,
+                                          const char *paths_json,
+                                          const char *query_id,
+                                          int32_t limit,
+                                          const char *schema,
+                                          AsyncCallback callback,
+                                          void *context);
+
+/**
+ * Open a pagination cursor for a read query. Returns JSON CursorHandle via callback.
+ */
+
+void pharos_open_cursor(const char *connection_id,
+                        const char *sql,
+                        const char *schema,
+                        AsyncCallback callback,
+                        void *context);
+
+/**
+ * Fetch the next `count` rows from a cursor. `query_id` (nullable) lets
+ * `pharos_cancel_query` stop the fetch. Returns JSON QueryResult via callback.
+ */
+
+void pharos_fetch_cursor(const char *cursor_id,
+                         uint32_t count,
+                         const char *query_id,
+                         AsyncCallback callback,
+                         void *context);
+
+/**
+ * Close a cursor. Returns "true" (closed) or "false" (unknown id) via callback.
+ */
+ void pharos_close_cursor(const char *cursor_id, AsyncCallback callback, void *context);
+
 /**
  * Execute a statement (INSERT/UPDATE/DELETE). Returns JSON ExecuteResult via callback.
  */
@@ -110,6 +258,20 @@ void pharos_execute_statement(const char *connection_id,
                               AsyncCallback callback,
                               void *context);
 
+/**
+ * Execute a DML statement only if EXPLAIN estimates it touches at most
+ * `max_rows` rows (<= 0 uses the setting), or `force` is set.
+ * Returns JSON GuardedExecuteResult via callback.
+ */
+
+void pharos_execute_with_rowcount_guard(const char *connection_id,
+                                        const char *sql,
+                                        const char *schema,
+                                        int64_t max_rows,
+                                        bool force,
+                                        AsyncCallback callback,
+                                        void *context);
+
 /**
  * Fetch more rows. Returns JSON QueryResult via callback.
  */
@@ -122,6 +284,12 @@ void pharos_fetch_more_rows(const char *connection_id,
                             AsyncCallback callback,
                             void *context);
 
+/**
+ * Preview the SQL `pharos_fetch_more_rows` would run for a page. Returns JSON
+ * `{"sql": ...}`, or `{"error": ...}` for a bad limit/offset. Caller must free.
+ */
+ char *pharos_preview_pagination_sql(const char *sql, int64_t limit, int64_t offset);
+
 /**
  * Cancel a running query. Returns immediately (synchronous).
  */
@@ -131,6 +299,28 @@ void pharos_cancel_query(const char *connection_id,
                          AsyncCallback callback,
                          void *context);
 
+/**
+ * Override a connection's statement timeout until disconnect; a negative
+ * `timeout_seconds` restores the settings' value and one too large to apply is
+ * an error. Returns JSON SessionTimeout via callback.
+ */
+
+void pharos_set_session_timeout(const char *connection_id,
+                                int64_t timeout_seconds,
+                                AsyncCallback callback,
+                                void *context);
+
+/**
+ * Get a connection's effective statement timeout. Returns JSON SessionTimeout via callback.
+ */
+ void pharos_get_session_timeout(const char *connection_id, AsyncCallback callback, void *context);
+
+/**
+ * List running queries (optionally for one connection; nullable) as a JSON
+ * array of `{queryId, connectionId, backendPid, elapsedMs}`.
+ */
+ char *pharos_list_running_queries(const char *connection_id);
+
 /**
  * Validate SQL syntax. Returns JSON ValidationResult via callback.
  */
@@ -141,6 +331,37 @@ void pharos_validate_sql(const char *connection_id,
                          AsyncCallback callback,
                          void *context);
 
+/**
+ * Validate each statement of a multi-statement script. Returns a JSON array of
+ * StatementValidation (script-relative offsets and error positions) via callback.
+ */
+
+void pharos_validate_script(const char *connection_id,
+                            const char *sql,
+                            const char *schema,
+                            AsyncCallback callback,
+                            void *context);
+
+/**
+ * Store an EXPLAIN (FORMAT JSON) result for `sql` so later runs can be compared.
+ * Returns the StoredPlan JSON, or error JSON.
+ */
+ char *pharos_record_plan(const char *connection_id, const char *sql, const char *plan_json);
+
+/**
+ * Plans stored for `sql` on a connection, oldest first. Returns JSON array.
+ */
+ char *pharos_get_stored_plans(const char *connection_id, const char *sql);
+
+/**
+ * Compare two stored plans of `sql` by ID. Returns PlanComparison JSON, or error JSON.
+ */
+
+char *pharos_compare_plans(const char *connection_id,
+                           const char *sql,
+                           const char *base_id,
+                           const char *other_id);
+
 /**
  * Load query history. `json` is JSON with optional filters: {connectionId?, search?, limit?, offset?}.
  * Returns JSON array. Caller must free.
@@ -158,6 +379,18 @@ char *pharos_load_query_history(const char *json);
  */
  char *pharos_get_query_history_result(const char *entry_id);
 
+/**
+ * Get the full SQL of a history entry. Returns a JSON string, NULL if the entry
+ * doesn't exist, or error JSON.
+ */
+ char *pharos_get_history_entry_sql(const char *entry_id);
+
+/**
+ * Delete query history by filter. `json` is {connectionId?, beforeDate?, search?};
+ * at least one is required. Returns the count of deleted entries as a string, or error JSON.
+ */
+ char *pharos_delete_query_history_filtered(const char *json);
+
 /**
  * Batch delete query history entries. `json` is a JSON array of ID strings.
  * Returns the count of deleted entries as a string, or error JSON.
@@ -174,6 +407,11 @@ char *pharos_load_query_history(const char *json);
  */
  char *pharos_create_saved_query(const char *json);
 
+/**
+ * Duplicate a saved query as "<name> (copy)". Returns JSON SavedQuery. Caller must free.
+ */
+ char *pharos_duplicate_saved_query(const char *query_id);
+
 /**
  * Update a saved query. `json` is JSON-encoded UpdateSavedQuery. Returns JSON SavedQuery or null.
  */
@@ -189,15 +427,41 @@ char *pharos_load_query_history(const char *json);
  */
  char *pharos_batch_delete_saved_queries(const char *json);
 
+/**
+ * Save a saved query's result grid layout. `layout_json` may be null to reset
+ * it. Returns "null" or an error object. Caller must free.
+ */
+ char *pharos_save_query_column_layout(const char *query_id, const char *layout_json);
+
+/**
+ * Get a saved query's result grid layout JSON, or "null" if it has none.
+ * Caller must free.
+ */
+ char *pharos_get_query_column_layout(const char *query_id);
+
+/**
+ * Rename a saved-query folder, including nested folders. Returns moved count as string.
+ */
+ char *pharos_rename_saved_query_folder(const char *old_folder, const char *new_folder);
+
 /**
  * Extract table names from SQL for display. Returns comma-separated names or NULL.
  */
  char *pharos_extract_table_names(const char *sql);
 
 /**
- * Get schemas. Returns JSON array via callback.
+ * Get schemas, optionally including system schemas. Returns JSON array via callback.
  */
- void pharos_get_schemas(const char *connection_id, AsyncCallback callback, void *context);
+
+void pharos_get_schemas(const char *connection_id,
+                        bool include_system,
+                        AsyncCallback callback,
+                        void *context);
+
+/**
+ * List connectable databases on the server. Returns JSON array of names via callback.
+ */
+ void pharos_list_databases(const char *connection_id, AsyncCallback callback, void *context);
 
 /**
  * Get tables for a schema. Returns JSON array via callback.
@@ -208,6 +472,17 @@ void pharos_get_tables(const char *connection_id,
                        AsyncCallback callback,
                        void *context);
 
+/**
+ * Search tables/views by name substring, optionally within one schema (nullable).
+ * Returns JSON array of TableInfo, best matches first, via callback.
+ */
+
+void pharos_search_tables(const char *connection_id,
+                          const char *term,
+                          const char *schema_name,
+                          AsyncCallback callback,
+                          void *context);
+
 /**
  * Get direct child partitions of a partitioned parent. Returns JSON array via callback.
  */
@@ -218,6 +493,26 @@ void pharos_get_partitions(const char *connection_id,
                            AsyncCallback callback,
                            void *context);
 
+/**
+ * Get a table's RLS flags and policies. Returns TableRlsPolicies JSON via callback.
+ */
+
+void pharos_get_rls_policies(const char *connection_id,
+                             const char *schema_name,
+                             const char *table_name,
+                             AsyncCallback callback,
+                             void *context);
+
+/**
+ * Get a table's inheritance parents and children. Returns TableInheritance JSON via callback.
+ */
+
+void pharos_get_inheritance(const char *connection_id,
+                            const char *schema_name,
+                            const char *table_name,
+                            AsyncCallback callback,
+                            void *context);
+
 /**
  * Get parent→child partition name map for a schema. Returns JSON array via callback.
  */
@@ -246,6 +541,15 @@ void pharos_get_schema_columns(const char *connection_id,
                                AsyncCallback callback,
                                void *context);
 
+/**
+ * Load a schema's tables with columns, indexes and constraints. Returns JSON array via callback.
+ */
+
+void pharos_load_schema_tree(const char *connection_id,
+                             const char *schema_name,
+                             AsyncCallback callback,
+                             void *context);
+
 /**
  * Analyze a schema. Returns JSON AnalyzeResult via callback.
  */
@@ -255,6 +559,32 @@ void pharos_analyze_schema(const char *connection_id,
                            AsyncCallback callback,
                            void *context);
 
+/**
+ * Refresh a materialized view. Returns the elapsed milliseconds via callback.
+ */
+
+void pharos_refresh_materialized_view(const char *connection_id,
+                                      const char *schema_name,
+                                      const char *view_name,
+                                      bool concurrently,
+                                      AsyncCallback callback,
+                                      void *context);
+
+/**
+ * Get autocompletion metadata (tables with their columns; types and comments
+ * too when `include_details`). Returns JSON array via callback.
+ */
+
+void pharos_get_completion_metadata(const char *connection_id,
+                                    bool include_details,
+                                    AsyncCallback callback,
+                                    void *context);
+
+/**
+ * Drop a connection's cached autocompletion metadata (both variants).
+ */
+ void pharos_clear_completion_metadata(const char *connection_id);
+
 /**
  * Get schema functions. Returns JSON array via callback.
  */
@@ -264,6 +594,111 @@ void pharos_get_schema_functions(const char *connection_id,
                                  AsyncCallback callback,
                                  void *context);
 
+/**
+ * Get schema sequences. Returns JSON array via callback.
+ */
+
+void pharos_get_sequences(const char *connection_id,
+                          const char *schema_name,
+                          AsyncCallback callback,
+                          void *context);
+
+/**
+ * Estimate table and index bloat in a schema. Returns JSON array of
+ * BloatEstimate, largest waste first, via callback.
+ */
+
+void pharos_get_bloat_estimate(const char *connection_id,
+                               const char *schema_name,
+                               AsyncCallback callback,
+                               void *context);
+
+/**
+ * Get the most expensive statements from pg_stat_statements. `limit` <= 0
+ * uses the default. Returns JSON array via callback.
+ */
+
+void pharos_get_top_statements(const char *connection_id,
+                               int32_t limit,
+                               AsyncCallback callback,
+                               void *context);
+
+/**
+ * List idle-in-transaction sessions in the current database.
+ * Returns JSON array via callback.
+ */
+
+void pharos_get_idle_in_transaction(const char *connection_id,
+                                    AsyncCallback callback,
+                                    void *context);
+
+/**
+ * Terminate idle-in-transaction sessions idle for at least `min_idle_seconds`.
+ * Returns a JSON array of terminated pids via callback.
+ */
+
+void pharos_terminate_idle_transactions(const char *connection_id,
+                                        uint32_t min_idle_seconds,
+                                        bool confirmed,
+                                        AsyncCallback callback,
+                                        void *context);
+
+/**
+ * List in-progress VACUUM / CREATE INDEX operations.
+ * Returns JSON array via callback.
+ */
+
+void pharos_get_running_maintenance(const char *connection_id,
+                                    AsyncCallback callback,
+                                    void *context);
+
+/**
+ * Cancel a running VACUUM / CREATE INDEX by backend pid.
+ */
+
+void pharos_cancel_maintenance(const char *connection_id,
+                               int32_t pid,
+                               AsyncCallback callback,
+                               void *context);
+
+/**
+ * List prepared (two-phase) transactions in the current database.
+ * Returns JSON array via callback.
+ */
+
+void pharos_get_prepared_transactions(const char *connection_id,
+                                      AsyncCallback callback,
+                                      void *context);
+
+/**
+ * Roll back a prepared transaction by gid.
+ */
+
+void pharos_rollback_prepared_transaction(const char *connection_id,
+                                          const char *gid,
+                                          AsyncCallback callback,
+                                          void *context);
+
+/**
+ * List the settings stored for the current database (`pg_db_role_setting`).
+ * Returns JSON array via callback.
+ */
+
+void pharos_get_database_settings(const char *connection_id,
+                                  AsyncCallback callback,
+                                  void *context);
+
+/**
+ * Set a whitelisted parameter on the current database; a null `value` resets
+ * it. Returns the database's settings afterwards as a JSON array via callback.
+ */
+
+void pharos_set_database_setting(const char *connection_id,
+                                 const char *name,
+                                 const char *value,
+                                 AsyncCallback callback,
+                                 void *context);
+
 /**
  * Load settings. Returns JSON. Caller must free.
  */
@@ -274,6 +709,17 @@ void pharos_get_schema_functions(const char *connection_id,
  */
  char *pharos_save_settings(const char *json);
 
+/**
+ * Write the keyboard shortcuts to a JSON file. Returns the count via callback.
+ */
+ void pharos_export_keymap(const char *file_path, AsyncCallback callback, void *context);
+
+/**
+ * Merge a keymap file over the current shortcuts and save them.
+ * Returns JSON KeymapImportResult via callback.
+ */
+ void pharos_import_keymap(const char *file_path, AsyncCallback callback, void *context);
+
 /**
  * Get table indexes. Returns JSON array via callback.
  */
@@ -294,6 +740,27 @@ void pharos_get_table_constraints(const char *connection_id,
                                   AsyncCallback callback,
                                   void *context);
 
+/**
+ * Get table triggers (internal constraint triggers excluded). Returns JSON
+ * array via callback.
+ */
+
+void pharos_get_table_triggers(const char *connection_id,
+                               const char *schema_name,
+                               const char *table_name,
+                               AsyncCallback callback,
+                               void *context);
+
+/**
+ * Generate DDL for a whole schema, comments included. Returns the script
+ * as a JSON string via callback.
+ */
+
+void pharos_generate_schema_ddl(const char *connection_id,
+                                const char *schema_name,
+                                AsyncCallback callback,
+                                void *context);
+
 /**
  * Generate table DDL (three detail variants). Returns JSON via callback.
  */
@@ -304,6 +771,27 @@ void pharos_generate_table_ddl(const char *connection_id,
                                AsyncCallback callback,
                                void *context);
 
+/**
+ * Generate CREATE VIEW / CREATE MATERIALIZED VIEW DDL. Returns the DDL as a
+ * JSON string via callback.
+ */
+
+void pharos_generate_view_ddl(const char *connection_id,
+                              const char *schema_name,
+                              const char *view_name,
+                              AsyncCallback callback,
+                              void *context);
+
+/**
+ * Get a table's storage parameters. Returns JSON array via callback.
+ */
+
+void pharos_get_table_storage_params(const char *connection_id,
+                                     const char *schema_name,
+                                     const char *table_name,
+                                     AsyncCallback callback,
+                                     void *context);
+
 /**
  * Clone a table. `json` is JSON-encoded CloneTableOptions.
  */
@@ -313,6 +801,70 @@ void pharos_clone_table(const char *connection_id,
                         AsyncCallback callback,
                         void *context);
 
+/**
+ * Copy a table from one connection to another. `json` is JSON-encoded
+ * CloneTableOptions. Returns JSON CopyTableResult via callback.
+ */
+
+void pharos_copy_table_across_connections(const char *source_connection_id,
+                                          const char *target_connection_id,
+                                          const char *json,
+                                          AsyncCallback callback,
+                                          void *context);
+
+/**
+ * Compute a content checksum of a table. `query_id` (nullable) lets
+ * `pharos_cancel_query` stop it. Returns JSON TableChecksum.
+ */
+
+void pharos_checksum_table(const char *connection_id,
+                           const char *schema_name,
+                           const char *table_name,
+                           const char *query_id,
+                           AsyncCallback callback,
+                           void *context);
+
+/**
+ * Compare a table's row estimate with its exact count (only counted when
+ * `exact`). `query_id` (nullable) lets `pharos_cancel_query` stop the count.
+ * Returns JSON CountAccuracy.
+ */
+
+void pharos_check_count_accuracy(const char *connection_id,
+                                 const char *schema_name,
+                                 const char *table_name,
+                                 bool exact,
+                                 const char *query_id,
+                                 AsyncCallback callback,
+                                 void *context);
+
+/**
+ * Preview the first and last `count` rows of a table. `key_columns_json` is a
+ * JSON array of ordering columns (normally the PK); empty orders by ctid.
+ * Returns JSON TableEndsPreview.
+ */
+
+void pharos_preview_table_ends(const char *connection_id,
+                               const char *schema_name,
+                               const char *table_name,
+                               const char *key_columns_json,
+                               uint32_t count,
+                               AsyncCallback callback,
+                               void *context);
+
+/**
+ * Find duplicate value groups across `columns_json` (a JSON array of column
+ * names). Returns JSON QueryResult via callback.
+ */
+
+void pharos_find_duplicate_rows(const char *connection_id,
+                                const char *schema_name,
+                                const char *table_name,
+                                const char *columns_json,
+                                uint32_t limit,
+                                AsyncCallback callback,
+                                void *context);
+
 /**
  * Export table data. `json` is JSON-encoded ExportTableOptions.
  */
@@ -322,6 +874,62 @@ void pharos_export_table(const char *connection_id,
                          AsyncCallback callback,
                          void *context);
 
+/**
+ * Write one cell's value to a file. `json` is JSON-encoded ExportCellOptions.
+ * Returns JSON ExportCellResult via callback.
+ */
+
+void pharos_export_cell_to_file(const char *connection_id,
+                                const char *json,
+                                AsyncCallback callback,
+                                void *context);
+
+/**
+ * Export all rows of a query to a newline-delimited JSON file. `schema` may be
+ * null. Returns JSON ExportTableResult via callback.
+ */
+
+void pharos_export_ndjson(const char *connection_id,
+                          const char *sql,
+                          const char *schema,
+                          const char *file_path,
+                          AsyncCallback callback,
+                          void *context);
+
+/**
+ * Export all rows of a query as a Markdown table. `schema` may be null; NULL
+ * cells are left empty when `null_as_empty`. Returns JSON ExportTableResult via callback.
+ */
+
+void pharos_export_markdown(const char *connection_id,
+                            const char *sql,
+                            const char *schema,
+                            const char *file_path,
+                            bool null_as_empty,
+                            AsyncCallback callback,
+                            void *context);
+
+/**
+ * Export in-memory query results to XLSX. `json` is JSON-encoded ExportResultsOptions.
+ */
+ void pharos_export_results(const char *json, AsyncCallback callback, void *context);
+
+/**
+ * Export a table as INSERT statements. `options_json` is JSON-encoded
+ * ExportSqlOptions. Returns JSON ExportTableResult via callback.
+ */
+
+void pharos_export_sql(const char *connection_id,
+                       const char *options_json,
+                       AsyncCallback callback,
+                       void *context);
+
+/**
+ * Export in-memory query results to a JSON array file. `json` is
+ * JSON-encoded ExportResultsOptions. Returns JSON ExportTableResult via callback.
+ */
+ void pharos_export_results_json(const char *json, AsyncCallback callback, void *context);
+
 /**
  * Get the live row count for an in-progress import.
  * `key` is `"{connection_id}|{schema}|{table}"`.
@@ -338,6 +946,93 @@ void pharos_import_csv(const char *connection_id,
                        AsyncCallback callback,
                        void *context);
 
+/**
+ * Render a fetched result set as INSERT statements for the clipboard.
+ * `result_json` is a JSON-encoded QueryResult; `schema_name` is nullable.
+ * Returns the statements as a JSON string, or error JSON.
+ */
+
+char *pharos_results_to_insert_string(const char *result_json,
+                                      const char *schema_name,
+                                      const char *table_name);
+
+/**
+ * Export a fetched result set as an HTML table. `result_json` is a JSON-encoded
+ * QueryResult; with `file_path` (nullable) the page is written there and the
+ * callback gets null, otherwise it gets the HTML as a JSON string.
+ */
+
+void pharos_export_html(const char *result_json,
+                        const char *file_path,
+                        AsyncCallback callback,
+                        void *context);
+
+/**
+ * Enable row-level security on a table. Calls back with null on success.
+ */
+
+void pharos_enable_rls(const char *connection_id,
+                       const char *schema_name,
+                       const char *table_name,
+                       AsyncCallback callback,
+                       void *context);
+
+/**
+ * Disable row-level security on a table; refused unless `confirmed`.
+ * Calls back with null on success.
+ */
+
+void pharos_disable_rls(const char *connection_id,
+                        const char *schema_name,
+                        const char *table_name,
+                        bool confirmed,
+                        AsyncCallback callback,
+                        void *context);
+
+/**
+ * Drop an RLS policy from a table. Calls back with null on success.
+ */
+
+void pharos_drop_policy(const char *connection_id,
+                        const char *schema_name,
+                        const char *table_name,
+                        const char *policy_name,
+                        AsyncCallback callback,
+                        void *context);
+
+/**
+ * Set one whitelisted storage parameter on a table. Returns the table's
+ * storage parameters afterwards as a JSON array via callback.
+ */
+
+void pharos_set_table_storage_param(const char *connection_id,
+                                    const char *schema_name,
+                                    const char *table_name,
+                                    const char *name,
+                                    const char *value,
+                                    AsyncCallback callback,
+                                    void *context);
+
+/**
+ * Dry-run a column type change in a rolled-back transaction. `json` is
+ * JSON-encoded AlterColumnTypeOptions. Returns JSON AlterColumnTypePreview via callback.
+ */
+
+void pharos_preview_alter_column_type(const char *connection_id,
+                                      const char *json,
+                                      AsyncCallback callback,
+                                      void *context);
+
+/**
+ * Change a column's type. `json` is JSON-encoded AlterColumnTypeOptions.
+ * Returns the executed statement as a JSON string via callback.
+ */
+
+void pharos_alter_column_type(const char *connection_id,
+                              const char *json,
+                              AsyncCallback callback,
+                              void *context);
+
 /**
  * Upsert a workspace. `json` = WorkspaceUpsert. Returns "true" or error JSON.
  */
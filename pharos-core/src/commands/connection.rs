@@ -1,6 +1,6 @@
 
 use crate::db::{credentials, postgres, sqlite};
-use crate::models::{ConnectionConfig, ConnectionInfo, ConnectionStatus, TestConnectionResult};
+use crate::models::{ConnectionConfig, ConnectionInfo, ConnectionStatus, ConnectionTestReport, ServerCapabilities, TestConnectionResult};
 use crate::state::AppState;
 
 /// Sanitize error messages to remove sensitive data like passwords
@@ -39,6 +39,11 @@ fn sanitize_error(error: &str) -> String {
     sanitized
 }
 
+/// Keychain key of a connection's SSH password or key passphrase
+fn ssh_secret_key(connection_id: &str) -> String {
+    format!("{}:ssh", connection_id)
+}
+
 /// Save a new connection configuration
 pub async fn save_connection(
     config: ConnectionConfig,
@@ -49,6 +54,16 @@ pub async fn save_connection(
         let mut cache = state.password_cache.lock().map_err(|e| e.to_string())?;
         credentials::store_password_with_cache(&config.id, &config.password, &mut cache)?;
     }
+    // A removed tunnel, or one without a secret, must not leave the old one behind.
+    {
+        let key = ssh_secret_key(&config.id);
+        let mut cache = state.password_cache.lock().map_err(|e| e.to_string())?;
+        match config.ssh_tunnel.as_ref().filter(|t| !t.password.is_empty()) {
+            Some(ssh) => credentials::store_password_with_cache(&key, &ssh.password, &mut cache)?,
+            None if cache.contains_key(&key) => credentials::delete_password_with_cache(&key, &mut cache)?,
+            None => {}
+        }
+    }
 
     // Save metadata to SQLite (without password)
     {
@@ -62,20 +77,50 @@ pub async fn save_connection(
     Ok(())
 }
 
+/// ID of the in-memory connection `connect_to_database` derives from a saved one.
+fn derived_connection_id(connection_id: &str, database: &str) -> String {
+    format!("{}::{}", connection_id, database)
+}
+
+/// Whether `connection_id` is an in-memory connection from `connect_to_database`
+/// (saved connection IDs are UUIDs and never contain `::`).
+pub(crate) fn is_derived_connection(connection_id: &str) -> bool {
+    connection_id.contains("::")
+}
+
+/// Release a connection's open cursors. Their pinned connections never return
+/// to the pool, so `close()` would otherwise wait on them forever.
+fn release_cursors(connection_id: &str, state: &AppState) {
+    for cursor in state.take_cursors_for_connection(connection_id) {
+        drop(cursor.conn.detach());
+    }
+}
+
 /// Delete a connection configuration
 pub async fn delete_connection(
     connection_id: String,
     state: &AppState,
 ) -> Result<(), String> {
-    // Disconnect if connected
+    // Disconnect if connected, along with connections derived from this one
+    let derived_prefix = derived_connection_id(&connection_id, "");
+    let derived: Vec<String> = {
+        let configs = state.connection_configs.lock().map_err(|e| e.to_string())?;
+        configs.keys().filter(|id| id.starts_with(&derived_prefix)).cloned().collect()
+    };
+    for id in derived {
+        disconnect_postgres(id, state).await?;
+    }
+    release_cursors(&connection_id, state);
     if let Some(pool) = state.remove_pool(&connection_id) {
         pool.close().await;
     }
+    state.close_tunnel(&connection_id);
 
     // Delete password from keychain and update cache
     {
         let mut cache = state.password_cache.lock().map_err(|e| e.to_string())?;
         credentials::delete_password_with_cache(&connection_id, &mut cache)?;
+        credentials::delete_password_with_cache(&ssh_secret_key(&connection_id), &mut cache)?;
     }
 
     // Delete from SQLite
@@ -112,6 +157,9 @@ pub async fn load_connections(state: &AppState) -> Result<Vec<ConnectionConfig>,
         if let Some(password) = state.get_cached_password(&config.id) {
             config.password = password;
         }
+        if let Some(ssh) = config.ssh_tunnel.as_mut() {
+            ssh.password = state.get_cached_password(&ssh_secret_key(&config.id)).unwrap_or_default();
+        }
     }
 
     // Update in-memory cache
@@ -122,6 +170,19 @@ pub async fn load_connections(state: &AppState) -> Result<Vec<ConnectionConfig>,
     Ok(configs)
 }
 
+/// Saved connections carrying `label` (case-insensitive), in list order.
+pub async fn filter_connections_by_label(
+    label: String,
+    state: &AppState,
+) -> Result<Vec<ConnectionConfig>, String> {
+    let label = label.trim();
+    let configs = load_connections(state).await?;
+    Ok(configs
+        .into_iter()
+        .filter(|c| c.labels.iter().any(|l| l.trim().eq_ignore_ascii_case(label)))
+        .collect())
+}
+
 /// Connect to a PostgreSQL database
 pub async fn connect_postgres(
     connection_id: String,
@@ -143,15 +204,27 @@ pub async fn connect_postgres(
             status: ConnectionStatus::Connected,
             error: None,
             latency_ms: None,
+            server_version: state.server_version_text(&connection_id),
         });
     }
 
     // Create the connection pool and measure latency
+    let permit = state.acquire_connect_permit().await;
     let start = std::time::Instant::now();
-    match postgres::create_pool(&config).await {
-        Ok(pool) => {
+    let created = postgres::create_pool(&config).await;
+    drop(permit);
+    match created {
+        Ok((pool, tunnel)) => {
             let latency = start.elapsed().as_millis() as u64;
-            state.add_pool(connection_id.clone(), pool);
+            // Non-PostgreSQL servers may not report a version; nothing is recorded then.
+            let server_version = match postgres::get_server_version(&pool).await {
+                Ok((version, version_num)) => {
+                    state.set_server_version(&connection_id, version.clone(), version_num);
+                    Some(version)
+                }
+                Err(_) => None,
+            };
+            state.add_pool(connection_id.clone(), pool, tunnel);
             Ok(ConnectionInfo {
                 id: config.id,
                 name: config.name,
@@ -161,6 +234,7 @@ pub async fn connect_postgres(
                 status: ConnectionStatus::Connected,
                 error: None,
                 latency_ms: Some(latency),
+                server_version,
             })
         }
         Err(e) => Ok(ConnectionInfo {
@@ -172,35 +246,170 @@ pub async fn connect_postgres(
             status: ConnectionStatus::Error,
             error: Some(sanitize_error(&e.to_string())),
             latency_ms: None,
+            server_version: None,
         }),
     }
 }
 
-/// Disconnect from a PostgreSQL database
+/// Connect to another database on the same server as an existing connection,
+/// reusing its host, credentials and settings. Unless `persist` is set, the
+/// derived connection lives only in memory until disconnected (id
+/// `<connection_id>::<database>`, so switching back to the same database
+/// reuses its pool).
+pub async fn connect_to_database(
+    connection_id: String,
+    database: String,
+    persist: bool,
+    state: &AppState,
+) -> Result<ConnectionInfo, String> {
+    if database.is_empty() {
+        return Err("Database name must not be empty".to_string());
+    }
+    let base = state
+        .get_config(&connection_id)
+        .ok_or_else(|| format!("Connection not found: {}", connection_id))?;
+
+    let mut config = base.clone();
+    config.name = format!("{} / {}", base.name, database);
+    config.database = database;
+    if config.password.is_empty() {
+        config.password = state.get_cached_password(&connection_id).unwrap_or_default();
+    }
+    if let Some(ssh) = config.ssh_tunnel.as_mut().filter(|t| t.password.is_empty()) {
+        ssh.password = state.get_cached_password(&ssh_secret_key(&connection_id)).unwrap_or_default();
+    }
+
+    if persist {
+        config.id = uuid::Uuid::new_v4().to_string();
+        save_connection(config.clone(), state).await?;
+    } else {
+        config.id = derived_connection_id(&connection_id, &config.database);
+        state.set_config(config.clone());
+    }
+
+    connect_postgres(config.id, state).await
+}
+
+/// Report server version, superuser status and installed extensions for a connection
+pub async fn get_server_capabilities(
+    connection_id: String,
+    state: &AppState,
+) -> Result<ServerCapabilities, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::get_server_capabilities(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Disconnect from a PostgreSQL database. An in-memory connection from
+/// `connect_to_database` is forgotten as well.
 pub async fn disconnect_postgres(
     connection_id: String,
     state: &AppState,
 ) -> Result<(), String> {
+    release_cursors(&connection_id, state);
     if let Some(pool) = state.remove_pool(&connection_id) {
         pool.close().await;
     }
+    state.close_tunnel(&connection_id);
     state.clear_analyze_denied(&connection_id);
+    if is_derived_connection(&connection_id) {
+        state.remove_config(&connection_id);
+    }
     Ok(())
 }
 
+/// Close pools that have been idle longer than the `idle_disconnect_minutes`
+/// setting (0 disables). Returns the closed connection IDs, which are also
+/// queued for the frontend to collect via `take_idle_disconnected`.
+pub async fn close_idle_connections(state: &AppState) -> Vec<String> {
+    let minutes = state
+        .metadata_db
+        .lock()
+        .ok()
+        .and_then(|db| sqlite::load_settings(&db).ok())
+        .map(|s| s.query.idle_disconnect_minutes)
+        .unwrap_or(0);
+    if minutes == 0 {
+        return vec![];
+    }
+
+    let idle = state.idle_connection_ids(std::time::Duration::from_secs(minutes as u64 * 60));
+    let mut closed = Vec::with_capacity(idle.len());
+    for connection_id in idle {
+        release_cursors(&connection_id, state);
+        if let Some(pool) = state.remove_pool(&connection_id) {
+            pool.close().await;
+            state.close_tunnel(&connection_id);
+            state.clear_analyze_denied(&connection_id);
+            log::info!("Closed idle connection {}", connection_id);
+            closed.push(connection_id);
+        }
+    }
+    state.push_idle_disconnected(&closed);
+    closed
+}
+
+/// Connections closed for inactivity since the last call (then cleared)
+pub fn take_idle_disconnected(state: &AppState) -> Vec<String> {
+    state.take_idle_disconnected()
+}
+
 /// Test a connection configuration without saving it
-pub async fn test_connection(config: ConnectionConfig) -> Result<TestConnectionResult, String> {
+pub async fn test_connection(config: ConnectionConfig, state: &AppState) -> Result<TestConnectionResult, String> {
+    let _permit = state.acquire_connect_permit().await;
     match postgres::test_connection(&config).await {
-        Ok(latency) => Ok(TestConnectionResult {
+        Ok((latency, server_version)) => Ok(TestConnectionResult {
             success: true,
             latency_ms: Some(latency),
             error: None,
+            server_version,
         }),
         Err(e) => Ok(TestConnectionResult {
             success: false,
             latency_ms: None,
             error: Some(sanitize_error(&e.to_string())),
+            server_version: None,
         }),
     }
 }
 
+/// Test every saved connection. Runs concurrently, bounded by the app-wide
+/// connection-attempt limit; results are in list order.
+pub async fn test_all_connections(state: &AppState) -> Result<Vec<ConnectionTestReport>, String> {
+    let configs = load_connections(state).await?;
+    let tests = configs.into_iter().map(|config| async move {
+        let connection_id = config.id.clone();
+        let result = test_connection(config, state).await?;
+        Ok::<_, String>(ConnectionTestReport { connection_id, result })
+    });
+    futures::future::join_all(tests).await.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(id: &str) -> ConnectionConfig {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "name": id, "host": "localhost", "port": 5432, "database": "postgres", "username": "postgres",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn disconnect_forgets_only_derived_connections() {
+        let state = AppState::new(rusqlite::Connection::open_in_memory().unwrap());
+        let derived = derived_connection_id("c1", "analytics");
+        state.set_config(config("c1"));
+        state.set_config(config(&derived));
+
+        disconnect_postgres(derived.clone(), &state).await.unwrap();
+        disconnect_postgres("c1".to_string(), &state).await.unwrap();
+        assert!(state.get_config(&derived).is_none());
+        assert!(state.get_config("c1").is_some());
+    }
+}
@@ -0,0 +1,142 @@
+//! User-selectable rendering of date/time values (the `datetime_format` setting),
+//! shared by the result grid and file exports.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+use std::fmt::Write;
+
+/// How timestamp, timestamptz and date values are rendered.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum DateTimeFormat {
+    /// Each output path's built-in format: PostgreSQL's own text in the grid,
+    /// `%Y-%m-%d %H:%M:%S%.f` / RFC 3339 / `%Y-%m-%d` in exports.
+    #[default]
+    Default,
+    /// ISO-8601: `T` separator, with the offset for timestamptz.
+    Iso8601,
+    /// A chrono strftime pattern. DATE values use it when it only has date
+    /// fields, and fall back to `%Y-%m-%d` otherwise.
+    Pattern(String),
+}
+
+impl DateTimeFormat {
+    /// Parse the setting: unset/empty is `Default`, "iso8601" is `Iso8601`,
+    /// anything else a strftime pattern.
+    pub fn from_setting(setting: Option<&str>) -> Self {
+        match setting.map(str::trim) {
+            None | Some("") => DateTimeFormat::Default,
+            Some(s) if s.eq_ignore_ascii_case("iso8601") => DateTimeFormat::Iso8601,
+            Some(s) => DateTimeFormat::Pattern(s.to_string()),
+        }
+    }
+
+    /// Render a timestamp without time zone; None keeps the caller's default.
+    pub fn timestamp(&self, value: NaiveDateTime) -> Option<String> {
+        match self {
+            DateTimeFormat::Default => None,
+            DateTimeFormat::Iso8601 => Some(value.format("%Y-%m-%dT%H:%M:%S%.f").to_string()),
+            DateTimeFormat::Pattern(pattern) => try_format(value.format(pattern)),
+        }
+    }
+
+    /// Render a timestamp with time zone; None keeps the caller's default.
+    pub fn timestamptz(&self, value: DateTime<FixedOffset>) -> Option<String> {
+        match self {
+            DateTimeFormat::Default => None,
+            DateTimeFormat::Iso8601 => Some(value.to_rfc3339()),
+            DateTimeFormat::Pattern(pattern) => try_format(value.format(pattern)),
+        }
+    }
+
+    /// Render a date; None keeps the caller's default.
+    pub fn date(&self, value: NaiveDate) -> Option<String> {
+        match self {
+            DateTimeFormat::Default => None,
+            DateTimeFormat::Iso8601 => Some(value.format("%Y-%m-%d").to_string()),
+            DateTimeFormat::Pattern(pattern) => {
+                try_format(value.format(pattern)).or_else(|| Some(value.format("%Y-%m-%d").to_string()))
+            }
+        }
+    }
+
+    /// Re-render PostgreSQL's text output (ISO DateStyle) for a date/time
+    /// column. None when the format is `Default`, the type isn't a date/time
+    /// type, or the text doesn't parse (`infinity`, BC dates), so callers
+    /// pass the server text through unchanged.
+    pub fn reformat_pg_text(&self, text: &str, type_name: &str) -> Option<String> {
+        if *self == DateTimeFormat::Default {
+            return None;
+        }
+        match type_name.to_uppercase().as_str() {
+            "TIMESTAMP" => NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f")
+                .ok()
+                .and_then(|v| self.timestamp(v)),
+            "TIMESTAMPTZ" => DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f%#z")
+                .ok()
+                .and_then(|v| self.timestamptz(v)),
+            "DATE" => NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|v| self.date(v)),
+            _ => None,
+        }
+    }
+}
+
+/// Format lazily so an invalid pattern (or one asking for fields the value
+/// doesn't have) yields None instead of panicking.
+fn try_format(formatted: impl std::fmt::Display) -> Option<String> {
+    let mut out = String::new();
+    write!(out, "{}", formatted).ok()?;
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_leaves_server_text_alone() {
+        let format = DateTimeFormat::from_setting(None);
+        assert_eq!(format.reformat_pg_text("2024-01-15 12:34:56", "TIMESTAMP"), None);
+        assert_eq!(DateTimeFormat::from_setting(Some(" ")), DateTimeFormat::Default);
+    }
+
+    #[test]
+    fn iso8601_rewrites_each_type() {
+        let format = DateTimeFormat::from_setting(Some("ISO8601"));
+        assert_eq!(
+            format.reformat_pg_text("2024-01-15 12:34:56.5", "TIMESTAMP").as_deref(),
+            Some("2024-01-15T12:34:56.500")
+        );
+        assert_eq!(
+            format.reformat_pg_text("2024-01-15 12:34:56+05:30", "timestamptz").as_deref(),
+            Some("2024-01-15T12:34:56+05:30")
+        );
+        assert_eq!(format.reformat_pg_text("2024-01-15", "DATE").as_deref(), Some("2024-01-15"));
+        assert_eq!(format.reformat_pg_text("infinity", "TIMESTAMP"), None);
+        assert_eq!(format.reformat_pg_text("12:00:00", "TIME"), None);
+    }
+
+    #[test]
+    fn pattern_applies_and_dates_fall_back_without_time_fields() {
+        let format = DateTimeFormat::from_setting(Some("%d/%m/%Y %H:%M"));
+        assert_eq!(
+            format.reformat_pg_text("2024-01-15 09:05:00", "TIMESTAMP").as_deref(),
+            Some("15/01/2024 09:05")
+        );
+        assert_eq!(
+            format.reformat_pg_text("2024-01-15 09:05:00-08", "TIMESTAMPTZ").as_deref(),
+            Some("15/01/2024 09:05")
+        );
+        assert_eq!(format.reformat_pg_text("2024-01-15", "DATE").as_deref(), Some("2024-01-15"));
+
+        let date_only = DateTimeFormat::from_setting(Some("%d.%m.%Y"));
+        assert_eq!(date_only.reformat_pg_text("2024-01-15", "DATE").as_deref(), Some("15.01.2024"));
+    }
+
+    #[test]
+    fn unusable_pattern_keeps_default() {
+        let format = DateTimeFormat::from_setting(Some("%Y %z"));
+        // A naive timestamp has no offset to print.
+        assert_eq!(format.reformat_pg_text("2024-01-15 09:05:00", "TIMESTAMP"), None);
+    }
+}
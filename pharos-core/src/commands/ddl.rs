@@ -1,4 +1,5 @@
-//! Pure reconstruction of a table's CREATE TABLE DDL from raw pg_catalog parts.
+//! Pure reconstruction of a table's CREATE TABLE DDL, a view's CREATE VIEW
+//! DDL, and a whole schema's DDL, from raw pg_catalog parts.
 
 use crate::commands::table::escape_identifier;
 use serde::{Deserialize, Serialize};
@@ -14,6 +15,8 @@ pub struct DdlColumn {
     pub identity: String,
     /// pg_attribute.attgenerated as text: "" (none), "s" (stored)
     pub generated: String,
+    /// col_description, if any.
+    pub comment: Option<String>,
 }
 
 /// One constraint, name + full definition from pg_get_constraintdef.
@@ -21,6 +24,8 @@ pub struct DdlColumn {
 pub struct DdlConstraint {
     pub name: String,
     pub definition: String,
+    /// obj_description, if any.
+    pub comment: Option<String>,
 }
 
 /// All raw parts needed to compose a table's DDL.
@@ -34,6 +39,49 @@ pub struct TableDdlParts {
     /// The partition clause from pg_get_partkeydef (e.g. "RANGE (created_at)"),
     /// or None for a non-partitioned table.
     pub partition_by: Option<String>,
+    /// The table's obj_description, if any.
+    pub comment: Option<String>,
+}
+
+/// One table of a schema dump.
+#[derive(Debug, Clone)]
+pub struct DdlTable {
+    pub name: String,
+    pub parts: TableDdlParts,
+    pub partition_of: Option<PartitionOf>,
+}
+
+/// Where a partition attaches.
+#[derive(Debug, Clone)]
+pub struct PartitionOf {
+    pub parent_schema: String,
+    pub parent_table: String,
+    /// pg_get_expr(relpartbound), e.g. "FOR VALUES FROM ('2024-01-01') TO ('2025-01-01')" or "DEFAULT".
+    pub bound: String,
+}
+
+/// One function or procedure of a schema dump.
+#[derive(Debug, Clone)]
+pub struct DdlFunction {
+    /// Name plus identity arguments, e.g. `"public"."add"(a integer, b integer)`.
+    pub signature: String,
+    pub is_procedure: bool,
+    /// Full `CREATE OR REPLACE ...` statement from pg_get_functiondef.
+    pub definition: String,
+    pub comment: Option<String>,
+}
+
+/// A view or materialized view's raw DDL ingredients.
+#[derive(Debug, Clone)]
+pub struct DdlView {
+    pub materialized: bool,
+    /// reloptions, e.g. "check_option=local, security_barrier=true".
+    pub options: Option<String>,
+    /// Pretty-printed query from pg_get_viewdef(oid, true).
+    pub query: String,
+    /// Full `CREATE INDEX ...` statements; only materialized views have any.
+    pub index_defs: Vec<String>,
+    pub comment: Option<String>,
 }
 
 /// The three ready-to-display DDL variants sent to Swift.
@@ -98,6 +146,46 @@ fn render_create_table(
     )
 }
 
+/// Quote a comment as a SQL string literal.
+fn comment_literal(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// `"schema"."table"`.
+fn qualified(schema: &str, table: &str) -> String {
+    format!("\"{}\".\"{}\"", escape_identifier(schema), escape_identifier(table))
+}
+
+/// COMMENT ON statements for a table, its columns and its constraints.
+fn render_table_comments(schema: &str, table: &str, parts: &TableDdlParts) -> Vec<String> {
+    let target = qualified(schema, table);
+    let mut lines = Vec::new();
+    if let Some(comment) = &parts.comment {
+        lines.push(format!("COMMENT ON TABLE {} IS {};", target, comment_literal(comment)));
+    }
+    for col in &parts.columns {
+        if let Some(comment) = &col.comment {
+            lines.push(format!(
+                "COMMENT ON COLUMN {}.\"{}\" IS {};",
+                target,
+                escape_identifier(&col.name),
+                comment_literal(comment)
+            ));
+        }
+    }
+    for con in &parts.constraints {
+        if let Some(comment) = &con.comment {
+            lines.push(format!(
+                "COMMENT ON CONSTRAINT \"{}\" ON {} IS {};",
+                escape_identifier(&con.name),
+                target,
+                comment_literal(comment)
+            ));
+        }
+    }
+    lines
+}
+
 /// Compose the three DDL variants from raw parts. Pure — no I/O.
 pub fn compose_table_ddl(schema: &str, table: &str, parts: &TableDdlParts) -> TableDdl {
     let col_lines: Vec<String> = parts.columns.iter().map(render_column).collect();
@@ -119,6 +207,11 @@ pub fn compose_table_ddl(schema: &str, table: &str, parts: &TableDdlParts) -> Ta
                 .join("\n"),
         );
     }
+    let comments = render_table_comments(schema, table, parts);
+    if !comments.is_empty() {
+        full.push_str("\n\n");
+        full.push_str(&comments.join("\n"));
+    }
 
     TableDdl {
         columns_only,
@@ -127,6 +220,135 @@ pub fn compose_table_ddl(schema: &str, table: &str, parts: &TableDdlParts) -> Ta
     }
 }
 
+/// Compose a view's DDL: `CREATE OR REPLACE VIEW`, or `CREATE MATERIALIZED
+/// VIEW` plus its indexes, then the COMMENT ON if any. Pure — no I/O.
+pub fn compose_view_ddl(schema: &str, name: &str, view: &DdlView) -> String {
+    let (create, kind) = if view.materialized {
+        ("CREATE MATERIALIZED VIEW", "MATERIALIZED VIEW")
+    } else {
+        ("CREATE OR REPLACE VIEW", "VIEW")
+    };
+    let target = qualified(schema, name);
+    let options = view
+        .options
+        .as_ref()
+        .map(|o| format!(" WITH ({})", o))
+        .unwrap_or_default();
+    let query = view.query.trim_end().trim_end_matches(';');
+
+    let mut ddl = format!("{} {}{} AS\n{};", create, target, options, query);
+    for index in &view.index_defs {
+        ddl.push_str(&format!("\n\n{};", index));
+    }
+    if let Some(comment) = &view.comment {
+        ddl.push_str(&format!("\n\nCOMMENT ON {} {} IS {};", kind, target, comment_literal(comment)));
+    }
+    ddl
+}
+
+/// Compose a whole schema's DDL: functions, tables (foreign keys deferred to
+/// ALTER TABLE so creation order doesn't matter), partitions, then every
+/// COMMENT ON. `tables` must list partition parents before their partitions.
+pub fn compose_schema_ddl(
+    schema: &str,
+    schema_comment: Option<&str>,
+    tables: &[DdlTable],
+    functions: &[DdlFunction],
+) -> String {
+    let is_foreign_key = |con: &DdlConstraint| con.definition.starts_with("FOREIGN KEY");
+    let mut sections: Vec<String> = Vec::new();
+
+    let mut header = vec![
+        // Function bodies may reference tables created further down.
+        "SET check_function_bodies = false;".to_string(),
+        format!("CREATE SCHEMA IF NOT EXISTS \"{}\";", escape_identifier(schema)),
+    ];
+    if let Some(comment) = schema_comment {
+        header.push(format!(
+            "COMMENT ON SCHEMA \"{}\" IS {};",
+            escape_identifier(schema),
+            comment_literal(comment)
+        ));
+    }
+    sections.push(header.join("\n"));
+
+    for function in functions {
+        sections.push(format!("{};", function.definition.trim_end().trim_end_matches(';')));
+    }
+
+    for table in tables {
+        let parts = &table.parts;
+        let mut statement = match &table.partition_of {
+            Some(PartitionOf { parent_schema, parent_table, bound }) => {
+                let partition_by = parts
+                    .partition_by
+                    .as_ref()
+                    .map(|p| format!(" PARTITION BY {}", p))
+                    .unwrap_or_default();
+                format!(
+                    "CREATE TABLE {} PARTITION OF {} {}{};",
+                    qualified(schema, &table.name),
+                    qualified(parent_schema, parent_table),
+                    bound,
+                    partition_by
+                )
+            }
+            None => {
+                let col_lines: Vec<String> = parts.columns.iter().map(render_column).collect();
+                let constraint_lines: Vec<String> = parts
+                    .constraints
+                    .iter()
+                    .filter(|c| !is_foreign_key(c))
+                    .map(render_constraint)
+                    .collect();
+                render_create_table(schema, &table.name, &col_lines, &constraint_lines, parts.partition_by.as_deref())
+            }
+        };
+        for index in &parts.index_defs {
+            statement.push_str(&format!("\n{};", index));
+        }
+        sections.push(statement);
+    }
+
+    let foreign_keys: Vec<String> = tables
+        .iter()
+        .filter(|t| t.partition_of.is_none())
+        .flat_map(|t| {
+            t.parts.constraints.iter().filter(|c| is_foreign_key(c)).map(move |c| {
+                format!(
+                    "ALTER TABLE {} ADD CONSTRAINT \"{}\" {};",
+                    qualified(schema, &t.name),
+                    escape_identifier(&c.name),
+                    c.definition
+                )
+            })
+        })
+        .collect();
+    if !foreign_keys.is_empty() {
+        sections.push(foreign_keys.join("\n"));
+    }
+
+    let mut comments: Vec<String> = tables
+        .iter()
+        .flat_map(|t| render_table_comments(schema, &t.name, &t.parts))
+        .collect();
+    for function in functions {
+        if let Some(comment) = &function.comment {
+            comments.push(format!(
+                "COMMENT ON {} {} IS {};",
+                if function.is_procedure { "PROCEDURE" } else { "FUNCTION" },
+                function.signature,
+                comment_literal(comment)
+            ));
+        }
+    }
+    if !comments.is_empty() {
+        sections.push(comments.join("\n"));
+    }
+
+    sections.join("\n\n") + "\n"
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -141,6 +363,7 @@ mod tests {
                     default_expr: None,
                     identity: "a".into(),
                     generated: "".into(),
+                    comment: None,
                 },
                 DdlColumn {
                     name: "cust_id".into(),
@@ -149,6 +372,7 @@ mod tests {
                     default_expr: None,
                     identity: "".into(),
                     generated: "".into(),
+                    comment: None,
                 },
                 DdlColumn {
                     name: "total".into(),
@@ -157,6 +381,7 @@ mod tests {
                     default_expr: Some("0".into()),
                     identity: "".into(),
                     generated: "".into(),
+                    comment: None,
                 },
                 DdlColumn {
                     name: "total_with_tax".into(),
@@ -165,6 +390,7 @@ mod tests {
                     default_expr: Some("(total * 1.2)".into()),
                     identity: "".into(),
                     generated: "s".into(),
+                    comment: None,
                 },
                 DdlColumn {
                     name: "created_at".into(),
@@ -173,26 +399,31 @@ mod tests {
                     default_expr: Some("now()".into()),
                     identity: "".into(),
                     generated: "".into(),
+                    comment: None,
                 },
             ],
             constraints: vec![
                 DdlConstraint {
                     name: "orders_pkey".into(),
                     definition: "PRIMARY KEY (id)".into(),
+                    comment: None,
                 },
                 DdlConstraint {
                     name: "orders_total_check".into(),
                     definition: "CHECK ((total >= (0)::numeric))".into(),
+                    comment: None,
                 },
                 DdlConstraint {
                     name: "orders_cust_fkey".into(),
                     definition: "FOREIGN KEY (cust_id) REFERENCES customers(id)".into(),
+                    comment: None,
                 },
             ],
             index_defs: vec![
                 "CREATE INDEX orders_cust_idx ON public.orders USING btree (cust_id)".into(),
             ],
             partition_by: None,
+            comment: None,
         }
     }
 
@@ -270,4 +501,75 @@ mod tests {
             .columns_only
             .starts_with("CREATE TABLE \"9d56a337-0e17-4c6e-8ebc-ea490bef2923\".\"9d56a337-0e17-4c6e-8ebc-ea490bef2923\" ("));
     }
+
+    #[test]
+    fn full_appends_comments_with_escaped_text() {
+        let mut parts = sample_parts();
+        parts.comment = Some("Customer orders".into());
+        parts.columns[2].comment = Some("Pre-tax, in the customer's currency".into());
+        parts.constraints[1].comment = Some("No refunds here".into());
+        let ddl = compose_table_ddl("public", "orders", &parts);
+        assert!(ddl.full.contains("COMMENT ON TABLE \"public\".\"orders\" IS 'Customer orders';"));
+        assert!(ddl
+            .full
+            .contains("COMMENT ON COLUMN \"public\".\"orders\".\"total\" IS 'Pre-tax, in the customer''s currency';"));
+        assert!(ddl
+            .full
+            .contains("COMMENT ON CONSTRAINT \"orders_total_check\" ON \"public\".\"orders\" IS 'No refunds here';"));
+        assert!(!ddl.with_constraints.contains("COMMENT ON"));
+    }
+
+    #[test]
+    fn schema_ddl_defers_foreign_keys_and_ends_with_comments() {
+        let mut parts = sample_parts();
+        parts.comment = Some("Orders".into());
+        let tables = vec![DdlTable { name: "orders".into(), parts, partition_of: None }];
+        let functions = vec![DdlFunction {
+            signature: "\"public\".\"noop\"()".into(),
+            is_procedure: true,
+            definition: "CREATE OR REPLACE PROCEDURE public.noop()\n LANGUAGE sql\nAS $procedure$ SELECT 1 $procedure$\n".into(),
+            comment: Some("Does nothing".into()),
+        }];
+        let ddl = compose_schema_ddl("public", Some("Standard schema"), &tables, &functions);
+
+        let create = ddl.find("CREATE TABLE").unwrap();
+        let fk = ddl
+            .find("ALTER TABLE \"public\".\"orders\" ADD CONSTRAINT \"orders_cust_fkey\" FOREIGN KEY")
+            .unwrap();
+        let comment = ddl.find("COMMENT ON TABLE").unwrap();
+        assert!(create < fk && fk < comment);
+        assert!(!ddl[create..fk].contains("FOREIGN KEY"));
+        assert!(ddl.contains("COMMENT ON SCHEMA \"public\" IS 'Standard schema';"));
+        assert!(ddl.contains("$procedure$;"));
+        assert!(ddl.contains("COMMENT ON PROCEDURE \"public\".\"noop\"() IS 'Does nothing';"));
+    }
+
+    #[test]
+    fn view_ddl_variants() {
+        let view = DdlView {
+            materialized: false,
+            options: Some("security_barrier=true".into()),
+            query: " SELECT id\n   FROM orders\n  WHERE total > 0;".into(),
+            index_defs: vec![],
+            comment: None,
+        };
+        assert_eq!(
+            compose_view_ddl("public", "big_orders", &view),
+            "CREATE OR REPLACE VIEW \"public\".\"big_orders\" WITH (security_barrier=true) AS\n SELECT id\n   FROM orders\n  WHERE total > 0;"
+        );
+
+        let matview = DdlView {
+            materialized: true,
+            options: None,
+            query: " SELECT 1 AS one;".into(),
+            index_defs: vec!["CREATE UNIQUE INDEX mv_one ON public.mv USING btree (one)".into()],
+            comment: Some("Bob's view".into()),
+        };
+        assert_eq!(
+            compose_view_ddl("public", "mv", &matview),
+            "CREATE MATERIALIZED VIEW \"public\".\"mv\" AS\n SELECT 1 AS one;\n\n\
+             CREATE UNIQUE INDEX mv_one ON public.mv USING btree (one);\n\n\
+             COMMENT ON MATERIALIZED VIEW \"public\".\"mv\" IS 'Bob''s view';"
+        );
+    }
 }
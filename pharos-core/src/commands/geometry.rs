@@ -0,0 +1,202 @@
+//! Pure decoding of PostGIS (E)WKB hex text into GeoJSON, for geometry/geography columns.
+
+use serde_json::{json, Value};
+
+const SRID_FLAG: u32 = 0x2000_0000;
+const Z_FLAG: u32 = 0x8000_0000;
+const M_FLAG: u32 = 0x4000_0000;
+
+/// Deepest nesting of multi-geometries and collections decoded. Each level
+/// recurses, so corrupt or crafted input must not be able to exhaust the stack.
+const MAX_NESTING: usize = 32;
+
+/// Cursor over WKB bytes, honouring the per-geometry byte order marker.
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WkbReader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let slice = self.bytes.get(self.pos..self.pos + n)?;
+        self.pos += n;
+        Some(slice)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self, little: bool) -> Option<u32> {
+        let b: [u8; 4] = self.take(4)?.try_into().ok()?;
+        Some(if little { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    fn f64(&mut self, little: bool) -> Option<f64> {
+        let b: [u8; 8] = self.take(8)?.try_into().ok()?;
+        Some(if little { f64::from_le_bytes(b) } else { f64::from_be_bytes(b) })
+    }
+}
+
+/// Geometry header after the byte order marker: base type, coordinate dimensions
+/// to emit (2 or 3), and total stored dimensions per point.
+fn read_header(r: &mut WkbReader, little: bool) -> Option<(u32, usize, usize)> {
+    let raw = r.u32(little)?;
+    if raw & SRID_FLAG != 0 {
+        r.u32(little)?; // SRID — GeoJSON assumes WGS84, so it's not carried
+    }
+    // EWKB flags in the high bits, or ISO WKB's +1000/+2000/+3000 type offsets.
+    let flags = raw & (Z_FLAG | M_FLAG);
+    let iso = (raw & 0x0FFF_FFFF) / 1000;
+    let base = (raw & 0x0FFF_FFFF) % 1000;
+    let has_z = flags & Z_FLAG != 0 || iso == 1 || iso == 3;
+    let has_m = flags & M_FLAG != 0 || iso == 2 || iso == 3;
+    let stored = 2 + has_z as usize + has_m as usize;
+    let emitted = 2 + has_z as usize;
+    Some((base, emitted, stored))
+}
+
+fn read_position(r: &mut WkbReader, little: bool, emitted: usize, stored: usize) -> Option<Vec<f64>> {
+    let mut coords = Vec::with_capacity(emitted);
+    for i in 0..stored {
+        let v = r.f64(little)?;
+        if i < emitted {
+            coords.push(v);
+        }
+    }
+    Some(coords)
+}
+
+fn positions_json(positions: Vec<Vec<f64>>) -> Value {
+    Value::Array(positions.into_iter().map(|p| json!(p)).collect())
+}
+
+fn read_points(r: &mut WkbReader, little: bool, emitted: usize, stored: usize) -> Option<Value> {
+    let n = r.u32(little)? as usize;
+    let mut points = Vec::with_capacity(n.min(1 << 16));
+    for _ in 0..n {
+        points.push(read_position(r, little, emitted, stored)?);
+    }
+    Some(positions_json(points))
+}
+
+fn read_rings(r: &mut WkbReader, little: bool, emitted: usize, stored: usize) -> Option<Value> {
+    let n = r.u32(little)? as usize;
+    let mut rings = Vec::with_capacity(n.min(1 << 16));
+    for _ in 0..n {
+        rings.push(read_points(r, little, emitted, stored)?);
+    }
+    Some(Value::Array(rings))
+}
+
+/// Read one complete geometry (byte order marker + header + body) as GeoJSON.
+/// `depth` is how many multi-geometries or collections enclose it.
+fn read_geometry(r: &mut WkbReader, depth: usize) -> Option<Value> {
+    if depth > MAX_NESTING {
+        return None;
+    }
+    let little = match r.u8()? {
+        0 => false,
+        1 => true,
+        _ => return None,
+    };
+    let (base, emitted, stored) = read_header(r, little)?;
+    let geometry = match base {
+        1 => {
+            let p = read_position(r, little, emitted, stored)?;
+            // Empty points are encoded as all-NaN coordinates.
+            let coords = if p.iter().all(|c| c.is_nan()) { json!([]) } else { json!(p) };
+            json!({"type": "Point", "coordinates": coords})
+        }
+        2 => json!({"type": "LineString", "coordinates": read_points(r, little, emitted, stored)?}),
+        3 => json!({"type": "Polygon", "coordinates": read_rings(r, little, emitted, stored)?}),
+        4..=6 => {
+            let n = r.u32(little)? as usize;
+            let mut parts = Vec::with_capacity(n.min(1 << 16));
+            for _ in 0..n {
+                parts.push(read_geometry(r, depth + 1)?.get("coordinates")?.clone());
+            }
+            let kind = match base {
+                4 => "MultiPoint",
+                5 => "MultiLineString",
+                _ => "MultiPolygon",
+            };
+            json!({"type": kind, "coordinates": parts})
+        }
+        7 => {
+            let n = r.u32(little)? as usize;
+            let mut geometries = Vec::with_capacity(n.min(1 << 16));
+            for _ in 0..n {
+                geometries.push(read_geometry(r, depth + 1)?);
+            }
+            json!({"type": "GeometryCollection", "geometries": geometries})
+        }
+        _ => return None,
+    };
+    Some(geometry)
+}
+
+/// Decode PostGIS hex (E)WKB — the text output of geometry/geography columns —
+/// into a GeoJSON geometry. Returns None for anything that isn't valid WKB, so
+/// callers can fall back to the raw text.
+pub fn ewkb_hex_to_geojson(hex: &str) -> Option<Value> {
+    let hex = hex.trim();
+    if hex.len() < 10 || hex.len() % 2 != 0 {
+        return None;
+    }
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<_>>()?;
+    let mut reader = WkbReader { bytes: &bytes, pos: 0 };
+    let geometry = read_geometry(&mut reader, 0)?;
+    if reader.pos != bytes.len() {
+        return None;
+    }
+    Some(geometry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_plain_point() {
+        let g = ewkb_hex_to_geojson("0101000000000000000000F03F0000000000000040").unwrap();
+        assert_eq!(g, json!({"type": "Point", "coordinates": [1.0, 2.0]}));
+    }
+
+    #[test]
+    fn decodes_point_with_srid() {
+        let g = ewkb_hex_to_geojson("0101000020E6100000000000000000F03F0000000000000040").unwrap();
+        assert_eq!(g, json!({"type": "Point", "coordinates": [1.0, 2.0]}));
+    }
+
+    #[test]
+    fn decodes_linestring() {
+        // LINESTRING(0 0, 1 1)
+        let hex = "010200000002000000\
+                   00000000000000000000000000000000\
+                   000000000000F03F000000000000F03F";
+        let g = ewkb_hex_to_geojson(hex).unwrap();
+        assert_eq!(g, json!({"type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]]}));
+    }
+
+    #[test]
+    fn bounds_collection_nesting() {
+        // GEOMETRYCOLLECTION(GEOMETRYCOLLECTION(... POINT(1 2) ...)) nested `levels` deep
+        let nested = |levels: usize| {
+            format!("{}0101000000000000000000F03F0000000000000040", "010700000001000000".repeat(levels))
+        };
+        let g = ewkb_hex_to_geojson(&nested(MAX_NESTING)).unwrap();
+        assert_eq!(g["type"], "GeometryCollection");
+        assert!(ewkb_hex_to_geojson(&nested(MAX_NESTING + 1)).is_none());
+        assert!(ewkb_hex_to_geojson(&nested(1_000_000)).is_none());
+    }
+
+    #[test]
+    fn rejects_non_wkb_text() {
+        assert!(ewkb_hex_to_geojson("hello").is_none());
+        assert!(ewkb_hex_to_geojson("0101000000000000000000F03F").is_none());
+    }
+}
@@ -1,18 +1,35 @@
 
+use crate::commands::table::{escape_identifier, validate_identifier};
 use crate::db::postgres;
-use crate::models::{AnalyzeResult, ColumnInfo, ConstraintInfo, FunctionInfo, IndexInfo, PartitionRef, SchemaColumnInfo, SchemaInfo, TableInfo};
+use crate::models::{AnalyzeResult, BloatEstimate, ColumnInfo, CompletionTable, ConstraintInfo, FunctionInfo, IndexInfo, PartitionRef, SchemaColumnInfo, SchemaInfo, SequenceInfo, TableInfo, TableInheritance, TableRlsPolicies, TableTreeNode, TriggerInfo};
 use crate::state::AppState;
 
-/// Get all schemas for a connection
+/// Get all schemas for a connection. System schemas (pg_catalog,
+/// information_schema, pg_toast) are only included when `include_system` is set.
 pub async fn get_schemas(
     connection_id: String,
+    include_system: bool,
     state: &AppState,
 ) -> Result<Vec<SchemaInfo>, String> {
     let pool = state
         .get_pool(&connection_id)
         .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
 
-    postgres::get_schemas(&pool)
+    postgres::get_schemas(&pool, include_system)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List databases on the connection's server that the user can connect to
+pub async fn list_databases(
+    connection_id: String,
+    state: &AppState,
+) -> Result<Vec<String>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::list_databases(&pool)
         .await
         .map_err(|e| e.to_string())
 }
@@ -32,6 +49,30 @@ pub async fn get_tables(
         .map_err(|e| e.to_string())
 }
 
+/// Maximum number of tables returned by `search_tables`.
+const SEARCH_TABLES_LIMIT: u32 = 200;
+
+/// Filter tables and views by name across a connection, or within one schema.
+/// Results are ranked exact, then prefix, then substring matches.
+pub async fn search_tables(
+    connection_id: String,
+    term: String,
+    schema_name: Option<String>,
+    state: &AppState,
+) -> Result<Vec<TableInfo>, String> {
+    let term = term.trim();
+    if term.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::search_tables(&pool, term, schema_name.as_deref(), SEARCH_TABLES_LIMIT)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Get direct child partitions of a partitioned parent.
 pub async fn get_partitions(
     connection_id: String,
@@ -47,6 +88,36 @@ pub async fn get_partitions(
         .map_err(|e| e.to_string())
 }
 
+/// Get a table's direct inheritance parents and children.
+pub async fn get_inheritance(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    state: &AppState,
+) -> Result<TableInheritance, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+    postgres::get_inheritance(&pool, &schema_name, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get a table's row-level security flags and policies.
+pub async fn get_rls_policies(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    state: &AppState,
+) -> Result<TableRlsPolicies, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+    postgres::get_rls_policies(&pool, &schema_name, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Get parent→child partition name map for a schema (filter index).
 pub async fn get_partition_map(
     connection_id: String,
@@ -85,6 +156,41 @@ pub async fn analyze_schema(
     Ok(result)
 }
 
+/// Refresh a materialized view, returning how long it took in milliseconds.
+/// `concurrently` keeps the view readable during the refresh but needs a
+/// unique index on it; PostgreSQL's hint is passed on when that's missing.
+pub async fn refresh_materialized_view(
+    connection_id: String,
+    schema_name: String,
+    view_name: String,
+    concurrently: bool,
+    state: &AppState,
+) -> Result<u64, String> {
+    validate_identifier(&schema_name)?;
+    validate_identifier(&view_name)?;
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let sql = format!(
+        "REFRESH MATERIALIZED VIEW {}\"{}\".\"{}\"",
+        if concurrently { "CONCURRENTLY " } else { "" },
+        escape_identifier(&schema_name),
+        escape_identifier(&view_name)
+    );
+    let start = std::time::Instant::now();
+    sqlx::raw_sql(&sql).execute(&pool).await.map_err(|e| {
+        let hint = e
+            .as_database_error()
+            .and_then(|db| db.try_downcast_ref::<sqlx::postgres::PgDatabaseError>())
+            .and_then(|pg| pg.hint())
+            .map(|h| format!(". {}", h))
+            .unwrap_or_default();
+        format!("Failed to refresh materialized view: {}{}", e, hint)
+    })?;
+    Ok(start.elapsed().as_millis() as u64)
+}
+
 /// Get all columns for a table
 pub async fn get_columns(
     connection_id: String,
@@ -116,6 +222,33 @@ pub async fn get_schema_columns(
         .map_err(|e| e.to_string())
 }
 
+/// Table and column names for editor autocompletion, cached per connection.
+/// `include_details` adds column types and comments (for hover tooltips);
+/// each variant is loaded and cached separately.
+pub async fn get_completion_metadata(
+    connection_id: String,
+    include_details: bool,
+    state: &AppState,
+) -> Result<Vec<CompletionTable>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+    if let Some(tables) = state.completion_metadata(&connection_id, include_details) {
+        return Ok(tables);
+    }
+
+    let tables = postgres::get_completion_metadata(&pool, include_details)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.set_completion_metadata(&connection_id, include_details, tables.clone());
+    Ok(tables)
+}
+
+/// Forget cached autocompletion metadata, e.g. after DDL changed the schema.
+pub fn clear_completion_metadata(connection_id: String, state: &AppState) {
+    state.clear_completion_metadata(&connection_id);
+}
+
 /// Get indexes for a table
 pub async fn get_table_indexes(
     connection_id: String,
@@ -148,6 +281,63 @@ pub async fn get_table_constraints(
         .map_err(|e| e.to_string())
 }
 
+/// Get user-defined triggers for a table
+pub async fn get_table_triggers(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    state: &AppState,
+) -> Result<Vec<TriggerInfo>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::get_table_triggers(&pool, &schema_name, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Load every table in a schema together with its columns, indexes and constraints.
+/// Tables are fetched concurrently, bounded by a semaphore that leaves one pool
+/// connection free so the rest of the app isn't starved while the tree loads.
+pub async fn load_schema_tree(
+    connection_id: String,
+    schema_name: String,
+    state: &AppState,
+) -> Result<Vec<TableTreeNode>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let tables = postgres::get_tables(&pool, &schema_name)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let permits = (pool.options().get_max_connections() as usize).saturating_sub(1).max(1);
+    let semaphore = tokio::sync::Semaphore::new(permits);
+
+    let loads = tables.into_iter().map(|table| {
+        let pool = &pool;
+        let semaphore = &semaphore;
+        let schema_name = &schema_name;
+        async move {
+            let _permit = semaphore.acquire().await.map_err(|e| e.to_string())?;
+            let columns = postgres::get_columns(pool, schema_name, &table.name)
+                .await
+                .map_err(|e| e.to_string())?;
+            let indexes = postgres::get_table_indexes(pool, schema_name, &table.name)
+                .await
+                .map_err(|e| e.to_string())?;
+            let constraints = postgres::get_table_constraints(pool, schema_name, &table.name)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok::<_, String>(TableTreeNode { table, columns, indexes, constraints })
+        }
+    });
+
+    futures::future::join_all(loads).await.into_iter().collect()
+}
+
 /// Get functions and procedures in a schema
 pub async fn get_schema_functions(
     connection_id: String,
@@ -163,3 +353,32 @@ pub async fn get_schema_functions(
         .map_err(|e| e.to_string())
 }
 
+/// Get sequences in a schema
+pub async fn get_sequences(
+    connection_id: String,
+    schema_name: String,
+    state: &AppState,
+) -> Result<Vec<SequenceInfo>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::get_sequences(&pool, &schema_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Estimated table and index bloat in a schema, largest waste first
+pub async fn get_bloat_estimate(
+    connection_id: String,
+    schema_name: String,
+    state: &AppState,
+) -> Result<Vec<BloatEstimate>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::get_bloat_estimates(&pool, &schema_name)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,19 +1,27 @@
 pub mod connection;
+pub mod datetime_format;
 pub mod ddl;
+pub mod geometry;
 pub mod metadata;
+pub mod plan;
 pub mod query;
 pub mod query_history;
 pub mod saved_query;
+pub mod server;
 pub mod settings;
 pub mod table;
 pub mod workspace;
 
 pub use connection::*;
+pub use datetime_format::*;
 pub use ddl::*;
+pub use geometry::*;
 pub use metadata::*;
+pub use plan::*;
 pub use query::*;
 pub use query_history::*;
 pub use saved_query::*;
+pub use server::*;
 pub use settings::*;
 pub use table::*;
 pub use workspace::*;
@@ -0,0 +1,264 @@
+//! Stored EXPLAIN (FORMAT JSON) plans per statement, and node-by-node comparison of two runs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::{AppState, StoredPlan};
+
+/// A cost or row count must move by at least this factor to count as changed.
+const SIGNIFICANT_CHANGE_RATIO: f64 = 1.5;
+
+/// One plan node position compared across two runs. `path` is the chain of
+/// child indexes from the root; a side is `None` when that run has no node there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanNodeDiff {
+    pub path: Vec<usize>,
+    pub base_node: Option<String>,
+    pub other_node: Option<String>,
+    pub base_total_cost: Option<f64>,
+    pub other_total_cost: Option<f64>,
+    /// Actual rows across all loops (EXPLAIN ANALYZE only).
+    pub base_actual_rows: Option<f64>,
+    pub other_actual_rows: Option<f64>,
+    /// The node type or relation differs, or exists in only one plan.
+    pub node_changed: bool,
+    pub cost_changed: bool,
+    pub rows_changed: bool,
+}
+
+/// Node-by-node comparison of two stored plans for the same query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanComparison {
+    pub base_id: String,
+    pub other_id: String,
+    pub base_planning_time_ms: Option<f64>,
+    pub other_planning_time_ms: Option<f64>,
+    pub base_execution_time_ms: Option<f64>,
+    pub other_execution_time_ms: Option<f64>,
+    pub nodes: Vec<PlanNodeDiff>,
+}
+
+/// Key plans by the statement being explained: EXPLAIN and its options are
+/// dropped and whitespace collapsed, so `EXPLAIN ANALYZE q` and `EXPLAIN q` match.
+pub fn normalize_plan_sql(sql: &str) -> String {
+    let mut rest = sql.trim().trim_end_matches(';').trim();
+    if rest.get(..7).is_some_and(|w| w.eq_ignore_ascii_case("EXPLAIN")) {
+        rest = rest[7..].trim_start();
+        if let Some(options) = rest.strip_prefix('(') {
+            rest = options.find(')').map(|i| options[i + 1..].trim_start()).unwrap_or("");
+        } else {
+            loop {
+                let word_len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let word = &rest[..word_len];
+                if ["ANALYZE", "ANALYSE", "VERBOSE"].iter().any(|k| word.eq_ignore_ascii_case(k)) {
+                    rest = rest[word_len..].trim_start();
+                } else {
+                    break;
+                }
+            }
+        }
+    }
+    rest.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Store an EXPLAIN (FORMAT JSON) result for later comparison. Accepts the raw
+/// JSON array EXPLAIN returns or its single element.
+pub fn record_plan(
+    connection_id: String,
+    sql: String,
+    plan_json: String,
+    state: &AppState,
+) -> Result<StoredPlan, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(&plan_json).map_err(|e| format!("Invalid plan JSON: {}", e))?;
+    let plan = match value {
+        serde_json::Value::Array(mut items) if items.len() == 1 => items.remove(0),
+        other => other,
+    };
+    if plan.get("Plan").map_or(true, |p| !p.is_object()) {
+        return Err("Not an EXPLAIN (FORMAT JSON) plan".to_string());
+    }
+
+    let stored = StoredPlan {
+        id: uuid::Uuid::new_v4().to_string(),
+        captured_at: chrono::Utc::now().to_rfc3339(),
+        plan,
+    };
+    state.push_plan(&connection_id, normalize_plan_sql(&sql), stored.clone());
+    Ok(stored)
+}
+
+/// Plans stored for a query on a connection, oldest first.
+pub fn get_stored_plans(connection_id: String, sql: String, state: &AppState) -> Vec<StoredPlan> {
+    state.get_plans(&connection_id, &normalize_plan_sql(&sql))
+}
+
+/// Compare two stored plans of a query by ID.
+pub fn compare_plans(
+    connection_id: String,
+    sql: String,
+    base_id: String,
+    other_id: String,
+    state: &AppState,
+) -> Result<PlanComparison, String> {
+    let plans = get_stored_plans(connection_id, sql, state);
+    let find = |id: &str| {
+        plans
+            .iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("Plan not found: {}", id))
+    };
+    Ok(diff_plans(find(&base_id)?, find(&other_id)?))
+}
+
+fn diff_plans(base: &StoredPlan, other: &StoredPlan) -> PlanComparison {
+    let number = |plan: &serde_json::Value, key: &str| plan.get(key).and_then(|v| v.as_f64());
+    let mut nodes = Vec::new();
+    diff_nodes(base.plan.get("Plan"), other.plan.get("Plan"), &mut Vec::new(), &mut nodes);
+    PlanComparison {
+        base_id: base.id.clone(),
+        other_id: other.id.clone(),
+        base_planning_time_ms: number(&base.plan, "Planning Time"),
+        other_planning_time_ms: number(&other.plan, "Planning Time"),
+        base_execution_time_ms: number(&base.plan, "Execution Time"),
+        other_execution_time_ms: number(&other.plan, "Execution Time"),
+        nodes,
+    }
+}
+
+/// Walk both trees in step. Children are only compared while the node at a
+/// position is the same; below a changed node the shapes aren't comparable.
+fn diff_nodes(
+    base: Option<&serde_json::Value>,
+    other: Option<&serde_json::Value>,
+    path: &mut Vec<usize>,
+    out: &mut Vec<PlanNodeDiff>,
+) {
+    let base_node = base.map(node_label);
+    let other_node = other.map(node_label);
+    let cost = |n: Option<&serde_json::Value>| n.and_then(|n| n.get("Total Cost")).and_then(|v| v.as_f64());
+    let rows = |n: Option<&serde_json::Value>| {
+        let n = n?;
+        let per_loop = n.get("Actual Rows")?.as_f64()?;
+        let loops = n.get("Actual Loops").and_then(|v| v.as_f64()).unwrap_or(1.0);
+        Some(per_loop * loops)
+    };
+    let node_changed = base_node != other_node;
+    let (base_cost, other_cost) = (cost(base), cost(other));
+    let (base_rows, other_rows) = (rows(base), rows(other));
+    out.push(PlanNodeDiff {
+        path: path.clone(),
+        base_node,
+        other_node,
+        base_total_cost: base_cost,
+        other_total_cost: other_cost,
+        base_actual_rows: base_rows,
+        other_actual_rows: other_rows,
+        node_changed,
+        cost_changed: changed_significantly(base_cost, other_cost),
+        rows_changed: changed_significantly(base_rows, other_rows),
+    });
+    if node_changed {
+        return;
+    }
+
+    let (base_children, other_children) = (child_nodes(base), child_nodes(other));
+    for i in 0..base_children.len().max(other_children.len()) {
+        path.push(i);
+        diff_nodes(base_children.get(i), other_children.get(i), path, out);
+        path.pop();
+    }
+}
+
+fn child_nodes(node: Option<&serde_json::Value>) -> &[serde_json::Value] {
+    node.and_then(|n| n.get("Plans"))
+        .and_then(|p| p.as_array())
+        .map(|a| a.as_slice())
+        .unwrap_or(&[])
+}
+
+/// "Seq Scan on orders", "Index Scan using orders_pkey on orders", "Hash Join".
+fn node_label(node: &serde_json::Value) -> String {
+    let field = |key: &str| node.get(key).and_then(|v| v.as_str());
+    let mut label = field("Node Type").unwrap_or("?").to_string();
+    if let Some(index) = field("Index Name") {
+        label.push_str(&format!(" using {}", index));
+    }
+    if let Some(relation) = field("Relation Name") {
+        label.push_str(&format!(" on {}", relation));
+    }
+    label
+}
+
+fn changed_significantly(base: Option<f64>, other: Option<f64>) -> bool {
+    match (base, other) {
+        (Some(a), Some(b)) => {
+            let (low, high) = if a <= b { (a, b) } else { (b, a) };
+            // Ignore jitter on tiny values (e.g. 0 vs 1 row).
+            high - low >= 1.0 && high >= low.max(1.0) * SIGNIFICANT_CHANGE_RATIO
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(id: &str, plan: serde_json::Value) -> StoredPlan {
+        StoredPlan { id: id.to_string(), captured_at: String::new(), plan }
+    }
+
+    #[test]
+    fn explain_prefix_is_ignored_for_the_key() {
+        let plain = normalize_plan_sql("select *\n  from t where id = 1;");
+        assert_eq!(plain, "select * from t where id = 1");
+        assert_eq!(normalize_plan_sql("EXPLAIN ANALYZE select * from t where id = 1"), plain);
+        assert_eq!(normalize_plan_sql("explain (analyze, buffers) select * from t where id = 1"), plain);
+    }
+
+    #[test]
+    fn diff_flags_changed_nodes_and_rows() {
+        let base = stored("a", serde_json::json!({
+            "Plan": {"Node Type": "Hash Join", "Total Cost": 1000.0, "Actual Rows": 10.0, "Plans": [
+                {"Node Type": "Seq Scan", "Relation Name": "orders", "Total Cost": 900.0, "Actual Rows": 5000.0},
+                {"Node Type": "Hash", "Total Cost": 50.0, "Actual Rows": 20.0, "Actual Loops": 1.0},
+            ]},
+            "Execution Time": 42.0,
+        }));
+        let other = stored("b", serde_json::json!({
+            "Plan": {"Node Type": "Hash Join", "Total Cost": 120.0, "Actual Rows": 10.0, "Plans": [
+                {"Node Type": "Index Scan", "Index Name": "orders_customer_idx", "Relation Name": "orders", "Total Cost": 30.0, "Actual Rows": 12.0},
+                {"Node Type": "Hash", "Total Cost": 55.0, "Actual Rows": 20.0},
+            ]},
+            "Execution Time": 3.0,
+        }));
+        let diff = diff_plans(&base, &other);
+        assert_eq!(diff.base_execution_time_ms, Some(42.0));
+        assert_eq!(diff.nodes.len(), 3);
+
+        let root = &diff.nodes[0];
+        assert!(!root.node_changed && root.cost_changed && !root.rows_changed);
+
+        let scan = &diff.nodes[1];
+        assert_eq!(scan.path, vec![0]);
+        assert_eq!(scan.other_node.as_deref(), Some("Index Scan using orders_customer_idx on orders"));
+        assert!(scan.node_changed);
+
+        let hash = &diff.nodes[2];
+        assert!(!hash.node_changed && !hash.cost_changed && !hash.rows_changed);
+    }
+
+    #[test]
+    fn stored_plans_are_dropped_on_disconnect() {
+        let state = AppState::new(rusqlite::Connection::open_in_memory().unwrap());
+        let plan = r#"[{"Plan": {"Node Type": "Seq Scan"}}]"#.to_string();
+        record_plan("c1".to_string(), "select 1".to_string(), plan.clone(), &state).unwrap();
+        record_plan("c2".to_string(), "select 1".to_string(), plan, &state).unwrap();
+
+        state.remove_pool("c1");
+        assert!(get_stored_plans("c1".to_string(), "select 1".to_string(), &state).is_empty());
+        assert_eq!(get_stored_plans("c2".to_string(), "select 1".to_string(), &state).len(), 1);
+    }
+}
@@ -4,9 +4,10 @@ use sqlx::{Column, Executor, Row, ValueRef};
 use std::sync::atomic::Ordering;
 use std::time::Instant;
 
-use crate::db::sqlite;
-use crate::models::QueryHistoryEntry;
-use crate::state::AppState;
+use crate::db::notices::capture_notices;
+use crate::db::{postgres, sqlite};
+use crate::models::{QueryHistoryEntry, QueryProfile, StatementCounters};
+use crate::state::{AppState, OpenCursor, QueryRegistration, RunningQueryInfo};
 
 /// Validate and set the search_path on a connection for a given schema.
 /// Validates: non-empty, 1-63 chars, no null bytes. Escapes `"` as `""`.
@@ -28,15 +29,62 @@ pub(crate) async fn set_search_path(
     Ok(())
 }
 
-/// Read the user's query timeout (seconds) from settings, falling back to the default.
-fn query_timeout_seconds(state: &AppState) -> u32 {
+/// The value `current_setting('search_path')` reports after `set_search_path`.
+/// PostgreSQL only quotes names that need it; when this guess differs from the
+/// server's rendering (e.g. a keyword), the comparison just fails and SET runs.
+fn search_path_setting(schema_name: &str) -> String {
+    let plain = schema_name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && schema_name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '$');
+    if plain {
+        format!("{}, public", schema_name)
+    } else {
+        format!("\"{}\", public", schema_name.replace('"', "\"\""))
+    }
+}
+
+/// Read the user's query settings, falling back to the defaults.
+pub(crate) fn query_settings(state: &AppState) -> crate::models::QuerySettings {
     state
         .metadata_db
         .lock()
         .ok()
         .and_then(|db| sqlite::load_settings(&db).ok())
-        .map(|s| s.query.timeout_seconds)
-        .unwrap_or_else(|| crate::models::QuerySettings::default().timeout_seconds)
+        .map(|s| s.query)
+        .unwrap_or_default()
+}
+
+/// Whether executions on this connection should be written to query history:
+/// off when history is disabled globally or for the connection.
+fn history_enabled(state: &AppState, connection_id: &str, settings: &crate::models::QuerySettings) -> bool {
+    settings.history_enabled
+        && !state
+            .get_config(connection_id)
+            .map(|c| c.disable_history)
+            .unwrap_or(false)
+}
+
+/// The query timeout (seconds) for a connection: its session override if set,
+/// otherwise the user's setting (falling back to the default).
+fn query_timeout_seconds(state: &AppState, connection_id: &str) -> u32 {
+    state
+        .session_timeout(connection_id)
+        .unwrap_or_else(|| query_settings(state).timeout_seconds)
+}
+
+/// Clamp a requested row limit to the configured safety cap.
+/// Returns the effective limit and whether the cap was applied.
+fn clamp_to_row_cap(requested: u64, max_result_rows: u32) -> (u64, bool) {
+    let cap = (max_result_rows as u64).max(1);
+    if requested > cap {
+        (cap, true)
+    } else {
+        (requested, false)
+    }
 }
 
 /// Apply the user's statement timeout on this connection. PostgreSQL-specific —
@@ -52,6 +100,12 @@ async fn apply_statement_timeout(
     Ok(())
 }
 
+/// The query timeout as a `SET LOCAL`, for a transaction that spans several
+/// calls (a cursor's); it ends with the transaction instead of needing a reset.
+fn local_statement_timeout_sql(timeout_seconds: u32) -> String {
+    format!("SET LOCAL statement_timeout = {}", (timeout_seconds as u64).saturating_mul(1000))
+}
+
 /// Reset statement_timeout before the connection returns to the pool so that
 /// metadata queries and background ANALYZE on reused connections aren't capped
 /// by the per-query timeout.
@@ -61,6 +115,36 @@ async fn reset_statement_timeout(conn: &mut sqlx::pool::PoolConnection<sqlx::Pos
         .await;
 }
 
+/// Format a failed query's error, naming the limit when it hit the statement
+/// timeout. A user cancel fails with the same SQLSTATE (57014), so the
+/// query's cancel flag tells them apart; the message text may be localized.
+fn describe_query_error(error: &sqlx::Error, timeout_seconds: u32, user_cancelled: bool) -> String {
+    if timeout_seconds > 0 && !user_cancelled && pg_error_code(error).as_deref() == Some("57014") {
+        format!("Query timed out after {}s", timeout_seconds)
+    } else {
+        format_db_error(error)
+    }
+}
+
+/// Switch the session to `role` for `execute_query`'s `run_as_role`.
+async fn set_role(conn: &mut sqlx::pool::PoolConnection<sqlx::Postgres>, role: &str) -> Result<(), String> {
+    super::table::validate_identifier(role)?;
+    let set_sql = format!("SET ROLE \"{}\"", role.replace('"', "\"\""));
+    (&mut **conn)
+        .execute(sqlx::raw_sql(&set_sql))
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Cannot run as role \"{}\": {}", role, clean_error_message(&e.to_string())))
+}
+
+/// Undo `set_role`. A session whose role can't be reset must never be reused,
+/// so on failure the connection is closed instead of returned to the pool.
+async fn reset_role(conn: &mut sqlx::pool::PoolConnection<sqlx::Postgres>) {
+    if (&mut **conn).execute(sqlx::raw_sql("RESET ROLE")).await.is_err() {
+        conn.close_on_drop();
+    }
+}
+
 /// Format a database error, preserving PostgreSQL's character position if available.
 /// sqlx's `.to_string()` drops the position field; this re-extracts it from PgDatabaseError.
 fn format_db_error(e: &sqlx::Error) -> String {
@@ -78,6 +162,20 @@ fn format_db_error(e: &sqlx::Error) -> String {
 pub struct ColumnDef {
     pub name: String,
     pub data_type: String,
+    /// PostgreSQL type OID — stable for domains, arrays and extension types
+    /// whose names vary. 0 when the server didn't report one.
+    #[serde(default)]
+    pub type_oid: u32,
+}
+
+impl ColumnDef {
+    fn from_column(col: &sqlx::postgres::PgColumn) -> Self {
+        ColumnDef {
+            name: col.name().to_string(),
+            data_type: col.type_info().to_string(),
+            type_oid: col.type_info().oid().map(|oid| oid.0).unwrap_or(0),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,9 +186,23 @@ pub struct QueryResult {
     pub execution_time_ms: u64,
     pub has_more: bool,
     pub history_entry_id: Option<String>,
+    /// True when the requested limit exceeded `max_result_rows` and was clamped,
+    /// so the UI can explain why fewer rows came back than asked for.
+    #[serde(default)]
+    pub row_cap_applied: bool,
+    /// NOTICE/WARNING messages the server sent while the query ran.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notices: Vec<String>,
+    /// Hash of the normalized SQL and the server snapshot it ran under. A re-run
+    /// with a different key may see different data; None when the server
+    /// couldn't report a snapshot.
+    #[serde(default)]
+    pub snapshot_id: Option<String>,
 }
 
-/// Execute a SQL query and return results
+/// Execute a SQL query and return results. With `run_as_role` the query runs
+/// after `SET ROLE`, e.g. to check grants or RLS policies; the role is reset afterwards.
+#[allow(clippy::too_many_arguments)]
 pub async fn execute_query(
     connection_id: String,
     sql: String,
@@ -98,55 +210,205 @@ pub async fn execute_query(
     limit: Option<u32>,
     schema: Option<String>,
     source: Option<String>,
+    run_as_role: Option<String>,
     state: &AppState,
 ) -> Result<QueryResult, String> {
     let pool = state
         .get_pool(&connection_id)
         .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
 
-    let limit = limit.unwrap_or(1000);
-    let start = Instant::now();
+    // Acquire a dedicated connection from the pool so that SET search_path
+    // and the query run on the same connection. The query is registered
+    // (without a backend yet) while waiting so it can be cancelled.
     let query_id = query_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let registration = state.track_query(query_id, &connection_id);
+    let conn = acquire_with_retry(&pool, query_settings(state).acquire_retries, registration.cancelled()).await?;
 
-    // Acquire a dedicated connection from the pool so that SET search_path
-    // and the query run on the same connection
-    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    with_notices(run_query_on_connection(&pool, conn, connection_id, sql, None, registration, limit, schema, source, run_as_role.as_deref(), state)).await
+}
+
+/// Wait before the first acquire retry; doubled for each later one.
+const ACQUIRE_RETRY_INITIAL_DELAY: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// How long an acquire attempt that will be retried waits for a free
+/// connection; only the last attempt waits out the pool's own timeout.
+const ACQUIRE_ATTEMPT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Acquire a pooled connection, retrying up to `retries` more times with
+/// exponential backoff when the pool is exhausted (`PoolTimedOut`). Any other
+/// error means the server or network is unusable and fails at once.
+async fn acquire_with_retry(
+    pool: &sqlx::PgPool,
+    retries: u32,
+    cancelled: &std::sync::atomic::AtomicBool,
+) -> Result<sqlx::pool::PoolConnection<sqlx::Postgres>, String> {
+    let mut delay = ACQUIRE_RETRY_INITIAL_DELAY;
+    let mut attempt = 0;
+    loop {
+        let acquire = async {
+            if attempt < retries {
+                tokio::time::timeout(ACQUIRE_ATTEMPT_TIMEOUT, pool.acquire())
+                    .await
+                    .unwrap_or(Err(sqlx::Error::PoolTimedOut))
+            } else {
+                pool.acquire().await
+            }
+        };
+        let acquired = tokio::select! {
+            acquired = acquire => acquired,
+            _ = wait_for_cancel(cancelled) => return Err("Query was cancelled".to_string()),
+        };
+        match acquired {
+            Ok(conn) => return Ok(conn),
+            Err(sqlx::Error::PoolTimedOut) if attempt < retries => {}
+            Err(e) => return Err(e.to_string()),
+        }
+        attempt += 1;
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = wait_for_cancel(cancelled) => return Err("Query was cancelled".to_string()),
+        }
+        delay *= 2;
+    }
+}
+
+/// Resolve once `cancelled` is set.
+async fn wait_for_cancel(cancelled: &std::sync::atomic::AtomicBool) {
+    while !cancelled.load(Ordering::SeqCst) {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+}
+
+/// The session's current transaction snapshot (`xmin:xmax:xip_list`), or None
+/// if the server can't report one. `txid_current_snapshot` is used over
+/// `pg_current_snapshot` because it exists on servers older than 13.
+async fn current_snapshot(conn: &mut sqlx::pool::PoolConnection<sqlx::Postgres>) -> Option<String> {
+    let rows = (&mut **conn)
+        .fetch_all(sqlx::raw_sql("SELECT txid_current_snapshot()::text"))
+        .await
+        .ok()?;
+    rows.first().and_then(|row| row.try_get::<String, _>(0).ok())
+}
+
+/// Memoization key for a result: SHA-256 of the SQL, with whitespace collapsed
+/// outside literals, quoted identifiers and comments, and the snapshot it ran under.
+fn snapshot_key(sql: &str, snapshot: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let chars: Vec<char> = sql.chars().collect();
+    let mut normalized = String::with_capacity(sql.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(next) = skip_non_code(&chars, i) {
+            normalized.extend(&chars[i..next]);
+            i = next;
+        } else if chars[i].is_whitespace() {
+            while i < chars.len() && chars[i].is_whitespace() {
+                i += 1;
+            }
+            normalized.push(' ');
+        } else {
+            normalized.push(chars[i]);
+            i += 1;
+        }
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(normalized.trim().trim_end_matches(';').trim_end().as_bytes());
+    hasher.update([0]);
+    hasher.update(snapshot.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Await a query, attaching the server notices it raised to its result.
+async fn with_notices(
+    query: impl std::future::Future<Output = Result<QueryResult, String>>,
+) -> Result<QueryResult, String> {
+    let (result, notices) = capture_notices(query).await;
+    result.map(|mut result| {
+        result.notices = notices;
+        result
+    })
+}
+
+/// Body of `execute_query`, run on a connection the caller already holds so
+/// that preceding work (e.g. an EXPLAIN) shares its session state. When `sql`
+/// wraps the user's statement, `history_sql` is the statement to record.
+#[allow(clippy::too_many_arguments)]
+async fn run_query_on_connection(
+    pool: &sqlx::PgPool,
+    mut conn: sqlx::pool::PoolConnection<sqlx::Postgres>,
+    connection_id: String,
+    sql: String,
+    history_sql: Option<String>,
+    registration: QueryRegistration<'_>,
+    limit: Option<u32>,
+    schema: Option<String>,
+    source: Option<String>,
+    run_as_role: Option<&str>,
+    state: &AppState,
+) -> Result<QueryResult, String> {
+    let settings = query_settings(state);
+    let value_options = ValueOptions::from_settings(&settings);
+    let (limit, row_cap_applied) = clamp_to_row_cap(
+        limit.unwrap_or(1000) as u64,
+        settings.max_result_rows,
+    );
+    let limit = limit as u32;
+    let start = Instant::now();
 
     // Get the backend PID for this connection so we can cancel it later.
     // Use raw_sql (simple protocol) and make it optional — non-PG servers
     // like ClickHouse don't have pg_backend_pid(). If the call fails and
-    // kills the connection, re-acquire a fresh one.
-    let backend_pid: i32 = {
-        let mut stream = sqlx::raw_sql("SELECT pg_backend_pid()").fetch(&mut *conn);
+    // kills the connection, re-acquire a fresh one. When a schema is requested
+    // the session's current search_path comes back in the same round trip, so
+    // a redundant SET can be skipped.
+    let check_search_path = settings.skip_redundant_search_path && schema.is_some();
+    let pid_sql = if check_search_path {
+        "SELECT pg_backend_pid(), current_setting('search_path')"
+    } else {
+        "SELECT pg_backend_pid()"
+    };
+    let (backend_pid, current_search_path): (i32, Option<String>) = {
+        let mut stream = sqlx::raw_sql(pid_sql).fetch(&mut *conn);
         match stream.next().await {
             Some(Ok(row)) => {
                 let pid = row.try_get::<i32, _>(0).unwrap_or(0);
+                let search_path = if check_search_path { row.try_get::<String, _>(1).ok() } else { None };
                 drop(stream);
-                pid
+                (pid, search_path)
             }
             _ => {
                 drop(stream);
                 // Connection may be dead — re-acquire
                 drop(conn);
                 conn = pool.acquire().await.map_err(|e| e.to_string())?;
-                0
+                (0, None)
             }
         }
     };
 
-    // Register this query for potential cancellation
-    let cancelled = state.register_query(query_id.clone(), backend_pid);
+    // Let a cancel reach the backend from here on; one that arrived earlier
+    // stops the query before it starts.
+    registration.set_backend_pid(backend_pid);
+    let cancelled = registration.cancelled();
+    if cancelled.load(Ordering::SeqCst) {
+        return Err("Query was cancelled".to_string());
+    }
 
     // Apply the user's query timeout on this connection. Non-PG servers don't
     // support it — re-acquire on failure (the failed SET may kill the connection).
-    if apply_statement_timeout(&mut conn, query_timeout_seconds(state)).await.is_err() {
+    let timeout_seconds = query_timeout_seconds(state, &connection_id);
+    if apply_statement_timeout(&mut conn, timeout_seconds).await.is_err() {
         drop(conn);
         conn = pool.acquire().await.map_err(|e| e.to_string())?;
     }
 
     // Set search_path if schema is specified. Non-PG servers like ClickHouse
     // don't support this — silently skip on failure rather than blocking the query.
-    if let Some(ref schema_name) = schema {
+    let search_path_current = match (&schema, &current_search_path) {
+        (Some(schema_name), Some(current)) => *current == search_path_setting(schema_name),
+        _ => false,
+    };
+    if let Some(schema_name) = schema.as_deref().filter(|_| !search_path_current) {
         if let Err(_) = set_search_path(&mut conn, schema_name).await {
             // Connection may be dead — re-acquire
             drop(conn);
@@ -154,38 +416,74 @@ pub async fn execute_query(
         }
     }
 
-    // Use simple query protocol (text format) — PostgreSQL formats all values as text,
-    // so we get arrays as {1,2,3}, timestamps as 2024-01-15 12:34:56, etc.
-    let mut stream = sqlx::raw_sql(&sql).fetch(&mut *conn);
+    // Set last, after any step that may swap the connection, so the role is
+    // always reset on the connection it was set on.
+    if let Some(role) = run_as_role {
+        if let Err(e) = set_role(&mut conn, role).await {
+            reset_statement_timeout(&mut conn).await;
+            return Err(e);
+        }
+    }
+
+    // Read before the query runs, so a commit landing while it executes can't be
+    // credited to the result. Only on PostgreSQL (a real backend PID) —
+    // elsewhere the lookup may fail and take the connection down with it.
+    let snapshot = if backend_pid != 0 { current_snapshot(&mut conn).await } else { None };
+
     let mut rows: Vec<sqlx::postgres::PgRow> = Vec::with_capacity((limit + 1) as usize);
     let mut fetch_error: Option<String> = None;
 
-    while let Some(row_result) = stream.next().await {
-        // Check for cancellation
-        if cancelled.load(Ordering::SeqCst) {
-            drop(stream);
-            state.unregister_query(&query_id);
-            reset_statement_timeout(&mut conn).await;
-            return Err("Query was cancelled".to_string());
+    let mut stream_rows = true;
+    if settings.fetch_size > 0 && is_cursor_eligible(&sql) {
+        match fetch_via_cursor(&mut conn, &sql, limit as usize + 1, settings.fetch_size, timeout_seconds, cancelled).await {
+            Ok(Some(fetched)) => {
+                rows = fetched;
+                stream_rows = false;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                fetch_error = Some(e);
+                stream_rows = false;
+            }
         }
+    }
+    if stream_rows {
+        // Use simple query protocol (text format) — PostgreSQL formats all values as text,
+        // so we get arrays as {1,2,3}, timestamps as 2024-01-15 12:34:56, etc.
+        let mut stream = sqlx::raw_sql(&sql).fetch(&mut *conn);
+
+        while let Some(row_result) = stream.next().await {
+            // Check for cancellation
+            if cancelled.load(Ordering::SeqCst) {
+                drop(stream);
+                reset_statement_timeout(&mut conn).await;
+                if run_as_role.is_some() {
+                    reset_role(&mut conn).await;
+                }
+                return Err("Query was cancelled".to_string());
+            }
 
-        match row_result {
-            Ok(row) => {
-                rows.push(row);
-                if rows.len() > limit as usize {
+            match row_result {
+                Ok(row) => {
+                    rows.push(row);
+                    if rows.len() > limit as usize {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    fetch_error = Some(describe_query_error(&e, timeout_seconds, cancelled.load(Ordering::SeqCst)));
                     break;
                 }
             }
-            Err(e) => {
-                fetch_error = Some(format_db_error(&e));
-                break;
-            }
         }
     }
 
-    drop(stream);
-    state.unregister_query(&query_id);
+    drop(registration);
+    let snapshot_id = snapshot.filter(|_| fetch_error.is_none()).map(|snapshot| snapshot_key(&sql, &snapshot));
     reset_statement_timeout(&mut conn).await;
+    if run_as_role.is_some() {
+        reset_role(&mut conn).await;
+    }
 
     if let Some(err) = fetch_error {
         return Err(err);
@@ -200,10 +498,7 @@ pub async fn execute_query(
             Ok(desc) => desc
                 .columns()
                 .iter()
-                .map(|col| ColumnDef {
-                    name: col.name().to_string(),
-                    data_type: col.type_info().to_string(),
-                })
+                .map(ColumnDef::from_column)
                 .collect(),
             Err(_) => vec![],
         };
@@ -214,6 +509,9 @@ pub async fn execute_query(
             execution_time_ms,
             has_more: false,
             history_entry_id: None,
+            row_cap_applied,
+            notices: Vec::new(),
+            snapshot_id,
         });
     }
 
@@ -222,10 +520,7 @@ pub async fn execute_query(
     let columns: Vec<ColumnDef> = first_row
         .columns()
         .iter()
-        .map(|col| ColumnDef {
-            name: col.name().to_string(),
-            data_type: col.type_info().to_string(),
-        })
+        .map(ColumnDef::from_column)
         .collect();
 
     // Determine if there are more rows
@@ -233,32 +528,37 @@ pub async fn execute_query(
     let row_limit = std::cmp::min(rows.len(), limit as usize);
 
     // Convert rows to JSON
-    let json_rows: Vec<serde_json::Value> = rows
+    let mut json_rows: Vec<serde_json::Value> = rows
         .into_iter()
         .take(row_limit)
         .map(|row| {
             let values: Vec<serde_json::Value> = columns
                 .iter()
                 .enumerate()
-                .map(|(i, col)| extract_value(&row, i, &col.data_type))
+                .map(|(i, col)| extract_value(&row, i, &col.data_type, &value_options))
                 .collect();
             serde_json::Value::Array(values)
         })
         .collect();
 
+    // Mask before caching so real values never reach the history cache.
+    mask_rows(&mut json_rows, &masked_column_indexes(state, &connection_id, &columns));
+
     // Auto-save to query history with cached results (fire-and-forget)
     let history_id = uuid::Uuid::new_v4().to_string();
-    {
+    let record_history = history_enabled(state, &connection_id, &settings);
+    if record_history {
         let connection_name = state
             .get_config(&connection_id)
             .map(|c| c.name)
             .unwrap_or_else(|| connection_id.clone());
-        let table_names = extract_table_names_for_history(&sql);
+        let history_sql = history_sql.unwrap_or_else(|| sql.clone());
+        let table_names = extract_table_names_for_history(&history_sql);
         let entry = QueryHistoryEntry {
             id: history_id.clone(),
             connection_id: connection_id.clone(),
             connection_name,
-            sql: sql.clone(),
+            sql: history_sql.clone(),
             row_count: Some(row_limit as i64),
             execution_time_ms: execution_time_ms as i64,
             executed_at: chrono::Utc::now().to_rfc3339(),
@@ -267,6 +567,7 @@ pub async fn execute_query(
             column_count: Some(columns.len() as i64),
             table_names,
             source: source.clone(),
+            is_mutation: is_mutating_statement(&history_sql),
         };
 
         // Serialize results for caching (skip if too large)
@@ -301,20 +602,46 @@ pub async fn execute_query(
         row_count: row_limit,
         execution_time_ms,
         has_more,
-        history_entry_id: Some(history_id),
+        history_entry_id: record_history.then_some(history_id),
+        row_cap_applied,
+        notices: Vec::new(),
+        snapshot_id,
     })
 }
 
+/// Per-query switches for how `extract_value` renders cells, read from settings once per call.
+#[derive(Debug, Clone, Default)]
+struct ValueOptions {
+    /// Convert PostGIS geometry/geography hex EWKB into a GeoJSON string.
+    decode_geometry: bool,
+    /// Re-render date/time values per the `datetime_format` setting.
+    datetime_format: super::datetime_format::DateTimeFormat,
+}
+
+impl ValueOptions {
+    fn from_settings(settings: &crate::models::QuerySettings) -> Self {
+        ValueOptions {
+            decode_geometry: settings.decode_postgis_geometry,
+            datetime_format: super::datetime_format::DateTimeFormat::from_setting(settings.datetime_format.as_deref()),
+        }
+    }
+}
+
 /// Extract a value from a row at the given index.
 /// With simple query protocol (raw_sql), all values arrive in PostgreSQL text format.
-/// We just read the text representation directly — no per-type decoding needed.
-fn extract_value(row: &sqlx::postgres::PgRow, index: usize, _type_name: &str) -> serde_json::Value {
+/// We read the text representation directly; only opt-in decodings look at the type name.
+fn extract_value(
+    row: &sqlx::postgres::PgRow,
+    index: usize,
+    type_name: &str,
+    options: &ValueOptions,
+) -> serde_json::Value {
     match row.try_get_raw(index) {
         Ok(raw) => {
             if raw.is_null() {
                 serde_json::Value::Null
             } else if let Ok(s) = raw.as_str() {
-                serde_json::Value::String(s.to_string())
+                text_to_value(s, type_name, options)
             } else {
                 serde_json::Value::Null
             }
@@ -323,198 +650,1710 @@ fn extract_value(row: &sqlx::postgres::PgRow, index: usize, _type_name: &str) ->
     }
 }
 
-/// Fetch more rows from an already-executed query using LIMIT/OFFSET
-pub async fn fetch_more_rows(
+/// Convert a cell's text representation to the JSON sent to the UI.
+fn text_to_value(text: &str, type_name: &str, options: &ValueOptions) -> serde_json::Value {
+    let upper = type_name.to_uppercase();
+    if options.decode_geometry && (upper == "GEOMETRY" || upper == "GEOGRAPHY") {
+        // GeoJSON is delivered as a string, like json/jsonb cells; unparseable
+        // input (e.g. a cast to text) is passed through unchanged.
+        if let Some(geojson) = super::geometry::ewkb_hex_to_geojson(text) {
+            return serde_json::Value::String(geojson.to_string());
+        }
+    }
+    if upper == "VECTOR" {
+        if let Some(vector) = parse_vector(text) {
+            return vector;
+        }
+    }
+    if let Some(formatted) = options.datetime_format.reformat_pg_text(text, &upper) {
+        return serde_json::Value::String(formatted);
+    }
+    serde_json::Value::String(text.to_string())
+}
+
+/// Vectors with more dimensions than this are shown as a head/tail preview.
+const VECTOR_PREVIEW_MAX_DIMS: usize = 16;
+/// Elements kept at each end of a previewed vector.
+const VECTOR_PREVIEW_EDGE: usize = 4;
+
+/// Parse pgvector's `[0.1,0.2,...]` text into a JSON array of numbers. High-dimensional
+/// embeddings become `{"dimensions", "head", "tail"}` so the grid stays usable.
+fn parse_vector(text: &str) -> Option<serde_json::Value> {
+    let inner = text.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let values: Vec<f64> = if inner.trim().is_empty() {
+        vec![]
+    } else {
+        inner
+            .split(',')
+            .map(|v| v.trim().parse::<f64>().ok())
+            .collect::<Option<_>>()?
+    };
+    if values.len() <= VECTOR_PREVIEW_MAX_DIMS {
+        return Some(serde_json::json!(values));
+    }
+    Some(serde_json::json!({
+        "dimensions": values.len(),
+        "head": &values[..VECTOR_PREVIEW_EDGE],
+        "tail": &values[values.len() - VECTOR_PREVIEW_EDGE..],
+    }))
+}
+
+/// A jsonb path to extract server-side from one result column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonPathExtraction {
+    /// Result column name (must be json or jsonb).
+    pub column: String,
+    /// Path segments, e.g. `["address", "city"]` or `["tags", "0"]`.
+    pub path: Vec<String>,
+}
+
+/// Build a jsonb text-array path literal (`'{"a","b"}'`) from validated segments.
+fn json_path_literal(path: &[String]) -> Result<String, String> {
+    if path.is_empty() {
+        return Err("JSON path must have at least one segment".to_string());
+    }
+    let mut elements = Vec::with_capacity(path.len());
+    for segment in path {
+        if segment.is_empty() || segment.chars().any(|c| c.is_control()) {
+            return Err(format!("Invalid JSON path segment: {:?}", segment));
+        }
+        elements.push(format!("\"{}\"", segment.replace('\\', "\\\\").replace('"', "\\\"")));
+    }
+    Ok(format!("'{{{}}}'", elements.join(",").replace('\'', "''")))
+}
+
+/// Select `statement`'s columns with those named in `paths` replaced by the
+/// text at their path. Columns are taken positionally, under placeholder names,
+/// so duplicate output names (`SELECT a.id, b.id`) stay unambiguous.
+fn json_paths_sql(statement: &str, columns: &[ColumnDef], paths: &[JsonPathExtraction]) -> Result<String, String> {
+    for extraction in paths {
+        if !columns.iter().any(|c| c.name == extraction.column) {
+            return Err(format!("Column not found in result: {}", extraction.column));
+        }
+    }
+
+    let mut placeholders = Vec::with_capacity(columns.len());
+    let mut select_list = Vec::with_capacity(columns.len());
+    for (i, col) in columns.iter().enumerate() {
+        let placeholder = format!("pharos_c{}", i);
+        let ident = format!("\"{}\"", col.name.replace('"', "\"\""));
+        match paths.iter().find(|p| p.column == col.name) {
+            Some(extraction) => {
+                if !matches!(col.data_type.to_uppercase().as_str(), "JSON" | "JSONB") {
+                    return Err(format!("Column {} is {}, not json/jsonb", col.name, col.data_type));
+                }
+                select_list.push(format!(
+                    "{placeholder}::jsonb #>> {} AS {ident}",
+                    json_path_literal(&extraction.path)?
+                ));
+            }
+            None => select_list.push(format!("{placeholder} AS {ident}")),
+        }
+        placeholders.push(placeholder);
+    }
+    Ok(format!(
+        "SELECT {} FROM ({}) AS pharos_src({})",
+        select_list.join(", "),
+        statement,
+        placeholders.join(", ")
+    ))
+}
+
+/// Run a query with some json/jsonb columns replaced by the text at a path
+/// (`#>>`), computed server-side so only the extracted scalar is transferred.
+/// Other columns pass through unchanged. History records `sql` as written.
+pub async fn execute_query_with_json_paths(
     connection_id: String,
     sql: String,
-    limit: i64,
-    offset: i64,
+    paths: Vec<JsonPathExtraction>,
+    query_id: Option<String>,
+    limit: Option<u32>,
     schema: Option<String>,
     state: &AppState,
 ) -> Result<QueryResult, String> {
+    let spans = split_statements(&sql);
+    let span = match spans.as_slice() {
+        [span] => span.clone(),
+        [] => return Err("No query to run".to_string()),
+        _ => return Err("JSON paths can only be extracted from a single query".to_string()),
+    };
+    let statement: String = sql.chars().skip(span.start).take(span.len()).collect();
+    let statement = strip_trailing_comments(&statement);
     let pool = state
         .get_pool(&connection_id)
         .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
 
-    let start = Instant::now();
-
+    let registration = state.track_query(query_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()), &connection_id);
     let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
-
-    // Apply the user's query timeout (non-fatal for non-PG servers)
-    if apply_statement_timeout(&mut conn, query_timeout_seconds(state)).await.is_err() {
-        drop(conn);
-        conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    if let Some(ref schema_name) = schema {
+        set_search_path(&mut conn, schema_name).await?;
     }
 
-    // Set search_path if schema is specified (non-fatal for non-PG servers)
-    if let Some(ref schema_name) = schema {
-        if let Err(_) = set_search_path(&mut conn, schema_name).await {
-            drop(conn);
-            conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let desc = (&mut *conn)
+        .describe(statement.as_str())
+        .await
+        .map_err(|e| format_db_error(&e))?;
+    let columns: Vec<ColumnDef> = desc.columns().iter().map(ColumnDef::from_column).collect();
+    let wrapped = json_paths_sql(&statement, &columns, &paths)?;
+
+    with_notices(run_query_on_connection(&pool, conn, connection_id, wrapped, Some(sql), registration, limit, schema, None, None, state)).await
+}
+
+/// A `QueryResult` laid out by column: `data[i]` holds every value of
+/// `columns[i]`, so wide numeric results don't repeat per-row structure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnarQueryResult {
+    pub columns: Vec<ColumnDef>,
+    pub data: Vec<Vec<serde_json::Value>>,
+    pub row_count: usize,
+    pub execution_time_ms: u64,
+    pub has_more: bool,
+    pub history_entry_id: Option<String>,
+    #[serde(default)]
+    pub row_cap_applied: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notices: Vec<String>,
+    #[serde(default)]
+    pub snapshot_id: Option<String>,
+}
+
+impl From<QueryResult> for ColumnarQueryResult {
+    fn from(result: QueryResult) -> Self {
+        let mut data: Vec<Vec<serde_json::Value>> = (0..result.columns.len())
+            .map(|_| Vec::with_capacity(result.rows.len()))
+            .collect();
+        for row in result.rows {
+            if let serde_json::Value::Array(values) = row {
+                for (column, value) in data.iter_mut().zip(values) {
+                    column.push(value);
+                }
+            }
+        }
+        ColumnarQueryResult {
+            columns: result.columns,
+            data,
+            row_count: result.row_count,
+            execution_time_ms: result.execution_time_ms,
+            has_more: result.has_more,
+            history_entry_id: result.history_entry_id,
+            row_cap_applied: result.row_cap_applied,
+            notices: result.notices,
+            snapshot_id: result.snapshot_id,
         }
     }
+}
 
-    // Wrap the original SQL with LIMIT/OFFSET
-    let wrapped_sql = format!(
-        "SELECT * FROM ({}) AS _pharos_paginated LIMIT {} OFFSET {}",
-        sql.trim().trim_end_matches(';'),
-        limit + 1,
-        offset
-    );
+/// `execute_query`, with the result returned column by column. Values are
+/// moved, not copied, out of the row arrays.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_query_columnar(
+    connection_id: String,
+    sql: String,
+    query_id: Option<String>,
+    limit: Option<u32>,
+    schema: Option<String>,
+    source: Option<String>,
+    run_as_role: Option<String>,
+    state: &AppState,
+) -> Result<ColumnarQueryResult, String> {
+    execute_query(connection_id, sql, query_id, limit, schema, source, run_as_role, state)
+        .await
+        .map(ColumnarQueryResult::from)
+}
 
-    let mut stream = sqlx::raw_sql(&wrapped_sql).fetch(&mut *conn);
-    let mut rows: Vec<sqlx::postgres::PgRow> = Vec::with_capacity((limit + 1) as usize);
+/// Placeholder shown instead of values in masked columns.
+const MASKED_VALUE: &str = "****";
+
+/// Case-insensitive glob match supporting `*` (any run) and `?` (one char).
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let n: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ni));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            ni = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
 
-    while let Some(row_result) = stream.next().await {
-        match row_result {
-            Ok(row) => {
-                rows.push(row);
-                if rows.len() > limit as usize {
-                    break;
+/// Indexes of result columns whose names match the connection's mask patterns.
+fn masked_column_indexes(state: &AppState, connection_id: &str, columns: &[ColumnDef]) -> Vec<usize> {
+    let patterns = state
+        .get_config(connection_id)
+        .map(|c| c.masked_columns)
+        .unwrap_or_default();
+    if patterns.is_empty() {
+        return vec![];
+    }
+    columns
+        .iter()
+        .enumerate()
+        .filter(|(_, col)| patterns.iter().any(|p| glob_matches(p, &col.name)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Replace non-NULL values in the given columns with `MASKED_VALUE`.
+fn mask_rows(rows: &mut [serde_json::Value], masked: &[usize]) {
+    if masked.is_empty() {
+        return;
+    }
+    for row in rows.iter_mut() {
+        if let serde_json::Value::Array(values) = row {
+            for &i in masked {
+                if let Some(v) = values.get_mut(i) {
+                    if !v.is_null() {
+                        *v = serde_json::Value::String(MASKED_VALUE.to_string());
+                    }
                 }
             }
-            Err(e) => {
-                drop(stream);
-                reset_statement_timeout(&mut conn).await;
-                return Err(e.to_string());
-            }
         }
     }
-    drop(stream);
-    reset_statement_timeout(&mut conn).await;
+}
 
-    let execution_time_ms = start.elapsed().as_millis() as u64;
+/// The plan and result of `explain_and_run`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainAndRunResult {
+    /// `EXPLAIN` text output, one line per plan row. `None` when the
+    /// statement isn't a plain read and the plan fetch was skipped.
+    pub plan: Option<String>,
+    pub result: QueryResult,
+}
 
-    if rows.is_empty() {
-        return Ok(QueryResult {
-            columns: vec![],
-            rows: vec![],
-            row_count: 0,
-            execution_time_ms,
-            has_more: false,
-            history_entry_id: None,
-        });
-    }
+/// Fetch the (non-ANALYZE) plan for a single statement and then execute it,
+/// both on one dedicated connection so they see the same search_path and
+/// session state. The plan step is skipped for statements that aren't SELECT-like.
+pub async fn explain_and_run(
+    connection_id: String,
+    sql: String,
+    query_id: Option<String>,
+    limit: Option<u32>,
+    schema: Option<String>,
+    state: &AppState,
+) -> Result<ExplainAndRunResult, String> {
+    // A script would run in full inside the EXPLAIN round trip (the simple
+    // protocol executes every statement) and then again in the run step.
+    let statement = match split_statements(&sql).as_slice() {
+        [span] => strip_trailing_comments(&sql.chars().skip(span.start).take(span.len()).collect::<String>()),
+        [] => return Err("No query to run".to_string()),
+        _ => return Err("Explain and run requires exactly one statement".to_string()),
+    };
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
 
-    let first_row = &rows[0];
-    let columns: Vec<ColumnDef> = first_row
-        .columns()
+    let registration = state.track_query(query_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()), &connection_id);
+    let mut conn = acquire_with_retry(&pool, query_settings(state).acquire_retries, registration.cancelled()).await?;
+
+    let plan = if is_read_statement(&statement) {
+        if let Some(ref schema_name) = schema {
+            set_search_path(&mut conn, schema_name).await?;
+        }
+        let timeout_seconds = query_timeout_seconds(state, &connection_id);
+        let _ = apply_statement_timeout(&mut conn, timeout_seconds).await;
+        let explain_sql = format!("EXPLAIN {}", statement);
+        let rows = (&mut *conn).fetch_all(sqlx::raw_sql(&explain_sql)).await;
+        reset_statement_timeout(&mut conn).await;
+        let rows = rows.map_err(|e| describe_query_error(&e, timeout_seconds, registration.cancelled().load(Ordering::SeqCst)))?;
+        let lines: Vec<String> = rows
+            .iter()
+            .filter_map(|row| match row.try_get_raw(0) {
+                Ok(raw) if !raw.is_null() => raw.as_str().ok().map(|s| s.to_string()),
+                _ => None,
+            })
+            .collect();
+        Some(lines.join("\n"))
+    } else {
+        None
+    };
+
+    let result = with_notices(run_query_on_connection(&pool, conn, connection_id, statement, Some(sql), registration, limit, schema, None, None, state)).await?;
+
+    Ok(ExplainAndRunResult { plan, result })
+}
+
+/// Output of `explain_query`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainResult {
+    /// The plan object for FORMAT JSON; for TEXT, XML and YAML the server's
+    /// output as a string.
+    pub plan: serde_json::Value,
+    /// Planning and (with ANALYZE) execution time, read from a JSON plan.
+    pub planning_time_ms: Option<f64>,
+    pub execution_time_ms: Option<f64>,
+    /// JSON plans are also kept in the plan store for `compare_plans`.
+    pub stored_plan_id: Option<String>,
+}
+
+/// Statements EXPLAIN accepts (CREATE only as `CREATE TABLE ... AS` and
+/// `CREATE MATERIALIZED VIEW`, which the server checks).
+const EXPLAINABLE_KEYWORDS: [&str; 11] = [
+    "SELECT", "WITH", "VALUES", "TABLE", "INSERT", "UPDATE", "DELETE", "MERGE", "EXECUTE", "DECLARE", "CREATE",
+];
+
+/// EXPLAIN's SUMMARY option (planning time without ANALYZE) arrived in 10.
+const EXPLAIN_SUMMARY_VERSION: i32 = 100000;
+
+/// EXPLAIN a single statement. `format` is json, text, xml or yaml. With
+/// `analyze` the statement really runs, inside a transaction that is always
+/// rolled back so writes don't persist (sequence advances and other
+/// non-transactional side effects still happen).
+pub async fn explain_query(
+    connection_id: String,
+    sql: String,
+    analyze: bool,
+    format: String,
+    schema: Option<String>,
+    state: &AppState,
+) -> Result<ExplainResult, String> {
+    let format = match format.to_ascii_uppercase().as_str() {
+        f @ ("JSON" | "TEXT" | "XML" | "YAML") => f.to_string(),
+        other => return Err(format!("Unsupported EXPLAIN format: {}", other)),
+    };
+    let statements = split_statements(&sql);
+    if statements.len() != 1 {
+        return Err("EXPLAIN requires exactly one statement".to_string());
+    }
+    let statement: String = sql.chars().skip(statements[0].start).take(statements[0].len()).collect();
+    let statement = statement.trim().trim_end_matches(';').to_string();
+    let keyword = leading_keyword(&statement);
+    if keyword == "EXPLAIN" {
+        return Err("The statement is already an EXPLAIN; remove it and choose the options here".to_string());
+    }
+    if !EXPLAINABLE_KEYWORDS.contains(&keyword.as_str()) {
+        return Err(format!(
+            "{} statements can't be explained; EXPLAIN supports SELECT, INSERT, UPDATE, DELETE, MERGE, VALUES, EXECUTE, DECLARE and CREATE TABLE AS",
+            if keyword.is_empty() { "Empty" } else { keyword.as_str() }
+        ));
+    }
+
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    if let Some(ref schema_name) = schema {
+        set_search_path(&mut conn, schema_name).await?;
+    }
+
+    let summary = state.server_version(&connection_id).is_some_and(|v| v >= EXPLAIN_SUMMARY_VERSION);
+    let explain_sql = format!(
+        "EXPLAIN (FORMAT {}, ANALYZE {}{}) {}",
+        format,
+        analyze,
+        if summary { ", SUMMARY true" } else { "" },
+        statement
+    );
+
+    let rows = if analyze {
+        (&mut *conn)
+            .execute(sqlx::raw_sql("BEGIN"))
+            .await
+            .map_err(|e| format_db_error(&e))?;
+        let _ = apply_statement_timeout(&mut conn, query_timeout_seconds(state, &connection_id)).await;
+        let rows = (&mut *conn).fetch_all(sqlx::raw_sql(&explain_sql)).await;
+        let _ = (&mut *conn).execute(sqlx::raw_sql("ROLLBACK")).await;
+        reset_statement_timeout(&mut conn).await;
+        rows
+    } else {
+        (&mut *conn).fetch_all(sqlx::raw_sql(&explain_sql)).await
+    }
+    .map_err(|e| format!("EXPLAIN failed: {}", clean_error_message(&format_db_error(&e))))?;
+
+    let output = rows
+        .iter()
+        .filter_map(|row| match row.try_get_raw(0) {
+            Ok(raw) if !raw.is_null() => raw.as_str().ok().map(|s| s.to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if format != "JSON" {
+        return Ok(ExplainResult {
+            plan: serde_json::Value::String(output),
+            planning_time_ms: None,
+            execution_time_ms: None,
+            stored_plan_id: None,
+        });
+    }
+
+    let stored = super::plan::record_plan(connection_id, statement, output, state)?;
+    let time = |key: &str| stored.plan.get(key).and_then(|t| t.as_f64());
+    Ok(ExplainResult {
+        planning_time_ms: time("Planning Time"),
+        execution_time_ms: time("Execution Time"),
+        stored_plan_id: Some(stored.id),
+        plan: stored.plan,
+    })
+}
+
+/// Run a single statement and attribute its `pg_stat_statements` counters to
+/// this run by diffing snapshots taken just before and after on the same
+/// connection. Without a readable pg_stat_statements the statement still runs
+/// and only the wall-clock time is returned. The run is registered under
+/// `query_id` so `cancel_query` can stop it, and is bound by the query timeout.
+pub async fn profile_query(
+    connection_id: String,
+    sql: String,
+    schema: Option<String>,
+    query_id: Option<String>,
+    state: &AppState,
+) -> Result<QueryProfile, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let statements = split_statements(&sql);
+    if statements.len() != 1 {
+        return Err("Profiling requires exactly one statement".to_string());
+    }
+    let statement: String = sql.chars().skip(statements[0].start).take(statements[0].len()).collect();
+    let registration = state.track_query(query_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()), &connection_id);
+
+    let capabilities = postgres::get_server_capabilities(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    if let Some(ref schema_name) = schema {
+        set_search_path(&mut conn, schema_name).await?;
+    }
+
+    let mut note = None;
+    let before = if capabilities.pg_stat_statements_available {
+        match postgres::get_statement_counters(&mut conn, capabilities.major_version).await {
+            Ok(counters) => Some(counters),
+            Err(e) => {
+                note = Some(format!("Could not read pg_stat_statements: {}", e));
+                None
+            }
+        }
+    } else {
+        note = Some(if capabilities.pg_stat_statements_version.is_none() {
+            "pg_stat_statements is not installed; only wall-clock time was measured".to_string()
+        } else {
+            "pg_stat_statements is not in shared_preload_libraries; only wall-clock time was measured".to_string()
+        });
+        None
+    };
+
+    let backend_pid: i32 = (&mut *conn)
+        .fetch_one(sqlx::raw_sql("SELECT pg_backend_pid()"))
+        .await
+        .ok()
+        .and_then(|row| row.try_get(0).ok())
+        .unwrap_or(0);
+    registration.set_backend_pid(backend_pid);
+    if registration.cancelled().load(Ordering::SeqCst) {
+        return Err("Query was cancelled".to_string());
+    }
+
+    let timeout_seconds = query_timeout_seconds(state, &connection_id);
+    let _ = apply_statement_timeout(&mut conn, timeout_seconds).await;
+    let start = Instant::now();
+    let result = (&mut *conn).execute(sqlx::raw_sql(&statement)).await;
+    let wall_clock_ms = start.elapsed().as_millis() as u64;
+    reset_statement_timeout(&mut conn).await;
+    let cancelled = registration.cancelled().load(Ordering::SeqCst);
+    drop(registration);
+    result.map_err(|e| {
+        if cancelled {
+            "Query was cancelled".to_string()
+        } else {
+            describe_query_error(&e, timeout_seconds, false)
+        }
+    })?;
+
+    let mut server_stats = None;
+    if let Some(before) = before {
+        match postgres::get_statement_counters(&mut conn, capabilities.major_version).await {
+            Ok(after) => match attribute_counters(&statement, &before, &after) {
+                Some(delta) => {
+                    if delta.calls > 1 {
+                        note = Some(format!(
+                            "{} calls of this statement were recorded during the run; counters include other sessions",
+                            delta.calls
+                        ));
+                    }
+                    server_stats = Some(delta);
+                }
+                None => note = Some("No matching pg_stat_statements entry was found for this run".to_string()),
+            },
+            Err(e) => note = Some(format!("Could not read pg_stat_statements: {}", e)),
+        }
+    }
+
+    Ok(QueryProfile { wall_clock_ms, server_stats, note })
+}
+
+/// Pick the entry whose counters moved during the run, preferring one whose
+/// normalized text matches `sql`, and return its change against the baseline.
+fn attribute_counters(
+    sql: &str,
+    before: &[StatementCounters],
+    after: &[StatementCounters],
+) -> Option<StatementCounters> {
+    let changed: Vec<StatementCounters> = after
+        .iter()
+        .filter_map(|entry| {
+            let base = before.iter().find(|b| b.query_id == entry.query_id);
+            let calls = entry.calls - base.map_or(0, |b| b.calls);
+            (calls > 0).then(|| counters_delta(entry, base))
+        })
+        .collect();
+
+    let shape = statement_shape(sql);
+    let matched = changed.iter().position(|c| statement_shape(&c.query) == shape);
+    match (matched, changed.len()) {
+        (Some(i), _) => changed.into_iter().nth(i),
+        (None, 1) => changed.into_iter().next(),
+        _ => None,
+    }
+}
+
+fn counters_delta(after: &StatementCounters, before: Option<&StatementCounters>) -> StatementCounters {
+    let Some(b) = before else {
+        return after.clone();
+    };
+    StatementCounters {
+        query_id: after.query_id,
+        query: after.query.clone(),
+        calls: after.calls - b.calls,
+        rows: after.rows - b.rows,
+        planning_time_ms: after.planning_time_ms.map(|t| t - b.planning_time_ms.unwrap_or(0.0)),
+        execution_time_ms: after.execution_time_ms - b.execution_time_ms,
+        shared_blks_hit: after.shared_blks_hit - b.shared_blks_hit,
+        shared_blks_read: after.shared_blks_read - b.shared_blks_read,
+        temp_blks_read: after.temp_blks_read - b.temp_blks_read,
+        temp_blks_written: after.temp_blks_written - b.temp_blks_written,
+    }
+}
+
+/// Reduce a statement to what pg_stat_statements keeps stable: comments
+/// dropped, literals and `$n` parameters replaced by `?`, keywords lowercased
+/// and whitespace collapsed.
+fn statement_shape(sql: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '"' {
+            let end = skip_non_code(&chars, i).unwrap_or(chars.len());
+            out.extend(&chars[i..end]);
+            i = end;
+        } else if let Some(mut next) = skip_non_code(&chars, i) {
+            let is_comment = c == '-' || c == '/';
+            // A doubled quote ('it''s') lexes as two adjacent literals.
+            while c == '\'' && chars.get(next) == Some(&'\'') {
+                next = skip_non_code(&chars, next).unwrap_or(chars.len());
+            }
+            out.push_str(if is_comment { " " } else { "?" });
+            i = next;
+        } else if c == '$' || (c.is_ascii_digit() && !chars.get(i.wrapping_sub(1)).is_some_and(|p| p.is_alphanumeric() || *p == '_')) {
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            out.push('?');
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            out.push_str(&chars[start..i].iter().collect::<String>().to_lowercase());
+        } else {
+            out.push(c);
+            i += 1;
+        }
+    }
+    out.trim().trim_end_matches(';').split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Return the first keyword of a statement, uppercased, skipping leading
+/// whitespace, `--` line comments and `/* */` block comments.
+pub(crate) fn leading_keyword(sql: &str) -> String {
+    let mut rest = sql;
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("--") {
+            rest = after.find('\n').map(|i| &after[i + 1..]).unwrap_or("");
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            rest = after.find("*/").map(|i| &after[i + 2..]).unwrap_or("");
+        } else {
+            break;
+        }
+    }
+    rest.chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// True for statements that only read (and so can be EXPLAINed without side effects).
+fn is_read_statement(sql: &str) -> bool {
+    matches!(leading_keyword(sql).as_str(), "SELECT" | "WITH" | "VALUES" | "TABLE")
+        && !is_mutating_statement(sql)
+}
+
+/// True for DML that modifies data — including `INSERT/UPDATE/DELETE ... RETURNING`
+/// run through `execute_query` and `WITH` queries containing data-modifying CTEs.
+pub(crate) fn is_mutating_statement(sql: &str) -> bool {
+    const DML: [&str; 4] = ["INSERT", "UPDATE", "DELETE", "MERGE"];
+    match leading_keyword(sql).as_str() {
+        "WITH" => {
+            let words = sql_words(sql);
+            (0..words.len()).any(|i| DML.contains(&words[i].as_str()) && !(words[i] == "UPDATE" && ends_row_lock(&words[..i])))
+        }
+        kw => DML.contains(&kw),
+    }
+}
+
+/// Whether `preceding` ends in `FOR` or `FOR NO KEY`, making the UPDATE after
+/// it a row-locking clause of a read rather than a data-modifying statement.
+fn ends_row_lock(preceding: &[String]) -> bool {
+    match preceding {
+        [.., last] if last == "FOR" => true,
+        [.., a, b, c] => a == "FOR" && b == "NO" && c == "KEY",
+        _ => false,
+    }
+}
+
+/// If a comment, string literal, quoted identifier or dollar-quoted body starts
+/// at `i`, return the index just past it.
+fn skip_non_code(chars: &[char], i: usize) -> Option<usize> {
+    let c = chars[i];
+    let next = if c == '-' && chars.get(i + 1) == Some(&'-') {
+        let mut j = i;
+        while j < chars.len() && chars[j] != '\n' {
+            j += 1;
+        }
+        j
+    } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+        let mut j = i + 2;
+        while j < chars.len() && !(chars[j] == '*' && chars.get(j + 1) == Some(&'/')) {
+            j += 1;
+        }
+        j + 2
+    } else if c == '\'' || c == '"' {
+        let mut j = i + 1;
+        while j < chars.len() && chars[j] != c {
+            j += 1;
+        }
+        j + 1
+    } else if c == '$' {
+        // Dollar quote: $tag$ ... $tag$
+        let tag_end = chars[i + 1..]
+            .iter()
+            .position(|&ch| !(ch.is_alphanumeric() || ch == '_'))
+            .map(|p| i + 1 + p);
+        match tag_end {
+            Some(end) if chars[end] == '$' => {
+                let tag: String = chars[i..=end].iter().collect();
+                let body: String = chars[end + 1..].iter().collect();
+                match body.find(&tag) {
+                    Some(pos) => end + 1 + body[..pos].chars().count() + tag.chars().count(),
+                    None => chars.len(),
+                }
+            }
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+    Some(next.min(chars.len()))
+}
+
+/// Uppercased bare words of a statement, skipping comments, string literals,
+/// quoted identifiers and dollar-quoted bodies.
+fn sql_words(sql: &str) -> Vec<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(next) = skip_non_code(&chars, i) {
+            i = next;
+            continue;
+        }
+        let c = chars[i];
+        if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            words.push(chars[start..i].iter().collect::<String>().to_uppercase());
+        } else {
+            i += 1;
+        }
+    }
+    words
+}
+
+/// Split a script on top-level `;` into statement spans, as char ranges into
+/// `sql`. Spans are trimmed, exclude the `;`, and comment-only pieces are dropped.
+pub(crate) fn split_statements(sql: &str) -> Vec<std::ops::Range<usize>> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut spans = Vec::new();
+    let mut push_span = |start: usize, end: usize| {
+        let mut start = start;
+        let mut end = end;
+        while start < end && chars[start].is_whitespace() {
+            start += 1;
+        }
+        while end > start && chars[end - 1].is_whitespace() {
+            end -= 1;
+        }
+        let text: String = chars[start..end].iter().collect();
+        if !sql_words(&text).is_empty() {
+            spans.push(start..end);
+        }
+    };
+    let mut start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(next) = skip_non_code(&chars, i) {
+            i = next;
+            continue;
+        }
+        if chars[i] == ';' {
+            push_span(start, i);
+            start = i + 1;
+        }
+        i += 1;
+    }
+    push_span(start, chars.len());
+    spans
+}
+
+/// True when `expr` has balanced parentheses and brackets and no top-level `,`
+/// or `;`, so it can't close the clause it is pasted into and start another.
+pub(crate) fn is_single_expression(expr: &str) -> bool {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(next) = skip_non_code(&chars, i) {
+            i = next;
+            continue;
+        }
+        match chars[i] {
+            '(' | '[' => depth += 1,
+            ')' | ']' => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            ',' | ';' if depth == 0 => return false,
+            _ => {}
+        }
+        i += 1;
+    }
+    depth == 0
+}
+
+/// Session limits for statements that are only run to check them, inside a
+/// transaction that is rolled back: a blocked lock or a long table rewrite
+/// gives up instead of holding locks for as long as it takes.
+pub(crate) const DRY_RUN_TIMEOUTS: &str = "SET LOCAL lock_timeout = '2s'; SET LOCAL statement_timeout = '30s'";
+
+/// Largest LIMIT or OFFSET `fetch_more_rows` will interpolate into a paginated query.
+const MAX_PAGINATION_BOUND: i64 = 1_000_000_000;
+
+/// Drop trailing `--` and `/* */` comments (and whitespace) from a statement, so
+/// nothing after it can swallow SQL appended to the end.
+pub(crate) fn strip_trailing_comments(sql: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut code_end = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(next) = skip_non_code(&chars, i) {
+            let is_comment = matches!((chars[i], chars.get(i + 1)), ('-', Some('-')) | ('/', Some('*')));
+            if !is_comment {
+                code_end = next;
+            }
+            i = next;
+            continue;
+        }
+        if !chars[i].is_whitespace() {
+            code_end = i + 1;
+        }
+        i += 1;
+    }
+    chars[..code_end].iter().collect()
+}
+
+/// Reject a negative LIMIT/OFFSET, or one above `MAX_PAGINATION_BOUND`.
+fn check_pagination_bounds(limit: i64, offset: i64) -> Result<(), String> {
+    for (name, value) in [("limit", limit), ("offset", offset)] {
+        if !(0..=MAX_PAGINATION_BOUND).contains(&value) {
+            return Err(format!(
+                "Pagination {} must be between 0 and {}, got {}",
+                name, MAX_PAGINATION_BOUND, value
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Wrap a single statement as `SELECT * FROM (<sql>) LIMIT .. OFFSET ..`. The
+/// bounds must be non-negative and at most `MAX_PAGINATION_BOUND`; a trailing `;`
+/// and trailing comments are stripped from the inner statement.
+fn paginated_sql(sql: &str, limit: i64, offset: i64) -> Result<String, String> {
+    check_pagination_bounds(limit, offset)?;
+    let spans = split_statements(sql);
+    let span = match spans.as_slice() {
+        [span] => span.clone(),
+        [] => return Err("No statement to paginate".to_string()),
+        _ => return Err("Only a single statement can be paginated".to_string()),
+    };
+    let statement: String = sql.chars().skip(span.start).take(span.len()).collect();
+    Ok(format!(
+        "SELECT * FROM ({}) AS _pharos_paginated LIMIT {} OFFSET {}",
+        strip_trailing_comments(&statement),
+        limit,
+        offset
+    ))
+}
+
+/// The only statement in `sql`, without trailing comments, or None for a script.
+fn single_statement(sql: &str) -> Option<String> {
+    match split_statements(sql).as_slice() {
+        [span] => {
+            let statement: String = sql.chars().skip(span.start).take(span.len()).collect();
+            Some(strip_trailing_comments(&statement))
+        }
+        _ => None,
+    }
+}
+
+/// A single plain read that can be wrapped in `DECLARE ... CURSOR FOR`.
+/// `SELECT ... INTO` creates a table, so it isn't one.
+fn is_cursor_eligible(sql: &str) -> bool {
+    single_statement(sql).is_some_and(|statement| {
+        is_read_statement(&statement) && !sql_words(&statement).iter().any(|w| w == "INTO")
+    })
+}
+
+/// Read up to `max_rows` rows through a server-side cursor, `fetch_size` rows per
+/// round trip, so wide rows aren't all buffered by the server at once. The cursor
+/// lives in a read-only transaction that is rolled back afterwards. A query that
+/// turns out to write (a volatile function, `FOR UPDATE`) fails there rather than
+/// having its effects rolled back, and `Ok(None)` tells the caller to run it
+/// normally instead.
+async fn fetch_via_cursor(
+    conn: &mut sqlx::pool::PoolConnection<sqlx::Postgres>,
+    sql: &str,
+    max_rows: usize,
+    fetch_size: u32,
+    timeout_seconds: u32,
+    cancelled: &std::sync::atomic::AtomicBool,
+) -> Result<Option<Vec<sqlx::postgres::PgRow>>, String> {
+    let statement = single_statement(sql).ok_or_else(|| "Only a single query can use a cursor".to_string())?;
+    (&mut **conn)
+        .execute(sqlx::raw_sql("BEGIN READ ONLY"))
+        .await
+        .map_err(|e| format_db_error(&e))?;
+
+    // 25006: read_only_sql_transaction
+    let cursor_error = |e: sqlx::Error| match pg_error_code(&e).as_deref() {
+        Some("25006") => None,
+        _ => Some(describe_query_error(&e, timeout_seconds, cancelled.load(Ordering::SeqCst))),
+    };
+    let result = async {
+        let declare = format!("DECLARE pharos_fetch NO SCROLL CURSOR FOR {}", statement);
+        if let Err(e) = (&mut **conn).execute(sqlx::raw_sql(&declare)).await {
+            return cursor_error(e).map_or(Ok(None), Err);
+        }
+
+        let mut rows = Vec::new();
+        while rows.len() < max_rows {
+            if cancelled.load(Ordering::SeqCst) {
+                return Err("Query was cancelled".to_string());
+            }
+            let batch = (fetch_size as usize).min(max_rows - rows.len());
+            let fetched = match (&mut **conn)
+                .fetch_all(sqlx::raw_sql(&format!("FETCH {} FROM pharos_fetch", batch)))
+                .await
+            {
+                Ok(fetched) => fetched,
+                Err(e) => return cursor_error(e).map_or(Ok(None), Err),
+            };
+            let exhausted = fetched.len() < batch;
+            rows.extend(fetched);
+            if exhausted {
+                break;
+            }
+        }
+        Ok(Some(rows))
+    }
+    .await;
+
+    let _ = (&mut **conn).execute(sqlx::raw_sql("ROLLBACK")).await;
+    result
+}
+
+/// The SQL `fetch_more_rows` would run for this page: the statement wrapped with
+/// the validated OFFSET and a LIMIT clamped to the row cap, plus one look-ahead row.
+pub fn preview_pagination_sql(sql: &str, limit: i64, offset: i64, state: &AppState) -> Result<String, String> {
+    check_pagination_bounds(limit, offset)?;
+    let (limit, _) = clamp_to_row_cap(limit as u64, query_settings(state).max_result_rows);
+    paginated_sql(sql, limit as i64 + 1, offset)
+}
+
+/// Fetch more rows from an already-executed query using LIMIT/OFFSET
+pub async fn fetch_more_rows(
+    connection_id: String,
+    sql: String,
+    limit: i64,
+    offset: i64,
+    schema: Option<String>,
+    state: &AppState,
+) -> Result<QueryResult, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let settings = query_settings(state);
+    let value_options = ValueOptions::from_settings(&settings);
+    // Validate before clamping so a negative or huge request is rejected, not coerced
+    check_pagination_bounds(limit, offset)?;
+    let (limit, row_cap_applied) = clamp_to_row_cap(limit as u64, settings.max_result_rows);
+    let limit = limit as i64;
+    // One extra row tells us whether another page follows
+    let wrapped_sql = paginated_sql(&sql, limit + 1, offset)?;
+    let start = Instant::now();
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    // Apply the user's query timeout (non-fatal for non-PG servers)
+    if apply_statement_timeout(&mut conn, query_timeout_seconds(state, &connection_id)).await.is_err() {
+        drop(conn);
+        conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    }
+
+    // Set search_path if schema is specified (non-fatal for non-PG servers)
+    if let Some(ref schema_name) = schema {
+        if let Err(_) = set_search_path(&mut conn, schema_name).await {
+            drop(conn);
+            conn = pool.acquire().await.map_err(|e| e.to_string())?;
+        }
+    }
+
+    let mut stream = sqlx::raw_sql(&wrapped_sql).fetch(&mut *conn);
+    let mut rows: Vec<sqlx::postgres::PgRow> = Vec::with_capacity((limit + 1) as usize);
+
+    while let Some(row_result) = stream.next().await {
+        match row_result {
+            Ok(row) => {
+                rows.push(row);
+                if rows.len() > limit as usize {
+                    break;
+                }
+            }
+            Err(e) => {
+                drop(stream);
+                reset_statement_timeout(&mut conn).await;
+                return Err(e.to_string());
+            }
+        }
+    }
+    drop(stream);
+    reset_statement_timeout(&mut conn).await;
+
+    let execution_time_ms = start.elapsed().as_millis() as u64;
+
+    if rows.is_empty() {
+        return Ok(QueryResult {
+            columns: vec![],
+            rows: vec![],
+            row_count: 0,
+            execution_time_ms,
+            has_more: false,
+            history_entry_id: None,
+            row_cap_applied,
+            notices: Vec::new(),
+            snapshot_id: None,
+        });
+    }
+
+    let first_row = &rows[0];
+    let columns: Vec<ColumnDef> = first_row
+        .columns()
+        .iter()
+        .map(ColumnDef::from_column)
+        .collect();
+
+    let has_more = rows.len() > limit as usize;
+    let row_limit = std::cmp::min(rows.len(), limit as usize);
+
+    let mut json_rows: Vec<serde_json::Value> = rows
+        .into_iter()
+        .take(row_limit)
+        .map(|row| {
+            let values: Vec<serde_json::Value> = columns
+                .iter()
+                .enumerate()
+                .map(|(i, col)| extract_value(&row, i, &col.data_type, &value_options))
+                .collect();
+            serde_json::Value::Array(values)
+        })
+        .collect();
+
+    mask_rows(&mut json_rows, &masked_column_indexes(state, &connection_id, &columns));
+
+    Ok(QueryResult {
+        columns,
+        rows: json_rows,
+        row_count: row_limit,
+        execution_time_ms,
+        has_more,
+        history_entry_id: None,
+        row_cap_applied,
+        notices: Vec::new(),
+        snapshot_id: None,
+    })
+}
+
+/// Execute a statement that doesn't return rows (INSERT, UPDATE, DELETE, etc.)
+pub async fn execute_statement(
+    connection_id: String,
+    sql: String,
+    schema: Option<String>,
+    state: &AppState,
+) -> Result<ExecuteResult, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let start = Instant::now();
+
+    // Acquire a dedicated connection so SET search_path and the statement
+    // run on the same connection
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    // Apply the user's query timeout (non-fatal for non-PG servers)
+    let timeout_seconds = query_timeout_seconds(state, &connection_id);
+    if apply_statement_timeout(&mut conn, timeout_seconds).await.is_err() {
+        drop(conn);
+        conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    }
+
+    // Set search_path if schema is specified (non-fatal for non-PG servers)
+    if let Some(ref schema_name) = schema {
+        if let Err(_) = set_search_path(&mut conn, schema_name).await {
+            drop(conn);
+            conn = pool.acquire().await.map_err(|e| e.to_string())?;
+        }
+    }
+
+    let (result, notices) = capture_notices((&mut *conn).execute(sqlx::raw_sql(&sql))).await;
+    reset_statement_timeout(&mut conn).await;
+    let result = result.map_err(|e| describe_query_error(&e, timeout_seconds, false))?;
+
+    let execution_time_ms = start.elapsed().as_millis() as u64;
+
+    let rows_affected = result.rows_affected();
+
+    // Auto-save to query history (fire-and-forget, no results for statements)
+    let statement_history_id = uuid::Uuid::new_v4().to_string();
+    let record_history = history_enabled(state, &connection_id, &query_settings(state));
+    if record_history {
+        let connection_name = state
+            .get_config(&connection_id)
+            .map(|c| c.name)
+            .unwrap_or_else(|| connection_id.clone());
+        let table_names = extract_table_names_for_history(&sql);
+        let entry = QueryHistoryEntry {
+            id: statement_history_id.clone(),
+            connection_id: connection_id.clone(),
+            connection_name,
+            sql: sql.clone(),
+            row_count: Some(rows_affected as i64),
+            execution_time_ms: execution_time_ms as i64,
+            executed_at: chrono::Utc::now().to_rfc3339(),
+            has_results: false,
+            schema: schema.clone(),
+            column_count: None,
+            table_names,
+            source: None,
+            is_mutation: is_mutating_statement(&sql),
+        };
+        if let Ok(db) = state.metadata_db.lock() {
+            if let Err(e) = sqlite::save_query_history(&db, &entry, None, None) {
+                log::warn!("Failed to save query history: {}", e);
+            }
+        }
+    }
+
+    Ok(ExecuteResult {
+        rows_affected,
+        execution_time_ms,
+        history_entry_id: record_history.then_some(statement_history_id),
+        notices,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecuteResult {
+    pub rows_affected: u64,
+    pub execution_time_ms: u64,
+    // NOTE: this struct intentionally has NO `rename_all` — the Swift side maps
+    // these via explicit snake_case CodingKeys, so fields must stay snake_case.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_entry_id: Option<String>,
+    /// NOTICE/WARNING messages the server sent while the statement ran
+    /// (e.g. `RAISE NOTICE` in a procedure).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notices: Vec<String>,
+}
+
+/// Outcome of `execute_with_rowcount_guard`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardedExecuteResult {
+    /// Planner estimate of rows the statement would modify.
+    pub estimated_rows: u64,
+    pub max_rows: u64,
+    /// False when the estimate exceeded `max_rows` and `force` wasn't set.
+    pub executed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<ExecuteResult>,
+}
+
+/// Planner estimate of rows a DML statement touches, from `EXPLAIN (FORMAT JSON)`
+/// output. ModifyTable nodes estimate 0 rows out (without RETURNING), so the
+/// estimate is taken from the subplans feeding them (`Outer`, or `Member` for
+/// inheritance children), not from InitPlans or SubPlans.
+fn estimated_affected_rows(explain: &serde_json::Value) -> Option<f64> {
+    let plan = explain.get(0)?.get("Plan")?;
+    if plan.get("Node Type").and_then(|t| t.as_str()) != Some("ModifyTable") {
+        return plan.get("Plan Rows")?.as_f64();
+    }
+    let inputs: Vec<f64> = plan
+        .get("Plans")
+        .and_then(|p| p.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|child| {
+            matches!(
+                child.get("Parent Relationship").and_then(|r| r.as_str()),
+                Some("Outer") | Some("Member")
+            )
+        })
+        .filter_map(|child| child.get("Plan Rows")?.as_f64())
+        .collect();
+    if inputs.is_empty() {
+        plan.get("Plan Rows")?.as_f64()
+    } else {
+        Some(inputs.iter().sum())
+    }
+}
+
+/// Execute a data-modifying statement only if the planner estimates it touches
+/// at most `max_rows` rows (default: the `rowcount_guard_max_rows` setting),
+/// or `force` is set. The estimate comes from a plain EXPLAIN, so nothing runs
+/// when the guard refuses.
+pub async fn execute_with_rowcount_guard(
+    connection_id: String,
+    sql: String,
+    schema: Option<String>,
+    max_rows: Option<u64>,
+    force: bool,
+    state: &AppState,
+) -> Result<GuardedExecuteResult, String> {
+    let spans = split_statements(&sql);
+    let span = match spans.as_slice() {
+        [span] => span.clone(),
+        [] => return Err("No statement to execute".to_string()),
+        _ => return Err("The row-count guard only applies to a single statement".to_string()),
+    };
+    let statement: String = sql.chars().skip(span.start).take(span.len()).collect();
+    let statement = strip_trailing_comments(&statement);
+    if !is_mutating_statement(&statement) {
+        return Err("The row-count guard only applies to INSERT, UPDATE, DELETE and MERGE statements".to_string());
+    }
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+    let max_rows = max_rows.unwrap_or_else(|| query_settings(state).rowcount_guard_max_rows);
+
+    let estimated_rows = {
+        let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+        if let Some(ref schema_name) = schema {
+            set_search_path(&mut conn, schema_name).await?;
+        }
+        let explain_sql = format!("EXPLAIN (FORMAT JSON) {}", statement);
+        let row = (&mut *conn)
+            .fetch_one(sqlx::raw_sql(&explain_sql))
+            .await
+            .map_err(|e| format_db_error(&e))?;
+        let plan_text = row
+            .try_get_raw(0)
+            .ok()
+            .and_then(|raw| raw.as_str().ok().map(|s| s.to_string()))
+            .ok_or_else(|| "EXPLAIN returned no plan".to_string())?;
+        let plan: serde_json::Value = serde_json::from_str(&plan_text)
+            .map_err(|e| format!("Failed to parse plan: {}", e))?;
+        estimated_affected_rows(&plan)
+            .ok_or_else(|| "Plan has no row estimate".to_string())?
+            .ceil() as u64
+    };
+
+    if estimated_rows > max_rows && !force {
+        return Ok(GuardedExecuteResult {
+            estimated_rows,
+            max_rows,
+            executed: false,
+            result: None,
+        });
+    }
+
+    let result = execute_statement(connection_id, statement, schema, state).await?;
+    Ok(GuardedExecuteResult {
+        estimated_rows,
+        max_rows,
+        executed: true,
+        result: Some(result),
+    })
+}
+
+/// Handle for a cursor opened by `open_cursor`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorHandle {
+    pub cursor_id: String,
+}
+
+const PAGINATION_CURSOR: &str = "pharos_page";
+
+/// Open a server-side cursor for a read query on a connection pinned until
+/// `close_cursor`. Every page fetched from it comes from the same snapshot,
+/// so concurrent writes can't cause duplicated or skipped rows between pages.
+pub async fn open_cursor(
+    connection_id: String,
+    sql: String,
+    schema: Option<String>,
+    state: &AppState,
+) -> Result<CursorHandle, String> {
+    let statement = match single_statement(&sql) {
+        Some(statement) if is_cursor_eligible(&statement) => statement,
+        _ => return Err("Only a single SELECT-like query can be paged with a cursor".to_string()),
+    };
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    if let Some(ref schema_name) = schema {
+        set_search_path(&mut conn, schema_name).await?;
+    }
+    let timeout_seconds = query_timeout_seconds(state, &connection_id);
+    let open_sql = format!(
+        "BEGIN READ ONLY; {}; DECLARE {} NO SCROLL CURSOR FOR {}",
+        local_statement_timeout_sql(timeout_seconds),
+        PAGINATION_CURSOR,
+        statement
+    );
+    if let Err(e) = (&mut *conn).execute(sqlx::raw_sql(&open_sql)).await {
+        let _ = (&mut *conn).execute(sqlx::raw_sql("ROLLBACK")).await;
+        return Err(describe_query_error(&e, timeout_seconds, false));
+    }
+    let backend_pid: i32 = (&mut *conn)
+        .fetch_one(sqlx::raw_sql("SELECT pg_backend_pid()"))
+        .await
+        .ok()
+        .and_then(|row| row.try_get(0).ok())
+        .unwrap_or(0);
+
+    let cursor_id = uuid::Uuid::new_v4().to_string();
+    state.put_cursor(cursor_id.clone(), OpenCursor { connection_id, conn, backend_pid });
+    Ok(CursorHandle { cursor_id })
+}
+
+/// Fetch the next `count` rows from an open cursor. `has_more` is true when a
+/// full page came back (the following fetch may then be empty). Columns are
+/// only reported for non-empty pages. Each fetch is bound by the query timeout
+/// and registered under `query_id` so `cancel_query` can stop it. On error the
+/// cursor is closed.
+pub async fn fetch_cursor(
+    cursor_id: String,
+    count: u32,
+    query_id: Option<String>,
+    state: &AppState,
+) -> Result<QueryResult, String> {
+    let mut cursor = state
+        .take_cursor(&cursor_id)
+        .ok_or_else(|| format!("Cursor not found: {}", cursor_id))?;
+    state.touch_connection(&cursor.connection_id);
+
+    let settings = query_settings(state);
+    let value_options = ValueOptions::from_settings(&settings);
+    let (count, row_cap_applied) = clamp_to_row_cap(count.max(1) as u64, settings.max_result_rows);
+    let start = Instant::now();
+
+    // The timeout may have changed since the cursor was opened.
+    let timeout_seconds = query_timeout_seconds(state, &cursor.connection_id);
+    let registration = state.track_query(query_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()), &cursor.connection_id);
+    registration.set_backend_pid(cursor.backend_pid);
+    let fetch_sql = format!(
+        "{}; FETCH FORWARD {} FROM {}",
+        local_statement_timeout_sql(timeout_seconds),
+        count,
+        PAGINATION_CURSOR
+    );
+    let result = (&mut *cursor.conn).fetch_all(sqlx::raw_sql(&fetch_sql)).await;
+    let cancelled = registration.cancelled().load(Ordering::SeqCst);
+    drop(registration);
+    let rows = match result {
+        Ok(rows) => rows,
+        Err(e) => {
+            let _ = (&mut *cursor.conn).execute(sqlx::raw_sql("ROLLBACK")).await;
+            return Err(describe_query_error(&e, timeout_seconds, cancelled));
+        }
+    };
+    let execution_time_ms = start.elapsed().as_millis() as u64;
+
+    let columns: Vec<ColumnDef> = rows
+        .first()
+        .map(|row| row.columns().iter().map(ColumnDef::from_column).collect())
+        .unwrap_or_default();
+    let has_more = rows.len() as u64 == count;
+    let row_count = rows.len();
+    let mut json_rows: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| {
+            serde_json::Value::Array(
+                columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, col)| extract_value(row, i, &col.data_type, &value_options))
+                    .collect(),
+            )
+        })
+        .collect();
+    mask_rows(&mut json_rows, &masked_column_indexes(state, &cursor.connection_id, &columns));
+
+    state.put_cursor(cursor_id, cursor);
+
+    Ok(QueryResult {
+        columns,
+        rows: json_rows,
+        row_count,
+        execution_time_ms,
+        has_more,
+        history_entry_id: None,
+        row_cap_applied,
+        notices: Vec::new(),
+        snapshot_id: None,
+    })
+}
+
+/// Close a cursor, ending its transaction and returning its connection to the pool.
+pub async fn close_cursor(cursor_id: String, state: &AppState) -> Result<bool, String> {
+    match state.take_cursor(&cursor_id) {
+        Some(mut cursor) => {
+            let _ = (&mut *cursor.conn).execute(sqlx::raw_sql("ROLLBACK")).await;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Rows at both ends of a table, for sanity-checking its range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableEndsPreview {
+    pub columns: Vec<ColumnDef>,
+    pub first_rows: Vec<serde_json::Value>,
+    /// The last rows, in ascending order like `first_rows`.
+    pub last_rows: Vec<serde_json::Value>,
+    /// True when no key columns were given and rows were ordered by ctid
+    /// (physical order) instead.
+    pub ordered_by_ctid: bool,
+    pub execution_time_ms: u64,
+}
+
+/// ORDER BY list for `preview_table_ends`: the key columns, or ctid without them.
+fn table_ends_order_by(key_columns: &[String], descending: bool) -> String {
+    let direction = if descending { " DESC" } else { "" };
+    if key_columns.is_empty() {
+        return format!("ctid{}", direction);
+    }
+    key_columns
+        .iter()
+        .map(|c| format!("\"{}\"{}", crate::commands::table::escape_identifier(c), direction))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Fetch the first and last `count` rows of a table ordered by `key_columns`
+/// (normally the primary key), falling back to ctid when none are given.
+pub async fn preview_table_ends(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    key_columns: Vec<String>,
+    count: u32,
+    state: &AppState,
+) -> Result<TableEndsPreview, String> {
+    use crate::commands::table::{escape_identifier, validate_identifier};
+
+    validate_identifier(&schema_name)?;
+    validate_identifier(&table_name)?;
+    for column in &key_columns {
+        validate_identifier(column)?;
+    }
+
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let settings = query_settings(state);
+    let value_options = ValueOptions::from_settings(&settings);
+    let (count, _) = clamp_to_row_cap(count.max(1) as u64, settings.max_result_rows);
+    let table = format!("\"{}\".\"{}\"", escape_identifier(&schema_name), escape_identifier(&table_name));
+    let first_sql = format!(
+        "SELECT * FROM {} ORDER BY {} LIMIT {}",
+        table,
+        table_ends_order_by(&key_columns, false),
+        count
+    );
+    let last_sql = format!(
+        "SELECT * FROM {} ORDER BY {} LIMIT {}",
+        table,
+        table_ends_order_by(&key_columns, true),
+        count
+    );
+
+    let start = Instant::now();
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let first = (&mut *conn)
+        .fetch_all(sqlx::raw_sql(&first_sql))
+        .await
+        .map_err(|e| format_db_error(&e))?;
+    let mut last = (&mut *conn)
+        .fetch_all(sqlx::raw_sql(&last_sql))
+        .await
+        .map_err(|e| format_db_error(&e))?;
+    last.reverse();
+    let execution_time_ms = start.elapsed().as_millis() as u64;
+
+    let columns: Vec<ColumnDef> = match first.first() {
+        Some(row) => row.columns().iter().map(ColumnDef::from_column).collect(),
+        None => match (&mut *conn).describe(first_sql.as_str()).await {
+            Ok(desc) => desc.columns().iter().map(ColumnDef::from_column).collect(),
+            Err(_) => vec![],
+        },
+    };
+    let to_json = |rows: Vec<sqlx::postgres::PgRow>| -> Vec<serde_json::Value> {
+        rows.iter()
+            .map(|row| {
+                serde_json::Value::Array(
+                    columns
+                        .iter()
+                        .enumerate()
+                        .map(|(i, col)| extract_value(row, i, &col.data_type, &value_options))
+                        .collect(),
+                )
+            })
+            .collect()
+    };
+    let mut first_rows = to_json(first);
+    let mut last_rows = to_json(last);
+    let masked = masked_column_indexes(state, &connection_id, &columns);
+    mask_rows(&mut first_rows, &masked);
+    mask_rows(&mut last_rows, &masked);
+
+    Ok(TableEndsPreview {
+        columns,
+        first_rows,
+        last_rows,
+        ordered_by_ctid: key_columns.is_empty(),
+        execution_time_ms,
+    })
+}
+
+/// Find groups of rows that share the same values in `columns`, largest groups
+/// first, as a standard result set: the columns plus `duplicate_count`.
+pub async fn find_duplicate_rows(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    columns: Vec<String>,
+    limit: u32,
+    state: &AppState,
+) -> Result<QueryResult, String> {
+    use crate::commands::table::{escape_identifier, validate_identifier};
+
+    validate_identifier(&schema_name)?;
+    validate_identifier(&table_name)?;
+    if columns.is_empty() {
+        return Err("Select at least one column to check for duplicates".to_string());
+    }
+    for column in &columns {
+        validate_identifier(column)?;
+    }
+
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let settings = query_settings(state);
+    let value_options = ValueOptions::from_settings(&settings);
+    let (limit, row_cap_applied) = clamp_to_row_cap(limit.max(1) as u64, settings.max_result_rows);
+    let column_list = columns
         .iter()
-        .map(|col| ColumnDef {
-            name: col.name().to_string(),
-            data_type: col.type_info().to_string(),
-        })
-        .collect();
+        .map(|c| format!("\"{}\"", escape_identifier(c)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    // One extra row tells us whether there are more groups than the limit.
+    let sql = format!(
+        "SELECT {cols}, COUNT(*) AS duplicate_count FROM \"{}\".\"{}\" \
+         GROUP BY {cols} HAVING COUNT(*) > 1 ORDER BY COUNT(*) DESC LIMIT {}",
+        escape_identifier(&schema_name),
+        escape_identifier(&table_name),
+        limit + 1,
+        cols = column_list,
+    );
 
-    let has_more = rows.len() > limit as usize;
-    let row_limit = std::cmp::min(rows.len(), limit as usize);
+    let start = Instant::now();
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let mut rows = (&mut *conn)
+        .fetch_all(sqlx::raw_sql(&sql))
+        .await
+        .map_err(|e| format_db_error(&e))?;
+    let execution_time_ms = start.elapsed().as_millis() as u64;
+    let has_more = rows.len() as u64 > limit;
+    rows.truncate(limit as usize);
 
-    let json_rows: Vec<serde_json::Value> = rows
-        .into_iter()
-        .take(row_limit)
+    let columns: Vec<ColumnDef> = match rows.first() {
+        Some(row) => row.columns().iter().map(ColumnDef::from_column).collect(),
+        None => match (&mut *conn).describe(sql.as_str()).await {
+            Ok(desc) => desc.columns().iter().map(ColumnDef::from_column).collect(),
+            Err(_) => vec![],
+        },
+    };
+    let mut json_rows: Vec<serde_json::Value> = rows
+        .iter()
         .map(|row| {
-            let values: Vec<serde_json::Value> = columns
-                .iter()
-                .enumerate()
-                .map(|(i, col)| extract_value(&row, i, &col.data_type))
-                .collect();
-            serde_json::Value::Array(values)
+            serde_json::Value::Array(
+                columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, col)| extract_value(row, i, &col.data_type, &value_options))
+                    .collect(),
+            )
         })
         .collect();
+    let masked = masked_column_indexes(state, &connection_id, &columns);
+    mask_rows(&mut json_rows, &masked);
 
     Ok(QueryResult {
         columns,
+        row_count: json_rows.len(),
         rows: json_rows,
-        row_count: row_limit,
         execution_time_ms,
         has_more,
         history_entry_id: None,
+        row_cap_applied,
+        notices: Vec::new(),
+        snapshot_id: None,
     })
 }
 
-/// Execute a statement that doesn't return rows (INSERT, UPDATE, DELETE, etc.)
-pub async fn execute_statement(
-    connection_id: String,
-    sql: String,
-    schema: Option<String>,
-    state: &AppState,
-) -> Result<ExecuteResult, String> {
-    let pool = state
-        .get_pool(&connection_id)
-        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
-
-    let start = Instant::now();
+/// Queries currently registered for cancellation, optionally for one connection.
+/// Lets the UI find and cancel a query whose ID it no longer has.
+pub fn list_running_queries(connection_id: Option<String>, state: &AppState) -> Vec<RunningQueryInfo> {
+    state.running_queries(connection_id.as_deref())
+}
 
-    // Acquire a dedicated connection so SET search_path and the statement
-    // run on the same connection
-    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+/// A connection's effective statement timeout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTimeout {
+    /// 0 means no timeout.
+    pub timeout_seconds: u32,
+    /// Set with `set_session_timeout` rather than taken from the query settings.
+    pub is_override: bool,
+    /// `statement_timeout` as the server reports it once applied (e.g. `30s`);
+    /// None when it couldn't be read back.
+    #[serde(default)]
+    pub server_value: Option<String>,
+}
 
-    // Apply the user's query timeout (non-fatal for non-PG servers)
-    if apply_statement_timeout(&mut conn, query_timeout_seconds(state)).await.is_err() {
-        drop(conn);
-        conn = pool.acquire().await.map_err(|e| e.to_string())?;
+fn session_timeout(connection_id: &str, state: &AppState) -> SessionTimeout {
+    SessionTimeout {
+        timeout_seconds: query_timeout_seconds(state, connection_id),
+        is_override: state.session_timeout(connection_id).is_some(),
+        server_value: None,
     }
+}
 
-    // Set search_path if schema is specified (non-fatal for non-PG servers)
-    if let Some(ref schema_name) = schema {
-        if let Err(_) = set_search_path(&mut conn, schema_name).await {
-            drop(conn);
-            conn = pool.acquire().await.map_err(|e| e.to_string())?;
-        }
+/// Largest statement timeout the server accepts, in whole seconds
+/// (`statement_timeout` is an int of milliseconds).
+const MAX_STATEMENT_TIMEOUT_SECONDS: u32 = i32::MAX as u32 / 1000;
+
+/// Apply `timeout_seconds` on a pooled connection the way a query would and
+/// read back what the server made of it.
+async fn server_statement_timeout(pool: &sqlx::PgPool, timeout_seconds: u32) -> Option<String> {
+    let mut conn = pool.acquire().await.ok()?;
+    if apply_statement_timeout(&mut conn, timeout_seconds).await.is_err() {
+        conn.detach();
+        return None;
     }
-
-    let result = (&mut *conn).execute(sqlx::raw_sql(&sql)).await;
+    let value = (&mut *conn)
+        .fetch_one(sqlx::raw_sql("SELECT current_setting('statement_timeout')"))
+        .await
+        .ok()
+        .and_then(|row| row.try_get::<String, _>(0).ok());
     reset_statement_timeout(&mut conn).await;
-    let result = result.map_err(|e| format_db_error(&e))?;
-
-    let execution_time_ms = start.elapsed().as_millis() as u64;
-
-    let rows_affected = result.rows_affected();
+    value
+}
 
-    // Auto-save to query history (fire-and-forget, no results for statements)
-    let statement_history_id = uuid::Uuid::new_v4().to_string();
-    {
-        let connection_name = state
-            .get_config(&connection_id)
-            .map(|c| c.name)
-            .unwrap_or_else(|| connection_id.clone());
-        let table_names = extract_table_names_for_history(&sql);
-        let entry = QueryHistoryEntry {
-            id: statement_history_id.clone(),
-            connection_id: connection_id.clone(),
-            connection_name,
-            sql: sql.clone(),
-            row_count: Some(rows_affected as i64),
-            execution_time_ms: execution_time_ms as i64,
-            executed_at: chrono::Utc::now().to_rfc3339(),
-            has_results: false,
-            schema: schema.clone(),
-            column_count: None,
-            table_names,
-            source: None,
-        };
-        if let Ok(db) = state.metadata_db.lock() {
-            if let Err(e) = sqlite::save_query_history(&db, &entry, None, None) {
-                log::warn!("Failed to save query history: {}", e);
-            }
-        }
+/// Temporarily change the statement timeout for queries on a connection,
+/// without touching the saved settings; `None` restores the settings' value.
+/// Each query applies it on its pooled connection and resets it on release;
+/// the override itself lasts until disconnect.
+pub async fn set_session_timeout(
+    connection_id: String,
+    timeout_seconds: Option<u32>,
+    state: &AppState,
+) -> Result<SessionTimeout, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+    if let Some(seconds) = timeout_seconds.filter(|&s| s > MAX_STATEMENT_TIMEOUT_SECONDS) {
+        return Err(format!(
+            "Statement timeout must be at most {}s, got {}s",
+            MAX_STATEMENT_TIMEOUT_SECONDS, seconds
+        ));
     }
-
-    Ok(ExecuteResult {
-        rows_affected,
-        execution_time_ms,
-        history_entry_id: Some(statement_history_id),
-    })
+    state.set_session_timeout(&connection_id, timeout_seconds);
+    let mut timeout = session_timeout(&connection_id, state);
+    timeout.server_value = server_statement_timeout(&pool, timeout.timeout_seconds).await;
+    Ok(timeout)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ExecuteResult {
-    pub rows_affected: u64,
-    pub execution_time_ms: u64,
-    // NOTE: this struct intentionally has NO `rename_all` — the Swift side maps
-    // these via explicit snake_case CodingKeys, so fields must stay snake_case.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub history_entry_id: Option<String>,
+/// The statement timeout queries on a connection currently run with, as
+/// tracked here and as the server reports it once applied.
+pub async fn get_session_timeout(connection_id: String, state: &AppState) -> Result<SessionTimeout, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+    let mut timeout = session_timeout(&connection_id, state);
+    timeout.server_value = server_statement_timeout(&pool, timeout.timeout_seconds).await;
+    Ok(timeout)
 }
 
 /// Cancel a running query
@@ -535,6 +2374,10 @@ pub async fn cancel_query(
 
     // Mark the query as cancelled
     state.mark_query_cancelled(&query_id);
+    // Still waiting for a connection: the flag alone stops it.
+    if backend_pid == 0 {
+        return Ok(true);
+    }
 
     // Send cancel signal to PostgreSQL (pg_cancel_backend is PG-specific)
     let cancel_sql = format!("SELECT pg_cancel_backend({})", backend_pid);
@@ -631,20 +2474,7 @@ pub async fn validate_sql(
         Err(e) => {
             let error_msg = e.to_string();
 
-            // Extract position directly from PgDatabaseError (e.to_string() drops it)
-            let raw_position = if let sqlx::Error::Database(ref db_err) = e {
-                if let Some(pg_err) = db_err.try_downcast_ref::<sqlx::postgres::PgDatabaseError>() {
-                    if let Some(sqlx::postgres::PgErrorPosition::Original(pos)) = pg_err.position() {
-                        Some(pos as usize)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+            let raw_position = pg_error_position(&e);
 
             // Adjust position: subtract PREPARE prefix, add back leading whitespace
             let position = raw_position.map(|p| {
@@ -675,6 +2505,167 @@ pub async fn validate_sql(
     }
 }
 
+/// Validation outcome for one statement of a script. `start`/`end` are the
+/// statement's char offsets within the script; error positions are script-relative.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatementValidation {
+    pub start: usize,
+    pub end: usize,
+    /// False when the statement kind can't be checked safely (transaction
+    /// control, DO/CALL, COPY, VACUUM, ...); such statements report as valid.
+    pub checked: bool,
+    pub valid: bool,
+    pub error: Option<ValidationError>,
+}
+
+/// Statements PREPARE accepts; checked without running them.
+const PREPARABLE_KEYWORDS: [&str; 8] = ["SELECT", "WITH", "VALUES", "TABLE", "INSERT", "UPDATE", "DELETE", "MERGE"];
+/// DDL that is checked by running it inside the validation transaction, which is
+/// always rolled back, so later statements see the objects it creates.
+const TRANSACTIONAL_DDL_KEYWORDS: [&str; 6] = ["CREATE", "ALTER", "DROP", "COMMENT", "GRANT", "REVOKE"];
+
+/// Validate every statement of a script on one connection. The whole run happens in
+/// a transaction that is rolled back, under `DRY_RUN_TIMEOUTS`; each statement gets
+/// its own savepoint so one failure doesn't mask the rest.
+pub async fn validate_script(
+    connection_id: String,
+    sql: String,
+    schema: Option<String>,
+    state: &AppState,
+) -> Result<Vec<StatementValidation>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let spans = split_statements(&sql);
+    if spans.is_empty() {
+        return Ok(Vec::new());
+    }
+    let chars: Vec<char> = sql.chars().collect();
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    if let Some(ref schema_name) = schema {
+        if set_search_path(&mut conn, schema_name).await.is_err() {
+            drop(conn);
+            conn = pool.acquire().await.map_err(|e| e.to_string())?;
+        }
+    }
+
+    (&mut *conn)
+        .execute(sqlx::raw_sql(&format!("BEGIN; {}", DRY_RUN_TIMEOUTS)))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut results = Vec::with_capacity(spans.len());
+    let outcome: Result<(), String> = async {
+        for span in spans {
+            let statement: String = chars[span.clone()].iter().collect();
+            let keyword = leading_keyword(&statement);
+            let (check_sql, prefix_len) = if PREPARABLE_KEYWORDS.contains(&keyword.as_str()) {
+                let prefix = "PREPARE pharos_validate_script AS ";
+                (format!("{}{}", prefix, statement), prefix.chars().count())
+            } else if TRANSACTIONAL_DDL_KEYWORDS.contains(&keyword.as_str()) {
+                (statement.clone(), 0)
+            } else {
+                results.push(StatementValidation {
+                    start: span.start,
+                    end: span.end,
+                    checked: false,
+                    valid: true,
+                    error: None,
+                });
+                continue;
+            };
+
+            (&mut *conn)
+                .execute(sqlx::raw_sql("SAVEPOINT pharos_validate"))
+                .await
+                .map_err(|e| e.to_string())?;
+            let checked = (&mut *conn).execute(sqlx::raw_sql(&check_sql)).await;
+            let cleanup = match checked {
+                Ok(_) if prefix_len > 0 => "DEALLOCATE pharos_validate_script; RELEASE SAVEPOINT pharos_validate",
+                Ok(_) => "RELEASE SAVEPOINT pharos_validate",
+                Err(_) => "ROLLBACK TO SAVEPOINT pharos_validate",
+            };
+            (&mut *conn)
+                .execute(sqlx::raw_sql(cleanup))
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let validation = match checked {
+                Ok(_) => StatementValidation {
+                    start: span.start,
+                    end: span.end,
+                    checked: true,
+                    valid: true,
+                    error: None,
+                },
+                // Can't run in a transaction (e.g. CREATE INDEX CONCURRENTLY),
+                // blocked on a lock or too slow to try: not a problem with the SQL itself.
+                Err(ref e) if matches!(pg_error_code(e).as_deref(), Some("25001") | Some("55P03") | Some("57014")) => StatementValidation {
+                    start: span.start,
+                    end: span.end,
+                    checked: false,
+                    valid: true,
+                    error: None,
+                },
+                Err(e) => {
+                    // PG positions are 1-based chars within the submitted text.
+                    let position = pg_error_position(&e)
+                        .map(|p| span.start + p.saturating_sub(prefix_len).max(1));
+                    let (line, column) = match position {
+                        Some(pos) => {
+                            let (l, c) = char_position_to_line_col(&sql, pos);
+                            (Some(l), Some(c))
+                        }
+                        None => (None, None),
+                    };
+                    StatementValidation {
+                        start: span.start,
+                        end: span.end,
+                        checked: true,
+                        valid: false,
+                        error: Some(ValidationError {
+                            message: clean_error_message(&e.to_string()),
+                            position,
+                            line,
+                            column,
+                        }),
+                    }
+                }
+            };
+            results.push(validation);
+        }
+        Ok(())
+    }
+    .await;
+
+    // Roll back everything the DDL checks did; drop the connection if that fails
+    // so it doesn't return to the pool mid-transaction.
+    if (&mut *conn).execute(sqlx::raw_sql("ROLLBACK")).await.is_err() {
+        conn.detach();
+    }
+    outcome.map(|_| results)
+}
+
+/// The error's position within the submitted SQL (1-based chars), if the server gave one.
+fn pg_error_position(e: &sqlx::Error) -> Option<usize> {
+    // Extract position directly from PgDatabaseError (e.to_string() drops it)
+    let sqlx::Error::Database(db_err) = e else { return None };
+    let pg_err = db_err.try_downcast_ref::<sqlx::postgres::PgDatabaseError>()?;
+    match pg_err.position() {
+        Some(sqlx::postgres::PgErrorPosition::Original(pos)) => Some(pos),
+        _ => None,
+    }
+}
+
+fn pg_error_code(e: &sqlx::Error) -> Option<String> {
+    match e {
+        sqlx::Error::Database(db_err) => db_err.code().map(|c| c.to_string()),
+        _ => None,
+    }
+}
+
 /// Convert a character position to line and column numbers
 fn char_position_to_line_col(sql: &str, position: usize) -> (usize, usize) {
     let mut line = 1;
@@ -710,6 +2701,58 @@ fn clean_error_message(error_msg: &str) -> String {
     }
 }
 
+/// Pretty-print a JSON/JSONB cell value for a detail view. Object keys come out
+/// sorted (serde_json's map is ordered by key), so equal values always render alike.
+pub fn format_json_value(json: &str) -> Result<String, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+    serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+}
+
+/// Outcome of `validate_jsonb`: where parsing failed, if it did.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonbValidation {
+    pub valid: bool,
+    pub error: Option<ValidationError>,
+}
+
+/// Check a jsonb cell edit before it is committed. The text itself is what gets
+/// sent: re-serializing it here would lose number precision that jsonb keeps.
+/// Also rejects `\u0000`, which jsonb can't store even though it is valid JSON.
+pub fn validate_jsonb(json: &str) -> JsonbValidation {
+    let invalid = |message: String, line: usize, column: usize| JsonbValidation {
+        valid: false,
+        error: Some(ValidationError {
+            message,
+            position: Some(line_col_to_char_position(json, line, column)),
+            line: Some(line),
+            column: Some(column),
+        }),
+    };
+    if let Err(e) = serde_json::from_str::<serde::de::IgnoredAny>(json) {
+        let message = e.to_string();
+        // serde_json appends " at line L column C"; the location is reported separately.
+        let message = message.split(" at line ").next().unwrap_or(&message).to_string();
+        return invalid(message, e.line(), e.column());
+    }
+    // An escape only when preceded by an even run of backslashes (`\\u0000` is text).
+    let nul_escape = json.match_indices("\\u0000").map(|(i, _)| i).find(|&i| {
+        json[..i].bytes().rev().take_while(|&b| b == b'\\').count() % 2 == 0
+    });
+    if let Some(offset) = nul_escape {
+        let (line, column) = char_position_to_line_col(json, json[..offset].chars().count() + 1);
+        return invalid("unsupported Unicode escape sequence \\u0000".to_string(), line, column);
+    }
+    JsonbValidation { valid: true, error: None }
+}
+
+/// Inverse of `char_position_to_line_col`: the 1-based char position of a
+/// 1-based line and column.
+fn line_col_to_char_position(text: &str, line: usize, column: usize) -> usize {
+    let preceding: usize = text.split('\n').take(line.saturating_sub(1)).map(|l| l.chars().count() + 1).sum();
+    preceding + column.max(1)
+}
+
 /// Extract table names from SQL for history display.
 /// Scans for FROM and JOIN keywords, returns comma-separated table names.
 pub fn extract_table_names_for_history(sql: &str) -> Option<String> {
@@ -792,3 +2835,464 @@ fn parse_identifier(s: &str) -> Option<(String, &str)> {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{state_with_pool, test_pool};
+
+    #[test]
+    fn affected_rows_estimate_reads_below_modify_table() {
+        let plan = serde_json::json!([{"Plan": {
+            "Node Type": "ModifyTable", "Operation": "Delete", "Plan Rows": 0,
+            "Plans": [{"Node Type": "Seq Scan", "Parent Relationship": "Outer", "Plan Rows": 4821}]
+        }}]);
+        assert_eq!(estimated_affected_rows(&plan), Some(4821.0));
+
+        let with_init_plan = serde_json::json!([{"Plan": {
+            "Node Type": "ModifyTable", "Operation": "Update", "Plan Rows": 0,
+            "Plans": [
+                {"Node Type": "Result", "Parent Relationship": "InitPlan", "Plan Rows": 1},
+                {"Node Type": "Seq Scan", "Parent Relationship": "Outer", "Plan Rows": 250}
+            ]
+        }}]);
+        assert_eq!(estimated_affected_rows(&with_init_plan), Some(250.0));
+
+        let inherited = serde_json::json!([{"Plan": {
+            "Node Type": "ModifyTable", "Operation": "Delete", "Plan Rows": 0,
+            "Plans": [
+                {"Node Type": "Seq Scan", "Parent Relationship": "Member", "Plan Rows": 10},
+                {"Node Type": "Seq Scan", "Parent Relationship": "Member", "Plan Rows": 5}
+            ]
+        }}]);
+        assert_eq!(estimated_affected_rows(&inherited), Some(15.0));
+
+        let returning = serde_json::json!([{"Plan": {"Node Type": "Result", "Plan Rows": 3}}]);
+        assert_eq!(estimated_affected_rows(&returning), Some(3.0));
+        assert_eq!(estimated_affected_rows(&serde_json::json!([])), None);
+    }
+
+    #[test]
+    fn table_ends_order_by_key_columns_or_ctid() {
+        let keys = vec!["tenant".to_string(), "id".to_string()];
+        assert_eq!(table_ends_order_by(&keys, false), "\"tenant\", \"id\"");
+        assert_eq!(table_ends_order_by(&keys, true), "\"tenant\" DESC, \"id\" DESC");
+        assert_eq!(table_ends_order_by(&[], true), "ctid DESC");
+    }
+
+    #[test]
+    fn limit_under_cap_is_unchanged() {
+        assert_eq!(clamp_to_row_cap(1000, 1_000_000), (1000, false));
+    }
+
+    #[test]
+    fn limit_at_cap_is_not_flagged() {
+        assert_eq!(clamp_to_row_cap(500, 500), (500, false));
+    }
+
+    #[test]
+    fn limit_over_cap_is_clamped_and_flagged() {
+        assert_eq!(clamp_to_row_cap(10_000_000, 1_000_000), (1_000_000, true));
+    }
+
+    #[test]
+    fn zero_cap_still_allows_one_row() {
+        assert_eq!(clamp_to_row_cap(10, 0), (1, true));
+    }
+
+    #[test]
+    fn split_statements_respects_quotes_and_comments() {
+        let sql = "select ';';\n-- a; b\ncreate function f() returns int as $$ select 1; $$ language sql;\n\n  /* only; comment */ ;";
+        let pieces: Vec<String> = split_statements(sql)
+            .into_iter()
+            .map(|r| sql.chars().skip(r.start).take(r.end - r.start).collect())
+            .collect();
+        assert_eq!(
+            pieces,
+            vec![
+                "select ';'".to_string(),
+                "-- a; b\ncreate function f() returns int as $$ select 1; $$ language sql".to_string(),
+            ]
+        );
+        assert!(split_statements("  ;; ").is_empty());
+    }
+
+    #[test]
+    fn paginated_sql_strips_trailing_semicolons_and_comments() {
+        let wrap = |inner: &str| format!("SELECT * FROM ({}) AS _pharos_paginated LIMIT 51 OFFSET 100", inner);
+        assert_eq!(
+            paginated_sql("select * from t -- newest first", 51, 100).unwrap(),
+            wrap("select * from t")
+        );
+        assert_eq!(
+            paginated_sql("select * from t; /* trailing */ -- done\n", 51, 100).unwrap(),
+            wrap("select * from t")
+        );
+        assert_eq!(
+            paginated_sql("select '--' as a, 1 /* c */ as b /* end */", 51, 100).unwrap(),
+            wrap("select '--' as a, 1 /* c */ as b")
+        );
+        assert_eq!(
+            paginated_sql("-- header\nselect *\nfrom t -- why\norder by id\nlimit 10;", 51, 100).unwrap(),
+            wrap("-- header\nselect *\nfrom t -- why\norder by id\nlimit 10")
+        );
+    }
+
+    #[test]
+    fn paginated_sql_rejects_bad_bounds_and_scripts() {
+        assert!(paginated_sql("select 1", -1, 0).is_err());
+        assert!(paginated_sql("select 1", 10, -5).is_err());
+        assert!(paginated_sql("select 1", 10, MAX_PAGINATION_BOUND + 1).is_err());
+        assert!(paginated_sql("select 1", i64::MAX, 0).is_err());
+        assert!(paginated_sql("select 1", MAX_PAGINATION_BOUND, MAX_PAGINATION_BOUND).is_ok());
+        assert!(paginated_sql("select 1; select 2", 10, 0).is_err());
+        assert!(paginated_sql(" ; -- nothing", 10, 0).is_err());
+    }
+
+    #[test]
+    fn search_path_setting_quotes_like_postgres() {
+        assert_eq!(search_path_setting("sales"), "sales, public");
+        assert_eq!(search_path_setting("Sales"), "\"Sales\", public");
+        assert_eq!(search_path_setting("my schema"), "\"my schema\", public");
+        assert_eq!(search_path_setting("1st"), "\"1st\", public");
+    }
+
+    #[test]
+    fn leading_keyword_skips_comments_and_whitespace() {
+        assert_eq!(leading_keyword("  -- note\n/* block */ select 1"), "SELECT");
+        assert_eq!(leading_keyword("with x as (select 1) select * from x"), "WITH");
+        assert_eq!(leading_keyword(""), "");
+    }
+
+    #[test]
+    fn geometry_decoding_is_opt_in() {
+        let hex = "0101000000000000000000F03F0000000000000040";
+        let off = ValueOptions::default();
+        let on = ValueOptions { decode_geometry: true, ..Default::default() };
+        assert_eq!(text_to_value(hex, "geometry", &off), serde_json::json!(hex));
+        assert_eq!(
+            text_to_value(hex, "geometry", &on),
+            serde_json::json!(r#"{"coordinates":[1.0,2.0],"type":"Point"}"#)
+        );
+        assert_eq!(text_to_value(hex, "text", &on), serde_json::json!(hex));
+    }
+
+    #[test]
+    fn vectors_parse_to_number_arrays() {
+        let opts = ValueOptions::default();
+        assert_eq!(text_to_value("[0.5,1,-2]", "vector", &opts), serde_json::json!([0.5, 1.0, -2.0]));
+        assert_eq!(text_to_value("[1,oops]", "vector", &opts), serde_json::json!("[1,oops]"));
+    }
+
+    #[test]
+    fn long_vectors_are_previewed() {
+        let text = format!("[{}]", (0..100).map(|i| i.to_string()).collect::<Vec<_>>().join(","));
+        let preview = parse_vector(&text).unwrap();
+        assert_eq!(preview["dimensions"], 100);
+        assert_eq!(preview["head"], serde_json::json!([0.0, 1.0, 2.0, 3.0]));
+        assert_eq!(preview["tail"], serde_json::json!([96.0, 97.0, 98.0, 99.0]));
+    }
+
+    #[test]
+    fn json_values_are_pretty_printed_with_sorted_keys() {
+        let formatted = format_json_value(r#"{"b":1,"a":{"d":[1,2],"c":null}}"#).unwrap();
+        assert_eq!(
+            formatted,
+            "{\n  \"a\": {\n    \"c\": null,\n    \"d\": [\n      1,\n      2\n    ]\n  },\n  \"b\": 1\n}"
+        );
+        assert!(format_json_value("{not json").is_err());
+    }
+
+    #[test]
+    fn glob_patterns_match_column_names() {
+        assert!(glob_matches("ssn", "SSN"));
+        assert!(glob_matches("*password*", "user_password_hash"));
+        assert!(glob_matches("card_????", "card_last"));
+        assert!(!glob_matches("card_????", "card_number"));
+        assert!(glob_matches("*_token", "api_token"));
+        assert!(!glob_matches("*_token", "token_count"));
+    }
+
+    #[test]
+    fn masking_keeps_nulls_and_other_columns() {
+        let mut rows = vec![serde_json::json!(["alice", "123-45-6789"]), serde_json::json!(["bob", null])];
+        mask_rows(&mut rows, &[1]);
+        assert_eq!(rows[0], serde_json::json!(["alice", "****"]));
+        assert_eq!(rows[1], serde_json::json!(["bob", null]));
+    }
+
+    #[test]
+    fn only_single_reads_use_a_cursor() {
+        assert!(is_cursor_eligible("SELECT * FROM t;"));
+        assert!(!is_cursor_eligible("SELECT 1; SELECT 2"));
+        assert!(!is_cursor_eligible("DELETE FROM t"));
+        assert!(is_cursor_eligible("SELECT 1; -- trailing note"));
+        assert!(!is_cursor_eligible("SELECT * INTO copy_of_t FROM t"));
+        assert!(is_cursor_eligible("SELECT 'INTO' AS \"into\" FROM t"));
+    }
+
+    #[test]
+    fn returning_dml_is_a_mutation() {
+        assert!(is_mutating_statement("DELETE FROM t WHERE id = 1 RETURNING *"));
+        assert!(is_mutating_statement("insert into t values (1) returning id"));
+        assert!(is_mutating_statement("WITH gone AS (DELETE FROM t RETURNING *) SELECT * FROM gone"));
+        assert!(!is_mutating_statement("WITH x AS (SELECT 'delete' AS \"update\") SELECT * FROM x"));
+        assert!(!is_mutating_statement("SELECT $$ insert $$"));
+        assert!(!is_mutating_statement("WITH x AS (SELECT * FROM t FOR UPDATE) SELECT * FROM x"));
+        assert!(!is_mutating_statement("WITH x AS (SELECT * FROM t FOR NO KEY UPDATE SKIP LOCKED) SELECT * FROM x"));
+        assert!(is_mutating_statement("WITH x AS (SELECT id FROM t FOR UPDATE) UPDATE t SET n = 1 FROM x WHERE t.id = x.id"));
+        assert!(is_read_statement("WITH x AS (SELECT * FROM t FOR UPDATE) SELECT * FROM x"));
+        assert!(!is_read_statement("WITH gone AS (DELETE FROM t RETURNING *) SELECT * FROM gone"));
+    }
+
+    #[test]
+    fn json_paths_select_columns_by_position() {
+        let column = |name: &str, data_type: &str| ColumnDef {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            type_oid: 0,
+        };
+        let columns = vec![column("id", "INT4"), column("id", "INT4"), column("doc", "JSONB")];
+        let paths = vec![JsonPathExtraction { column: "doc".to_string(), path: vec!["a".to_string()] }];
+        assert_eq!(
+            json_paths_sql("SELECT a.id, b.id, a.doc FROM a JOIN b USING (k)", &columns, &paths).unwrap(),
+            "SELECT pharos_c0 AS \"id\", pharos_c1 AS \"id\", pharos_c2::jsonb #>> '{\"a\"}' AS \"doc\" \
+             FROM (SELECT a.id, b.id, a.doc FROM a JOIN b USING (k)) AS pharos_src(pharos_c0, pharos_c1, pharos_c2)"
+        );
+
+        let missing = vec![JsonPathExtraction { column: "nope".to_string(), path: vec!["a".to_string()] }];
+        assert!(json_paths_sql("SELECT 1", &columns, &missing).is_err());
+        let not_json = vec![JsonPathExtraction { column: "id".to_string(), path: vec!["a".to_string()] }];
+        assert!(json_paths_sql("SELECT 1", &columns, &not_json).is_err());
+    }
+
+    #[test]
+    fn json_path_literal_quotes_segments() {
+        let path = vec!["address".to_string(), "it's \"x\"".to_string(), "0".to_string()];
+        assert_eq!(
+            json_path_literal(&path).unwrap(),
+            r#"'{"address","it''s \"x\"","0"}'"#
+        );
+        assert!(json_path_literal(&[]).is_err());
+        assert!(json_path_literal(&["".to_string()]).is_err());
+    }
+
+    #[test]
+    fn empty_string_stays_distinct_from_null() {
+        let opts = ValueOptions::default();
+        assert_eq!(text_to_value("", "text", &opts), serde_json::json!(""));
+        assert_ne!(text_to_value("", "text", &opts), serde_json::Value::Null);
+    }
+
+    #[test]
+    fn only_reads_are_explained() {
+        assert!(is_read_statement("SELECT * FROM t"));
+        assert!(!is_read_statement("DELETE FROM t"));
+        assert!(!is_read_statement("-- SELECT\nUPDATE t SET a = 1"));
+    }
+
+    #[test]
+    fn statement_shape_matches_normalized_text() {
+        assert_eq!(
+            statement_shape("SELECT * FROM t1 WHERE id = 42 AND name = 'x''y' -- note\n;"),
+            statement_shape("select * from t1 where id = $1 and name = $2"),
+        );
+        assert_ne!(statement_shape(r#"SELECT "A" FROM t"#), statement_shape(r#"SELECT "a" FROM t"#));
+    }
+
+    #[test]
+    fn snapshot_key_ignores_layout_but_not_snapshot() {
+        let key = snapshot_key("SELECT *\n  FROM t;", "100:105:");
+        assert_eq!(key, snapshot_key("SELECT * FROM t", "100:105:"));
+        assert_ne!(key, snapshot_key("SELECT * FROM t", "100:106:"));
+        assert_ne!(key, snapshot_key("SELECT * FROM u", "100:105:"));
+        assert_ne!(
+            snapshot_key("SELECT * FROM t WHERE s = 'a  b'", "100:105:"),
+            snapshot_key("SELECT * FROM t WHERE s = 'a b'", "100:105:")
+        );
+        assert_eq!(key.len(), 64);
+    }
+
+    #[test]
+    fn jsonb_validation_locates_errors() {
+        let ok = validate_jsonb("{\"b\": 1, \"a\": [true, null], \"n\": 12345678901234567890.123456789}");
+        assert!(ok.valid);
+        assert!(ok.error.is_none());
+
+        let bad = validate_jsonb("{\n  \"a\": 1,\n}");
+        assert!(!bad.valid);
+        let error = bad.error.unwrap();
+        assert_eq!((error.line, error.column), (Some(3), Some(1)));
+        assert_eq!(error.position, Some(13));
+
+        let nul = validate_jsonb(r#"{"a": "x\u0000"}"#);
+        assert!(!nul.valid);
+        assert_eq!(nul.error.unwrap().column, Some(9));
+        assert!(validate_jsonb(r#"{"a": "x\\u0000"}"#).valid);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn statement_timeouts_name_the_limit_but_user_cancels_do_not() {
+        let pool = test_pool(2).await;
+        let state = state_with_pool("sleep", pool);
+        state.set_session_timeout("sleep", Some(1));
+        let run = |query_id: &str| {
+            execute_query("sleep".into(), "SELECT pg_sleep(5)".into(), Some(query_id.into()), None, None, None, None, &state)
+        };
+
+        let err = run("timed-out").await.unwrap_err();
+        assert_eq!(err, "Query timed out after 1s");
+
+        let cancel = async {
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            cancel_query("sleep".into(), "cancelled".into(), &state).await
+        };
+        let (result, cancelled) = tokio::join!(run("cancelled"), cancel);
+        assert!(cancelled.unwrap());
+        let err = result.unwrap_err();
+        assert_ne!(err, "Query timed out after 1s");
+        assert!(err.contains("cancel"), "{}", err);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn profiled_statements_time_out_cancel_and_unregister_when_dropped() {
+        let pool = test_pool(2).await;
+        let state = state_with_pool("profile", pool);
+        state.set_session_timeout("profile", Some(1));
+        let run = |query_id: &str| profile_query("profile".into(), "SELECT pg_sleep(5)".into(), None, Some(query_id.into()), &state);
+
+        let err = run("timed-out").await.unwrap_err();
+        assert_eq!(err, "Query timed out after 1s");
+
+        let cancel = async {
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            cancel_query("profile".into(), "cancelled".into(), &state).await
+        };
+        let (result, cancelled) = tokio::join!(run("cancelled"), cancel);
+        assert!(cancelled.unwrap());
+        assert_eq!(result.unwrap_err(), "Query was cancelled");
+
+        let abandoned = tokio::time::timeout(std::time::Duration::from_millis(300), run("abandoned")).await;
+        assert!(abandoned.is_err());
+        assert!(state.running_queries(None).is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn cursor_fetches_time_out_and_can_be_cancelled() {
+        let pool = test_pool(2).await;
+        let state = state_with_pool("cursor", pool);
+        state.set_session_timeout("cursor", Some(1));
+        let open = || open_cursor("cursor".into(), "SELECT pg_sleep(5) FROM generate_series(1, 2)".into(), None, &state);
+
+        let cursor = open().await.unwrap();
+        let err = fetch_cursor(cursor.cursor_id, 1, Some("timed-out".into()), &state).await.unwrap_err();
+        assert_eq!(err, "Query timed out after 1s");
+
+        let cursor = open().await.unwrap();
+        let cancel = async {
+            tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+            cancel_query("cursor".into(), "cancelled".into(), &state).await
+        };
+        let (result, cancelled) = tokio::join!(fetch_cursor(cursor.cursor_id, 1, Some("cancelled".into()), &state), cancel);
+        assert!(cancelled.unwrap());
+        let err = result.unwrap_err();
+        assert!(err.contains("cancel"), "{}", err);
+        assert!(state.running_queries(None).is_empty());
+    }
+
+    #[test]
+    fn columnar_result_transposes_rows() {
+        let result: QueryResult = serde_json::from_value(serde_json::json!({
+            "columns": [
+                {"name": "id", "data_type": "INT4"},
+                {"name": "score", "data_type": "FLOAT8"},
+            ],
+            "rows": [[1, 0.5], [2, null]],
+            "row_count": 2,
+            "execution_time_ms": 3,
+            "has_more": false,
+            "history_entry_id": null,
+        }))
+        .unwrap();
+        let columnar = ColumnarQueryResult::from(result);
+        assert_eq!(columnar.columns.len(), 2);
+        assert_eq!(columnar.data, vec![
+            vec![serde_json::json!(1), serde_json::json!(2)],
+            vec![serde_json::json!(0.5), serde_json::Value::Null],
+        ]);
+        assert_eq!(columnar.row_count, 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn session_timeout_is_read_back_from_the_server() {
+        let pool = test_pool(1).await;
+        let state = state_with_pool("timeout", pool);
+
+        let set = set_session_timeout("timeout".into(), Some(90), &state).await.unwrap();
+        assert_eq!(set.server_value.as_deref(), Some("90s"));
+        let got = get_session_timeout("timeout".into(), &state).await.unwrap();
+        assert_eq!((got.timeout_seconds, got.is_override), (90, true));
+        assert_eq!(got.server_value.as_deref(), Some("90s"));
+
+        let too_long = set_session_timeout("timeout".into(), Some(MAX_STATEMENT_TIMEOUT_SECONDS + 1), &state).await;
+        assert!(too_long.is_err());
+        assert_eq!(state.session_timeout("timeout"), Some(90));
+    }
+
+    #[test]
+    fn session_timeout_overrides_setting_until_disconnect() {
+        let state = AppState::new(rusqlite::Connection::open_in_memory().unwrap());
+        let default = crate::models::QuerySettings::default().timeout_seconds;
+        assert_eq!(query_timeout_seconds(&state, "c1"), default);
+
+        state.set_session_timeout("c1", Some(0));
+        assert_eq!(query_timeout_seconds(&state, "c1"), 0);
+        assert_eq!(query_timeout_seconds(&state, "c2"), default);
+
+        state.remove_pool("c1");
+        assert_eq!(query_timeout_seconds(&state, "c1"), default);
+        assert!(!session_timeout("c1", &state).is_override);
+    }
+
+    #[test]
+    fn query_registration_keeps_an_early_cancel_and_unregisters_on_drop() {
+        let state = AppState::new(rusqlite::Connection::open_in_memory().unwrap());
+        let registration = state.track_query("q1".to_string(), "c1");
+        assert!(state.mark_query_cancelled("q1"));
+        registration.set_backend_pid(4242);
+        assert!(registration.cancelled().load(Ordering::SeqCst));
+        assert_eq!(state.get_query_backend_pid("q1"), Some(4242));
+
+        drop(registration);
+        assert!(state.running_queries(None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn rowcount_guard_rejects_scripts_before_connecting() {
+        let state = AppState::new(rusqlite::Connection::open_in_memory().unwrap());
+        let guard = |sql: &str| execute_with_rowcount_guard("c1".into(), sql.into(), None, None, false, &state);
+
+        let err = guard("DELETE FROM t WHERE id = 1; DROP TABLE t").await.unwrap_err();
+        assert_eq!(err, "The row-count guard only applies to a single statement");
+        let err = guard("-- nothing here").await.unwrap_err();
+        assert_eq!(err, "No statement to execute");
+        let err = guard("DELETE FROM t; -- done").await.unwrap_err();
+        assert_eq!(err, "Not connected to: c1");
+    }
+
+    #[tokio::test]
+    async fn explain_and_run_rejects_scripts_before_connecting() {
+        let state = AppState::new(rusqlite::Connection::open_in_memory().unwrap());
+        let run = |sql: &str| explain_and_run("c1".into(), sql.into(), None, None, None, &state);
+
+        let err = run("SELECT 1; DELETE FROM t").await.unwrap_err();
+        assert_eq!(err, "Explain and run requires exactly one statement");
+        let err = run("/* nothing */").await.unwrap_err();
+        assert_eq!(err, "No query to run");
+        let err = run("SELECT 1; -- done").await.unwrap_err();
+        assert_eq!(err, "Not connected to: c1");
+    }
+}
@@ -49,6 +49,24 @@ pub async fn delete_query_history_entry(
         .map_err(|e| format!("Failed to delete history entry: {}", e))
 }
 
+/// Delete history matching all given filters (connection, executed before a
+/// date, full-text search). At least one filter is required — this is not a
+/// way to clear all history. Returns the number deleted.
+pub async fn delete_query_history_filtered(
+    connection_id: Option<String>,
+    before_date: Option<String>,
+    search: Option<String>,
+    state: &AppState,
+) -> Result<usize, String> {
+    let search = search.filter(|s| !s.trim().is_empty());
+    if connection_id.is_none() && before_date.is_none() && search.is_none() {
+        return Err("At least one history filter is required".to_string());
+    }
+    let db = state.metadata_db.lock().map_err(|e| e.to_string())?;
+    sqlite::delete_query_history_filtered(&db, connection_id.as_deref(), before_date.as_deref(), search.as_deref())
+        .map_err(|e| format!("Failed to delete history entries: {}", e))
+}
+
 /// Batch delete query history entries
 pub async fn batch_delete_query_history_entries(
     ids: Vec<String>,
@@ -59,6 +77,16 @@ pub async fn batch_delete_query_history_entries(
         .map_err(|e| format!("Failed to batch delete history entries: {}", e))
 }
 
+/// Full SQL of a history entry — the exact text sent to the server, for re-running
+pub async fn get_history_entry_sql(
+    entry_id: String,
+    state: &AppState,
+) -> Result<Option<String>, String> {
+    let db = state.metadata_db.lock().map_err(|e| e.to_string())?;
+    sqlite::get_query_history_sql(&db, &entry_id)
+        .map_err(|e| format!("Failed to load history entry: {}", e))
+}
+
 /// Load cached result data for a specific history entry
 pub async fn get_query_history_result(
     entry_id: String,
@@ -13,6 +13,36 @@ pub async fn create_saved_query(
     sqlite::create_saved_query(&db, &id, &query).map_err(|e| format!("Failed to create saved query: {}", e))
 }
 
+/// Copy a saved query under a fresh id, named "<name> (copy)", keeping its SQL,
+/// folder, connection, variables and column layout.
+pub async fn duplicate_saved_query(
+    state: &AppState,
+    query_id: String,
+) -> Result<SavedQuery, String> {
+    let db = state.metadata_db.lock().map_err(|e| e.to_string())?;
+
+    let original = sqlite::get_saved_query(&db, &query_id)
+        .map_err(|e| format!("Failed to get saved query: {}", e))?
+        .ok_or_else(|| format!("Saved query not found: {}", query_id))?;
+    let copy = CreateSavedQuery {
+        name: format!("{} (copy)", original.name),
+        folder: original.folder,
+        sql: original.sql,
+        connection_id: original.connection_id,
+        variables: original.variables,
+    };
+    let id = uuid::Uuid::new_v4().to_string();
+
+    let mut saved =
+        sqlite::create_saved_query(&db, &id, &copy).map_err(|e| format!("Failed to create saved query: {}", e))?;
+    if original.column_layout.is_some() {
+        sqlite::set_saved_query_column_layout(&db, &id, original.column_layout.as_deref())
+            .map_err(|e| format!("Failed to copy column layout: {}", e))?;
+        saved.column_layout = original.column_layout;
+    }
+    Ok(saved)
+}
+
 pub async fn load_saved_queries(state: &AppState) -> Result<Vec<SavedQuery>, String> {
     let db = state.metadata_db.lock().map_err(|e| e.to_string())?;
 
@@ -37,6 +67,38 @@ pub async fn update_saved_query(
     sqlite::update_saved_query(&db, &update).map_err(|e| format!("Failed to update saved query: {}", e))
 }
 
+/// Save the result grid layout for a saved query; `None` resets it to the
+/// default. The layout is opaque JSON owned by the frontend.
+pub async fn save_query_column_layout(
+    state: &AppState,
+    query_id: String,
+    layout: Option<String>,
+) -> Result<(), String> {
+    if let Some(ref json) = layout {
+        serde_json::from_str::<serde_json::Value>(json).map_err(|e| format!("Invalid column layout JSON: {}", e))?;
+    }
+    let db = state.metadata_db.lock().map_err(|e| e.to_string())?;
+
+    match sqlite::set_saved_query_column_layout(&db, &query_id, layout.as_deref()) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("Saved query not found: {}", query_id)),
+        Err(e) => Err(format!("Failed to save column layout: {}", e)),
+    }
+}
+
+/// Get a saved query's result grid layout, or `None` if it has none.
+pub async fn get_query_column_layout(
+    state: &AppState,
+    query_id: String,
+) -> Result<Option<String>, String> {
+    let db = state.metadata_db.lock().map_err(|e| e.to_string())?;
+
+    let query = sqlite::get_saved_query(&db, &query_id)
+        .map_err(|e| format!("Failed to get saved query: {}", e))?
+        .ok_or_else(|| format!("Saved query not found: {}", query_id))?;
+    Ok(query.column_layout)
+}
+
 pub async fn delete_saved_query(
     state: &AppState,
     query_id: String,
@@ -54,3 +116,28 @@ pub async fn batch_delete_saved_queries(
 
     sqlite::batch_delete_saved_queries(&db, &ids).map_err(|e| format!("Failed to batch delete saved queries: {}", e))
 }
+
+/// Rename a folder (and every folder nested under it) across saved queries and
+/// the settings' empty-folder list in one transaction. Returns the number of
+/// saved queries moved.
+pub async fn rename_saved_query_folder(
+    state: &AppState,
+    old_folder: String,
+    new_folder: String,
+) -> Result<usize, String> {
+    let old_folder = old_folder.trim().trim_matches('/').to_string();
+    let new_folder = new_folder.trim().trim_matches('/').to_string();
+    if old_folder.is_empty() || new_folder.is_empty() {
+        return Err("Folder names cannot be empty".to_string());
+    }
+    if new_folder == old_folder {
+        return Ok(0);
+    }
+    if new_folder.starts_with(&format!("{}/", old_folder)) {
+        return Err("Cannot move a folder into itself".to_string());
+    }
+    let mut db = state.metadata_db.lock().map_err(|e| e.to_string())?;
+
+    sqlite::rename_saved_query_folder(&mut db, &old_folder, &new_folder)
+        .map_err(|e| format!("Failed to rename folder: {}", e))
+}
@@ -0,0 +1,394 @@
+use crate::db::postgres;
+use crate::models::{DatabaseSetting, IdleTransactionSession, MaintenanceProgress, PreparedTransaction, TopStatement};
+use crate::state::AppState;
+
+/// Get the server's most expensive statements from pg_stat_statements,
+/// ordered by total execution time.
+pub async fn get_top_statements(
+    connection_id: String,
+    limit: Option<u32>,
+    state: &AppState,
+) -> Result<Vec<TopStatement>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let capabilities = postgres::get_server_capabilities(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !capabilities.pg_stat_statements_available {
+        return Err(if capabilities.pg_stat_statements_version.is_none() {
+            "pg_stat_statements is not installed in this database (CREATE EXTENSION pg_stat_statements)".to_string()
+        } else {
+            "pg_stat_statements is installed but not readable; it must be listed in shared_preload_libraries".to_string()
+        });
+    }
+
+    postgres::get_top_statements(&pool, capabilities.major_version, limit.unwrap_or(50) as i64)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List sessions in the connection's database that are idle inside a transaction.
+pub async fn get_idle_in_transaction(
+    connection_id: String,
+    state: &AppState,
+) -> Result<Vec<IdleTransactionSession>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::get_idle_in_transaction(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Terminate idle-in-transaction sessions idle for at least `min_idle_seconds`,
+/// releasing their locks. Returns the terminated pids. Requires `confirmed`.
+pub async fn terminate_idle_transactions(
+    connection_id: String,
+    min_idle_seconds: u32,
+    confirmed: bool,
+    state: &AppState,
+) -> Result<Vec<i32>, String> {
+    if !confirmed {
+        return Err("Terminating sessions must be confirmed".to_string());
+    }
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::terminate_idle_transactions(&pool, min_idle_seconds)
+        .await
+        .map_err(|e| format!("Failed to terminate sessions: {}", e))
+}
+
+/// List in-progress VACUUM and CREATE INDEX operations with their phase and
+/// completion. Servers without the progress views (before 9.6) return none.
+pub async fn get_running_maintenance(
+    connection_id: String,
+    state: &AppState,
+) -> Result<Vec<MaintenanceProgress>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+    let Some(version) = state.server_version(&connection_id) else {
+        return Ok(vec![]);
+    };
+
+    postgres::get_running_maintenance(&pool, version)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Cancel an in-progress VACUUM or CREATE INDEX by backend pid.
+pub async fn cancel_maintenance(
+    connection_id: String,
+    pid: i32,
+    state: &AppState,
+) -> Result<(), String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+    let version = state.server_version(&connection_id).unwrap_or(0);
+
+    match postgres::cancel_maintenance(&pool, version, pid).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("No running maintenance operation with pid {}", pid)),
+        Err(e) => Err(format!("Failed to cancel maintenance: {}", e)),
+    }
+}
+
+/// List prepared (two-phase commit) transactions in the connection's database.
+pub async fn get_prepared_transactions(
+    connection_id: String,
+    state: &AppState,
+) -> Result<Vec<PreparedTransaction>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::get_prepared_transactions(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Maximum length of a prepared transaction identifier (GIDSIZE - 1).
+const MAX_GID_LEN: usize = 199;
+
+/// Roll back an orphaned prepared transaction, releasing its locks.
+pub async fn rollback_prepared_transaction(
+    connection_id: String,
+    gid: String,
+    state: &AppState,
+) -> Result<(), String> {
+    validate_gid(&gid)?;
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    match postgres::rollback_prepared_transaction(&pool, &gid).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("No prepared transaction '{}' in this database", gid)),
+        Err(e) => Err(format!("Failed to roll back prepared transaction: {}", e)),
+    }
+}
+
+fn validate_gid(gid: &str) -> Result<(), String> {
+    if gid.is_empty() {
+        return Err("Transaction identifier cannot be empty".to_string());
+    }
+    if gid.len() > MAX_GID_LEN {
+        return Err(format!("Transaction identifier too long (max {} bytes)", MAX_GID_LEN));
+    }
+    if gid.contains('\0') {
+        return Err("Transaction identifier must not contain null bytes".to_string());
+    }
+    Ok(())
+}
+
+/// What a whitelisted database-level parameter accepts.
+#[derive(Clone, Copy)]
+enum DatabaseSettingKind {
+    Boolean,
+    Integer,
+    Real,
+    /// Integer with an optional time unit (`ms`, `s`, `min`, `h`, `d`).
+    Duration,
+    /// Integer with an optional memory unit (`B`, `kB`, `MB`, `GB`, `TB`).
+    Memory,
+    Choice(&'static [&'static str]),
+    /// Comma-separated schema names.
+    SearchPath,
+    /// A time zone name or offset; PostgreSQL checks it exists.
+    TimeZone,
+}
+
+const TIME_UNITS: &[&str] = &["ms", "s", "min", "h", "d"];
+const MEMORY_UNITS: &[&str] = &["B", "kB", "MB", "GB", "TB"];
+const ISOLATION_LEVELS: &[&str] = &["serializable", "repeatable read", "read committed", "read uncommitted"];
+const MESSAGE_LEVELS: &[&str] = &["debug5", "debug4", "debug3", "debug2", "debug1", "log", "notice", "warning", "error"];
+const INTERVAL_STYLES: &[&str] = &["postgres", "postgres_verbose", "sql_standard", "iso_8601"];
+
+/// Parameters `set_database_setting` may change.
+const DATABASE_SETTINGS: &[(&str, DatabaseSettingKind)] = &[
+    ("search_path", DatabaseSettingKind::SearchPath),
+    ("timezone", DatabaseSettingKind::TimeZone),
+    ("intervalstyle", DatabaseSettingKind::Choice(INTERVAL_STYLES)),
+    ("statement_timeout", DatabaseSettingKind::Duration),
+    ("lock_timeout", DatabaseSettingKind::Duration),
+    ("idle_in_transaction_session_timeout", DatabaseSettingKind::Duration),
+    ("log_min_duration_statement", DatabaseSettingKind::Duration),
+    ("work_mem", DatabaseSettingKind::Memory),
+    ("maintenance_work_mem", DatabaseSettingKind::Memory),
+    ("temp_buffers", DatabaseSettingKind::Memory),
+    ("effective_cache_size", DatabaseSettingKind::Memory),
+    ("random_page_cost", DatabaseSettingKind::Real),
+    ("seq_page_cost", DatabaseSettingKind::Real),
+    ("default_statistics_target", DatabaseSettingKind::Integer),
+    ("default_transaction_isolation", DatabaseSettingKind::Choice(ISOLATION_LEVELS)),
+    ("default_transaction_read_only", DatabaseSettingKind::Boolean),
+    ("client_min_messages", DatabaseSettingKind::Choice(MESSAGE_LEVELS)),
+    ("jit", DatabaseSettingKind::Boolean),
+    ("row_security", DatabaseSettingKind::Boolean),
+];
+
+/// List the settings stored for the connection's database, including
+/// per-role overrides within it.
+pub async fn get_database_settings(
+    connection_id: String,
+    state: &AppState,
+) -> Result<Vec<DatabaseSetting>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::get_database_settings(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Set a whitelisted parameter for the connection's database with `ALTER
+/// DATABASE ... SET`, or reset it when `value` is `None`. Applies to sessions
+/// started afterwards. Returns the database's settings afterwards.
+pub async fn set_database_setting(
+    connection_id: String,
+    name: String,
+    value: Option<String>,
+    state: &AppState,
+) -> Result<Vec<DatabaseSetting>, String> {
+    let clause = match value {
+        Some(value) => format!("SET {}", database_setting_assignment(&name, &value)?),
+        None => format!("RESET {}", database_setting_kind(&name)?.0),
+    };
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::alter_current_database(&pool, &clause).await.map_err(|e| {
+        let pg = e
+            .as_database_error()
+            .and_then(|db| db.try_downcast_ref::<sqlx::postgres::PgDatabaseError>());
+        match pg {
+            // e.g. "must be owner of database app"
+            Some(pg) if pg.code() == "42501" => format!("Permission denied: {}", pg.message()),
+            Some(pg) => format!("Failed to set database setting: {}", pg.message()),
+            None => format!("Failed to set database setting: {}", e),
+        }
+    })?;
+
+    postgres::get_database_settings(&pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Look a parameter up in `DATABASE_SETTINGS`.
+fn database_setting_kind(name: &str) -> Result<(&'static str, DatabaseSettingKind), String> {
+    let name = name.trim().to_lowercase();
+    DATABASE_SETTINGS
+        .iter()
+        .find(|(param, _)| *param == name)
+        .copied()
+        .ok_or_else(|| format!("Unsupported database setting: {}", name))
+}
+
+/// Check a parameter against the whitelist and re-render its value from the
+/// parsed form for its kind, so nothing but a literal reaches the SQL.
+fn database_setting_assignment(name: &str, value: &str) -> Result<String, String> {
+    let (name, kind) = database_setting_kind(name)?;
+    let value = value.trim();
+    let rendered = match kind {
+        DatabaseSettingKind::Boolean => match value.to_lowercase().as_str() {
+            "true" | "on" | "1" => "on".to_string(),
+            "false" | "off" | "0" => "off".to_string(),
+            _ => return Err(format!("{} must be true or false", name)),
+        },
+        DatabaseSettingKind::Integer => value
+            .parse::<i64>()
+            .map(|n| n.to_string())
+            .map_err(|_| format!("{} must be an integer", name))?,
+        DatabaseSettingKind::Real => match value.parse::<f64>() {
+            Ok(n) if n.is_finite() => n.to_string(),
+            _ => return Err(format!("{} must be a number", name)),
+        },
+        DatabaseSettingKind::Duration => quantity_literal(value, TIME_UNITS)
+            .ok_or_else(|| format!("{} must be an integer with an optional unit ({})", name, TIME_UNITS.join(", ")))?,
+        DatabaseSettingKind::Memory => quantity_literal(value, MEMORY_UNITS)
+            .ok_or_else(|| format!("{} must be an integer with an optional unit ({})", name, MEMORY_UNITS.join(", ")))?,
+        DatabaseSettingKind::Choice(choices) => {
+            let choice = value.to_lowercase();
+            if !choices.contains(&choice.as_str()) {
+                return Err(format!("{} must be one of: {}", name, choices.join(", ")));
+            }
+            format!("'{}'", choice)
+        }
+        DatabaseSettingKind::SearchPath => {
+            let schemas: Vec<String> = value
+                .split(',')
+                .map(|s| s.trim().trim_matches('"'))
+                .map(|s| match s {
+                    "" => Err(format!("{} must not contain empty schema names", name)),
+                    s if s.contains('"') || s.contains('\0') => Err(format!("Invalid schema name: {}", s)),
+                    s => Ok(format!("\"{}\"", s)),
+                })
+                .collect::<Result<_, _>>()?;
+            schemas.join(", ")
+        }
+        DatabaseSettingKind::TimeZone => {
+            let valid = !value.is_empty()
+                && value.chars().all(|c| c.is_ascii_alphanumeric() || "/_+-:".contains(c));
+            if !valid {
+                return Err(format!("Invalid time zone: {}", value));
+            }
+            format!("'{}'", value)
+        }
+    };
+    Ok(format!("{} = {}", name, rendered))
+}
+
+/// Render `<integer>[unit]` as a literal, e.g. `30 s` as `'30s'`; `None` if the
+/// number isn't an integer or the unit isn't one of `units` (case-sensitive,
+/// as in PostgreSQL).
+fn quantity_literal(value: &str, units: &[&str]) -> Option<String> {
+    let split = value.find(|c: char| !(c.is_ascii_digit() || c == '-')).unwrap_or(value.len());
+    let number = value[..split].parse::<i64>().ok()?;
+    let unit = value[split..].trim();
+    if unit.is_empty() {
+        Some(number.to_string())
+    } else if units.contains(&unit) {
+        Some(format!("'{}{}'", number, unit))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn database_settings_are_whitelisted_and_typed() {
+        assert_eq!(database_setting_assignment("Work_Mem", " 64 MB ").unwrap(), "work_mem = '64MB'");
+        assert_eq!(database_setting_assignment("statement_timeout", "0").unwrap(), "statement_timeout = 0");
+        assert_eq!(database_setting_assignment("lock_timeout", "5s").unwrap(), "lock_timeout = '5s'");
+        assert_eq!(database_setting_assignment("jit", "OFF").unwrap(), "jit = off");
+        assert_eq!(database_setting_assignment("random_page_cost", "1.1").unwrap(), "random_page_cost = 1.1");
+        assert_eq!(
+            database_setting_assignment("search_path", "$user, \"Sales\", public").unwrap(),
+            "search_path = \"$user\", \"Sales\", \"public\""
+        );
+        assert_eq!(database_setting_assignment("timezone", "Europe/Berlin").unwrap(), "timezone = 'Europe/Berlin'");
+        assert_eq!(
+            database_setting_assignment("default_transaction_isolation", "Repeatable Read").unwrap(),
+            "default_transaction_isolation = 'repeatable read'"
+        );
+
+        assert!(database_setting_assignment("shared_buffers", "1GB").is_err());
+        assert!(database_setting_assignment("work_mem", "64mb").is_err());
+        assert!(database_setting_assignment("work_mem", "lots").is_err());
+        assert!(database_setting_assignment("statement_timeout", "1s; drop table t").is_err());
+        assert!(database_setting_assignment("timezone", "UTC'; --").is_err());
+        assert!(database_setting_assignment("search_path", "a,,b").is_err());
+        assert!(database_setting_assignment("jit", "maybe").is_err());
+        assert!(database_setting_assignment("client_min_messages", "verbose").is_err());
+    }
+
+    #[test]
+    fn gid_validation() {
+        assert!(validate_gid("order-42:branch'1").is_ok());
+        assert!(validate_gid("").is_err());
+        assert!(validate_gid(&"x".repeat(200)).is_err());
+        assert!(validate_gid("a\0b").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn database_settings_round_trip_and_report_permission_errors() {
+        // Settings are stored per database; a scratch one keeps them off the shared test database.
+        let scratch = crate::test_support::ScratchDatabase::create().await;
+        let pool = scratch.pool(1).await;
+        let state = crate::test_support::state_with_pool("db", pool.clone());
+        let find = |settings: &[DatabaseSetting]| {
+            settings
+                .iter()
+                .find(|s| s.name == "default_statistics_target" && s.role_name.is_none())
+                .map(|s| s.value.clone())
+        };
+
+        let settings = set_database_setting("db".to_string(), "default_statistics_target".to_string(), Some("250".to_string()), &state)
+            .await
+            .unwrap();
+        assert_eq!(find(&settings).as_deref(), Some("250"));
+        let settings = set_database_setting("db".to_string(), "default_statistics_target".to_string(), None, &state)
+            .await
+            .unwrap();
+        assert_eq!(find(&settings), None);
+
+        let role = format!("pharos_role_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!("CREATE ROLE {role}; SET ROLE {role}")).execute(&pool).await.unwrap();
+        let denied = set_database_setting("db".to_string(), "jit".to_string(), Some("off".to_string()), &state).await;
+        sqlx::raw_sql(&format!("RESET ROLE; DROP ROLE {role}")).execute(&pool).await.unwrap();
+        let error = denied.unwrap_err();
+        assert!(error.starts_with("Permission denied: must be owner of database"), "{}", error);
+    }
+}
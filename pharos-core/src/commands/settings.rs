@@ -1,6 +1,9 @@
 
+use serde::{Deserialize, Serialize};
+
+use crate::commands::table::validate_file_path;
 use crate::db::sqlite;
-use crate::models::AppSettings;
+use crate::models::{AppSettings, KeyboardSettings, KeyboardShortcut};
 use crate::state::AppState;
 
 pub async fn load_settings(state: &AppState) -> Result<AppSettings, String> {
@@ -15,5 +18,190 @@ pub async fn save_settings(
 ) -> Result<(), String> {
     let db = state.metadata_db.lock().map_err(|e| e.to_string())?;
 
-    sqlite::save_settings(&db, &settings).map_err(|e| format!("Failed to save settings: {}", e))
+    sqlite::save_settings(&db, &settings).map_err(|e| format!("Failed to save settings: {}", e))?;
+    state.set_connect_limit(settings.query.max_concurrent_connects as usize);
+    Ok(())
+}
+
+/// An imported shortcut left out because its key combination is already
+/// bound to a different shortcut.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeymapConflict {
+    pub shortcut_id: String,
+    pub conflicts_with: String,
+    /// The contested combination, e.g. "cmd+shift+k".
+    pub binding: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeymapImportResult {
+    pub imported: usize,
+    pub conflicts: Vec<KeymapConflict>,
+}
+
+/// Write the keyboard shortcuts (only) to a JSON file. Returns how many were written.
+pub async fn export_keymap(file_path: String, state: &AppState) -> Result<usize, String> {
+    validate_file_path(&file_path)?;
+    let keyboard = load_settings(state).await?.keyboard;
+    let json = serde_json::to_string_pretty(&keyboard).map_err(|e| e.to_string())?;
+    std::fs::write(&file_path, json).map_err(|e| format!("Failed to write keymap: {}", e))?;
+    Ok(keyboard.shortcuts.len())
+}
+
+/// Merge the shortcuts from a keymap file over the current ones and save.
+/// An imported shortcut replaces the current one with the same id; one whose
+/// key combination another shortcut already uses is skipped and reported.
+pub async fn import_keymap(file_path: String, state: &AppState) -> Result<KeymapImportResult, String> {
+    validate_file_path(&file_path)?;
+    let json = std::fs::read_to_string(&file_path).map_err(|e| format!("Failed to read keymap: {}", e))?;
+    let imported: KeyboardSettings =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid keymap file: {}", e))?;
+
+    let mut settings = load_settings(state).await?;
+    let result = merge_keymap(&mut settings.keyboard, imported.shortcuts);
+    save_settings(state, settings).await?;
+    Ok(result)
+}
+
+/// Normalized key combination: lowercase, modifiers sorted, e.g. "cmd+shift+k".
+fn binding(shortcut: &KeyboardShortcut) -> String {
+    let mut parts: Vec<String> = shortcut.modifiers.iter().map(|m| m.to_lowercase()).collect();
+    parts.sort();
+    parts.dedup();
+    parts.push(shortcut.key.to_lowercase());
+    parts.join("+")
+}
+
+/// `base` with each of `updates` replacing the shortcut with its id, or appended.
+fn with_shortcuts(base: &[KeyboardShortcut], updates: &[KeyboardShortcut]) -> Vec<KeyboardShortcut> {
+    let mut merged = base.to_vec();
+    for shortcut in updates {
+        match merged.iter_mut().find(|s| s.id == shortcut.id) {
+            Some(existing) => *existing = shortcut.clone(),
+            None => merged.push(shortcut.clone()),
+        }
+    }
+    merged
+}
+
+/// Conflicts are judged against the merged result rather than the current
+/// shortcuts, so import order doesn't matter and shortcuts can swap bindings.
+/// Leaving out a clashing shortcut restores its old binding, which may clash
+/// in turn, so this repeats until the merged map has none.
+fn merge_keymap(current: &mut KeyboardSettings, imported: Vec<KeyboardShortcut>) -> KeymapImportResult {
+    let mut accepted = imported;
+    let mut conflicts = Vec::new();
+    loop {
+        let merged = with_shortcuts(&current.shortcuts, &accepted);
+        let mut clashes = Vec::new();
+        accepted.retain(|shortcut| {
+            let combo = binding(shortcut);
+            match merged.iter().find(|s| s.id != shortcut.id && binding(s) == combo) {
+                Some(other) => {
+                    clashes.push(KeymapConflict {
+                        shortcut_id: shortcut.id.clone(),
+                        conflicts_with: other.id.clone(),
+                        binding: combo,
+                    });
+                    false
+                }
+                None => true,
+            }
+        });
+        if clashes.is_empty() {
+            current.shortcuts = merged;
+            return KeymapImportResult { imported: accepted.len(), conflicts };
+        }
+        conflicts.extend(clashes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut(id: &str, key: &str, modifiers: &[&str]) -> KeyboardShortcut {
+        KeyboardShortcut {
+            id: id.to_string(),
+            label: id.to_string(),
+            description: String::new(),
+            key: key.to_string(),
+            modifiers: modifiers.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn keymap_merge_replaces_by_id_and_reports_conflicts() {
+        let mut current = KeyboardSettings {
+            shortcuts: vec![shortcut("run", "Enter", &["cmd"]), shortcut("format", "F", &["cmd", "shift"])],
+        };
+        let result = merge_keymap(
+            &mut current,
+            vec![
+                shortcut("run", "R", &["cmd"]),
+                shortcut("newTab", "f", &["Shift", "Cmd"]),
+                shortcut("close", "W", &["cmd"]),
+            ],
+        );
+
+        assert_eq!(result.imported, 2);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].shortcut_id, "newTab");
+        assert_eq!(result.conflicts[0].conflicts_with, "format");
+        assert_eq!(result.conflicts[0].binding, "cmd+shift+f");
+        let ids: Vec<&str> = current.shortcuts.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["run", "format", "close"]);
+        assert_eq!(current.shortcuts[0].key, "R");
+    }
+
+    #[test]
+    fn keymap_merge_allows_swaps_in_any_order() {
+        let base = KeyboardSettings {
+            shortcuts: vec![shortcut("run", "Enter", &["cmd"]), shortcut("format", "F", &["cmd"])],
+        };
+        let swap = vec![shortcut("run", "F", &["cmd"]), shortcut("format", "Enter", &["cmd"])];
+        for imported in [swap.clone(), swap.into_iter().rev().collect()] {
+            let mut current = base.clone();
+            let result = merge_keymap(&mut current, imported);
+            assert_eq!(result.imported, 2);
+            assert!(result.conflicts.is_empty());
+            assert_eq!(current.shortcuts[0].key, "F");
+            assert_eq!(current.shortcuts[1].key, "Enter");
+        }
+
+        // Rejecting `run` keeps its old Enter binding, which `format` now wants too.
+        let mut current = base.clone();
+        let result = merge_keymap(
+            &mut current,
+            vec![
+                shortcut("format", "Enter", &["cmd"]),
+                shortcut("run", "W", &["cmd"]),
+                shortcut("close", "W", &["cmd"]),
+            ],
+        );
+        assert_eq!(result.imported, 0);
+        let rejected: Vec<&str> = result.conflicts.iter().map(|c| c.shortcut_id.as_str()).collect();
+        assert_eq!(rejected, vec!["run", "close", "format"]);
+        assert_eq!(current.shortcuts.len(), 2);
+        assert_eq!(current.shortcuts[0].key, "Enter");
+    }
+
+    #[tokio::test]
+    async fn lowering_connect_limit_retires_permits_in_use() {
+        let state = AppState::new(rusqlite::Connection::open_in_memory().unwrap());
+        state.set_connect_limit(2);
+        let first = state.acquire_connect_permit().await;
+        let second = state.acquire_connect_permit().await;
+
+        state.set_connect_limit(1);
+        drop(first);
+        assert_eq!(state.connect_semaphore.available_permits(), 0);
+        drop(second);
+        assert_eq!(state.connect_semaphore.available_permits(), 1);
+
+        state.set_connect_limit(3);
+        assert_eq!(state.connect_semaphore.available_permits(), 3);
+    }
 }
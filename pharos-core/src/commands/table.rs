@@ -1,17 +1,21 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{Column, Row};
+use sqlx::postgres::types::PgPoint;
+use sqlx::{Column, Executor, Row, ValueRef};
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
 
-use crate::commands::query::set_search_path;
+use crate::commands::datetime_format::DateTimeFormat;
+use crate::commands::ddl::TableDdlParts;
+use crate::commands::query::{query_settings, set_search_path, QueryResult};
 use crate::db::postgres;
+use crate::models::TableStorageParam;
 use crate::state::AppState;
 
 /// Validate that a file path is safe (not attempting path traversal).
 /// Canonicalizes the parent directory (which must exist) since the file itself
 /// may not exist yet (e.g. when saving a new export).
-fn validate_file_path(path: &str) -> Result<(), String> {
+pub(crate) fn validate_file_path(path: &str) -> Result<(), String> {
     let path = Path::new(path);
 
     if !path.is_absolute() {
@@ -213,6 +217,556 @@ pub async fn clone_table(
     })
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyTableResult {
+    pub success: bool,
+    pub rows_copied: Option<u64>,
+    /// Columns created as `text` because their type doesn't exist on the target.
+    pub columns_as_text: Vec<String>,
+}
+
+/// Copy a table (structure, and data if `include_data`) from one connection to
+/// another — e.g. a small reference table between databases. The table is
+/// created from the source's reconstructed DDL and filled by streaming `COPY
+/// TO STDOUT` into `COPY FROM STDIN`, in one transaction on the target.
+/// See `cross_connection_ddl_parts` for what doesn't carry over.
+pub async fn copy_table_across_connections(
+    source_connection_id: String,
+    target_connection_id: String,
+    options: CloneTableOptions,
+    state: &AppState,
+) -> Result<CopyTableResult, String> {
+    use futures::StreamExt;
+    use sqlx::postgres::PgPoolCopyExt;
+
+    validate_identifier(&options.source_schema)?;
+    validate_identifier(&options.source_table)?;
+    validate_identifier(&options.target_schema)?;
+    validate_identifier(&options.target_table)?;
+    let source = state
+        .get_pool(&source_connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", source_connection_id))?;
+    let target = state
+        .get_pool(&target_connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", target_connection_id))?;
+
+    let parts = postgres::get_table_ddl_parts(&source, &options.source_schema, &options.source_table)
+        .await
+        .map_err(|e| format!("Failed to read table definition: {}", e))?;
+    if parts.columns.is_empty() {
+        return Err(format!("Table not found: {}.{}", options.source_schema, options.source_table));
+    }
+
+    let mut type_names: Vec<String> = parts.columns.iter().map(|c| c.type_str.clone()).collect();
+    type_names.sort();
+    type_names.dedup();
+    let missing = postgres::missing_types(&target, &type_names)
+        .await
+        .map_err(|e| format!("Failed to check column types: {}", e))?;
+    let (parts, columns_as_text) = cross_connection_ddl_parts(parts, &missing);
+    let create_sql = crate::commands::ddl::compose_table_ddl(&options.target_schema, &options.target_table, &parts)
+        .with_constraints;
+
+    let mut tx = target.begin().await.map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    (&mut *tx)
+        .execute(sqlx::raw_sql(&create_sql))
+        .await
+        .map_err(|e| format!("Failed to create table: {}", e))?;
+
+    let mut rows_copied = None;
+    if options.include_data {
+        // Generated columns are recomputed on the target and can't be copied into
+        let column_list = parts
+            .columns
+            .iter()
+            .filter(|c| c.generated.is_empty())
+            .map(|c| format!("\"{}\"", escape_identifier(&c.name)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let copy_out_sql = format!(
+            "COPY (SELECT {} FROM \"{}\".\"{}\") TO STDOUT",
+            column_list,
+            escape_identifier(&options.source_schema),
+            escape_identifier(&options.source_table)
+        );
+        let copy_in_sql = format!(
+            "COPY \"{}\".\"{}\" ({}) FROM STDIN",
+            escape_identifier(&options.target_schema),
+            escape_identifier(&options.target_table),
+            column_list
+        );
+
+        let mut rows = source
+            .copy_out_raw(&copy_out_sql)
+            .await
+            .map_err(|e| format!("Failed to read table data: {}", e))?;
+        let mut copy_in = tx
+            .copy_in_raw(&copy_in_sql)
+            .await
+            .map_err(|e| format!("Failed to start COPY: {}", e))?;
+        while let Some(chunk) = rows.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    copy_in.abort(e.to_string()).await.ok();
+                    return Err(format!("Failed to read table data: {}", e));
+                }
+            };
+            copy_in
+                .send(chunk)
+                .await
+                .map_err(|e| format!("Failed to copy data: {}", e))?;
+        }
+        rows_copied = Some(
+            copy_in
+                .finish()
+                .await
+                .map_err(|e| format!("Failed to copy data: {}", e))?,
+        );
+
+        // COPY doesn't advance identity sequences; move them past the copied values
+        let target_name = format!(
+            "\"{}\".\"{}\"",
+            escape_identifier(&options.target_schema),
+            escape_identifier(&options.target_table)
+        );
+        for col in parts.columns.iter().filter(|c| !c.identity.is_empty()) {
+            let column = format!("\"{}\"", escape_identifier(&col.name));
+            let setval_sql = format!(
+                "SELECT setval(pg_get_serial_sequence('{}', '{}'), coalesce(max({}), 1), max({}) IS NOT NULL) FROM {}",
+                target_name.replace('\'', "''"),
+                col.name.replace('\'', "''"),
+                column,
+                column,
+                target_name
+            );
+            (&mut *tx)
+                .execute(sqlx::raw_sql(&setval_sql))
+                .await
+                .map_err(|e| format!("Failed to reset identity sequence: {}", e))?;
+        }
+    }
+
+    tx.commit().await.map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    Ok(CopyTableResult {
+        success: true,
+        rows_copied,
+        columns_as_text,
+    })
+}
+
+/// Adapt a table's DDL parts for creating it on another server. Columns whose
+/// type is in `missing_types` become `text` (COPY's text output loads into
+/// them unchanged) and lose their default, as do defaults drawing from a
+/// sequence. Foreign keys, constraints mentioning a missing type, indexes
+/// (their definitions name the source table), comments and partitioning are
+/// dropped. Returns the parts and the names of the columns made `text`.
+fn cross_connection_ddl_parts(mut parts: TableDdlParts, missing_types: &[String]) -> (TableDdlParts, Vec<String>) {
+    let mut columns_as_text = Vec::new();
+    for col in &mut parts.columns {
+        if missing_types.contains(&col.type_str) {
+            col.type_str = "text".to_string();
+            if col.generated.is_empty() {
+                col.default_expr = None;
+            }
+            columns_as_text.push(col.name.clone());
+        }
+        if col.default_expr.as_deref().is_some_and(|d| d.contains("nextval(")) {
+            col.default_expr = None;
+        }
+        col.comment = None;
+    }
+    parts.constraints.retain(|con| {
+        !con.definition.starts_with("FOREIGN KEY")
+            && !missing_types.iter().any(|t| con.definition.contains(t.as_str()))
+    });
+    parts.index_defs.clear();
+    parts.partition_by = None;
+    parts.comment = None;
+    (parts, columns_as_text)
+}
+
+// ============================================================================
+// Row-Level Security
+// ============================================================================
+
+/// Turn row-level security on for a table.
+pub async fn enable_rls(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    state: &AppState,
+) -> Result<(), String> {
+    alter_row_level_security(&connection_id, &schema_name, &table_name, "ENABLE", state).await
+}
+
+/// Turn row-level security off for a table. This exposes every row to anyone
+/// with table privileges, so the caller must pass `confirmed`.
+pub async fn disable_rls(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    confirmed: bool,
+    state: &AppState,
+) -> Result<(), String> {
+    if !confirmed {
+        return Err("Disabling row-level security must be confirmed".to_string());
+    }
+    alter_row_level_security(&connection_id, &schema_name, &table_name, "DISABLE", state).await
+}
+
+async fn alter_row_level_security(
+    connection_id: &str,
+    schema_name: &str,
+    table_name: &str,
+    action: &str,
+    state: &AppState,
+) -> Result<(), String> {
+    let pool = state
+        .get_pool(connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    validate_identifier(schema_name)?;
+    validate_identifier(table_name)?;
+
+    let sql = format!(
+        "ALTER TABLE \"{}\".\"{}\" {} ROW LEVEL SECURITY",
+        escape_identifier(schema_name),
+        escape_identifier(table_name),
+        action
+    );
+    sqlx::raw_sql(&sql)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to {} row-level security: {}", action.to_lowercase(), e))?;
+    Ok(())
+}
+
+/// Drop one RLS policy from a table.
+pub async fn drop_policy(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    policy_name: String,
+    state: &AppState,
+) -> Result<(), String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    // Policy names are free text (spaces, punctuation), so names are escaped
+    // rather than validated.
+    let sql = format!(
+        "DROP POLICY \"{}\" ON \"{}\".\"{}\"",
+        escape_identifier(&policy_name),
+        escape_identifier(&schema_name),
+        escape_identifier(&table_name)
+    );
+    sqlx::raw_sql(&sql)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to drop policy: {}", ddl_error_message(&e)))?;
+    Ok(())
+}
+
+// ============================================================================
+// Storage Parameters
+// ============================================================================
+
+/// What a whitelisted storage parameter accepts.
+#[derive(Clone, Copy)]
+enum StorageParamKind {
+    Integer,
+    Real,
+    Boolean,
+}
+
+/// Table storage parameters that `set_table_storage_param` may change.
+const STORAGE_PARAMS: &[(&str, StorageParamKind)] = &[
+    ("fillfactor", StorageParamKind::Integer),
+    ("toast_tuple_target", StorageParamKind::Integer),
+    ("parallel_workers", StorageParamKind::Integer),
+    ("vacuum_truncate", StorageParamKind::Boolean),
+    ("autovacuum_enabled", StorageParamKind::Boolean),
+    ("autovacuum_vacuum_threshold", StorageParamKind::Integer),
+    ("autovacuum_vacuum_scale_factor", StorageParamKind::Real),
+    ("autovacuum_vacuum_insert_threshold", StorageParamKind::Integer),
+    ("autovacuum_vacuum_insert_scale_factor", StorageParamKind::Real),
+    ("autovacuum_analyze_threshold", StorageParamKind::Integer),
+    ("autovacuum_analyze_scale_factor", StorageParamKind::Real),
+    ("autovacuum_vacuum_cost_delay", StorageParamKind::Real),
+    ("autovacuum_vacuum_cost_limit", StorageParamKind::Integer),
+    ("autovacuum_freeze_min_age", StorageParamKind::Integer),
+    ("autovacuum_freeze_max_age", StorageParamKind::Integer),
+    ("autovacuum_freeze_table_age", StorageParamKind::Integer),
+    ("autovacuum_multixact_freeze_min_age", StorageParamKind::Integer),
+    ("autovacuum_multixact_freeze_max_age", StorageParamKind::Integer),
+    ("autovacuum_multixact_freeze_table_age", StorageParamKind::Integer),
+    ("log_autovacuum_min_duration", StorageParamKind::Integer),
+];
+
+/// Check a storage parameter against the whitelist and re-render its value
+/// from the parsed number/boolean, so nothing but a literal reaches the SQL.
+fn storage_param_assignment(name: &str, value: &str) -> Result<String, String> {
+    let name = name.trim().to_lowercase();
+    let kind = STORAGE_PARAMS
+        .iter()
+        .find(|(param, _)| *param == name)
+        .map(|(_, kind)| *kind)
+        .ok_or_else(|| format!("Unsupported storage parameter: {}", name))?;
+
+    let value = value.trim();
+    let rendered = match kind {
+        StorageParamKind::Integer => value
+            .parse::<i64>()
+            .map(|n| n.to_string())
+            .map_err(|_| format!("{} must be an integer", name))?,
+        StorageParamKind::Real => match value.parse::<f64>() {
+            Ok(n) if n.is_finite() => n.to_string(),
+            _ => return Err(format!("{} must be a number", name)),
+        },
+        StorageParamKind::Boolean => match value.to_lowercase().as_str() {
+            "true" | "on" | "1" => "true".to_string(),
+            "false" | "off" | "0" => "false".to_string(),
+            _ => return Err(format!("{} must be true or false", name)),
+        },
+    };
+    Ok(format!("{} = {}", name, rendered))
+}
+
+/// Get a table's storage parameters (`pg_class.reloptions`).
+pub async fn get_table_storage_params(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    state: &AppState,
+) -> Result<Vec<TableStorageParam>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    postgres::get_table_storage_params(&pool, &schema_name, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Set one whitelisted storage parameter on a table, e.g. to tune autovacuum
+/// for a hot table. Returns the table's storage parameters afterwards.
+pub async fn set_table_storage_param(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    name: String,
+    value: String,
+    state: &AppState,
+) -> Result<Vec<TableStorageParam>, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    validate_identifier(&schema_name)?;
+    validate_identifier(&table_name)?;
+    let assignment = storage_param_assignment(&name, &value)?;
+
+    let sql = format!(
+        "ALTER TABLE \"{}\".\"{}\" SET ({})",
+        escape_identifier(&schema_name),
+        escape_identifier(&table_name),
+        assignment
+    );
+    sqlx::raw_sql(&sql)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to set storage parameter: {}", e))?;
+
+    postgres::get_table_storage_params(&pool, &schema_name, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Column Type Changes
+// ============================================================================
+
+/// Base types `alter_column_type` may convert a column to.
+const COLUMN_TYPES: &[&str] = &[
+    "smallint", "integer", "bigint", "int2", "int4", "int8", "numeric", "decimal",
+    "real", "double precision", "float4", "float8", "money",
+    "text", "varchar", "character varying", "char", "character", "citext",
+    "boolean", "bool", "bytea", "uuid", "json", "jsonb", "xml",
+    "date", "time", "timetz", "time with time zone", "time without time zone",
+    "timestamp", "timestamptz", "timestamp with time zone", "timestamp without time zone",
+    "interval", "inet", "cidr", "macaddr", "macaddr8",
+    "point", "line", "lseg", "box", "path", "polygon", "circle",
+    "bit", "bit varying", "varbit", "tsvector", "tsquery",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlterColumnTypeOptions {
+    pub schema_name: String,
+    pub table_name: String,
+    pub column_name: String,
+    /// Target type: a whitelisted base type with optional `(n)` / `(p, s)`
+    /// modifiers and `[]` array suffixes, e.g. `numeric(12, 2)` or `text[]`.
+    pub new_type: String,
+    /// Expression computing the new value from the old, for `USING`.
+    #[serde(default)]
+    pub using_expr: Option<String>,
+}
+
+/// Outcome of a dry-run type change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlterColumnTypePreview {
+    /// The statement that was tried.
+    pub sql: String,
+    pub success: bool,
+    /// Why PostgreSQL rejected it, e.g. a value that doesn't cast.
+    pub error: Option<String>,
+}
+
+/// Check a target type against `COLUMN_TYPES` and re-render it, so only a
+/// known type name and numeric modifiers reach the SQL.
+fn normalize_column_type(new_type: &str) -> Result<String, String> {
+    let mut base = new_type.trim().to_lowercase();
+    let mut dimensions = 0;
+    while let Some(rest) = base.strip_suffix("[]") {
+        base = rest.trim_end().to_string();
+        dimensions += 1;
+    }
+    // Modifiers may sit mid-name, as in `timestamp(3) with time zone`.
+    let words = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut modifiers = Vec::new();
+    let (before, after) = match base.find('(') {
+        Some(open) => {
+            let close = base[open..]
+                .find(')')
+                .map(|i| open + i)
+                .ok_or_else(|| format!("Invalid type: {}", new_type.trim()))?;
+            for arg in base[open + 1..close].split(',') {
+                let n = arg
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| format!("Invalid type modifier in {}", new_type.trim()))?;
+                modifiers.push(n.to_string());
+            }
+            (words(&base[..open]), words(&base[close + 1..]))
+        }
+        None => (words(&base), String::new()),
+    };
+    let full_name = words(&format!("{} {}", before, after));
+    if !COLUMN_TYPES.contains(&full_name.as_str()) {
+        return Err(format!("Unsupported column type: {}", new_type.trim()));
+    }
+    let mut rendered = before;
+    if !modifiers.is_empty() {
+        rendered.push_str(&format!("({})", modifiers.join(", ")));
+    }
+    if !after.is_empty() {
+        rendered.push(' ');
+        rendered.push_str(&after);
+    }
+    rendered.push_str(&"[]".repeat(dimensions));
+    Ok(rendered)
+}
+
+/// Build `ALTER TABLE ... ALTER COLUMN ... TYPE ... [USING ...]`. The USING
+/// expression must be a single expression: a trailing `;` and comments are
+/// dropped, and anything that would end the statement early is rejected.
+fn alter_column_type_sql(options: &AlterColumnTypeOptions) -> Result<String, String> {
+    validate_identifier(&options.schema_name)?;
+    validate_identifier(&options.table_name)?;
+    validate_identifier(&options.column_name)?;
+    let new_type = normalize_column_type(&options.new_type)?;
+
+    let mut sql = format!(
+        "ALTER TABLE \"{}\".\"{}\" ALTER COLUMN \"{}\" TYPE {}",
+        escape_identifier(&options.schema_name),
+        escape_identifier(&options.table_name),
+        escape_identifier(&options.column_name),
+        new_type
+    );
+    if let Some(expr) = options.using_expr.as_deref().filter(|e| !e.trim().is_empty()) {
+        let spans = crate::commands::query::split_statements(expr);
+        let [span] = spans.as_slice() else {
+            return Err("USING must be a single expression".to_string());
+        };
+        let expr: String = expr.chars().skip(span.start).take(span.len()).collect();
+        let expr = crate::commands::query::strip_trailing_comments(&expr);
+        if !crate::commands::query::is_single_expression(&expr) {
+            return Err("USING must be a single expression".to_string());
+        }
+        sql.push_str(" USING ");
+        sql.push_str(&expr);
+    }
+    Ok(sql)
+}
+
+/// The database's own message (plus hint) for a failed DDL statement, without
+/// sqlx's wrapping.
+fn ddl_error_message(e: &sqlx::Error) -> String {
+    match e
+        .as_database_error()
+        .and_then(|db| db.try_downcast_ref::<sqlx::postgres::PgDatabaseError>())
+    {
+        Some(pg) => match pg.hint() {
+            Some(hint) => format!("{}. {}", pg.message(), hint),
+            None => pg.message().to_string(),
+        },
+        None => e.to_string(),
+    }
+}
+
+/// Try a column type change inside a transaction that is always rolled back,
+/// surfacing cast errors without committing anything. Like the real change it
+/// rewrites the table and holds its exclusive lock while running, bounded by
+/// `DRY_RUN_TIMEOUTS`.
+pub async fn preview_alter_column_type(
+    connection_id: String,
+    options: AlterColumnTypeOptions,
+    state: &AppState,
+) -> Result<AlterColumnTypePreview, String> {
+    let sql = alter_column_type_sql(&options)?;
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let mut tx = pool.begin().await.map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    (&mut *tx)
+        .execute(sqlx::raw_sql(crate::commands::query::DRY_RUN_TIMEOUTS))
+        .await
+        .map_err(|e| format!("Failed to set preview timeouts: {}", e))?;
+    let outcome = (&mut *tx).execute(sqlx::raw_sql(&sql)).await;
+    tx.rollback().await.map_err(|e| format!("Failed to roll back preview: {}", e))?;
+
+    Ok(match outcome {
+        Ok(_) => AlterColumnTypePreview { sql, success: true, error: None },
+        Err(e) => AlterColumnTypePreview { sql, success: false, error: Some(ddl_error_message(&e)) },
+    })
+}
+
+/// Change a column's type, optionally converting values with a `USING`
+/// expression. Returns the statement that was run.
+pub async fn alter_column_type(
+    connection_id: String,
+    options: AlterColumnTypeOptions,
+    state: &AppState,
+) -> Result<String, String> {
+    let sql = alter_column_type_sql(&options)?;
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    sqlx::raw_sql(&sql)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to change column type: {}", ddl_error_message(&e)))?;
+    Ok(sql)
+}
+
 /// Generate the reconstructed CREATE TABLE DDL (three detail variants) for a table.
 pub async fn generate_table_ddl(
     connection_id: String,
@@ -240,6 +794,261 @@ pub async fn generate_table_ddl(
     ))
 }
 
+/// Generate the CREATE VIEW (or CREATE MATERIALIZED VIEW) DDL for a view.
+pub async fn generate_view_ddl(
+    connection_id: String,
+    schema_name: String,
+    view_name: String,
+    state: &AppState,
+) -> Result<String, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    // Read-only like generate_table_ddl, so names are escaped, not validated.
+    postgres::generate_view_ddl(&pool, &schema_name, &view_name)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("View not found: {}.{}", schema_name, view_name))
+}
+
+/// Generate DDL for a whole schema — functions, tables, partitions, indexes
+/// and foreign keys — followed by COMMENT ON statements for every described
+/// object, so documentation survives a dump and restore.
+pub async fn generate_schema_ddl(
+    connection_id: String,
+    schema_name: String,
+    state: &AppState,
+) -> Result<String, String> {
+    use crate::commands::ddl::{DdlTable, PartitionOf};
+    use crate::models::TableType;
+
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    let mut pending: std::collections::VecDeque<(String, Option<PartitionOf>)> =
+        postgres::get_tables(&pool, &schema_name)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|t| matches!(t.table_type, TableType::Table | TableType::PartitionedTable))
+            .map(|t| (t.name, None))
+            .collect();
+
+    // Breadth-first so every partition parent precedes its partitions.
+    let mut tables = Vec::new();
+    while let Some((name, partition_of)) = pending.pop_front() {
+        let parts = postgres::get_table_ddl_parts(&pool, &schema_name, &name)
+            .await
+            .map_err(|e| e.to_string())?;
+        if parts.partition_by.is_some() {
+            let children = postgres::get_partitions(&pool, &schema_name, &name)
+                .await
+                .map_err(|e| e.to_string())?;
+            for child in children.into_iter().filter(|c| c.schema_name == schema_name) {
+                let partition_of = PartitionOf {
+                    parent_schema: schema_name.clone(),
+                    parent_table: name.clone(),
+                    bound: child.partition_bound.unwrap_or_else(|| "DEFAULT".to_string()),
+                };
+                pending.push_back((child.name, Some(partition_of)));
+            }
+        }
+        tables.push(DdlTable { name, parts, partition_of });
+    }
+
+    let functions = postgres::get_function_ddls(&pool, &schema_name)
+        .await
+        .map_err(|e| e.to_string())?;
+    let schema_comment = postgres::get_schema_comment(&pool, &schema_name)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(crate::commands::ddl::compose_schema_ddl(
+        &schema_name,
+        schema_comment.as_deref(),
+        &tables,
+        &functions,
+    ))
+}
+
+// ============================================================================
+// Table Checksum
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableChecksum {
+    /// md5 of every row's text form, concatenated in a deterministic order.
+    pub checksum: String,
+    pub row_count: i64,
+    /// False when the table has no primary key, so rows were ordered by their
+    /// full text instead.
+    pub ordered_by_primary_key: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// Compute a content checksum of a table, for comparing copies of it across
+/// connections. Registered under `query_id` so `cancel_query` can stop it on
+/// large tables.
+pub async fn checksum_table(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    query_id: Option<String>,
+    state: &AppState,
+) -> Result<TableChecksum, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    // Read-only, like generate_table_ddl: names are escaped rather than validated.
+    let columns = postgres::get_columns(&pool, &schema_name, &table_name)
+        .await
+        .map_err(|e| format!("Failed to get table columns: {}", e))?;
+    if columns.is_empty() {
+        return Err(format!("Table not found: {}.{}", schema_name, table_name));
+    }
+    let pk_columns: Vec<String> = columns
+        .iter()
+        .filter(|c| c.is_primary_key)
+        .map(|c| c.name.clone())
+        .collect();
+    let sql = checksum_sql(&schema_name, &table_name, &pk_columns);
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let backend_pid: i32 = (&mut *conn)
+        .fetch_one(sqlx::raw_sql("SELECT pg_backend_pid()"))
+        .await
+        .ok()
+        .and_then(|row| row.try_get(0).ok())
+        .unwrap_or(0);
+    let registration = state.track_query(query_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()), &connection_id);
+    registration.set_backend_pid(backend_pid);
+
+    let result = (&mut *conn).fetch_one(sqlx::raw_sql(&sql)).await;
+    let cancelled = registration.cancelled().load(std::sync::atomic::Ordering::SeqCst);
+    drop(registration);
+    let row = result.map_err(|e| {
+        if cancelled {
+            "Checksum was cancelled".to_string()
+        } else {
+            format!("Failed to checksum table: {}", e)
+        }
+    })?;
+
+    let ordered_by_primary_key = !pk_columns.is_empty();
+    Ok(TableChecksum {
+        checksum: row.try_get(0).map_err(|e| e.to_string())?,
+        row_count: row.try_get(1).map_err(|e| e.to_string())?,
+        ordered_by_primary_key,
+        warning: (!ordered_by_primary_key).then(|| {
+            "Table has no primary key; rows were ordered by their full text, so the checksum \
+             only matches a copy whose every value renders identically"
+                .to_string()
+        }),
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountAccuracy {
+    #[serde(flatten)]
+    pub estimate: crate::models::RowCountEstimate,
+    /// `COUNT(*)`, only when requested.
+    pub exact_rows: Option<i64>,
+    /// How far the estimate is off, as a percentage of the exact count.
+    pub divergence_percent: Option<f64>,
+}
+
+/// Compare a table's planner row estimate (what the navigator shows) with its
+/// exact `COUNT(*)`. Counting scans the whole table, so it only runs when
+/// `exact` is set, registered under `query_id` so `cancel_query` can stop it.
+pub async fn check_count_accuracy(
+    connection_id: String,
+    schema_name: String,
+    table_name: String,
+    exact: bool,
+    query_id: Option<String>,
+    state: &AppState,
+) -> Result<CountAccuracy, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    // Read-only, like generate_table_ddl: names are escaped rather than validated.
+    let estimate = postgres::get_row_count_estimate(&pool, &schema_name, &table_name)
+        .await
+        .map_err(|e| format!("Failed to read row estimate: {}", e))?
+        .ok_or_else(|| format!("Table not found: {}.{}", schema_name, table_name))?;
+    if !exact {
+        return Ok(CountAccuracy { estimate, exact_rows: None, divergence_percent: None });
+    }
+
+    let sql = format!(
+        "SELECT count(*) FROM \"{}\".\"{}\"",
+        escape_identifier(&schema_name),
+        escape_identifier(&table_name)
+    );
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let backend_pid: i32 = (&mut *conn)
+        .fetch_one(sqlx::raw_sql("SELECT pg_backend_pid()"))
+        .await
+        .ok()
+        .and_then(|row| row.try_get(0).ok())
+        .unwrap_or(0);
+    let registration = state.track_query(query_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()), &connection_id);
+    registration.set_backend_pid(backend_pid);
+
+    let result = (&mut *conn).fetch_one(sqlx::raw_sql(&sql)).await;
+    let cancelled = registration.cancelled().load(std::sync::atomic::Ordering::SeqCst);
+    drop(registration);
+    let row = result.map_err(|e| {
+        if cancelled {
+            "Row count was cancelled".to_string()
+        } else {
+            format!("Failed to count rows: {}", e)
+        }
+    })?;
+    let exact_rows: i64 = row.try_get(0).map_err(|e| e.to_string())?;
+
+    Ok(CountAccuracy {
+        divergence_percent: estimate.estimated_rows.map(|est| count_divergence_percent(est, exact_rows)),
+        estimate,
+        exact_rows: Some(exact_rows),
+    })
+}
+
+/// `|estimate - exact|` as a percentage of `exact` (of 1 for an empty table,
+/// so an estimate of 5 rows for an empty table reads as 500%).
+fn count_divergence_percent(estimate: i64, exact: i64) -> f64 {
+    (estimate - exact).abs() as f64 / exact.max(1) as f64 * 100.0
+}
+
+/// Checksum query: rows are ordered by their primary key (or, without one, the
+/// whole row), compared as text under the "C" collation so the order doesn't
+/// depend on the server's locale.
+fn checksum_sql(schema_name: &str, table_name: &str, pk_columns: &[String]) -> String {
+    let order_by = if pk_columns.is_empty() {
+        "pharos_row::text COLLATE \"C\"".to_string()
+    } else {
+        pk_columns
+            .iter()
+            .map(|c| format!("pharos_row.\"{}\"::text COLLATE \"C\"", escape_identifier(c)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!(
+        "SELECT md5(COALESCE(string_agg(pharos_row::text, E'\\n' ORDER BY {}), '')), count(*)::int8 \
+         FROM \"{}\".\"{}\" pharos_row",
+        order_by,
+        escape_identifier(schema_name),
+        escape_identifier(table_name)
+    )
+}
+
 // ============================================================================
 // CSV Validation
 // ============================================================================
@@ -255,13 +1064,15 @@ pub struct CsvValidationResult {
     pub error: Option<String>,
 }
 
-/// Validate a CSV file for import into a table
+/// Validate a CSV file for import into a table. `trim_whitespace` mirrors the
+/// import option so reported headers match what the import will see.
 pub async fn validate_csv_for_import(
     connection_id: String,
     schema_name: String,
     table_name: String,
     file_path: String,
     has_headers: bool,
+    trim_whitespace: bool,
     state: &AppState,
 ) -> Result<CsvValidationResult, String> {
     let pool = state
@@ -280,7 +1091,13 @@ pub async fn validate_csv_for_import(
         .await
         .map_err(|e| format!("Failed to get table columns: {}", e))?;
 
-    let table_columns: Vec<String> = columns.iter().map(|c| c.name.clone()).collect();
+    // Generated columns can't be imported into; a file may still carry them.
+    let generated: Vec<bool> = columns.iter().map(|c| c.is_generated).collect();
+    let table_columns: Vec<String> = columns
+        .iter()
+        .filter(|c| !c.is_generated)
+        .map(|c| c.name.clone())
+        .collect();
 
     // Open and read the CSV file
     let file = File::open(&file_path)
@@ -288,6 +1105,7 @@ pub async fn validate_csv_for_import(
 
     let mut reader = csv::ReaderBuilder::new()
         .has_headers(has_headers)
+        .trim(if trim_whitespace { csv::Trim::All } else { csv::Trim::None })
         .from_reader(file);
 
     let csv_headers: Option<Vec<String>> = if has_headers {
@@ -328,7 +1146,7 @@ pub async fn validate_csv_for_import(
 
     // Check if column count matches table
     let table_column_count = table_columns.len();
-    if csv_column_count != table_column_count {
+    if csv_import_fields(&generated, csv_column_count).is_none() {
         return Ok(CsvValidationResult {
             valid: false,
             row_count,
@@ -364,6 +1182,40 @@ pub struct ImportCsvOptions {
     pub table_name: String,
     pub file_path: String,
     pub has_headers: bool,
+    /// Field text that means SQL NULL (e.g. "NULL", as written by exports with
+    /// `null_as_empty` off). When set, empty fields import as empty strings;
+    /// when absent, empty fields import as NULL.
+    #[serde(default)]
+    pub null_marker: Option<String>,
+    /// Strip leading/trailing whitespace from every field before binding, so
+    /// padded values like `" 42 "` cast cleanly. The null marker is compared
+    /// against the trimmed text.
+    #[serde(default)]
+    pub trim_whitespace: bool,
+    /// With `trim_whitespace`, import whitespace-only fields as NULL instead of
+    /// an empty string. Truly empty fields still follow `null_marker`.
+    #[serde(default)]
+    pub trimmed_empty_as_null: bool,
+    /// How fields destined for bytea columns are encoded in the file.
+    #[serde(default)]
+    pub bytea_encoding: ByteaEncoding,
+    /// Insert row by row instead of streaming the file through `COPY`, e.g. for
+    /// servers that don't support `COPY FROM STDIN`.
+    #[serde(default)]
+    pub per_row_insert: bool,
+}
+
+/// Encoding of binary data in CSV fields for bytea columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ByteaEncoding {
+    /// Pass the field to PostgreSQL as text (its own `\x...` / escape input format).
+    #[default]
+    Text,
+    /// Hex digits, with or without a leading `\x`.
+    Hex,
+    /// Standard base64, padding optional.
+    Base64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -373,7 +1225,16 @@ pub struct ImportCsvResult {
     pub rows_imported: u64,
 }
 
-/// Import CSV data into a table using parameterized queries
+/// Bytes read from the file per `COPY` data message.
+const COPY_CHUNK_SIZE: usize = 256 * 1024;
+
+/// UTF-8 byte order mark some tools (notably Excel) write at the start of a CSV.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Import CSV data into a table. Streams the file through `COPY ... FROM STDIN`
+/// unless a field needs preprocessing first (trimming, bytea decoding, skipping
+/// generated columns) or `per_row_insert` is set, in which case each row is
+/// inserted with parameterized queries in one transaction.
 pub async fn import_csv(
     connection_id: String,
     options: ImportCsvOptions,
@@ -410,10 +1271,22 @@ pub async fn import_csv(
         .await
         .map_err(|e| format!("Failed to get table columns: {}", e))?;
 
+    let generated: Vec<bool> = columns.iter().map(|c| c.is_generated).collect();
+    let columns: Vec<_> = columns.into_iter().filter(|c| !c.is_generated).collect();
     let num_columns = columns.len();
     let column_names: Vec<String> = columns.iter().map(|c| format!("\"{}\"", escape_identifier(&c.name))).collect();
     let column_list = column_names.join(", ");
 
+    let has_bytea = columns.iter().any(|c| c.data_type.eq_ignore_ascii_case("bytea"));
+    if !options.per_row_insert && copy_import_supported(&options, generated.contains(&true), has_bytea) {
+        let copy_sql = csv_copy_sql(&options, &column_list);
+        let rows_imported = copy_csv_file(&pool, &copy_sql, &options, &progress).await?;
+        return Ok(ImportCsvResult {
+            success: true,
+            rows_imported,
+        });
+    }
+
     // Build parameterized placeholders with type casts ($1::type, $2::type, ...)
     // This allows PostgreSQL to convert text values from CSV to the appropriate column types
     let placeholders: Vec<String> = columns.iter().enumerate().map(|(i, col)| {
@@ -422,6 +1295,12 @@ pub async fn import_csv(
     }).collect();
     let placeholder_list = placeholders.join(", ");
 
+    // Columns whose fields are decoded to raw bytes instead of bound as text
+    let decode_bytes: Vec<bool> = columns
+        .iter()
+        .map(|c| options.bytea_encoding != ByteaEncoding::Text && c.data_type.eq_ignore_ascii_case("bytea"))
+        .collect();
+
     // Build the INSERT statement with parameters
     let insert_sql = format!(
         "INSERT INTO \"{}\".\"{}\" ({}) VALUES ({})",
@@ -437,6 +1316,7 @@ pub async fn import_csv(
 
     let mut reader = csv::ReaderBuilder::new()
         .has_headers(options.has_headers)
+        .trim(if options.trim_whitespace { csv::Trim::Headers } else { csv::Trim::None })
         .from_reader(file);
 
     // Begin a transaction
@@ -448,23 +1328,28 @@ pub async fn import_csv(
         let record = result.map_err(|e| format!("Failed to read CSV row: {}", e))?;
 
         // Verify column count matches
-        if record.len() != num_columns {
+        let Some(fields) = csv_import_fields(&generated, record.len()) else {
             tx.rollback().await.ok();
             return Err(format!(
                 "CSV row has {} columns but table has {} columns",
                 record.len(),
                 num_columns
             ));
-        }
+        };
 
         // Build query with bound parameters
         let mut query = sqlx::query(&insert_sql);
 
-        for value in record.iter() {
-            if value.is_empty() {
-                query = query.bind(None::<String>);
+        for (i, field) in fields.into_iter().enumerate() {
+            let param = prepare_csv_field(&record[field], &options);
+            if decode_bytes[i] {
+                let bytes = param
+                    .map(|v| decode_bytea_field(v, options.bytea_encoding))
+                    .transpose()
+                    .map_err(|e| format!("Row {}, column \"{}\": {}", rows_imported + 1, columns[i].name, e))?;
+                query = query.bind(bytes);
             } else {
-                query = query.bind(value);
+                query = query.bind(param);
             }
         }
 
@@ -487,6 +1372,161 @@ pub async fn import_csv(
     })
 }
 
+/// Whether the file can go to `COPY` as-is: PostgreSQL parses every field from
+/// text exactly as the per-row casts do, but can't trim fields, decode hex or
+/// base64 bytea, or drop the fields of generated columns.
+fn copy_import_supported(options: &ImportCsvOptions, has_generated: bool, has_bytea: bool) -> bool {
+    !options.trim_whitespace
+        && !has_generated
+        && (options.bytea_encoding == ByteaEncoding::Text || !has_bytea)
+}
+
+/// The `COPY ... FROM STDIN` statement for a CSV import. `FORCE_NULL` makes
+/// quoted fields follow the same NULL rules as unquoted ones, matching the
+/// per-row path: empty fields are NULL unless a null marker is set.
+fn csv_copy_sql(options: &ImportCsvOptions, column_list: &str) -> String {
+    let mut copy_options = vec!["FORMAT csv".to_string()];
+    if options.has_headers {
+        copy_options.push("HEADER true".to_string());
+    }
+    if let Some(marker) = &options.null_marker {
+        copy_options.push(format!("NULL '{}'", marker.replace('\'', "''")));
+    }
+    copy_options.push(format!("FORCE_NULL ({})", column_list));
+    format!(
+        "COPY \"{}\".\"{}\" ({}) FROM STDIN WITH ({})",
+        escape_identifier(&options.schema_name),
+        escape_identifier(&options.table_name),
+        column_list,
+        copy_options.join(", ")
+    )
+}
+
+/// Stream a CSV file to the server through `copy_sql`, returning the rows the
+/// server reports copied. Progress counts lines sent, so it runs ahead of the
+/// row count for fields containing newlines until the final total is stored.
+async fn copy_csv_file(
+    pool: &sqlx::PgPool,
+    copy_sql: &str,
+    options: &ImportCsvOptions,
+    progress: &std::sync::atomic::AtomicU64,
+) -> Result<u64, String> {
+    use sqlx::postgres::PgPoolCopyExt;
+    use std::io::Read;
+    use std::sync::atomic::Ordering;
+
+    let mut file = File::open(&options.file_path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut copy = pool
+        .copy_in_raw(copy_sql)
+        .await
+        .map_err(|e| format!("Failed to start COPY: {}", e))?;
+
+    let header_lines = u64::from(options.has_headers);
+    let mut lines_sent: u64 = 0;
+    let mut buf = vec![0u8; COPY_CHUNK_SIZE];
+    let mut first_chunk = true;
+    loop {
+        let n = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) => {
+                copy.abort(e.to_string()).await.ok();
+                return Err(format!("Failed to read file: {}", e));
+            }
+        };
+        // COPY would take a UTF-8 byte order mark (as Excel writes) as part of
+        // the first value.
+        let mut chunk = &buf[..n];
+        if std::mem::take(&mut first_chunk) {
+            chunk = chunk.strip_prefix(UTF8_BOM).unwrap_or(chunk);
+        }
+        copy.send(chunk)
+            .await
+            .map_err(|e| format!("Failed to import CSV: {}", e))?;
+        lines_sent += chunk.iter().filter(|&&b| b == b'\n').count() as u64;
+        progress.store(lines_sent.saturating_sub(header_lines), Ordering::Relaxed);
+    }
+
+    let rows_imported = copy
+        .finish()
+        .await
+        .map_err(|e| format!("Failed to import CSV: {}", e))?;
+    progress.store(rows_imported, Ordering::Relaxed);
+    Ok(rows_imported)
+}
+
+/// Which CSV fields to insert, in column order, for a row of `field_count`
+/// fields. `generated` flags each table column. A row may list only the
+/// writable columns, or every column (e.g. a full-table CSV export), in which
+/// case the generated columns' fields are skipped. `None` if it's neither.
+fn csv_import_fields(generated: &[bool], field_count: usize) -> Option<Vec<usize>> {
+    let writable = generated.iter().filter(|g| !**g).count();
+    if field_count == writable {
+        Some((0..field_count).collect())
+    } else if field_count == generated.len() {
+        Some((0..field_count).filter(|&i| !generated[i]).collect())
+    } else {
+        None
+    }
+}
+
+/// Map a CSV field to its bound parameter: `None` binds SQL NULL. Without a
+/// null marker an empty field is NULL; with one, only the marker is NULL and
+/// an empty field stays an empty string.
+fn csv_field_to_param<'a>(value: &'a str, null_marker: Option<&str>) -> Option<&'a str> {
+    match null_marker {
+        Some(marker) if value == marker => None,
+        Some(_) => Some(value),
+        None if value.is_empty() => None,
+        None => Some(value),
+    }
+}
+
+/// Apply the import's trimming and NULL rules to a raw CSV field.
+fn prepare_csv_field<'a>(raw: &'a str, options: &ImportCsvOptions) -> Option<&'a str> {
+    if !options.trim_whitespace {
+        return csv_field_to_param(raw, options.null_marker.as_deref());
+    }
+    let trimmed = raw.trim();
+    if trimmed.is_empty() && !raw.is_empty() {
+        return if options.trimmed_empty_as_null { None } else { Some(trimmed) };
+    }
+    csv_field_to_param(trimmed, options.null_marker.as_deref())
+}
+
+/// Decode a CSV field for a bytea column into raw bytes.
+fn decode_bytea_field(value: &str, encoding: ByteaEncoding) -> Result<Vec<u8>, String> {
+    let value = value.trim();
+    match encoding {
+        ByteaEncoding::Text => Ok(value.as_bytes().to_vec()),
+        ByteaEncoding::Hex => {
+            let digits = value
+                .strip_prefix("\\x")
+                .or_else(|| value.strip_prefix("\\X"))
+                .unwrap_or(value);
+            if digits.len() % 2 != 0 {
+                return Err("hex value has an odd number of digits".to_string());
+            }
+            (0..digits.len())
+                .step_by(2)
+                .map(|i| {
+                    digits
+                        .get(i..i + 2)
+                        .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                        .ok_or_else(|| "invalid hex value".to_string())
+                })
+                .collect()
+        }
+        ByteaEncoding::Base64 => {
+            use base64::Engine;
+            base64::engine::general_purpose::STANDARD_NO_PAD
+                .decode(value.trim_end_matches('='))
+                .map_err(|e| format!("invalid base64 value: {}", e))
+        }
+    }
+}
+
 // ============================================================================
 // Table Export (multi-format)
 // ============================================================================
@@ -503,16 +1543,41 @@ pub enum ExportFormat {
     Xlsx,
 }
 
+/// Column order for a table export that doesn't list its columns explicitly.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportColumnOrder {
+    /// Table ordinal position (`SELECT *` order).
+    #[default]
+    Ordinal,
+    /// Sorted by column name, so exports diff cleanly across environments
+    /// whose ordinal positions differ.
+    Alphabetical,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportTableOptions {
     pub schema_name: String,
     pub table_name: String,
+    /// Columns to export, written in exactly this order. Empty exports every
+    /// column, ordered by `column_order`.
     pub columns: Vec<String>,
     pub include_headers: bool,
     pub null_as_empty: bool,
     pub file_path: String,
     pub format: ExportFormat,
+    /// Ignored when `columns` is non-empty.
+    #[serde(default)]
+    pub column_order: ExportColumnOrder,
+    /// Write CSV/TSV exactly as `COPY ... TO` does, so the file loads back with
+    /// `COPY ... FROM` unchanged. See `copy_csv_field` and `copy_text_field`.
+    #[serde(default)]
+    pub copy_compatible: bool,
+    /// Keep generated columns in a SQL INSERT export. Off by default, since
+    /// the INSERTs would fail on a table that has them.
+    #[serde(default)]
+    pub include_generated_columns: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -527,6 +1592,18 @@ pub async fn export_table(
     connection_id: String,
     options: ExportTableOptions,
     state: &AppState,
+) -> Result<ExportTableResult, String> {
+    export_table_with_layout(connection_id, options, 1, None, state).await
+}
+
+/// `export_table`, with SQL INSERT output batching `rows_per_insert` rows per
+/// statement and starting with `preamble`.
+async fn export_table_with_layout(
+    connection_id: String,
+    options: ExportTableOptions,
+    rows_per_insert: usize,
+    preamble: Option<String>,
+    state: &AppState,
 ) -> Result<ExportTableResult, String> {
     let pool = state
         .get_pool(&connection_id)
@@ -542,11 +1619,38 @@ pub async fn export_table(
         validate_identifier(col)?;
     }
 
+    // Explicit columns keep the caller's order; otherwise apply column_order.
+    let skip_generated = matches!(options.format, ExportFormat::SqlInsert) && !options.include_generated_columns;
+    let alphabetical = options.columns.is_empty() && options.column_order == ExportColumnOrder::Alphabetical;
+    let columns = if skip_generated || alphabetical {
+        let table_columns = postgres::get_columns(&pool, &options.schema_name, &options.table_name)
+            .await
+            .map_err(|e| format!("Failed to get table columns: {}", e))?;
+        let is_generated =
+            |name: &str| skip_generated && table_columns.iter().any(|c| c.is_generated && c.name == name);
+        let names: Vec<String> = if options.columns.is_empty() {
+            table_columns.iter().map(|c| c.name.clone()).collect()
+        } else {
+            options.columns.clone()
+        };
+        let names: Vec<String> = names.into_iter().filter(|n| !is_generated(n)).collect();
+        if names.is_empty() {
+            return Err("No insertable columns to export".to_string());
+        }
+        if alphabetical {
+            alphabetical_columns(names)
+        } else {
+            names
+        }
+    } else {
+        options.columns.clone()
+    };
+
     // Build column list for SELECT
-    let column_list = if options.columns.is_empty() {
+    let column_list = if columns.is_empty() {
         "*".to_string()
     } else {
-        options.columns.iter()
+        columns.iter()
             .map(|c| format!("\"{}\"", escape_identifier(c)))
             .collect::<Vec<_>>()
             .join(", ")
@@ -560,25 +1664,104 @@ pub async fn export_table(
     );
 
     // SQL INSERT target for this table export
-    let sql_insert_target = format!(
-        "\"{}\".\"{}\"",
-        escape_identifier(&options.schema_name),
-        escape_identifier(&options.table_name)
-    );
+    let sql_insert = SqlInsertLayout {
+        target: format!(
+            "\"{}\".\"{}\"",
+            escape_identifier(&options.schema_name),
+            escape_identifier(&options.table_name)
+        ),
+        rows_per_insert,
+        preamble,
+    };
+
+    let settings = query_settings(state);
+    let null_text = if options.null_as_empty {
+        ""
+    } else {
+        settings.export_null_display.as_deref().unwrap_or("NULL")
+    };
+
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+
+    stream_export(
+        &mut conn,
+        &select_sql,
+        &options.file_path,
+        &options.format,
+        &sql_insert,
+        null_text,
+        options.include_headers,
+        &DateTimeFormat::from_setting(settings.datetime_format.as_deref()),
+        options.copy_compatible,
+        state.server_version(&connection_id),
+        None,
+    )
+    .await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSqlOptions {
+    pub schema_name: String,
+    pub table_name: String,
+    /// Columns to export, in this order. Empty exports every column.
+    #[serde(default)]
+    pub columns: Vec<String>,
+    pub file_path: String,
+    /// Rows per INSERT statement; 0 or 1 writes one statement per row.
+    #[serde(default)]
+    pub rows_per_insert: u32,
+    /// Start the file with the table's CREATE TABLE DDL (`generate_table_ddl`).
+    #[serde(default)]
+    pub include_create_table: bool,
+    /// See `ExportTableOptions::include_generated_columns`.
+    #[serde(default)]
+    pub include_generated_columns: bool,
+}
 
-    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+/// Export a table as `INSERT INTO "schema"."table" (...) VALUES (...);`
+/// statements for loading into another environment, optionally several rows
+/// per statement and preceded by the table's DDL.
+pub async fn export_sql(
+    connection_id: String,
+    options: ExportSqlOptions,
+    state: &AppState,
+) -> Result<ExportTableResult, String> {
+    let preamble = if options.include_create_table {
+        validate_identifier(&options.schema_name)?;
+        validate_identifier(&options.table_name)?;
+        let ddl = generate_table_ddl(
+            connection_id.clone(),
+            options.schema_name.clone(),
+            options.table_name.clone(),
+            state,
+        )
+        .await?;
+        Some(ddl.full)
+    } else {
+        None
+    };
+    let table_options = ExportTableOptions {
+        schema_name: options.schema_name,
+        table_name: options.table_name,
+        columns: options.columns,
+        include_headers: false,
+        null_as_empty: false,
+        file_path: options.file_path,
+        format: ExportFormat::SqlInsert,
+        column_order: ExportColumnOrder::Ordinal,
+        copy_compatible: false,
+        include_generated_columns: options.include_generated_columns,
+    };
+    let rows_per_insert = options.rows_per_insert.max(1) as usize;
+    export_table_with_layout(connection_id, table_options, rows_per_insert, preamble, state).await
+}
 
-    stream_export(
-        &mut conn,
-        &select_sql,
-        &options.file_path,
-        &options.format,
-        &sql_insert_target,
-        options.null_as_empty,
-        options.include_headers,
-        None,
-    )
-    .await
+/// Sort column names for an alphabetical export: case-insensitive, with the
+/// exact name as a tie-breaker so the order is fully deterministic.
+fn alphabetical_columns(mut names: Vec<String>) -> Vec<String> {
+    names.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()).then_with(|| a.cmp(b)));
+    names
 }
 
 // ============================================================================
@@ -596,8 +1779,41 @@ pub struct ExportResultsColumn {
 #[serde(rename_all = "camelCase")]
 pub struct ExportResultsOptions {
     pub columns: Vec<ExportResultsColumn>,
+    /// Rows as positional arrays (as in `QueryResult.rows`) or name-keyed objects.
     pub rows: Vec<serde_json::Value>,
     pub file_path: String,
+    /// Indices into `columns`, in the order they should be written. `None`
+    /// exports every column in result order. Indices rather than names so
+    /// duplicate names from joins/expressions stay addressable.
+    #[serde(default)]
+    pub selected_columns: Option<Vec<usize>>,
+    /// JSON only: indent each object instead of writing it on one line.
+    #[serde(default)]
+    pub pretty: bool,
+}
+
+/// Resolve the export column order: the caller's selection (validated against
+/// the result width) or every column in result order.
+fn resolve_export_columns(column_count: usize, selection: Option<&[usize]>) -> Result<Vec<usize>, String> {
+    match selection {
+        None => Ok((0..column_count).collect()),
+        Some([]) => Err("No columns selected for export".to_string()),
+        Some(indices) => {
+            if let Some(bad) = indices.iter().find(|&&i| i >= column_count) {
+                return Err(format!("Column index {} out of range ({} columns)", bad, column_count));
+            }
+            Ok(indices.to_vec())
+        }
+    }
+}
+
+/// Look up a result cell by position (array rows) or by column name (object rows).
+fn result_cell<'a>(row: &'a serde_json::Value, index: usize, name: &str) -> Option<&'a serde_json::Value> {
+    match row {
+        serde_json::Value::Array(values) => values.get(index),
+        serde_json::Value::Object(obj) => obj.get(name),
+        _ => None,
+    }
 }
 
 /// Write text content to a file (for client-side text export formats)
@@ -624,23 +1840,24 @@ pub async fn export_results(
 ) -> Result<ExportTableResult, String> {
     validate_file_path(&options.file_path)?;
 
+    let order = resolve_export_columns(options.columns.len(), options.selected_columns.as_deref())?;
+
     let mut workbook = rust_xlsxwriter::Workbook::new();
     let worksheet = workbook.add_worksheet();
 
     // Write headers
-    for (col_idx, col) in options.columns.iter().enumerate() {
-        worksheet.write_string(0, col_idx as u16, &col.name)
+    for (out_idx, &src_idx) in order.iter().enumerate() {
+        worksheet.write_string(0, out_idx as u16, &options.columns[src_idx].name)
             .map_err(|e| format!("Failed to write header: {}", e))?;
     }
 
     // Write data rows
     for (row_idx, row) in options.rows.iter().enumerate() {
-        if let Some(obj) = row.as_object() {
-            for (col_idx, col) in options.columns.iter().enumerate() {
-                let cell_value = obj.get(&col.name);
-                write_xlsx_json_cell(worksheet, (row_idx as u32) + 1, col_idx as u16, cell_value, &col.data_type)
-                    .map_err(|e| format!("Failed to write cell: {}", e))?;
-            }
+        for (out_idx, &src_idx) in order.iter().enumerate() {
+            let col = &options.columns[src_idx];
+            let cell_value = result_cell(row, src_idx, &col.name);
+            write_xlsx_json_cell(worksheet, (row_idx as u32) + 1, out_idx as u16, cell_value, &col.data_type)
+                .map_err(|e| format!("Failed to write cell: {}", e))?;
         }
     }
 
@@ -655,6 +1872,79 @@ pub async fn export_results(
     })
 }
 
+/// Export in-memory query results as a JSON array of objects keyed by column
+/// name. Cells are written as the grid holds them (from `extract_value`), so
+/// the file matches what's on screen. Rows are streamed to the file.
+pub async fn export_results_json(
+    options: ExportResultsOptions,
+) -> Result<ExportTableResult, String> {
+    validate_file_path(&options.file_path)?;
+
+    let order = resolve_export_columns(options.columns.len(), options.selected_columns.as_deref())?;
+
+    let file = File::create(&options.file_path)
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    write_results_json(&mut writer, &options.columns, &order, &options.rows, options.pretty)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    writer.flush()
+        .map_err(|e| format!("Failed to flush file: {}", e))?;
+
+    Ok(ExportTableResult {
+        success: true,
+        rows_exported: options.rows.len() as u64,
+    })
+}
+
+/// A JSON object whose keys keep the export column order (serde_json's Map
+/// would sort them).
+struct OrderedJsonObject<'a>(Vec<(&'a str, &'a serde_json::Value)>);
+
+impl Serialize for OrderedJsonObject<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Write result rows as a JSON array, one object at a time.
+fn write_results_json<W: Write>(
+    writer: &mut W,
+    columns: &[ExportResultsColumn],
+    order: &[usize],
+    rows: &[serde_json::Value],
+    pretty: bool,
+) -> std::io::Result<()> {
+    if rows.is_empty() {
+        return writer.write_all(b"[]\n");
+    }
+    writer.write_all(b"[\n")?;
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",\n")?;
+        }
+        let obj = OrderedJsonObject(
+            order
+                .iter()
+                .map(|&idx| {
+                    let name = columns[idx].name.as_str();
+                    (name, result_cell(row, idx, name).unwrap_or(&serde_json::Value::Null))
+                })
+                .collect(),
+        );
+        if pretty {
+            serde_json::to_writer_pretty(&mut *writer, &obj)?;
+        } else {
+            serde_json::to_writer(&mut *writer, &obj)?;
+        }
+    }
+    writer.write_all(b"\n]\n")
+}
+
 // ============================================================================
 // Full Query Export (all rows, paginated, streamed to file)
 // ============================================================================
@@ -666,6 +1956,9 @@ pub struct ExportQueryOptions {
     pub schema: Option<String>,
     pub file_path: String,
     pub format: ExportFormat,
+    /// See `ExportTableOptions::copy_compatible`.
+    #[serde(default)]
+    pub copy_compatible: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -684,7 +1977,19 @@ pub async fn export_query(
     progress_callback: Option<Box<dyn Fn(u64, bool) + Send>>,
 ) -> Result<ExportTableResult, String> {
     validate_file_path(&options.file_path)?;
+    let settings = query_settings(state);
+    let null_text = settings.export_null_display.as_deref().unwrap_or("");
+    export_query_with_null_text(connection_id, options, null_text, state, progress_callback).await
+}
 
+/// `export_query`, writing SQL NULL in CSV/TSV/Markdown cells as `null_text`.
+async fn export_query_with_null_text(
+    connection_id: String,
+    options: ExportQueryOptions,
+    null_text: &str,
+    state: &AppState,
+    progress_callback: Option<Box<dyn Fn(u64, bool) + Send>>,
+) -> Result<ExportTableResult, String> {
     let pool = state
         .get_pool(&connection_id)
         .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
@@ -697,42 +2002,321 @@ pub async fn export_query(
     }
 
     let trimmed_sql = options.sql.trim().trim_end_matches(';').to_string();
+    let settings = query_settings(state);
 
     stream_export(
         &mut conn,
         &trimmed_sql,
         &options.file_path,
         &options.format,
-        "\"_query_results\"",
-        true,  // null_as_empty
+        &SqlInsertLayout::single_row("\"_query_results\""),
+        null_text,
         true,  // include_headers
+        &DateTimeFormat::from_setting(settings.datetime_format.as_deref()),
+        options.copy_compatible,
+        state.server_version(&connection_id),
         progress_callback,
     )
     .await
 }
 
+/// Export every row of a query as newline-delimited JSON — one compact object
+/// per line — for piping into jq or a data pipeline. Streams like `export_query`.
+pub async fn export_ndjson(
+    connection_id: String,
+    sql: String,
+    schema: Option<String>,
+    file_path: String,
+    state: &AppState,
+) -> Result<ExportTableResult, String> {
+    let options = ExportQueryOptions {
+        sql,
+        schema,
+        file_path,
+        format: ExportFormat::JsonLines,
+        copy_compatible: false,
+    };
+    export_query(connection_id, options, state, None).await
+}
+
+/// Export every row of a query as a GitHub-flavored Markdown table, for pasting
+/// into docs or pull requests. NULL is written as an empty cell when
+/// `null_as_empty`, otherwise as the NULL display setting. Streams like `export_query`.
+pub async fn export_markdown(
+    connection_id: String,
+    sql: String,
+    schema: Option<String>,
+    file_path: String,
+    null_as_empty: bool,
+    state: &AppState,
+) -> Result<ExportTableResult, String> {
+    validate_file_path(&file_path)?;
+    let settings = query_settings(state);
+    let null_text = if null_as_empty {
+        ""
+    } else {
+        settings.export_null_display.as_deref().unwrap_or("NULL")
+    };
+    let options = ExportQueryOptions {
+        sql,
+        schema,
+        file_path,
+        format: ExportFormat::Markdown,
+        copy_compatible: false,
+    };
+    export_query_with_null_text(connection_id, options, null_text, state, None).await
+}
+
+// ============================================================================
+// Cell Export
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportCellOptions {
+    pub schema_name: String,
+    pub table_name: String,
+    pub column: String,
+    /// The row's primary key values in text form, by column name. Every
+    /// primary key column must be present.
+    pub primary_key: std::collections::BTreeMap<String, String>,
+    pub file_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportCellResult {
+    pub bytes_written: u64,
+}
+
+/// Write one cell's value straight to a file, so large text or bytea values
+/// (stored documents, images) never pass through the results JSON. bytea is
+/// written as its raw bytes, anything else as its text form.
+pub async fn export_cell_to_file(
+    connection_id: String,
+    options: ExportCellOptions,
+    state: &AppState,
+) -> Result<ExportCellResult, String> {
+    let pool = state
+        .get_pool(&connection_id)
+        .ok_or_else(|| format!("Not connected to: {}", connection_id))?;
+
+    validate_file_path(&options.file_path)?;
+    validate_identifier(&options.schema_name)?;
+    validate_identifier(&options.table_name)?;
+    validate_identifier(&options.column)?;
+
+    let columns = postgres::get_columns(&pool, &options.schema_name, &options.table_name)
+        .await
+        .map_err(|e| format!("Failed to get table columns: {}", e))?;
+    if columns.is_empty() {
+        return Err(format!("Table not found: {}.{}", options.schema_name, options.table_name));
+    }
+    let target = columns
+        .iter()
+        .find(|c| c.name == options.column)
+        .ok_or_else(|| format!("Column not found: {}", options.column))?;
+
+    let pk_columns: Vec<(&str, &str)> = columns
+        .iter()
+        .filter(|c| c.is_primary_key)
+        .map(|c| (c.name.as_str(), map_data_type_for_cast(&c.data_type)))
+        .collect();
+    if pk_columns.is_empty() {
+        return Err("Table has no primary key; the row can't be identified".to_string());
+    }
+    let mut key_values = Vec::with_capacity(pk_columns.len());
+    for (name, _) in &pk_columns {
+        let value = options
+            .primary_key
+            .get(*name)
+            .ok_or_else(|| format!("Missing primary key value for column: {}", name))?;
+        key_values.push(value);
+    }
+
+    let sql = cell_export_sql(
+        &options.schema_name,
+        &options.table_name,
+        &options.column,
+        target.data_type.eq_ignore_ascii_case("bytea"),
+        &pk_columns,
+    );
+    let mut query = sqlx::query(&sql);
+    for value in key_values {
+        query = query.bind(value);
+    }
+    let row = query
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to read cell: {}", e))?
+        .ok_or_else(|| "Row not found".to_string())?;
+
+    // Binary protocol: a bytea value arrives as its raw bytes and text as UTF-8.
+    let value = row.try_get_raw(0).map_err(|e| e.to_string())?;
+    if value.is_null() {
+        return Err("Cell is NULL; nothing to export".to_string());
+    }
+    let bytes = value.as_bytes().map_err(|e| e.to_string())?;
+
+    let file = File::create(&options.file_path)
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    writer
+        .write_all(bytes)
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    Ok(ExportCellResult { bytes_written: bytes.len() as u64 })
+}
+
+/// Single-cell SELECT by primary key. Key values are bound as text and cast
+/// to their column's type so the primary key index is used; non-bytea values
+/// are read as `::text`.
+fn cell_export_sql(
+    schema_name: &str,
+    table_name: &str,
+    column: &str,
+    is_bytea: bool,
+    pk_columns: &[(&str, &str)],
+) -> String {
+    let filter = pk_columns
+        .iter()
+        .enumerate()
+        .map(|(i, (name, cast))| format!("\"{}\" = ${}::{}", escape_identifier(name), i + 1, cast))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    format!(
+        "SELECT \"{}\"{} FROM \"{}\".\"{}\" WHERE {}",
+        escape_identifier(column),
+        if is_bytea { "" } else { "::text" },
+        escape_identifier(schema_name),
+        escape_identifier(table_name),
+        filter
+    )
+}
+
 // ============================================================================
 // Shared Streaming Export Engine
 // ============================================================================
 
+/// First release where binary date/time values are always int64 microseconds
+/// (`--disable-integer-datetimes` was removed in PostgreSQL 10).
+const INTEGER_DATETIMES_ONLY_VERSION: i32 = 100000;
+
+/// First OID assigned to user-defined objects; types below it are built in.
+const FIRST_USER_TYPE_OID: u32 = 16384;
+
+/// OIDs of `money` and `money[]`.
+const MONEY_OID: u32 = 790;
+const MONEY_ARRAY_OID: u32 = 791;
+
+/// The cast that makes the server send a column of type `oid` as text, if it
+/// has no usable binary decoding. Binary money is a count of `lc_monetary`'s
+/// minor units with no scale attached, so it goes through numeric, which
+/// applies the locale's fractional digits (`1234.56`, or `1234` for yen).
+fn export_text_cast(oid: Option<u32>) -> Option<&'static str> {
+    match oid {
+        Some(MONEY_OID) => Some("::numeric::text"),
+        Some(MONEY_ARRAY_OID) => Some("::numeric[]::text"),
+        Some(oid) if oid < FIRST_USER_TYPE_OID => None,
+        _ => Some("::text"),
+    }
+}
+
+/// `base_sql` with each column that has a cast in `casts` converted under its
+/// original name. Columns are taken positionally, so duplicate names are fine.
+fn with_text_columns(base_sql: &str, names: &[String], casts: &[Option<&str>]) -> String {
+    let placeholders: Vec<String> = (0..names.len()).map(|i| format!("pharos_c{}", i)).collect();
+    let select_list: Vec<String> = names
+        .iter()
+        .zip(casts)
+        .zip(&placeholders)
+        .map(|((name, cast), placeholder)| {
+            format!("{}{} AS \"{}\"", placeholder, cast.unwrap_or(""), escape_identifier(name))
+        })
+        .collect();
+    format!(
+        "SELECT {} FROM ({}) AS pharos_src({})",
+        select_list.join(", "),
+        base_sql,
+        placeholders.join(", ")
+    )
+}
+
+/// How SQL INSERT export output is laid out.
+struct SqlInsertLayout {
+    /// Quoted target table, e.g. `"public"."orders"`.
+    target: String,
+    /// Rows per INSERT statement (at least 1).
+    rows_per_insert: usize,
+    /// Written before the first INSERT, e.g. CREATE TABLE DDL.
+    preamble: Option<String>,
+}
+
+impl SqlInsertLayout {
+    fn single_row(target: &str) -> Self {
+        SqlInsertLayout { target: target.to_string(), rows_per_insert: 1, preamble: None }
+    }
+}
+
+/// One INSERT statement for `tuples` (each a parenthesized value list).
+/// Multi-row statements put each tuple on its own line.
+fn render_insert_statement(target: &str, col_list: &str, tuples: &[String]) -> String {
+    if let [tuple] = tuples {
+        return format!("INSERT INTO {} ({}) VALUES {};", target, col_list, tuple);
+    }
+    format!("INSERT INTO {} ({}) VALUES\n    {};", target, col_list, tuples.join(",\n    "))
+}
+
 /// Shared streaming export: paginates through a SQL query with LIMIT/OFFSET
 /// and writes each batch to the target file in the specified format.
 ///
 /// `base_sql` is the bare SELECT (no trailing semicolon).
-/// `sql_insert_target` is the quoted table name used for SQL INSERT format output.
-/// `null_as_empty` and `include_headers` control formatting behavior.
+/// `sql_insert` is the target table and statement layout for SQL INSERT format output.
+/// `null_text` is written for NULL in text formats (empty for "null as empty");
+/// `include_headers` controls the header row.
+/// `copy_compatible` makes CSV/TSV match `COPY ... TO` (CSV and text formats):
+/// rows are fetched as server text output and `null_text`/`datetime_format` are ignored.
+/// `server_version` is the connection's `server_version_num`, if known.
 async fn stream_export(
     conn: &mut sqlx::pool::PoolConnection<sqlx::Postgres>,
     base_sql: &str,
     file_path: &str,
     format: &ExportFormat,
-    sql_insert_target: &str,
-    null_as_empty: bool,
+    sql_insert: &SqlInsertLayout,
+    null_text: &str,
     include_headers: bool,
+    datetime_format: &DateTimeFormat,
+    copy_compatible: bool,
+    server_version: Option<i32>,
     progress_callback: Option<Box<dyn Fn(u64, bool) + Send>>,
 ) -> Result<ExportTableResult, String> {
     use futures::StreamExt;
 
+    let copy_mode = copy_compatible && matches!(format, ExportFormat::Csv | ExportFormat::Tsv);
+    // Servers before 10 may be built with float datetimes, whose binary timestamps
+    // and intervals aren't the int64 microseconds sqlx decodes; have them send text.
+    let text_protocol = copy_mode || server_version.is_some_and(|v| v < INTEGER_DATETIMES_ONLY_VERSION);
+
+    // Enums, domains, extension types and money have no usable binary decoding
+    // here; have the server send them as text. Skipped if the server can't
+    // describe the query.
+    let mut base_sql = base_sql.to_string();
+    if !text_protocol {
+        if let Ok(desc) = (&mut **conn).describe(base_sql.as_str()).await {
+            let names: Vec<String> = desc.columns().iter().map(|c| c.name().to_string()).collect();
+            let casts: Vec<Option<&str>> = desc
+                .columns()
+                .iter()
+                .map(|c| export_text_cast(c.type_info().oid().map(|oid| oid.0)))
+                .collect();
+            if casts.iter().any(Option::is_some) {
+                base_sql = with_text_columns(&base_sql, &names, &casts);
+            }
+        }
+    }
+
+    let null_as_empty = null_text.is_empty();
     let batch_size: i64 = 5000;
     let mut total_exported: u64 = 0;
     let mut offset: i64 = 0;
@@ -751,6 +2335,11 @@ async fn stream_export(
         writer.write_all(b"[\n").map_err(|e| format!("Failed to write: {}", e))?;
     }
 
+    // SQL INSERT: the preamble (e.g. CREATE TABLE) is written even for an empty result
+    if let (ExportFormat::SqlInsert, Some(preamble)) = (format, &sql_insert.preamble) {
+        writeln!(writer, "{}\n", preamble.trim_end()).map_err(|e| format!("Failed to write: {}", e))?;
+    }
+
     // XLSX: build workbook in memory
     let is_xlsx = matches!(format, ExportFormat::Xlsx);
     let mut workbook = if is_xlsx { Some(rust_xlsxwriter::Workbook::new()) } else { None };
@@ -762,7 +2351,13 @@ async fn stream_export(
             base_sql, batch_size, offset
         );
 
-        let mut stream = sqlx::query(&wrapped_sql).fetch(&mut **conn);
+        // COPY output is the server's text representation, so fetch it as text
+        // rather than decoding binary values client-side.
+        let mut stream = if text_protocol {
+            sqlx::raw_sql(&wrapped_sql).fetch(&mut **conn)
+        } else {
+            sqlx::query(&wrapped_sql).fetch(&mut **conn)
+        };
         let mut batch: Vec<sqlx::postgres::PgRow> = Vec::with_capacity(batch_size as usize);
 
         while let Some(row_result) = stream.next().await {
@@ -791,6 +2386,27 @@ async fn stream_export(
 
         // Write batch based on format
         match format {
+            ExportFormat::Csv | ExportFormat::Tsv if copy_mode => {
+                let is_csv = matches!(format, ExportFormat::Csv);
+                let field = |value: Option<&str>| if is_csv { copy_csv_field(value) } else { copy_text_field(value) };
+                let sep = if is_csv { "," } else { "\t" };
+                if !headers_written && include_headers {
+                    let header_line: Vec<String> = col_names.iter().map(|n| field(Some(n))).collect();
+                    writeln!(writer, "{}", header_line.join(sep))
+                        .map_err(|e| format!("Failed to write headers: {}", e))?;
+                    headers_written = true;
+                }
+                for row in &batch {
+                    let record: Vec<String> = (0..row.len())
+                        .map(|i| match row.try_get_raw(i) {
+                            Ok(raw) if !raw.is_null() => field(raw.as_str().ok()),
+                            _ => field(None),
+                        })
+                        .collect();
+                    writeln!(writer, "{}", record.join(sep))
+                        .map_err(|e| format!("Failed to write row: {}", e))?;
+                }
+            }
             ExportFormat::Csv | ExportFormat::Tsv => {
                 let delimiter = match format {
                     ExportFormat::Tsv => b'\t',
@@ -809,7 +2425,7 @@ async fn stream_export(
                 for row in &batch {
                     let record: Vec<String> = row.columns().iter().enumerate()
                         .map(|(i, col)| {
-                            let text = extract_text_value(row, i, &col.type_info().to_string(), null_as_empty);
+                            let text = export_cell_text(row, i, &col.type_info().to_string(), null_text, datetime_format);
                             escape_csv_field(&text, delimiter)
                         })
                         .collect();
@@ -822,7 +2438,7 @@ async fn stream_export(
                     if total_exported > 0 || i > 0 {
                         writer.write_all(b",\n").map_err(|e| format!("Failed to write: {}", e))?;
                     }
-                    let obj = row_to_json_object(row, true);
+                    let obj = row_to_json_object(row, true, datetime_format);
                     let json_str = serde_json::to_string_pretty(&serde_json::Value::Object(obj))
                         .map_err(|e| format!("Failed to serialize: {}", e))?;
                     writer.write_all(json_str.as_bytes())
@@ -831,7 +2447,7 @@ async fn stream_export(
             }
             ExportFormat::JsonLines => {
                 for row in &batch {
-                    let obj = row_to_json_object(row, true);
+                    let obj = row_to_json_object(row, true, datetime_format);
                     let line = serde_json::to_string(&serde_json::Value::Object(obj))
                         .map_err(|e| format!("Failed to serialize: {}", e))?;
                     writeln!(writer, "{}", line).map_err(|e| format!("Failed to write: {}", e))?;
@@ -845,26 +2461,31 @@ async fn stream_export(
                     .map(|n| format!("\"{}\"", escape_identifier(n)))
                     .collect::<Vec<_>>()
                     .join(", ");
-                for row in &batch {
-                    let values: Vec<String> = row.columns().iter().enumerate()
-                        .map(|(i, col)| {
-                            let type_name = col.type_info().to_string();
-                            let text = extract_text_value(row, i, &type_name, false);
-                            if text == "NULL" {
-                                "NULL".to_string()
-                            } else {
+                let tuples: Vec<String> = batch.iter()
+                    .map(|row| {
+                        let values: Vec<String> = row.columns().iter().enumerate()
+                            .map(|(i, col)| {
+                                // Check the raw value: a text cell may itself read "NULL".
+                                if row.try_get_raw(i).map_or(true, |raw| raw.is_null()) {
+                                    return "NULL".to_string();
+                                }
+                                let type_name = col.type_info().to_string();
+                                let text = extract_text_value(row, i, &type_name, false, datetime_format);
                                 format_sql_value(&text, &type_name)
-                            }
-                        })
-                        .collect();
-                    writeln!(writer, "INSERT INTO {} ({}) VALUES ({});",
-                        sql_insert_target, col_list, values.join(", "))
+                            })
+                            .collect();
+                        format!("({})", values.join(", "))
+                    })
+                    .collect();
+                for chunk in tuples.chunks(sql_insert.rows_per_insert.max(1)) {
+                    writeln!(writer, "{}", render_insert_statement(&sql_insert.target, &col_list, chunk))
                         .map_err(|e| format!("Failed to write: {}", e))?;
                 }
             }
             ExportFormat::Markdown => {
                 if !headers_written {
-                    writeln!(writer, "| {} |", col_names.join(" | "))
+                    let header: Vec<String> = col_names.iter().map(|n| markdown_cell(n)).collect();
+                    writeln!(writer, "| {} |", header.join(" | "))
                         .map_err(|e| format!("Failed to write: {}", e))?;
                     let sep: Vec<&str> = col_names.iter().map(|_| "---").collect();
                     writeln!(writer, "| {} |", sep.join(" | "))
@@ -874,8 +2495,8 @@ async fn stream_export(
                 for row in &batch {
                     let values: Vec<String> = row.columns().iter().enumerate()
                         .map(|(i, col)| {
-                            let text = extract_text_value(row, i, &col.type_info().to_string(), null_as_empty);
-                            text.replace('|', "\\|")
+                            let text = export_cell_text(row, i, &col.type_info().to_string(), null_text, datetime_format);
+                            markdown_cell(&text)
                         })
                         .collect();
                     writeln!(writer, "| {} |", values.join(" | "))
@@ -905,6 +2526,7 @@ async fn stream_export(
                                 col_idx,
                                 &type_name,
                                 null_as_empty,
+                                datetime_format,
                             ).map_err(|e| format!("Failed to write cell: {}", e))?;
                         }
                     }
@@ -960,6 +2582,42 @@ fn escape_csv_field(value: &str, delimiter: u8) -> String {
     }
 }
 
+/// A field as `COPY ... TO (FORMAT csv)` writes it. Unlike `escape_csv_field`:
+/// NULL is an unquoted empty field, so an empty string is written as `""`,
+/// and a lone `\.` (the end-of-data marker) is quoted.
+fn copy_csv_field(value: Option<&str>) -> String {
+    match value {
+        None => String::new(),
+        Some(v) if v.is_empty() || v == "\\." || v.contains([',', '"', '\n', '\r']) => {
+            format!("\"{}\"", v.replace('"', "\"\""))
+        }
+        Some(v) => v.to_string(),
+    }
+}
+
+/// A field as `COPY ... TO` (text format, tab-delimited) writes it: NULL is
+/// `\N`, nothing is quoted, and backslashes and control characters are
+/// backslash-escaped instead.
+fn copy_text_field(value: Option<&str>) -> String {
+    let Some(v) = value else {
+        return "\\N".to_string();
+    };
+    let mut out = String::with_capacity(v.len());
+    for c in v.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\u{b}' => out.push_str("\\v"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -967,7 +2625,7 @@ fn escape_csv_field(value: &str, delimiter: u8) -> String {
 /// Validate an identifier (schema, table, or column name) to prevent SQL injection
 /// Uses strict whitelist approach: only ASCII alphanumeric, underscores, and hyphens allowed
 /// PostgreSQL allows these characters in quoted identifiers
-fn validate_identifier(name: &str) -> Result<(), String> {
+pub(crate) fn validate_identifier(name: &str) -> Result<(), String> {
     if name.is_empty() {
         return Err("Identifier cannot be empty".to_string());
     }
@@ -998,7 +2656,13 @@ pub(crate) fn escape_identifier(name: &str) -> String {
 }
 
 /// Extract a value from a row as a text string (used by all text-based export formats)
-fn extract_text_value(row: &sqlx::postgres::PgRow, index: usize, type_name: &str, null_as_empty: bool) -> String {
+fn extract_text_value(
+    row: &sqlx::postgres::PgRow,
+    index: usize,
+    type_name: &str,
+    null_as_empty: bool,
+    datetime_format: &DateTimeFormat,
+) -> String {
     let upper_type = type_name.to_uppercase();
 
     // Helper for NULL handling
@@ -1009,47 +2673,151 @@ fn extract_text_value(row: &sqlx::postgres::PgRow, index: usize, type_name: &str
         "INT2" | "SMALLINT" => {
             if let Ok(v) = row.try_get::<Option<i16>, _>(index) {
                 return match v {
-                    Some(n) => n.to_string(),
+                    Some(n) => n.to_string(),
+                    None => null_string(),
+                };
+            }
+        }
+        "INT4" | "INTEGER" | "SERIAL" => {
+            if let Ok(v) = row.try_get::<Option<i32>, _>(index) {
+                return match v {
+                    Some(n) => n.to_string(),
+                    None => null_string(),
+                };
+            }
+        }
+        "INT8" | "BIGINT" | "BIGSERIAL" => {
+            if let Ok(v) = row.try_get::<Option<i64>, _>(index) {
+                return match v {
+                    Some(n) => n.to_string(),
+                    None => null_string(),
+                };
+            }
+        }
+        "FLOAT4" | "REAL" => {
+            if let Ok(v) = row.try_get::<Option<f32>, _>(index) {
+                return match v {
+                    Some(n) => n.to_string(),
+                    None => null_string(),
+                };
+            }
+        }
+        "FLOAT8" | "DOUBLE PRECISION" => {
+            if let Ok(v) = row.try_get::<Option<f64>, _>(index) {
+                return match v {
+                    Some(n) => n.to_string(),
+                    None => null_string(),
+                };
+            }
+        }
+        "NUMERIC" | "DECIMAL" => {
+            if let Ok(v) = row.try_get::<Option<rust_decimal::Decimal>, _>(index) {
+                return match v {
+                    Some(d) => d.to_string(),
+                    None => null_string(),
+                };
+            }
+        }
+        "MONEY" => {
+            // Exports cast money through numeric up front; this is for a query
+            // the server couldn't describe. Binary money can't decode as String.
+            if let Ok(v) = row.try_get::<Option<sqlx::postgres::types::PgMoney>, _>(index) {
+                return match v {
+                    Some(m) => money_to_decimal_text(m),
+                    None => null_string(),
+                };
+            }
+        }
+        "TIMETZ" | "TIME WITH TIME ZONE" => {
+            // Like money, binary timetz can't decode as String.
+            if let Ok(v) = row.try_get::<Option<TimeTz>, _>(index) {
+                return match v {
+                    Some(t) => timetz_text(&t),
+                    None => null_string(),
+                };
+            }
+        }
+        "TIMETZ[]" | "_TIMETZ" => {
+            if let Ok(v) = row.try_get::<Option<Vec<TimeTz>>, _>(index) {
+                return match v {
+                    Some(items) => format!("{{{}}}", items.iter().map(timetz_text).collect::<Vec<_>>().join(",")),
+                    None => null_string(),
+                };
+            }
+        }
+        "BIT" | "VARBIT" | "BIT VARYING" => {
+            if let Ok(v) = row.try_get::<Option<bit_vec::BitVec>, _>(index) {
+                return match v {
+                    Some(bits) => bits_text(&bits),
+                    None => null_string(),
+                };
+            }
+        }
+        "BIT[]" | "VARBIT[]" | "_BIT" | "_VARBIT" => {
+            if let Ok(v) = row.try_get::<Option<Vec<bit_vec::BitVec>>, _>(index) {
+                return match v {
+                    Some(items) => format!("{{{}}}", items.iter().map(bits_array_element).collect::<Vec<_>>().join(",")),
+                    None => null_string(),
+                };
+            }
+        }
+        "POINT" => {
+            // Binary geometric values can't decode as String either.
+            if let Ok(v) = row.try_get::<Option<PgPoint>, _>(index) {
+                return match v {
+                    Some(p) => point_text(&p),
+                    None => null_string(),
+                };
+            }
+        }
+        "LINE" => {
+            if let Ok(v) = row.try_get::<Option<sqlx::postgres::types::PgLine>, _>(index) {
+                return match v {
+                    Some(l) => format!("{{{},{},{}}}", geo_number(l.a), geo_number(l.b), geo_number(l.c)),
                     None => null_string(),
                 };
             }
         }
-        "INT4" | "INTEGER" | "SERIAL" => {
-            if let Ok(v) = row.try_get::<Option<i32>, _>(index) {
+        "LSEG" => {
+            if let Ok(v) = row.try_get::<Option<sqlx::postgres::types::PgLSeg>, _>(index) {
                 return match v {
-                    Some(n) => n.to_string(),
+                    Some(l) => format!(
+                        "[{}]",
+                        points_text(&[PgPoint { x: l.start_x, y: l.start_y }, PgPoint { x: l.end_x, y: l.end_y }])
+                    ),
                     None => null_string(),
                 };
             }
         }
-        "INT8" | "BIGINT" | "BIGSERIAL" => {
-            if let Ok(v) = row.try_get::<Option<i64>, _>(index) {
+        "BOX" => {
+            if let Ok(v) = row.try_get::<Option<sqlx::postgres::types::PgBox>, _>(index) {
                 return match v {
-                    Some(n) => n.to_string(),
+                    Some(b) => box_text(&b),
                     None => null_string(),
                 };
             }
         }
-        "FLOAT4" | "REAL" => {
-            if let Ok(v) = row.try_get::<Option<f32>, _>(index) {
+        "PATH" => {
+            if let Ok(v) = row.try_get::<Option<sqlx::postgres::types::PgPath>, _>(index) {
                 return match v {
-                    Some(n) => n.to_string(),
+                    Some(p) if p.closed => format!("({})", points_text(&p.points)),
+                    Some(p) => format!("[{}]", points_text(&p.points)),
                     None => null_string(),
                 };
             }
         }
-        "FLOAT8" | "DOUBLE PRECISION" => {
-            if let Ok(v) = row.try_get::<Option<f64>, _>(index) {
+        "POLYGON" => {
+            if let Ok(v) = row.try_get::<Option<sqlx::postgres::types::PgPolygon>, _>(index) {
                 return match v {
-                    Some(n) => n.to_string(),
+                    Some(p) => format!("({})", points_text(&p.points)),
                     None => null_string(),
                 };
             }
         }
-        "NUMERIC" | "DECIMAL" => {
-            if let Ok(v) = row.try_get::<Option<rust_decimal::Decimal>, _>(index) {
+        "CIRCLE" => {
+            if let Ok(v) = row.try_get::<Option<sqlx::postgres::types::PgCircle>, _>(index) {
                 return match v {
-                    Some(d) => d.to_string(),
+                    Some(c) => format!("<{},{}>", point_text(&PgPoint { x: c.x, y: c.y }), geo_number(c.radius)),
                     None => null_string(),
                 };
             }
@@ -1073,7 +2841,9 @@ fn extract_text_value(row: &sqlx::postgres::PgRow, index: usize, type_name: &str
         "TIMESTAMP" | "TIMESTAMP WITHOUT TIME ZONE" => {
             if let Ok(v) = row.try_get::<Option<chrono::NaiveDateTime>, _>(index) {
                 return match v {
-                    Some(dt) => dt.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                    Some(dt) => datetime_format
+                        .timestamp(dt)
+                        .unwrap_or_else(|| dt.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
                     None => null_string(),
                 };
             }
@@ -1081,7 +2851,9 @@ fn extract_text_value(row: &sqlx::postgres::PgRow, index: usize, type_name: &str
         "TIMESTAMPTZ" | "TIMESTAMP WITH TIME ZONE" => {
             if let Ok(v) = row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>(index) {
                 return match v {
-                    Some(dt) => dt.to_rfc3339(),
+                    Some(dt) => datetime_format
+                        .timestamptz(dt.fixed_offset())
+                        .unwrap_or_else(|| dt.to_rfc3339()),
                     None => null_string(),
                 };
             }
@@ -1089,7 +2861,7 @@ fn extract_text_value(row: &sqlx::postgres::PgRow, index: usize, type_name: &str
         "DATE" => {
             if let Ok(v) = row.try_get::<Option<chrono::NaiveDate>, _>(index) {
                 return match v {
-                    Some(d) => d.to_string(),
+                    Some(d) => datetime_format.date(d).unwrap_or_else(|| d.to_string()),
                     None => null_string(),
                 };
             }
@@ -1113,18 +2885,147 @@ fn extract_text_value(row: &sqlx::postgres::PgRow, index: usize, type_name: &str
         };
     }
 
+    // Last resort for custom types that String refuses: the raw value, when it
+    // came in text format (exports cast such columns to text up front).
+    if let Ok(raw) = row.try_get_raw(index) {
+        if !raw.is_null() {
+            if let Some(text) = raw.as_bytes().ok().and_then(|bytes| raw_value_text(raw.format(), bytes)) {
+                return text.to_string();
+            }
+        }
+    }
+
     null_string()
 }
 
+/// The text of a raw column value, if it came in text format. Binary values
+/// of unknown types can't be told apart from text, so they have none.
+fn raw_value_text(format: sqlx::postgres::PgValueFormat, bytes: &[u8]) -> Option<&str> {
+    if format != sqlx::postgres::PgValueFormat::Text {
+        return None;
+    }
+    std::str::from_utf8(bytes).ok()
+}
+
+/// Render a binary money value as a plain decimal (`1234.56`). The binary
+/// format carries no locale, so this assumes the usual two fractional digits
+/// of `lc_monetary`; see `export_text_cast` for the locale-aware path.
+fn money_to_decimal_text(money: sqlx::postgres::types::PgMoney) -> String {
+    money.to_decimal(2).to_string()
+}
+
+/// Render a bit string as PostgreSQL prints it, e.g. `10110`.
+fn bits_text(bits: &bit_vec::BitVec) -> String {
+    bits.iter().map(|b| if b { '1' } else { '0' }).collect()
+}
+
+/// A bit string as an array element; an empty one is quoted, as PostgreSQL does.
+fn bits_array_element(bits: &bit_vec::BitVec) -> String {
+    if bits.is_empty() {
+        "\"\"".to_string()
+    } else {
+        bits_text(bits)
+    }
+}
+
+/// A geometric coordinate as PostgreSQL prints it: shortest round-trip
+/// digits, with `Infinity`/`NaN` spelled out. Like float8 output, exponents
+/// below -4 or from 15 up use `e` notation with a signed, two-digit exponent.
+fn geo_number(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    let scientific = format!("{:e}", value);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    if value == 0.0 || (-4..15).contains(&exponent) {
+        value.to_string()
+    } else {
+        format!("{}e{}{:02}", mantissa, if exponent < 0 { '-' } else { '+' }, exponent.abs())
+    }
+}
+
+/// Render a point as `(x,y)`.
+fn point_text(point: &PgPoint) -> String {
+    format!("({},{})", geo_number(point.x), geo_number(point.y))
+}
+
+/// Comma-separated points, without the enclosing brackets of a path or polygon.
+fn points_text(points: &[PgPoint]) -> String {
+    points.iter().map(point_text).collect::<Vec<_>>().join(",")
+}
+
+/// Render a box as `(upper right),(lower left)`.
+fn box_text(value: &sqlx::postgres::types::PgBox) -> String {
+    format!(
+        "{},{}",
+        point_text(&PgPoint { x: value.upper_right_x, y: value.upper_right_y }),
+        point_text(&PgPoint { x: value.lower_left_x, y: value.lower_left_y })
+    )
+}
+
+type TimeTz = sqlx::postgres::types::PgTimeTz<chrono::NaiveTime, chrono::FixedOffset>;
+
+/// Render a time with time zone as PostgreSQL prints it: `HH:MM:SS[.ffffff]`
+/// (trailing zeros trimmed) followed by the offset as `±HH`, with `:MM` (and
+/// `:SS`) only when non-zero.
+fn timetz_text(value: &TimeTz) -> String {
+    use chrono::Timelike;
+
+    let offset = value.offset.local_minus_utc();
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    let (hours, minutes, seconds) = (offset / 3600, offset / 60 % 60, offset % 60);
+    let mut text = value.time.format("%H:%M:%S").to_string();
+    let micros = value.time.nanosecond() / 1000;
+    if micros != 0 {
+        text.push_str(format!(".{:06}", micros).trim_end_matches('0'));
+    }
+    text.push_str(&format!("{}{:02}", sign, hours));
+    if minutes != 0 || seconds != 0 {
+        text.push_str(&format!(":{:02}", minutes));
+    }
+    if seconds != 0 {
+        text.push_str(&format!(":{:02}", seconds));
+    }
+    text
+}
+
+/// Escape text for a Markdown table cell: `|` would end the cell and a line
+/// break the row, so pipes are backslash-escaped and line breaks become `<br>`.
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+/// Cell text for CSV/TSV and Markdown exports, with SQL NULL written as `null_text`.
+fn export_cell_text(
+    row: &sqlx::postgres::PgRow,
+    index: usize,
+    type_name: &str,
+    null_text: &str,
+    datetime_format: &DateTimeFormat,
+) -> String {
+    if row.try_get_raw(index).map(|v| v.is_null()).unwrap_or(false) {
+        return null_text.to_string();
+    }
+    extract_text_value(row, index, type_name, null_text.is_empty(), datetime_format)
+}
+
 /// Convert a PgRow into a JSON object (used by JSON and JSONL export formats)
 fn row_to_json_object(
     row: &sqlx::postgres::PgRow,
     null_as_empty: bool,
+    datetime_format: &DateTimeFormat,
 ) -> serde_json::Map<String, serde_json::Value> {
     let mut obj = serde_json::Map::new();
     for (i, col) in row.columns().iter().enumerate() {
         let type_name = col.type_info().to_string();
-        let text = extract_text_value(row, i, &type_name, null_as_empty);
+        let text = extract_text_value(row, i, &type_name, null_as_empty, datetime_format);
         let val = text_to_json_value(&text, &type_name);
         obj.insert(col.name().to_string(), val);
     }
@@ -1168,9 +3069,12 @@ fn format_sql_value(text: &str, type_name: &str) -> String {
         | "FLOAT4" | "REAL" | "FLOAT8" | "DOUBLE PRECISION" | "NUMERIC" | "DECIMAL" => {
             text.to_string()
         }
-        "BOOL" | "BOOLEAN" => {
-            text.to_string()
-        }
+        "BOOL" | "BOOLEAN" => match text {
+            // Postgres' text output is t/f, which isn't valid as a bare literal.
+            "t" | "true" => "TRUE".to_string(),
+            "f" | "false" => "FALSE".to_string(),
+            _ => format!("'{}'", text.replace('\'', "''")),
+        },
         _ => {
             // Escape single quotes for SQL string literals
             format!("'{}'", text.replace('\'', "''"))
@@ -1178,7 +3082,126 @@ fn format_sql_value(text: &str, type_name: &str) -> String {
     }
 }
 
+/// Upper bound on the text produced by `results_to_insert_string`.
+const MAX_INSERT_STRING_BYTES: usize = 4 * 1024 * 1024;
+
+/// Render a fetched result set as INSERT statements for pasting into an editor.
+/// Values are quoted by column type like the SQL INSERT export; results larger than
+/// `MAX_INSERT_STRING_BYTES` are refused in favour of a file export.
+pub fn results_to_insert_string(
+    result: &QueryResult,
+    schema_name: Option<&str>,
+    table_name: &str,
+) -> Result<String, String> {
+    if table_name.trim().is_empty() {
+        return Err("Target table name is required".to_string());
+    }
+    if result.columns.is_empty() {
+        return Err("Result has no columns".to_string());
+    }
+    let target = match schema_name.filter(|s| !s.is_empty()) {
+        Some(schema) => format!("\"{}\".\"{}\"", escape_identifier(schema), escape_identifier(table_name)),
+        None => format!("\"{}\"", escape_identifier(table_name)),
+    };
+    let col_list = result.columns.iter()
+        .map(|c| format!("\"{}\"", escape_identifier(&c.name)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut out = String::new();
+    for row in &result.rows {
+        let values = result.columns.iter().enumerate()
+            .map(|(i, col)| json_cell_to_sql(result_cell(row, i, &col.name).unwrap_or(&serde_json::Value::Null), &col.data_type)
+                .ok_or_else(|| format!("Column \"{}\" holds a truncated preview and can't be copied as SQL", col.name)))
+            .collect::<Result<Vec<_>, _>>()?;
+        out.push_str(&format!("INSERT INTO {} ({}) VALUES ({});\n", target, col_list, values.join(", ")));
+        if out.len() > MAX_INSERT_STRING_BYTES {
+            return Err(format!(
+                "Result is too large to copy as INSERT statements (over {} MB); export it to a file instead",
+                MAX_INSERT_STRING_BYTES / (1024 * 1024)
+            ));
+        }
+    }
+    Ok(out)
+}
+
+/// Inline styles for `results_to_html`; email clients drop `<style>` blocks.
+const HTML_TABLE_STYLE: &str = "border-collapse:collapse;font-family:-apple-system,Helvetica,Arial,sans-serif;font-size:13px";
+const HTML_HEADER_STYLE: &str = "background:#e5e7eb;text-align:left;padding:6px 10px;border-bottom:2px solid #9ca3af";
+const HTML_CELL_STYLE: &str = "padding:4px 10px;border-bottom:1px solid #e5e7eb";
+const HTML_STRIPE_BACKGROUND: &str = "#f9fafb";
+const HTML_NULL_STYLE: &str = "color:#9ca3af;font-style:italic";
+
+/// Render a fetched result set as a standalone HTML table for pasting into an
+/// email: inline styles only, zebra-striped rows, and NULL shown muted.
+pub fn results_to_html(result: &QueryResult) -> String {
+    let mut out = String::from("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"></head><body>\n");
+    out.push_str(&format!("<table style=\"{}\">\n<thead><tr>", HTML_TABLE_STYLE));
+    for col in &result.columns {
+        out.push_str(&format!("<th style=\"{}\">{}</th>", HTML_HEADER_STYLE, escape_html(&col.name)));
+    }
+    out.push_str("</tr></thead>\n<tbody>\n");
+    for (row_index, row) in result.rows.iter().enumerate() {
+        if row_index % 2 == 1 {
+            out.push_str(&format!("<tr style=\"background:{}\">", HTML_STRIPE_BACKGROUND));
+        } else {
+            out.push_str("<tr>");
+        }
+        for (i, col) in result.columns.iter().enumerate() {
+            let cell = match result_cell(row, i, &col.name).unwrap_or(&serde_json::Value::Null) {
+                serde_json::Value::Null => format!("<span style=\"{}\">NULL</span>", HTML_NULL_STYLE),
+                serde_json::Value::String(text) => escape_html(text),
+                other => escape_html(&other.to_string()),
+            };
+            out.push_str(&format!("<td style=\"{}\">{}</td>", HTML_CELL_STYLE, cell));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</tbody>\n</table>\n</body></html>\n");
+    out
+}
+
+/// Escape text for HTML element content and attribute values.
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Export a fetched result set as an HTML table. With `file_path` the page is
+/// written there and None is returned; otherwise the HTML comes back for the clipboard.
+pub async fn export_html(result: &QueryResult, file_path: Option<String>) -> Result<Option<String>, String> {
+    let html = results_to_html(result);
+    match file_path {
+        Some(path) => write_text_export(path, html).await.map(|_| None),
+        None => Ok(Some(html)),
+    }
+}
+
+/// Convert a result-grid cell back to a SQL literal. Returns None for
+/// abbreviated values (high-dimensional vector previews) that lost data.
+fn json_cell_to_sql(value: &serde_json::Value, type_name: &str) -> Option<String> {
+    match value {
+        serde_json::Value::Null => Some("NULL".to_string()),
+        serde_json::Value::String(text) => Some(format_sql_value(text, type_name)),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Array(_) => Some(format_sql_value(&value.to_string(), type_name)),
+        serde_json::Value::Object(_) => None,
+    }
+}
+
 /// Write a typed cell value from a PgRow to an XLSX worksheet
+#[allow(clippy::too_many_arguments)]
 fn write_xlsx_cell(
     worksheet: &mut rust_xlsxwriter::Worksheet,
     row: u32,
@@ -1187,6 +3210,7 @@ fn write_xlsx_cell(
     index: usize,
     type_name: &str,
     null_as_empty: bool,
+    datetime_format: &DateTimeFormat,
 ) -> Result<(), String> {
     let upper = type_name.to_uppercase();
 
@@ -1242,7 +3266,7 @@ fn write_xlsx_cell(
     }
 
     // Fallback: write as text
-    let text = extract_text_value(pg_row, index, type_name, null_as_empty);
+    let text = extract_text_value(pg_row, index, type_name, null_as_empty, datetime_format);
     if text == "NULL" && !null_as_empty {
         // Leave cell empty for NULL values in XLSX
         return Ok(());
@@ -1282,3 +3306,844 @@ fn write_xlsx_json_cell(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{state_with_pool, test_pool, ScratchDatabase};
+
+    #[test]
+    fn count_divergence_is_relative_to_exact_count() {
+        assert_eq!(count_divergence_percent(1000, 1000), 0.0);
+        assert_eq!(count_divergence_percent(500, 1000), 50.0);
+        assert_eq!(count_divergence_percent(3000, 1000), 200.0);
+        assert_eq!(count_divergence_percent(0, 0), 0.0);
+        assert_eq!(count_divergence_percent(5, 0), 500.0);
+    }
+
+    #[test]
+    fn alphabetical_order_is_case_insensitive_and_stable() {
+        let names = vec!["b".to_string(), "A".to_string(), "a".to_string(), "C".to_string()];
+        assert_eq!(alphabetical_columns(names), vec!["A", "a", "b", "C"]);
+    }
+
+    #[test]
+    fn csv_empty_field_is_null_without_marker() {
+        assert_eq!(csv_field_to_param("", None), None);
+        assert_eq!(csv_field_to_param("NULL", None), Some("NULL"));
+        assert_eq!(csv_field_to_param("x", None), Some("x"));
+    }
+
+    #[test]
+    fn csv_null_marker_keeps_empty_strings_distinct() {
+        assert_eq!(csv_field_to_param("NULL", Some("NULL")), None);
+        assert_eq!(csv_field_to_param("", Some("NULL")), Some(""));
+        assert_eq!(csv_field_to_param("null", Some("NULL")), Some("null"));
+    }
+
+    fn import_options(trim_whitespace: bool, trimmed_empty_as_null: bool, null_marker: Option<&str>) -> ImportCsvOptions {
+        ImportCsvOptions {
+            schema_name: "public".to_string(),
+            table_name: "t".to_string(),
+            file_path: "/tmp/t.csv".to_string(),
+            has_headers: true,
+            null_marker: null_marker.map(|m| m.to_string()),
+            trim_whitespace,
+            trimmed_empty_as_null,
+            bytea_encoding: ByteaEncoding::Text,
+            per_row_insert: false,
+        }
+    }
+
+    #[test]
+    fn untrimmed_fields_pass_through() {
+        let options = import_options(false, true, None);
+        assert_eq!(prepare_csv_field(" 42 ", &options), Some(" 42 "));
+        assert_eq!(prepare_csv_field("  ", &options), Some("  "));
+        assert_eq!(prepare_csv_field("", &options), None);
+    }
+
+    #[test]
+    fn trimming_strips_padding_and_respects_null_marker() {
+        let options = import_options(true, false, Some("NULL"));
+        assert_eq!(prepare_csv_field(" 42 ", &options), Some("42"));
+        assert_eq!(prepare_csv_field(" NULL ", &options), None);
+        assert_eq!(prepare_csv_field("   ", &options), Some(""));
+        assert_eq!(prepare_csv_field("", &options), Some(""));
+    }
+
+    #[test]
+    fn whitespace_only_fields_can_import_as_null() {
+        let options = import_options(true, true, Some("NULL"));
+        assert_eq!(prepare_csv_field("   ", &options), None);
+        assert_eq!(prepare_csv_field("", &options), Some(""));
+    }
+
+    #[test]
+    fn copy_sql_mirrors_per_row_null_rules() {
+        let options = import_options(false, false, None);
+        assert_eq!(
+            csv_copy_sql(&options, "\"id\", \"name\""),
+            "COPY \"public\".\"t\" (\"id\", \"name\") FROM STDIN WITH \
+             (FORMAT csv, HEADER true, FORCE_NULL (\"id\", \"name\"))"
+        );
+        let options = ImportCsvOptions { has_headers: false, ..import_options(false, false, Some("it's null")) };
+        assert_eq!(
+            csv_copy_sql(&options, "\"id\""),
+            "COPY \"public\".\"t\" (\"id\") FROM STDIN WITH (FORMAT csv, NULL 'it''s null', FORCE_NULL (\"id\"))"
+        );
+    }
+
+    #[test]
+    fn copy_is_skipped_when_fields_need_preprocessing() {
+        let plain = import_options(false, false, None);
+        assert!(copy_import_supported(&plain, false, true));
+        assert!(!copy_import_supported(&plain, true, false));
+        assert!(!copy_import_supported(&import_options(true, false, None), false, false));
+        let hex = ImportCsvOptions { bytea_encoding: ByteaEncoding::Hex, ..plain };
+        assert!(!copy_import_supported(&hex, false, true));
+        assert!(copy_import_supported(&hex, false, false));
+    }
+
+    /// Imports 10k rows, with quoted newlines and empty strings, through both
+    /// the COPY and the per-row path and checks they store the same data.
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn import_csv_copy_matches_per_row_for_10k_rows() {
+        let pool = test_pool(2).await;
+        let state = state_with_pool("bench", pool.clone());
+        let schema = format!("pharos_import_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.t (id int, name text, note text, created date);"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let file_path = std::env::temp_dir().join(format!("{}.csv", schema));
+        let mut csv = String::from("id,name,note,created\n");
+        for i in 0..10_000 {
+            let note = match i % 3 {
+                0 => String::new(),
+                1 => "\"\"".to_string(),
+                _ => format!("\"line {}\nwith, comma\"", i),
+            };
+            csv.push_str(&format!("{},name {},{},2024-01-{:02}\n", i, i, note, i % 28 + 1));
+        }
+        std::fs::write(&file_path, csv).unwrap();
+
+        let mut counts = Vec::new();
+        for per_row_insert in [false, true] {
+            sqlx::raw_sql(&format!("TRUNCATE {schema}.t")).execute(&pool).await.unwrap();
+            let options = ImportCsvOptions {
+                schema_name: schema.clone(),
+                table_name: "t".to_string(),
+                file_path: file_path.to_string_lossy().into_owned(),
+                per_row_insert,
+                ..import_options(false, false, None)
+            };
+            let result = import_csv("bench".to_string(), options, &state).await.unwrap();
+            assert_eq!(result.rows_imported, 10_000);
+            let row = sqlx::query(&format!(
+                "SELECT count(*), count(note), count(*) FILTER (WHERE note LIKE '%\n%') FROM {schema}.t"
+            ))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            counts.push((row.get::<i64, _>(0), row.get::<i64, _>(1), row.get::<i64, _>(2)));
+        }
+
+        std::fs::remove_file(&file_path).ok();
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+        assert_eq!(counts[0], counts[1]);
+        assert_eq!(counts[0], (10_000, 3_333, 3_333));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn import_csv_copy_skips_a_byte_order_mark() {
+        let pool = test_pool(1).await;
+        let state = state_with_pool("bom", pool.clone());
+        let schema = format!("pharos_bom_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!("CREATE SCHEMA {schema}; CREATE TABLE {schema}.t (id int, name text);"))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let file_path = std::env::temp_dir().join(format!("{}.csv", schema));
+        std::fs::write(&file_path, "\u{feff}1,a\n2,b\n").unwrap();
+
+        let options = ImportCsvOptions {
+            schema_name: schema.clone(),
+            table_name: "t".to_string(),
+            file_path: file_path.to_string_lossy().into_owned(),
+            has_headers: false,
+            ..import_options(false, false, None)
+        };
+        let result = import_csv("bom".to_string(), options, &state).await;
+        let sum = sqlx::query(&format!("SELECT sum(id)::int FROM {schema}.t")).fetch_one(&pool).await;
+        std::fs::remove_file(&file_path).ok();
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        assert_eq!(result.unwrap().rows_imported, 2);
+        assert_eq!(sum.unwrap().get::<i32, _>(0), 3);
+    }
+
+    fn type_change(new_type: &str, using_expr: Option<&str>) -> AlterColumnTypeOptions {
+        AlterColumnTypeOptions {
+            schema_name: "public".to_string(),
+            table_name: "orders".to_string(),
+            column_name: "total".to_string(),
+            new_type: new_type.to_string(),
+            using_expr: using_expr.map(|e| e.to_string()),
+        }
+    }
+
+    #[test]
+    fn column_types_are_whitelisted_and_rerendered() {
+        assert_eq!(normalize_column_type(" NUMERIC( 12 ,2 ) ").unwrap(), "numeric(12, 2)");
+        assert_eq!(normalize_column_type("Double   Precision").unwrap(), "double precision");
+        assert_eq!(normalize_column_type("varchar(64)[] []").unwrap(), "varchar(64)[][]");
+        assert!(normalize_column_type("my_enum").is_err());
+        assert!(normalize_column_type("int; drop table t").is_err());
+        assert!(normalize_column_type("numeric(12, x)").is_err());
+        assert!(normalize_column_type("numeric(12").is_err());
+        assert_eq!(
+            normalize_column_type("Timestamp (3)  With Time Zone").unwrap(),
+            "timestamp(3) with time zone"
+        );
+        assert_eq!(normalize_column_type("time(0) without time zone[]").unwrap(), "time(0) without time zone[]");
+        assert!(normalize_column_type("timestamp(3) with time zone; drop table t").is_err());
+        assert!(normalize_column_type("numeric(12) (2)").is_err());
+    }
+
+    #[test]
+    fn alter_column_type_sql_appends_single_using_expression() {
+        assert_eq!(
+            alter_column_type_sql(&type_change("integer", None)).unwrap(),
+            "ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"total\" TYPE integer"
+        );
+        assert_eq!(
+            alter_column_type_sql(&type_change("integer", Some("  "))).unwrap(),
+            "ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"total\" TYPE integer"
+        );
+        assert_eq!(
+            alter_column_type_sql(&type_change("numeric(10,2)", Some("replace(total, ';', '')::numeric; -- strip"))).unwrap(),
+            "ALTER TABLE \"public\".\"orders\" ALTER COLUMN \"total\" TYPE numeric(10, 2) \
+             USING replace(total, ';', '')::numeric"
+        );
+        assert!(alter_column_type_sql(&type_change("integer", Some("total::int; drop table orders"))).is_err());
+        assert!(alter_column_type_sql(&type_change("integer", Some("total::integer, DROP COLUMN important"))).is_err());
+        assert!(alter_column_type_sql(&type_change("integer", Some("(total::integer), ALTER COLUMN x DROP NOT NULL"))).is_err());
+        assert!(alter_column_type_sql(&type_change("integer", Some("total::integer) , (1"))).is_err());
+        assert!(alter_column_type_sql(&type_change("integer", Some("coalesce(total, '0')::integer"))).is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn preview_alter_column_type_reports_cast_errors_without_committing() {
+        let pool = test_pool(1).await;
+        let state = state_with_pool("alter", pool.clone());
+        let schema = format!("pharos_alter_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.orders (total text); \
+             INSERT INTO {schema}.orders VALUES ('12'), ('$3');"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        let change = |using_expr: Option<&str>| AlterColumnTypeOptions {
+            schema_name: schema.clone(),
+            ..type_change("integer", using_expr)
+        };
+
+        // No implicit text -> integer cast: PostgreSQL's hint suggests USING
+        let preview = preview_alter_column_type("alter".to_string(), change(None), &state).await.unwrap();
+        let error = preview.error.unwrap();
+        assert!(!preview.success);
+        assert!(error.starts_with("column \"total\" cannot be cast automatically"), "{}", error);
+        assert!(error.contains("USING"), "{}", error);
+
+        let preview = preview_alter_column_type("alter".to_string(), change(Some("total::integer")), &state).await.unwrap();
+        assert!(preview.error.unwrap().starts_with("invalid input syntax for type integer"));
+
+        let using = Some("replace(total, '$', '')::integer");
+        let preview = preview_alter_column_type("alter".to_string(), change(using), &state).await.unwrap();
+        assert!(preview.success, "{:?}", preview.error);
+        let type_sql = format!(
+            "SELECT data_type::text FROM information_schema.columns WHERE table_schema = '{schema}' AND table_name = 'orders'"
+        );
+        let data_type: String = sqlx::query_scalar(&type_sql).fetch_one(&pool).await.unwrap();
+        assert_eq!(data_type, "text");
+
+        alter_column_type("alter".to_string(), change(using), &state).await.unwrap();
+        let data_type: String = sqlx::query_scalar(&type_sql).fetch_one(&pool).await.unwrap();
+        assert_eq!(data_type, "integer");
+
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+    }
+
+    #[test]
+    fn markdown_cells_escape_pipes_and_line_breaks() {
+        assert_eq!(markdown_cell("a|b"), "a\\|b");
+        assert_eq!(markdown_cell("one\ntwo\r\nthree\rfour"), "one<br>two<br>three<br>four");
+        assert_eq!(markdown_cell("plain"), "plain");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn export_markdown_writes_escaped_table() {
+        let pool = test_pool(1).await;
+        let state = state_with_pool("md", pool);
+        let file_path = std::env::temp_dir().join(format!("pharos_md_{}.md", uuid::Uuid::new_v4().simple()));
+        let sql = "SELECT 'a|b' AS \"x|y\", NULL::int AS n, E'l1\\nl2' AS t UNION ALL SELECT 'c', 2, ''";
+
+        for (null_as_empty, null_cell) in [(true, ""), (false, "NULL")] {
+            let path = file_path.to_string_lossy().into_owned();
+            let result = export_markdown("md".to_string(), sql.to_string(), None, path, null_as_empty, &state)
+                .await
+                .unwrap();
+            assert_eq!(result.rows_exported, 2);
+            assert_eq!(
+                std::fs::read_to_string(&file_path).unwrap(),
+                format!("| x\\|y | n | t |\n| --- | --- | --- |\n| a\\|b | {} | l1<br>l2 |\n| c | 2 |  |\n", null_cell)
+            );
+        }
+        std::fs::remove_file(&file_path).ok();
+    }
+
+    #[test]
+    fn cross_connection_ddl_drops_what_cannot_carry_over() {
+        use crate::commands::ddl::{DdlColumn, DdlConstraint};
+        let column = |name: &str, type_str: &str, default_expr: Option<&str>| DdlColumn {
+            name: name.to_string(),
+            type_str: type_str.to_string(),
+            not_null: false,
+            default_expr: default_expr.map(|d| d.to_string()),
+            identity: String::new(),
+            generated: String::new(),
+            comment: Some("note".to_string()),
+        };
+        let constraint = |name: &str, definition: &str| DdlConstraint {
+            name: name.to_string(),
+            definition: definition.to_string(),
+            comment: None,
+        };
+        let parts = TableDdlParts {
+            columns: vec![
+                column("id", "integer", Some("nextval('t_id_seq'::regclass)")),
+                column("mood", "mood", Some("'ok'::mood")),
+                column("created", "date", Some("CURRENT_DATE")),
+            ],
+            constraints: vec![
+                constraint("t_pkey", "PRIMARY KEY (id)"),
+                constraint("t_owner_fkey", "FOREIGN KEY (id) REFERENCES owners(id)"),
+                constraint("t_mood_check", "CHECK ((mood <> 'sad'::mood))"),
+            ],
+            index_defs: vec!["CREATE INDEX t_created ON public.t USING btree (created)".to_string()],
+            partition_by: Some("RANGE (created)".to_string()),
+            comment: Some("table".to_string()),
+        };
+
+        let (parts, columns_as_text) = cross_connection_ddl_parts(parts, &["mood".to_string()]);
+        assert_eq!(columns_as_text, vec!["mood"]);
+        let columns: Vec<(&str, Option<&str>)> = parts
+            .columns
+            .iter()
+            .map(|c| (c.type_str.as_str(), c.default_expr.as_deref()))
+            .collect();
+        assert_eq!(columns, vec![("integer", None), ("text", None), ("date", Some("CURRENT_DATE"))]);
+        assert!(parts.columns.iter().all(|c| c.comment.is_none()));
+        let constraints: Vec<&str> = parts.constraints.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(constraints, vec!["t_pkey"]);
+        assert!(parts.index_defs.is_empty());
+        assert_eq!(parts.partition_by, None);
+        assert_eq!(parts.comment, None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn copy_table_across_connections_to_another_database() {
+        let source_db = ScratchDatabase::create().await;
+        let target_db = ScratchDatabase::create().await;
+        let source = source_db.pool(1).await;
+        let target = target_db.pool(1).await;
+        sqlx::raw_sql(
+            "CREATE SCHEMA app; \
+             CREATE TYPE app.mood AS ENUM ('ok', 'sad'); \
+             CREATE TABLE app.owners (id int PRIMARY KEY); \
+             CREATE TABLE app.src ( \
+                 id int GENERATED ALWAYS AS IDENTITY PRIMARY KEY, \
+                 code serial, \
+                 owner_id int REFERENCES app.owners, \
+                 mood app.mood DEFAULT 'ok', \
+                 label text, \
+                 label_len int GENERATED ALWAYS AS (length(label)) STORED); \
+             INSERT INTO app.src (mood, label) \
+             SELECT CASE WHEN g % 2 = 0 THEN 'ok' ELSE 'sad' END::app.mood, 'row ' || g \
+             FROM generate_series(1, 500) g;",
+        )
+        .execute(&source)
+        .await
+        .unwrap();
+
+        let state = state_with_pool("source", source);
+        state.add_pool("target".to_string(), target.clone(), None);
+        let options = CloneTableOptions {
+            source_schema: "app".to_string(),
+            source_table: "src".to_string(),
+            target_schema: "public".to_string(),
+            target_table: "copied".to_string(),
+            include_data: true,
+        };
+        let result = copy_table_across_connections("source".to_string(), "target".to_string(), options, &state)
+            .await
+            .unwrap();
+        assert_eq!(result.rows_copied, Some(500));
+        assert_eq!(result.columns_as_text, vec!["mood"]);
+        let row = sqlx::query(
+            "INSERT INTO public.copied (code, label) VALUES (0, 'new') RETURNING id, \
+             (SELECT count(*) FROM public.copied WHERE mood = 'sad'), \
+             (SELECT sum(label_len) FROM public.copied)",
+        )
+        .fetch_one(&target)
+        .await
+        .unwrap();
+        assert_eq!(row.get::<i32, _>(0), 501);
+        assert_eq!(row.get::<i64, _>(1), 250);
+        assert!(row.get::<i64, _>(2) > 0);
+    }
+
+    #[test]
+    fn bytea_fields_decode_from_hex_and_base64() {
+        assert_eq!(decode_bytea_field("\\xDEADbeef", ByteaEncoding::Hex), Ok(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(decode_bytea_field("00ff", ByteaEncoding::Hex), Ok(vec![0x00, 0xff]));
+        assert_eq!(decode_bytea_field("3q2+7w==", ByteaEncoding::Base64), Ok(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(decode_bytea_field("3q2+7w", ByteaEncoding::Base64), Ok(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(decode_bytea_field("", ByteaEncoding::Base64), Ok(vec![]));
+    }
+
+    #[test]
+    fn malformed_bytea_fields_are_rejected() {
+        assert!(decode_bytea_field("abc", ByteaEncoding::Hex).is_err());
+        assert!(decode_bytea_field("zz", ByteaEncoding::Hex).is_err());
+        assert!(decode_bytea_field("not base64!", ByteaEncoding::Base64).is_err());
+    }
+
+    #[test]
+    fn checksum_orders_by_primary_key() {
+        let sql = checksum_sql("public", "order\"s", &["id".to_string(), "line".to_string()]);
+        assert_eq!(
+            sql,
+            "SELECT md5(COALESCE(string_agg(pharos_row::text, E'\\n' ORDER BY \
+             pharos_row.\"id\"::text COLLATE \"C\", pharos_row.\"line\"::text COLLATE \"C\"), '')), \
+             count(*)::int8 FROM \"public\".\"order\"\"s\" pharos_row"
+        );
+    }
+
+    #[test]
+    fn checksum_without_primary_key_orders_by_whole_row() {
+        let sql = checksum_sql("public", "log", &[]);
+        assert!(sql.contains("ORDER BY pharos_row::text COLLATE \"C\")"));
+    }
+
+    #[test]
+    fn no_selection_exports_all_columns_in_order() {
+        assert_eq!(resolve_export_columns(3, None), Ok(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn selection_reorders_and_subsets() {
+        assert_eq!(resolve_export_columns(4, Some(&[3, 0])), Ok(vec![3, 0]));
+    }
+
+    #[test]
+    fn out_of_range_selection_is_rejected() {
+        assert!(resolve_export_columns(2, Some(&[0, 2])).is_err());
+        assert!(resolve_export_columns(2, Some(&[])).is_err());
+    }
+
+    #[test]
+    fn insert_statements_batch_tuples() {
+        let target = "\"public\".\"t\"";
+        assert_eq!(
+            render_insert_statement(target, "\"id\"", &["(1)".to_string()]),
+            "INSERT INTO \"public\".\"t\" (\"id\") VALUES (1);"
+        );
+        assert_eq!(
+            render_insert_statement(target, "\"id\", \"s\"", &["(1, 'it''s')".to_string(), "(2, NULL)".to_string()]),
+            "INSERT INTO \"public\".\"t\" (\"id\", \"s\") VALUES\n    (1, 'it''s'),\n    (2, NULL);"
+        );
+    }
+
+    #[test]
+    fn results_json_keeps_grid_values_and_selection() {
+        let columns = vec![
+            ExportResultsColumn { name: "id".into(), data_type: "INT4".into() },
+            ExportResultsColumn { name: "tags".into(), data_type: "JSONB".into() },
+        ];
+        let rows = vec![serde_json::json!(["1", null]), serde_json::json!({"id": "2", "tags": "[\"a\"]"})];
+        let mut out = Vec::new();
+        write_results_json(&mut out, &columns, &[1, 0], &rows, false).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[\n{\"tags\":null,\"id\":\"1\"},\n{\"tags\":\"[\\\"a\\\"]\",\"id\":\"2\"}\n]\n"
+        );
+
+        let mut out = Vec::new();
+        write_results_json(&mut out, &columns, &[0], &rows[..1], true).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "[\n{\n  \"id\": \"1\"\n}\n]\n");
+
+        let mut out = Vec::new();
+        write_results_json(&mut out, &columns, &[0], &[], true).unwrap();
+        assert_eq!(out, b"[]\n");
+    }
+
+    #[test]
+    fn cells_resolve_from_array_and_object_rows() {
+        let array_row = serde_json::json!(["1", "x"]);
+        let object_row = serde_json::json!({"id": "1", "name": "x"});
+        assert_eq!(result_cell(&array_row, 1, "name"), Some(&serde_json::json!("x")));
+        assert_eq!(result_cell(&object_row, 1, "name"), Some(&serde_json::json!("x")));
+        assert_eq!(result_cell(&array_row, 5, "missing"), None);
+    }
+
+    #[test]
+    fn results_render_as_typed_insert_statements() {
+        let result: QueryResult = serde_json::from_value(serde_json::json!({
+            "columns": [
+                {"name": "id", "data_type": "INT4"},
+                {"name": "note", "data_type": "TEXT"},
+                {"name": "done", "data_type": "BOOL"},
+            ],
+            "rows": [["1", "it's", "t"], ["2", null, "f"]],
+            "row_count": 2,
+            "execution_time_ms": 0,
+            "has_more": false,
+            "history_entry_id": null,
+        }))
+        .unwrap();
+        let sql = results_to_insert_string(&result, Some("public"), "to\"do").unwrap();
+        assert_eq!(
+            sql,
+            "INSERT INTO \"public\".\"to\"\"do\" (\"id\", \"note\", \"done\") VALUES (1, 'it''s', TRUE);\n\
+             INSERT INTO \"public\".\"to\"\"do\" (\"id\", \"note\", \"done\") VALUES (2, NULL, FALSE);\n"
+        );
+    }
+
+    #[test]
+    fn copy_fields_match_copy_to_output() {
+        assert_eq!(copy_csv_field(None), "");
+        assert_eq!(copy_csv_field(Some("")), "\"\"");
+        assert_eq!(copy_csv_field(Some("a,\"b\"")), "\"a,\"\"b\"\"\"");
+        assert_eq!(copy_csv_field(Some("\\.")), "\"\\.\"");
+        assert_eq!(copy_csv_field(Some("plain")), "plain");
+
+        assert_eq!(copy_text_field(None), "\\N");
+        assert_eq!(copy_text_field(Some("")), "");
+        assert_eq!(copy_text_field(Some("a\tb\nc\\d")), "a\\tb\\nc\\\\d");
+    }
+
+    #[test]
+    fn storage_params_are_whitelisted_and_re_rendered() {
+        assert_eq!(storage_param_assignment("FillFactor", " 70 ").unwrap(), "fillfactor = 70");
+        assert_eq!(
+            storage_param_assignment("autovacuum_vacuum_scale_factor", "0.05").unwrap(),
+            "autovacuum_vacuum_scale_factor = 0.05"
+        );
+        assert_eq!(storage_param_assignment("autovacuum_enabled", "off").unwrap(), "autovacuum_enabled = false");
+        assert!(storage_param_assignment("fillfactor", "70); DROP TABLE t; --").is_err());
+        assert!(storage_param_assignment("autovacuum_vacuum_scale_factor", "NaN").is_err());
+        assert!(storage_param_assignment("oids", "true").is_err());
+    }
+
+    #[test]
+    fn money_renders_as_plain_decimal() {
+        use sqlx::postgres::types::PgMoney;
+        assert_eq!(money_to_decimal_text(PgMoney(123456)), "1234.56");
+        assert_eq!(money_to_decimal_text(PgMoney(-5)), "-0.05");
+        assert_eq!(money_to_decimal_text(PgMoney(0)), "0.00");
+    }
+
+    #[test]
+    fn timetz_renders_like_postgres() {
+        let at = |h, m, s, micro, offset_secs| TimeTz {
+            time: chrono::NaiveTime::from_hms_micro_opt(h, m, s, micro).unwrap(),
+            offset: chrono::FixedOffset::east_opt(offset_secs).unwrap(),
+        };
+        assert_eq!(timetz_text(&at(10, 30, 0, 0, 2 * 3600)), "10:30:00+02");
+        assert_eq!(timetz_text(&at(23, 59, 59, 500_000, -(3 * 3600 + 30 * 60))), "23:59:59.5-03:30");
+        assert_eq!(timetz_text(&at(0, 0, 0, 0, 0)), "00:00:00+00");
+    }
+
+    #[test]
+    fn bit_strings_render_as_zeros_and_ones() {
+        // bit(5) holding B'10110'
+        let mut fixed = bit_vec::BitVec::from_elem(5, false);
+        for i in [0, 2, 3] {
+            fixed.set(i, true);
+        }
+        assert_eq!(bits_text(&fixed), "10110");
+        // varbit holding B'1' and the empty bit string
+        assert_eq!(bits_text(&bit_vec::BitVec::from_elem(1, true)), "1");
+        assert_eq!(bits_text(&bit_vec::BitVec::new()), "");
+        assert_eq!(bits_array_element(&bit_vec::BitVec::new()), "\"\"");
+    }
+
+    #[test]
+    fn geometric_values_render_as_postgres_text() {
+        assert_eq!(point_text(&PgPoint { x: 1.0, y: -2.5 }), "(1,-2.5)");
+        assert_eq!(point_text(&PgPoint { x: f64::INFINITY, y: f64::NAN }), "(Infinity,NaN)");
+        let b = sqlx::postgres::types::PgBox {
+            upper_right_x: 3.0,
+            upper_right_y: 4.0,
+            lower_left_x: 0.0,
+            lower_left_y: 0.5,
+        };
+        assert_eq!(box_text(&b), "(3,4),(0,0.5)");
+        assert_eq!(point_text(&PgPoint { x: 1e20, y: 1e-7 }), "(1e+20,1e-07)");
+        assert_eq!(point_text(&PgPoint { x: 1.5e300, y: 123456789012345.6 }), "(1.5e+300,123456789012345.6)");
+        assert_eq!(point_text(&PgPoint { x: 0.0001, y: -0.0 }), "(0.0001,-0)");
+    }
+
+    #[test]
+    fn raw_fallback_only_trusts_text_format() {
+        use sqlx::postgres::PgValueFormat;
+        assert_eq!(raw_value_text(PgValueFormat::Text, b"(1,x)"), Some("(1,x)"));
+        // A binary value may look like text (an enum label) or not (a composite
+        // starts with its field count); neither is decoded.
+        assert_eq!(raw_value_text(PgValueFormat::Binary, b"happy"), None);
+        assert_eq!(raw_value_text(PgValueFormat::Binary, &[0, 0, 0, 1, 0, 0, 0, 23]), None);
+    }
+
+    /// Export `sql` through `stream_export` and return what was written.
+    async fn export_text(pool: &sqlx::PgPool, sql: &str, format: ExportFormat) -> String {
+        let file_path = std::env::temp_dir().join(format!("pharos_export_{}", uuid::Uuid::new_v4().simple()));
+        let mut conn = pool.acquire().await.unwrap();
+        let result = stream_export(
+            &mut conn,
+            sql,
+            file_path.to_str().unwrap(),
+            &format,
+            &SqlInsertLayout::single_row("t"),
+            "",
+            true,
+            &DateTimeFormat::default(),
+            false,
+            None,
+            None,
+        )
+        .await;
+        let written = std::fs::read_to_string(&file_path);
+        let _ = std::fs::remove_file(&file_path);
+        result.unwrap();
+        written.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn export_writes_custom_types_as_their_text() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_export_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TYPE {schema}.mood AS ENUM ('happy'); \
+             CREATE TYPE {schema}.pair AS (n int, s text);"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let written = export_text(
+            &pool,
+            &format!("SELECT 1 AS id, 'happy'::{schema}.mood AS m, ROW(2, 'x')::{schema}.pair AS p, 3 AS id"),
+            ExportFormat::Csv,
+        )
+        .await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        assert_eq!(written, "id,m,p,id\n1,happy,\"(2,x)\",3\n");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn export_writes_money_in_the_locale_scale() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_money_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.prices (id int, m money, ms money[]); \
+             INSERT INTO {schema}.prices VALUES (1, 1234.56, ARRAY[1.5, -2]::money[]), (2, NULL, NULL);"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let sql = format!("SELECT * FROM {schema}.prices ORDER BY id");
+        let csv = export_text(&pool, &sql, ExportFormat::Csv).await;
+        let json = export_text(&pool, &sql, ExportFormat::Json).await;
+        let scale: i32 = sqlx::query_scalar("SELECT scale(0::money::numeric)").fetch_one(&pool).await.unwrap();
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        // The test server's lc_monetary uses two fractional digits.
+        assert_eq!(scale, 2);
+        assert_eq!(csv, "id,m,ms\n1,1234.56,\"{1.50,-2.00}\"\n2,,\n");
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows[0]["m"], "1234.56");
+        assert_eq!(rows[0]["ms"], "{1.50,-2.00}");
+    }
+
+    /// Export all of `table` as JSON and read it back through the server's own
+    /// text output, as (exported, server) objects. Expects a single row with no NULLs.
+    async fn exported_and_server_text(pool: &sqlx::PgPool, table: &str) -> (serde_json::Value, serde_json::Value) {
+        let sql = format!("SELECT * FROM {}", table);
+        let json = export_text(pool, &sql, ExportFormat::Json).await;
+        let mut exported: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        let server = sqlx::raw_sql(&sql).fetch_one(pool).await.unwrap();
+        let server_text: serde_json::Map<String, serde_json::Value> = server
+            .columns()
+            .iter()
+            .enumerate()
+            .map(|(i, column)| (column.name().to_string(), server.try_get_raw(i).unwrap().as_str().unwrap().into()))
+            .collect();
+        assert_eq!(exported.len(), 1);
+        (exported.remove(0), server_text.into())
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn export_writes_timetz_like_postgres() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_timetz_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.shifts (starts timetz, breaks timetz[]); \
+             INSERT INTO {schema}.shifts VALUES \
+                 ('12:34:56.789+05:30', ARRAY['01:02:03-08', '23:59:59.5+00', '10:00:00+05:30:15']::timetz[]);"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let table = format!("{schema}.shifts");
+        let csv = export_text(&pool, &format!("SELECT starts FROM {table}"), ExportFormat::Csv).await;
+        let (exported, server_text) = exported_and_server_text(&pool, &table).await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        assert_eq!(csv, "starts\n12:34:56.789+05:30\n");
+        assert_eq!(exported, server_text);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn export_writes_bit_strings_as_zeros_and_ones() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_bits_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.flags (fixed bit(5), varying varbit, fixeds bit(3)[], varyings varbit[]); \
+             INSERT INTO {schema}.flags VALUES (B'10110', B'1', ARRAY[B'101', B'000'], ARRAY[B'', B'1111111110']);"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let table = format!("{schema}.flags");
+        let csv = export_text(&pool, &format!("SELECT fixed, varying FROM {table}"), ExportFormat::Csv).await;
+        let (exported, server_text) = exported_and_server_text(&pool, &table).await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        assert_eq!(csv, "fixed,varying\n10110,1\n");
+        assert_eq!(exported, server_text);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn export_writes_geometric_values_like_postgres() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_geo_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.shapes (p point, l line, s lseg, b box, open_path path, closed_path path, \
+                                           poly polygon, c circle); \
+             INSERT INTO {schema}.shapes VALUES ( \
+                 point(1e20, 1e-7), line '{{1,-1,0.5}}', lseg '[(0,0),(0.1,-2.5)]', box '((1,2),(-3,4.5))', \
+                 path '[(0,0),(1,1),(2,0)]', path '((0,0),(1,1),(2,0))', polygon '((0,0),(0,1),(1.5,0))', \
+                 circle '<(0.5,-1),2.25>');"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let table = format!("{schema}.shapes");
+        let csv = export_text(&pool, &format!("SELECT p, b, c FROM {table}"), ExportFormat::Csv).await;
+        let (exported, server_text) = exported_and_server_text(&pool, &table).await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        assert_eq!(csv, "p,b,c\n\"(1e+20,1e-07)\",\"(1,4.5),(-3,2)\",\"<(0.5,-1),2.25>\"\n");
+        assert_eq!(exported, server_text);
+    }
+
+    #[test]
+    fn money_and_custom_types_are_sent_as_text() {
+        assert_eq!(export_text_cast(Some(23)), None);
+        assert_eq!(export_text_cast(Some(MONEY_OID)), Some("::numeric::text"));
+        assert_eq!(export_text_cast(Some(MONEY_ARRAY_OID)), Some("::numeric[]::text"));
+        assert_eq!(export_text_cast(Some(FIRST_USER_TYPE_OID)), Some("::text"));
+        assert_eq!(export_text_cast(None), Some("::text"));
+    }
+
+    #[test]
+    fn custom_type_columns_are_cast_to_text_by_position() {
+        let names = vec!["id".to_string(), "mood".to_string(), "id".to_string()];
+        assert_eq!(
+            with_text_columns("SELECT * FROM t", &names, &[None, Some("::text"), None]),
+            "SELECT pharos_c0 AS \"id\", pharos_c1::text AS \"mood\", pharos_c2 AS \"id\" \
+             FROM (SELECT * FROM t) AS pharos_src(pharos_c0, pharos_c1, pharos_c2)"
+        );
+    }
+
+    #[test]
+    fn results_render_as_escaped_striped_html() {
+        let result: QueryResult = serde_json::from_value(serde_json::json!({
+            "columns": [
+                {"name": "id", "data_type": "INT4"},
+                {"name": "<note>", "data_type": "TEXT"},
+            ],
+            "rows": [["1", "a & b"], ["2", null]],
+            "row_count": 2,
+            "execution_time_ms": 0,
+            "has_more": false,
+            "history_entry_id": null,
+        }))
+        .unwrap();
+        let html = results_to_html(&result);
+        assert!(html.contains(">&lt;note&gt;</th>"));
+        assert!(html.contains(">a &amp; b</td>"));
+        assert!(html.contains(&format!("<tr style=\"background:{}\">", HTML_STRIPE_BACKGROUND)));
+        assert!(html.contains(&format!("<span style=\"{}\">NULL</span>", HTML_NULL_STYLE)));
+        assert_eq!(html.matches("<tr").count(), 3);
+    }
+
+    #[test]
+    fn cell_export_selects_by_typed_primary_key() {
+        assert_eq!(
+            cell_export_sql("public", "docs", "body", false, &[("id", "integer")]),
+            "SELECT \"body\"::text FROM \"public\".\"docs\" WHERE \"id\" = $1::integer"
+        );
+        assert_eq!(
+            cell_export_sql("public", "files", "data", true, &[("tenant", "uuid"), ("name", "text")]),
+            "SELECT \"data\" FROM \"public\".\"files\" WHERE \"tenant\" = $1::uuid AND \"name\" = $2::text"
+        );
+    }
+
+    #[test]
+    fn csv_import_skips_generated_fields_of_full_rows() {
+        let generated = [false, true, false];
+        assert_eq!(csv_import_fields(&generated, 2), Some(vec![0, 1]));
+        assert_eq!(csv_import_fields(&generated, 3), Some(vec![0, 2]));
+        assert_eq!(csv_import_fields(&generated, 4), None);
+        assert_eq!(csv_import_fields(&[false, false], 2), Some(vec![0, 1]));
+    }
+}
@@ -1,3 +1,5 @@
 pub mod credentials;
+pub mod notices;
 pub mod postgres;
 pub mod sqlite;
+pub mod ssh_tunnel;
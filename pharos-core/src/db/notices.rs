@@ -0,0 +1,107 @@
+//! Capture of PostgreSQL NOTICE/WARNING messages (`RAISE NOTICE` etc.).
+//!
+//! sqlx has no API for server notices: it only logs them, under the
+//! `sqlx::postgres::notice` target, while the query future is being polled.
+//! `init_logger` installs a logger that diverts those records into a
+//! task-local buffer opened by `capture_notices`, so each command collects
+//! just the notices raised by its own statements. Everything else (and
+//! notices raised outside a capture) goes to env_logger as before.
+
+use std::cell::RefCell;
+use std::future::Future;
+
+const NOTICE_TARGET: &str = "sqlx::postgres::notice";
+
+tokio::task_local! {
+    static NOTICES: RefCell<Vec<String>>;
+}
+
+struct NoticeLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for NoticeLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target() == NOTICE_TARGET || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if record.target() == NOTICE_TARGET && record_notice(record.level(), &record.args().to_string()) {
+            return;
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Install env_logger (configured from `RUST_LOG` as usual) behind the notice
+/// capturing logger. A no-op if a logger is already set.
+pub fn init_logger() {
+    let inner = env_logger::Builder::from_default_env().build();
+    if log::set_boxed_logger(Box::new(NoticeLogger { inner })).is_ok() {
+        // sqlx logs RAISE INFO/LOG at trace level, so let every level through;
+        // env_logger still applies its own filter to everything but notices.
+        log::set_max_level(log::LevelFilter::Trace);
+    }
+}
+
+/// Append a notice to the current capture, psql-style (`NOTICE: ...`).
+/// Returns false when no capture is active on this task.
+fn record_notice(level: log::Level, message: &str) -> bool {
+    // sqlx maps server severities onto log levels; map them back.
+    let severity = match level {
+        log::Level::Error => "ERROR",
+        log::Level::Warn => "WARNING",
+        log::Level::Info => "NOTICE",
+        log::Level::Debug => "DEBUG",
+        log::Level::Trace => "INFO",
+    };
+    NOTICES
+        .try_with(|notices| notices.borrow_mut().push(format!("{}: {}", severity, message)))
+        .is_ok()
+}
+
+/// Run `future`, returning its output along with the server notices received
+/// while it ran.
+pub async fn capture_notices<F: Future>(future: F) -> (F::Output, Vec<String>) {
+    NOTICES
+        .scope(RefCell::new(Vec::new()), async move {
+            let output = future.await;
+            let notices = NOTICES.with(|notices| notices.take());
+            (output, notices)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn notices_are_collected_per_capture() {
+        let (output, notices) = capture_notices(async {
+            assert!(record_notice(log::Level::Info, "table \"t\" does not exist, skipping"));
+            assert!(record_notice(log::Level::Warn, "there is no transaction in progress"));
+            42
+        })
+        .await;
+        assert_eq!(output, 42);
+        assert_eq!(
+            notices,
+            vec![
+                "NOTICE: table \"t\" does not exist, skipping",
+                "WARNING: there is no transaction in progress",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn notices_outside_a_capture_are_not_recorded() {
+        assert!(!record_notice(log::Level::Info, "dropped"));
+        let (_, notices) = capture_notices(async {}).await;
+        assert!(notices.is_empty());
+    }
+}
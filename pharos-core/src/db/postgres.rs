@@ -1,10 +1,11 @@
-use sqlx::postgres::PgPoolOptions;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgSslMode};
 use sqlx::{Executor, PgPool, Row, ValueRef};
 use std::collections::HashSet;
 use std::time::{Duration, Instant};
 
-use crate::models::{AnalyzeResult, ColumnInfo, ConnectionConfig, ConstraintInfo, FunctionInfo, IndexInfo, PartitionRef, PartitionStrategy, SchemaColumnInfo, SchemaInfo, TableInfo, TableType};
-use crate::commands::ddl::{DdlColumn, DdlConstraint, TableDdlParts};
+use crate::models::{AnalyzeResult, BloatEstimate, BloatObjectKind, ColumnInfo, CompletionColumn, CompletionTable, ConnectionConfig, ConstraintInfo, DatabaseSetting, DefaultKind, FunctionInfo, IdleTransactionSession, IndexInfo, InheritedTable, MaintenanceKind, MaintenanceProgress, PartitionRef, PartitionStrategy, PreparedTransaction, RlsPolicyInfo, RowCountEstimate, StatementCounters, SchemaColumnInfo, SchemaInfo, SequenceInfo, ServerCapabilities, SslMode, TableInfo, TableInheritance, TablePersistence, TableRlsPolicies, TableStorageParam, TableType, TopStatement, TriggerInfo};
+use crate::db::ssh_tunnel::SshTunnel;
+use crate::commands::ddl::{DdlColumn, DdlConstraint, DdlFunction, DdlView, TableDdlParts};
 
 /// Escape a string for safe use as a SQL string literal (防 SQL injection).
 /// Replaces single quotes with doubled single quotes.
@@ -28,35 +29,98 @@ fn raw_str(row: &sqlx::postgres::PgRow, col: &str) -> Option<String> {
     }
 }
 
-/// Build a connection string with proper URL encoding and SSL mode
-fn build_connection_string(config: &ConnectionConfig) -> String {
-    // URL encode all user-provided fields to prevent parameter injection
-    let username = urlencoding::encode(&config.username);
-    let password = urlencoding::encode(&config.password);
-    let host = urlencoding::encode(&config.host);
-    let database = urlencoding::encode(&config.database);
-
-    format!(
-        "postgres://{}:{}@{}:{}/{}?sslmode={}",
-        username,
-        password,
-        host,
-        config.port,
-        database,
-        config.ssl_mode
-    )
+/// A relation's persistence from its `relpersistence` column; logged when absent.
+fn table_persistence(row: &sqlx::postgres::PgRow) -> TablePersistence {
+    raw_str(row, "persistence")
+        .and_then(|s| s.chars().next())
+        .map(TablePersistence::from_pg_char)
+        .unwrap_or_default()
+}
+
+/// Percent-encode every byte but ASCII alphanumerics, for a connection URL.
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| if b.is_ascii_alphanumeric() { (b as char).to_string() } else { format!("%{:02X}", b) })
+        .collect()
 }
 
-/// Create a PostgreSQL connection pool for the given configuration
-pub async fn create_pool(config: &ConnectionConfig) -> Result<PgPool, sqlx::Error> {
-    let connection_string = build_connection_string(config);
+/// Build connect options for a saved connection. A host starting with `/` is
+/// a Unix socket directory (e.g. `/tmp`, `/var/run/postgresql`), as in libpq:
+/// the client connects to `<dir>/.s.PGSQL.<port>`, and SSL is skipped.
+/// Without a saved password, one is looked up in `.pgpass` (or `PGPASSFILE`).
+fn build_connect_options(config: &ConnectionConfig) -> PgConnectOptions {
+    let is_socket = config.host.starts_with('/');
+    let ssl_mode = match config.ssl_mode {
+        _ if is_socket => PgSslMode::Disable,
+        SslMode::Disable => PgSslMode::Disable,
+        SslMode::Prefer => PgSslMode::Prefer,
+        SslMode::Require => PgSslMode::Require,
+    };
+
+    // sqlx only consults .pgpass when parsing a URL (matching its host, port,
+    // user and database), so start from one when there's no password.
+    let base = if config.password.is_empty() {
+        format!(
+            "postgres://?host={}&port={}&user={}&dbname={}",
+            url_encode(&config.host),
+            config.port,
+            url_encode(&config.username),
+            url_encode(&config.database)
+        )
+        .parse::<PgConnectOptions>()
+        .unwrap_or_else(|_| PgConnectOptions::new_without_pgpass())
+    } else {
+        PgConnectOptions::new_without_pgpass()
+    };
+    let mut options = base
+        .port(config.port)
+        .username(&config.username)
+        .database(&config.database)
+        .ssl_mode(ssl_mode);
+    options = if is_socket {
+        options.socket(&config.host)
+    } else {
+        options.host(&config.host)
+    };
+    // Peer/trust auth over a socket needs no password; a .pgpass one is kept.
+    if !config.password.is_empty() {
+        options = options.password(&config.password);
+    }
+    options
+}
+
+/// Connect options for `config`, opening its SSH tunnel first if it has one.
+/// The tunnel must outlive every connection made with the returned options.
+async fn connect_target(config: &ConnectionConfig) -> Result<(PgConnectOptions, Option<SshTunnel>), sqlx::Error> {
+    let Some(ssh) = config.ssh_tunnel.clone() else {
+        return Ok((build_connect_options(config), None));
+    };
+    if config.host.starts_with('/') {
+        return Err(sqlx::Error::Configuration(
+            "A Unix socket host can't be reached through an SSH tunnel; use the server's TCP address".into(),
+        ));
+    }
+    let (host, port) = (config.host.clone(), config.port);
+    let tunnel = tokio::task::spawn_blocking(move || SshTunnel::open(&ssh, &host, port))
+        .await
+        .map_err(|e| sqlx::Error::Io(std::io::Error::other(e)))??;
+    let options = build_connect_options(config)
+        .host("127.0.0.1")
+        .port(tunnel.local_port());
+    Ok((options, Some(tunnel)))
+}
 
+/// Create a PostgreSQL connection pool for the given configuration. For a
+/// tunnelled connection the tunnel is returned too; keep it as long as the pool.
+pub async fn create_pool(config: &ConnectionConfig) -> Result<(PgPool, Option<SshTunnel>), sqlx::Error> {
+    let (options, tunnel) = connect_target(config).await?;
     let pool = PgPoolOptions::new()
         .max_connections(5)
         .acquire_timeout(Duration::from_secs(10))
         .idle_timeout(Duration::from_secs(600))
         .max_lifetime(Duration::from_secs(1800))
-        .connect(&connection_string)
+        .connect_with(options)
         .await?;
 
     // Try to set a session-level idle-in-transaction guard. This is
@@ -73,21 +137,21 @@ pub async fn create_pool(config: &ConnectionConfig) -> Result<PgPool, sqlx::Erro
             .await;
     }
 
-    Ok(pool)
+    Ok((pool, tunnel))
 }
 
-/// Test a PostgreSQL connection and return latency
-pub async fn test_connection(config: &ConnectionConfig) -> Result<u64, sqlx::Error> {
-    let connection_string = build_connection_string(config);
-
+/// Test a PostgreSQL connection and return latency, plus the server version
+/// when the server reports one
+pub async fn test_connection(config: &ConnectionConfig) -> Result<(u64, Option<String>), sqlx::Error> {
     let start = Instant::now();
 
+    let (options, _tunnel) = connect_target(config).await?;
     let pool = PgPoolOptions::new()
         .max_connections(1)
         .acquire_timeout(Duration::from_secs(10))
         .idle_timeout(Duration::from_secs(600))
         .max_lifetime(Duration::from_secs(1800))
-        .connect(&connection_string)
+        .connect_with(options)
         .await?;
 
     // Use raw_sql (simple query protocol) for compatibility with
@@ -96,22 +160,30 @@ pub async fn test_connection(config: &ConnectionConfig) -> Result<u64, sqlx::Err
     sqlx::raw_sql("SELECT 1").execute(&pool).await?;
 
     let latency = start.elapsed().as_millis() as u64;
+    let version = get_server_version(&pool).await.ok().map(|(version, _)| version);
 
     // Close the test pool
     pool.close().await;
 
-    Ok(latency)
+    Ok((latency, version))
 }
 
 /// Get all schemas in the database
-pub async fn get_schemas(pool: &PgPool) -> Result<Vec<SchemaInfo>, sqlx::Error> {
+pub async fn get_schemas(pool: &PgPool, include_system: bool) -> Result<Vec<SchemaInfo>, sqlx::Error> {
+    let filter = if include_system {
+        ""
+    } else {
+        "WHERE schema_name NOT IN ('pg_catalog', 'information_schema', 'pg_toast') "
+    };
     // No parameters needed — use raw_sql for simple protocol compatibility
-    let rows = sqlx::raw_sql(
+    let sql = format!(
         "SELECT schema_name, schema_owner \
          FROM information_schema.schemata \
-         WHERE schema_name NOT IN ('pg_catalog', 'information_schema', 'pg_toast') \
+         {}\
          ORDER BY schema_name",
-    )
+        filter
+    );
+    let rows = sqlx::raw_sql(&sql)
     .fetch_all(pool)
     .await?;
 
@@ -128,6 +200,420 @@ pub async fn get_schemas(pool: &PgPool) -> Result<Vec<SchemaInfo>, sqlx::Error>
     Ok(schemas)
 }
 
+/// The server's `server_version` (e.g. "16.2") and `server_version_num`
+/// (e.g. 160002). The number is None if the server reports a non-numeric one.
+pub async fn get_server_version(pool: &PgPool) -> Result<(String, Option<i32>), sqlx::Error> {
+    let row = sqlx::raw_sql(
+        "SELECT current_setting('server_version') AS server_version, \
+                current_setting('server_version_num') AS server_version_num",
+    )
+    .fetch_one(pool)
+    .await?;
+    let version = raw_str(&row, "server_version")
+        .ok_or_else(|| sqlx::Error::Decode("server_version is missing".into()))?;
+    let version_num = raw_str(&row, "server_version_num").and_then(|v| v.parse().ok());
+    Ok((version, version_num))
+}
+
+/// Probe server version, role privileges and key extensions.
+pub async fn get_server_capabilities(pool: &PgPool) -> Result<ServerCapabilities, sqlx::Error> {
+    let row = sqlx::raw_sql(
+        "SELECT current_setting('server_version') AS server_version, \
+                current_setting('server_version_num') AS server_version_num, \
+                (SELECT rolsuper FROM pg_catalog.pg_roles WHERE rolname = current_user) AS is_superuser",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let server_version = raw_str(&row, "server_version").unwrap_or_default();
+    let server_version_num: i32 = raw_str(&row, "server_version_num")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let is_superuser = matches!(raw_str(&row, "is_superuser").as_deref(), Some("t" | "true"));
+
+    let extensions = sqlx::raw_sql(
+        "SELECT extname, extversion FROM pg_catalog.pg_extension \
+         WHERE extname IN ('postgis', 'vector', 'pg_stat_statements')",
+    )
+    .fetch_all(pool)
+    .await?;
+    let version_of = |name: &str| {
+        extensions
+            .iter()
+            .find(|r| raw_str(r, "extname").as_deref() == Some(name))
+            .and_then(|r| raw_str(r, "extversion"))
+    };
+    let pg_stat_statements_version = version_of("pg_stat_statements");
+
+    // Installed but not preloaded still errors on read, so probe the view.
+    let pg_stat_statements_available = pg_stat_statements_version.is_some()
+        && sqlx::raw_sql("SELECT 1 FROM pg_stat_statements LIMIT 1")
+            .execute(pool)
+            .await
+            .is_ok();
+
+    Ok(ServerCapabilities {
+        server_version,
+        server_version_num,
+        major_version: server_version_num / 10000,
+        is_superuser,
+        postgis_version: version_of("postgis"),
+        pgvector_version: version_of("vector"),
+        pg_stat_statements_version,
+        pg_stat_statements_available,
+        supports_tablesample: server_version_num >= 90500,
+        supports_refresh_concurrently: server_version_num >= 90400,
+    })
+}
+
+/// Most expensive statements server-wide from `pg_stat_statements`, by total
+/// execution time. `major_version` picks the column names (renamed in 13).
+pub async fn get_top_statements(
+    pool: &PgPool,
+    major_version: i32,
+    limit: i64,
+) -> Result<Vec<TopStatement>, sqlx::Error> {
+    let (total_col, mean_col) = if major_version >= 13 {
+        ("total_exec_time", "mean_exec_time")
+    } else {
+        ("total_time", "mean_time")
+    };
+    let sql = format!(
+        "SELECT s.query, d.datname AS database, r.rolname AS role, s.calls, \
+                s.{total} AS total_exec_time, s.{mean} AS mean_exec_time, s.rows \
+         FROM pg_stat_statements s \
+         LEFT JOIN pg_catalog.pg_database d ON d.oid = s.dbid \
+         LEFT JOIN pg_catalog.pg_roles r ON r.oid = s.userid \
+         ORDER BY s.{total} DESC \
+         LIMIT {limit}",
+        total = total_col,
+        mean = mean_col,
+        limit = limit.max(1),
+    );
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| TopStatement {
+            query: raw_str(row, "query").unwrap_or_default(),
+            database: raw_str(row, "database"),
+            role: raw_str(row, "role"),
+            calls: raw_str(row, "calls").and_then(|v| v.parse().ok()).unwrap_or(0),
+            total_exec_time_ms: raw_str(row, "total_exec_time").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+            mean_exec_time_ms: raw_str(row, "mean_exec_time").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+            rows: raw_str(row, "rows").and_then(|v| v.parse().ok()).unwrap_or(0),
+        })
+        .collect())
+}
+
+/// Marker comment on the snapshot query so its own entry can be filtered out.
+const PROFILE_MARKER: &str = "pharos:profile";
+
+/// The current user's `pg_stat_statements` entries in the current database,
+/// used as before/after snapshots when profiling a single query.
+pub async fn get_statement_counters(
+    conn: &mut sqlx::PgConnection,
+    major_version: i32,
+) -> Result<Vec<StatementCounters>, sqlx::Error> {
+    let (plan_col, exec_col) = if major_version >= 13 {
+        ("s.total_plan_time", "s.total_exec_time")
+    } else {
+        ("NULL", "s.total_time")
+    };
+    let sql = format!(
+        "/* {marker} */ SELECT s.queryid, s.query, s.calls, s.rows, \
+                {plan} AS plan_time, {exec} AS exec_time, \
+                s.shared_blks_hit, s.shared_blks_read, s.temp_blks_read, s.temp_blks_written \
+         FROM pg_stat_statements s \
+         WHERE s.dbid = (SELECT oid FROM pg_catalog.pg_database WHERE datname = current_database()) \
+           AND s.userid = (SELECT oid FROM pg_catalog.pg_roles WHERE rolname = current_user) \
+           AND strpos(s.query, '{marker}') = 0",
+        marker = PROFILE_MARKER,
+        plan = plan_col,
+        exec = exec_col,
+    );
+    let rows = conn.fetch_all(sqlx::raw_sql(&sql)).await?;
+
+    let int = |row: &sqlx::postgres::PgRow, col: &str| raw_str(row, col).and_then(|v| v.parse().ok()).unwrap_or(0);
+    Ok(rows
+        .iter()
+        .map(|row| StatementCounters {
+            query_id: int(row, "queryid"),
+            query: raw_str(row, "query").unwrap_or_default(),
+            calls: int(row, "calls"),
+            rows: int(row, "rows"),
+            planning_time_ms: raw_str(row, "plan_time").and_then(|v| v.parse().ok()),
+            execution_time_ms: raw_str(row, "exec_time").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+            shared_blks_hit: int(row, "shared_blks_hit"),
+            shared_blks_read: int(row, "shared_blks_read"),
+            temp_blks_read: int(row, "temp_blks_read"),
+            temp_blks_written: int(row, "temp_blks_written"),
+        })
+        .collect())
+}
+
+/// Sessions in the current database that are idle inside a transaction,
+/// longest idle first. The querying session itself is excluded.
+pub async fn get_idle_in_transaction(pool: &PgPool) -> Result<Vec<IdleTransactionSession>, sqlx::Error> {
+    let rows = sqlx::raw_sql(
+        "SELECT pid, usename, application_name, client_addr::text AS client_addr, state, \
+                xact_start::text AS xact_start, \
+                EXTRACT(EPOCH FROM now() - state_change) AS idle_seconds, query \
+         FROM pg_catalog.pg_stat_activity \
+         WHERE datname = current_database() \
+           AND state IN ('idle in transaction', 'idle in transaction (aborted)') \
+           AND pid <> pg_backend_pid() \
+         ORDER BY state_change",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            Some(IdleTransactionSession {
+                pid: raw_str(row, "pid")?.parse().ok()?,
+                username: raw_str(row, "usename"),
+                application_name: raw_str(row, "application_name").filter(|s| !s.is_empty()),
+                client_addr: raw_str(row, "client_addr"),
+                state: raw_str(row, "state").unwrap_or_default(),
+                transaction_started_at: raw_str(row, "xact_start"),
+                idle_seconds: raw_str(row, "idle_seconds").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+                query: raw_str(row, "query"),
+            })
+        })
+        .collect())
+}
+
+/// Terminate every idle-in-transaction session in the current database that
+/// has been idle for at least `min_idle_seconds`, except the querying session.
+/// Returns the pids that were signalled.
+pub async fn terminate_idle_transactions(pool: &PgPool, min_idle_seconds: u32) -> Result<Vec<i32>, sqlx::Error> {
+    let sql = format!(
+        "SELECT pid, pg_terminate_backend(pid) AS terminated FROM pg_catalog.pg_stat_activity \
+         WHERE datname = current_database() \
+           AND state IN ('idle in transaction', 'idle in transaction (aborted)') \
+           AND pid <> pg_backend_pid() \
+           AND now() - state_change >= make_interval(secs => {})",
+        min_idle_seconds
+    );
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .filter(|row| raw_str(row, "terminated").as_deref() == Some("t"))
+        .filter_map(|row| raw_str(row, "pid").and_then(|v| v.parse().ok()))
+        .collect())
+}
+
+/// In-progress VACUUM (9.6+) and CREATE INDEX/REINDEX (12+) operations,
+/// server-wide, longest running first. Views missing from the server's
+/// version are skipped; with neither available the list is empty.
+pub async fn get_running_maintenance(
+    pool: &PgPool,
+    server_version_num: i32,
+) -> Result<Vec<MaintenanceProgress>, sqlx::Error> {
+    let mut parts = Vec::new();
+    if server_version_num >= 90600 {
+        // heap_blks_vacuumed only advances in the 'vacuuming heap' phase.
+        parts.push(
+            "SELECT p.pid, 'vacuum' AS kind, 'VACUUM' AS command, p.datname, p.relid, \
+                    NULL::oid AS index_relid, p.phase, p.heap_blks_total AS blocks_total, \
+                    CASE WHEN p.phase = 'vacuuming heap' THEN p.heap_blks_vacuumed \
+                         ELSE p.heap_blks_scanned END AS blocks_done \
+             FROM pg_catalog.pg_stat_progress_vacuum p",
+        );
+    }
+    if server_version_num >= 120000 {
+        parts.push(
+            "SELECT p.pid, 'createIndex', p.command, p.datname, p.relid, \
+                    NULLIF(p.index_relid, 0), p.phase, p.blocks_total, p.blocks_done \
+             FROM pg_catalog.pg_stat_progress_create_index p",
+        );
+    }
+    if parts.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let sql = format!(
+        "SELECT m.pid, m.kind, m.command, m.datname, \
+                n.nspname || '.' || c.relname AS relation, i.relname AS index_name, \
+                m.phase, m.blocks_total, m.blocks_done, \
+                a.query LIKE 'autovacuum:%' AS is_autovacuum, \
+                COALESCE(a.query_start, a.xact_start)::text AS started_at, \
+                EXTRACT(EPOCH FROM now() - COALESCE(a.query_start, a.xact_start)) AS elapsed_seconds \
+         FROM ({}) m \
+         LEFT JOIN pg_catalog.pg_stat_activity a ON a.pid = m.pid \
+         LEFT JOIN pg_catalog.pg_class c ON c.oid = m.relid \
+         LEFT JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+         LEFT JOIN pg_catalog.pg_class i ON i.oid = m.index_relid \
+         ORDER BY elapsed_seconds DESC NULLS LAST, m.pid",
+        parts.join(" UNION ALL ")
+    );
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+
+    let int = |row: &sqlx::postgres::PgRow, col: &str| raw_str(row, col).and_then(|v| v.parse().ok()).unwrap_or(0);
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let blocks_total: i64 = int(row, "blocks_total");
+            let blocks_done: i64 = int(row, "blocks_done");
+            Some(MaintenanceProgress {
+                pid: raw_str(row, "pid")?.parse().ok()?,
+                kind: match raw_str(row, "kind")?.as_str() {
+                    "vacuum" => MaintenanceKind::Vacuum,
+                    _ => MaintenanceKind::CreateIndex,
+                },
+                command: raw_str(row, "command").unwrap_or_default(),
+                is_autovacuum: raw_str(row, "is_autovacuum").as_deref() == Some("t"),
+                database: raw_str(row, "datname"),
+                relation: raw_str(row, "relation"),
+                index_name: raw_str(row, "index_name"),
+                phase: raw_str(row, "phase").unwrap_or_default(),
+                blocks_total,
+                blocks_done,
+                percent_complete: maintenance_percent(blocks_done, blocks_total),
+                started_at: raw_str(row, "started_at"),
+                elapsed_seconds: raw_str(row, "elapsed_seconds").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+            })
+        })
+        .collect())
+}
+
+fn maintenance_percent(done: i64, total: i64) -> Option<f64> {
+    (total > 0).then(|| (done.min(total) as f64 * 100.0 / total as f64 * 10.0).round() / 10.0)
+}
+
+/// Cancel a backend only if it is currently running a VACUUM or CREATE INDEX,
+/// so a stale pid can't cancel an unrelated query. Returns false if `pid` is
+/// not a maintenance operation.
+pub async fn cancel_maintenance(pool: &PgPool, server_version_num: i32, pid: i32) -> Result<bool, sqlx::Error> {
+    let running = get_running_maintenance(pool, server_version_num).await?;
+    if !running.iter().any(|m| m.pid == pid) {
+        return Ok(false);
+    }
+    let row = sqlx::raw_sql(&format!("SELECT pg_cancel_backend({}) AS cancelled", pid))
+        .fetch_one(pool)
+        .await?;
+    Ok(raw_str(&row, "cancelled").as_deref() == Some("t"))
+}
+
+/// Prepared (two-phase) transactions in the current database, oldest first.
+/// ROLLBACK PREPARED only works from the database a transaction was prepared
+/// in, so other databases' entries aren't actionable here.
+pub async fn get_prepared_transactions(pool: &PgPool) -> Result<Vec<PreparedTransaction>, sqlx::Error> {
+    let rows = sqlx::raw_sql(
+        "SELECT gid, transaction::text AS transaction, prepared, owner, database \
+         FROM pg_catalog.pg_prepared_xacts \
+         WHERE database = current_database() \
+         ORDER BY prepared",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| PreparedTransaction {
+            gid: raw_str(row, "gid").unwrap_or_default(),
+            transaction: raw_str(row, "transaction"),
+            prepared_at: raw_str(row, "prepared"),
+            owner: raw_str(row, "owner"),
+            database: raw_str(row, "database"),
+        })
+        .collect())
+}
+
+/// Roll back a prepared transaction in the current database. Returns false
+/// if no transaction with that gid is prepared here.
+pub async fn rollback_prepared_transaction(pool: &PgPool, gid: &str) -> Result<bool, sqlx::Error> {
+    let literal = escape_sql_literal(gid);
+    let exists_sql = format!(
+        "SELECT 1 FROM pg_catalog.pg_prepared_xacts \
+         WHERE gid = '{}' AND database = current_database()",
+        literal
+    );
+    if sqlx::raw_sql(&exists_sql).fetch_all(pool).await?.is_empty() {
+        return Ok(false);
+    }
+    sqlx::raw_sql(&format!("ROLLBACK PREPARED '{}'", literal))
+        .execute(pool)
+        .await?;
+    Ok(true)
+}
+
+/// Settings stored for the current database with `ALTER DATABASE ... SET` or
+/// `ALTER ROLE ... IN DATABASE ... SET` (`pg_db_role_setting`), database-wide first.
+pub async fn get_database_settings(pool: &PgPool) -> Result<Vec<DatabaseSetting>, sqlx::Error> {
+    let rows = sqlx::raw_sql(
+        "SELECT split_part(cfg, '=', 1) AS name, \
+                substr(cfg, strpos(cfg, '=') + 1) AS value, \
+                r.rolname AS role_name \
+         FROM pg_catalog.pg_db_role_setting s \
+         JOIN pg_catalog.pg_database d ON d.oid = s.setdatabase \
+         LEFT JOIN pg_catalog.pg_roles r ON r.oid = s.setrole \
+         CROSS JOIN LATERAL unnest(s.setconfig) AS cfg \
+         WHERE d.datname = current_database() \
+         ORDER BY r.rolname NULLS FIRST, 1",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| DatabaseSetting {
+            name: raw_str(row, "name").unwrap_or_default(),
+            value: raw_str(row, "value").unwrap_or_default(),
+            role_name: raw_str(row, "role_name"),
+        })
+        .collect())
+}
+
+/// Run `ALTER DATABASE <current database> <clause>`, e.g. `SET work_mem = '64MB'`.
+pub async fn alter_current_database(pool: &PgPool, clause: &str) -> Result<(), sqlx::Error> {
+    let rows = sqlx::raw_sql("SELECT pg_catalog.quote_ident(current_database()) AS name")
+        .fetch_all(pool)
+        .await?;
+    let name = rows.first().and_then(|row| raw_str(row, "name")).unwrap_or_default();
+    sqlx::raw_sql(&format!("ALTER DATABASE {} {}", name, clause))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Which of `type_names` (as printed by format_type) don't resolve to a type on
+/// this server, e.g. enums or extension types created only elsewhere.
+pub async fn missing_types(pool: &PgPool, type_names: &[String]) -> Result<Vec<String>, sqlx::Error> {
+    if type_names.is_empty() {
+        return Ok(vec![]);
+    }
+    let literals: Vec<String> = type_names
+        .iter()
+        .map(|t| format!("'{}'", escape_sql_literal(t)))
+        .collect();
+    let rows = sqlx::raw_sql(&format!(
+        "SELECT t FROM unnest(ARRAY[{}]::text[]) AS t WHERE to_regtype(t) IS NULL",
+        literals.join(", ")
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.iter().filter_map(|row| raw_str(row, "t")).collect())
+}
+
+/// List the non-template databases on the server that the current user may connect to.
+pub async fn list_databases(pool: &PgPool) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::raw_sql(
+        "SELECT datname \
+         FROM pg_catalog.pg_database \
+         WHERE NOT datistemplate \
+           AND datallowconn \
+           AND has_database_privilege(datname, 'CONNECT') \
+         ORDER BY datname",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().filter_map(|row| raw_str(row, "datname")).collect())
+}
+
 /// Analyze tables in a schema that have never been analyzed (reltuples = -1).
 /// Returns which tables were attempted and which had permission errors.
 /// Tables in `skip_denied` are known to be permission-denied from a previous
@@ -210,6 +696,35 @@ pub async fn analyze_schema(
     })
 }
 
+/// The row count estimate and statistics freshness for a table. `None` if no
+/// table, partitioned table, materialized view or foreign table has that name.
+pub async fn get_row_count_estimate(
+    pool: &PgPool,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<Option<RowCountEstimate>, sqlx::Error> {
+    // reltuples is -1 (PG 14+) or 0 with relpages = 0 (older) until the first ANALYZE
+    let sql = format!(
+        "SELECT CASE WHEN c.reltuples < 0 OR (c.reltuples = 0 AND c.relpages = 0 AND s.last_analyze IS NULL \
+                          AND s.last_autoanalyze IS NULL) THEN NULL \
+                     ELSE c.reltuples::int8 END AS estimated_rows, \
+                greatest(s.last_analyze, s.last_autoanalyze)::text AS last_analyzed_at, \
+                s.n_mod_since_analyze AS modifications_since_analyze \
+         FROM pg_catalog.pg_class c \
+         JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+         LEFT JOIN pg_catalog.pg_stat_all_tables s ON s.relid = c.oid \
+         WHERE n.nspname = '{}' AND c.relname = '{}' AND c.relkind IN ('r', 'p', 'm', 'f')",
+        escape_sql_literal(schema_name),
+        escape_sql_literal(table_name)
+    );
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+    Ok(rows.first().map(|row| RowCountEstimate {
+        estimated_rows: raw_str(row, "estimated_rows").and_then(|v| v.parse().ok()),
+        last_analyzed_at: raw_str(row, "last_analyzed_at"),
+        modifications_since_analyze: raw_str(row, "modifications_since_analyze").and_then(|v| v.parse().ok()),
+    }))
+}
+
 /// Get all tables and views in a schema
 pub async fn get_tables(pool: &PgPool, schema_name: &str) -> Result<Vec<TableInfo>, sqlx::Error> {
     let escaped = escape_sql_literal(schema_name);
@@ -248,7 +763,8 @@ pub async fn get_tables(pool: &PgPool, schema_name: &str) -> Result<Vec<TableInf
             CASE WHEN c.relkind = 'p' THEN pg_get_partkeydef(c.oid) ELSE NULL END as part_key, \
             CASE WHEN c.relkind = 'p' THEN ( \
                 SELECT count(*) FROM pg_inherits WHERE inhparent = c.oid)::bigint \
-                ELSE NULL END as part_count \
+                ELSE NULL END as part_count, \
+            c.relpersistence::text as persistence \
          FROM pg_catalog.pg_class c \
          JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
          LEFT JOIN pg_catalog.pg_stat_all_tables s ON s.relid = c.oid \
@@ -296,6 +812,7 @@ pub async fn get_tables(pool: &PgPool, schema_name: &str) -> Result<Vec<TableInf
                     partition_key: row.try_get("part_key").ok().flatten(),
                     partition_bound: None,
                     partition_count: row.try_get("part_count").ok().flatten(),
+                    persistence: table_persistence(&row),
                 }
             })
             .collect();
@@ -334,6 +851,7 @@ pub async fn get_tables(pool: &PgPool, schema_name: &str) -> Result<Vec<TableInf
                 partition_key: None,
                 partition_bound: None,
                 partition_count: None,
+                persistence: TablePersistence::Logged,
             })
         })
         .collect();
@@ -376,6 +894,7 @@ pub async fn get_partitions(
             CASE WHEN c.relkind = 'p' THEN ( \
                 SELECT count(*) FROM pg_inherits WHERE inhparent = c.oid)::bigint \
                 ELSE NULL END as part_count, \
+            c.relpersistence::text as persistence, \
             cn.nspname as child_schema \
          FROM pg_catalog.pg_inherits i \
          JOIN pg_catalog.pg_class parent ON parent.oid = i.inhparent \
@@ -416,6 +935,7 @@ pub async fn get_partitions(
                 partition_key: row.try_get("part_key").ok().flatten(),
                 partition_bound: row.try_get("part_bound").ok().flatten(),
                 partition_count: row.try_get("part_count").ok().flatten(),
+                persistence: table_persistence(&row),
             }
         })
         .collect();
@@ -423,6 +943,216 @@ pub async fn get_partitions(
     Ok(partitions)
 }
 
+/// Escape `%`, `_` and the escape character itself so user input matches literally in LIKE.
+fn escape_like_pattern(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        if matches!(ch, '\\' | '%' | '_') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Case-insensitive substring search over table/view names for the navigator filter box.
+/// Exact matches rank first, then prefix matches, then other substring matches;
+/// within a rank shorter names come first. Partitions and system schemas are skipped.
+pub async fn search_tables(
+    pool: &PgPool,
+    term: &str,
+    schema_name: Option<&str>,
+    limit: u32,
+) -> Result<Vec<TableInfo>, sqlx::Error> {
+    let literal = escape_sql_literal(term);
+    let pattern = escape_sql_literal(&escape_like_pattern(term));
+    let schema_filter = match schema_name {
+        Some(schema) => format!("n.nspname = '{}'", escape_sql_literal(schema)),
+        None => "n.nspname NOT IN ('pg_catalog', 'information_schema') \
+                 AND n.nspname NOT LIKE 'pg\\_toast%' \
+                 AND n.nspname NOT LIKE 'pg\\_temp\\_%'"
+            .to_string(),
+    };
+
+    let sql = format!(
+        "SELECT \
+            c.relname as table_name, \
+            n.nspname as schema_name, \
+            c.relkind::text as relkind, \
+            CASE WHEN c.reltuples >= 0 THEN c.reltuples::bigint ELSE NULL END as row_estimate, \
+            (c.relkind = 'p') as is_partitioned, \
+            c.relpersistence::text as persistence \
+         FROM pg_catalog.pg_class c \
+         JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+         WHERE c.relkind IN ('r', 'v', 'm', 'f', 'p') \
+           AND c.relispartition = false \
+           AND c.relname ILIKE '%{pattern}%' \
+           AND {schema_filter} \
+         ORDER BY \
+            CASE \
+                WHEN lower(c.relname) = lower('{literal}') THEN 0 \
+                WHEN c.relname ILIKE '{pattern}%' THEN 1 \
+                ELSE 2 \
+            END, \
+            length(c.relname), \
+            c.relname, \
+            n.nspname \
+         LIMIT {limit}"
+    );
+
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+    let tables = rows
+        .into_iter()
+        .map(|row| {
+            let relkind: String = row.get("relkind");
+            let table_type = match relkind.as_str() {
+                "v" | "m" => TableType::View,
+                "f" => TableType::ForeignTable,
+                "p" => TableType::PartitionedTable,
+                _ => TableType::Table,
+            };
+            TableInfo {
+                name: row.get("table_name"),
+                schema_name: row.get("schema_name"),
+                table_type,
+                row_count_estimate: row.try_get("row_estimate").ok().flatten(),
+                total_size_bytes: None,
+                is_partitioned: row.try_get("is_partitioned").unwrap_or(false),
+                is_partition: false,
+                partition_strategy: None,
+                partition_key: None,
+                partition_bound: None,
+                partition_count: None,
+                persistence: table_persistence(&row),
+            }
+        })
+        .collect();
+
+    Ok(tables)
+}
+
+/// Direct inheritance parents and children of a table (classic `INHERITS` and
+/// declarative partitions alike), in `inhseqno` / name order.
+pub async fn get_inheritance(
+    pool: &PgPool,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<TableInheritance, sqlx::Error> {
+    let escaped_schema = escape_sql_literal(schema_name);
+    let escaped_table = escape_sql_literal(table_name);
+
+    let sql = format!(
+        "SELECT 'parent' as direction, pn.nspname as schema_name, parent.relname as table_name, \
+                c.relispartition as is_partition, i.inhseqno as seq \
+         FROM pg_catalog.pg_inherits i \
+         JOIN pg_catalog.pg_class c ON c.oid = i.inhrelid \
+         JOIN pg_catalog.pg_namespace cn ON cn.oid = c.relnamespace \
+         JOIN pg_catalog.pg_class parent ON parent.oid = i.inhparent \
+         JOIN pg_catalog.pg_namespace pn ON pn.oid = parent.relnamespace \
+         WHERE cn.nspname = '{schema}' AND c.relname = '{table}' \
+         UNION ALL \
+         SELECT 'child', cn.nspname, c.relname, c.relispartition, 0 \
+         FROM pg_catalog.pg_inherits i \
+         JOIN pg_catalog.pg_class parent ON parent.oid = i.inhparent \
+         JOIN pg_catalog.pg_namespace pn ON pn.oid = parent.relnamespace \
+         JOIN pg_catalog.pg_class c ON c.oid = i.inhrelid \
+         JOIN pg_catalog.pg_namespace cn ON cn.oid = c.relnamespace \
+         WHERE pn.nspname = '{schema}' AND parent.relname = '{table}' \
+         ORDER BY direction, seq, schema_name, table_name",
+        schema = escaped_schema,
+        table = escaped_table
+    );
+
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+    let mut inheritance = TableInheritance::default();
+    for row in rows {
+        let entry = InheritedTable {
+            schema_name: row.get("schema_name"),
+            name: row.get("table_name"),
+            is_partition: row.try_get("is_partition").unwrap_or(false),
+        };
+        if raw_str(&row, "direction").as_deref() == Some("parent") {
+            inheritance.parents.push(entry);
+        } else {
+            inheritance.children.push(entry);
+        }
+    }
+    Ok(inheritance)
+}
+
+/// Row-level security flags and policies for a table. A missing table yields
+/// RLS off and no policies.
+pub async fn get_rls_policies(
+    pool: &PgPool,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<TableRlsPolicies, sqlx::Error> {
+    let escaped_schema = escape_sql_literal(schema_name);
+    let escaped_table = escape_sql_literal(table_name);
+
+    let flags_sql = format!(
+        "SELECT c.relrowsecurity as rls_enabled, c.relforcerowsecurity as rls_forced \
+         FROM pg_catalog.pg_class c \
+         JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+         WHERE n.nspname = '{}' AND c.relname = '{}'",
+        escaped_schema, escaped_table
+    );
+    let flags = sqlx::raw_sql(&flags_sql).fetch_all(pool).await?;
+    let mut result = TableRlsPolicies::default();
+    if let Some(row) = flags.first() {
+        result.rls_enabled = row.try_get("rls_enabled").unwrap_or(false);
+        result.rls_forced = row.try_get("rls_forced").unwrap_or(false);
+    }
+
+    let policies_sql = format!(
+        "SELECT policyname, cmd, permissive, to_json(roles)::text as roles, qual, with_check \
+         FROM pg_catalog.pg_policies \
+         WHERE schemaname = '{}' AND tablename = '{}' \
+         ORDER BY policyname",
+        escaped_schema, escaped_table
+    );
+    let rows = sqlx::raw_sql(&policies_sql).fetch_all(pool).await?;
+    result.policies = rows
+        .iter()
+        .map(|row| RlsPolicyInfo {
+            name: raw_str(row, "policyname").unwrap_or_default(),
+            command: raw_str(row, "cmd").unwrap_or_else(|| "ALL".to_string()),
+            permissive: raw_str(row, "permissive").as_deref() != Some("RESTRICTIVE"),
+            roles: raw_str(row, "roles")
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            using_expression: raw_str(row, "qual"),
+            with_check_expression: raw_str(row, "with_check"),
+        })
+        .collect();
+    Ok(result)
+}
+
+/// A table's storage parameters (`pg_class.reloptions`), in stored order.
+pub async fn get_table_storage_params(
+    pool: &PgPool,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<Vec<TableStorageParam>, sqlx::Error> {
+    let sql = format!(
+        "SELECT unnest(c.reloptions) AS opt \
+         FROM pg_catalog.pg_class c \
+         JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+         WHERE n.nspname = '{}' AND c.relname = '{}'",
+        escape_sql_literal(schema_name),
+        escape_sql_literal(table_name)
+    );
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let opt = raw_str(row, "opt")?;
+            let (name, value) = opt.split_once('=')?;
+            Some(TableStorageParam { name: name.to_string(), value: value.to_string() })
+        })
+        .collect())
+}
+
 /// Get a flat parent→child name map for all partitioned parents in a schema.
 /// Used to populate the sidebar filter index without loading full partition detail.
 pub async fn get_partition_map(
@@ -467,6 +1197,8 @@ pub async fn get_columns(
             c.is_nullable, \
             c.ordinal_position, \
             c.column_default, \
+            c.is_identity, \
+            c.is_generated, \
             CASE WHEN pk.column_name IS NOT NULL THEN true ELSE false END as is_primary_key \
          FROM information_schema.columns c \
          LEFT JOIN ( \
@@ -490,6 +1222,8 @@ pub async fn get_columns(
             .into_iter()
             .filter_map(|row| {
                 let is_pk_str = raw_str(&row, "is_primary_key").unwrap_or_default();
+                let column_default = raw_str(&row, "column_default");
+                let is_identity = raw_str(&row, "is_identity").as_deref() == Some("YES");
                 Some(ColumnInfo {
                     name: raw_str(&row, "column_name")?,
                     data_type: raw_str(&row, "data_type").unwrap_or_default(),
@@ -498,7 +1232,9 @@ pub async fn get_columns(
                     ordinal_position: raw_str(&row, "ordinal_position")
                         .and_then(|s| s.parse().ok())
                         .unwrap_or(0),
-                    column_default: raw_str(&row, "column_default"),
+                    default_kind: DefaultKind::classify(column_default.as_deref(), is_identity),
+                    column_default,
+                    is_generated: raw_str(&row, "is_generated").as_deref() == Some("ALWAYS"),
                 })
             })
             .collect();
@@ -527,6 +1263,7 @@ pub async fn get_columns(
     let columns = rows
         .into_iter()
         .filter_map(|row| {
+            let column_default = raw_str(&row, "column_default");
             Some(ColumnInfo {
                 name: raw_str(&row, "column_name")?,
                 data_type: raw_str(&row, "data_type").unwrap_or_default(),
@@ -535,7 +1272,9 @@ pub async fn get_columns(
                 ordinal_position: raw_str(&row, "ordinal_position")
                     .and_then(|s| s.parse().ok())
                     .unwrap_or(0),
-                column_default: raw_str(&row, "column_default"),
+                default_kind: DefaultKind::classify(column_default.as_deref(), false),
+                column_default,
+                is_generated: false,
             })
         })
         .collect();
@@ -560,6 +1299,8 @@ pub async fn get_schema_columns(
             c.is_nullable, \
             c.ordinal_position, \
             c.column_default, \
+            c.is_identity, \
+            c.is_generated, \
             CASE WHEN pk.column_name IS NOT NULL THEN true ELSE false END as is_primary_key \
          FROM information_schema.columns c \
          LEFT JOIN ( \
@@ -581,6 +1322,8 @@ pub async fn get_schema_columns(
             .into_iter()
             .filter_map(|row| {
                 let is_pk_str = raw_str(&row, "is_primary_key").unwrap_or_default();
+                let column_default = raw_str(&row, "column_default");
+                let is_identity = raw_str(&row, "is_identity").as_deref() == Some("YES");
                 Some(SchemaColumnInfo {
                     table_name: raw_str(&row, "table_name")?,
                     name: raw_str(&row, "column_name")?,
@@ -590,7 +1333,9 @@ pub async fn get_schema_columns(
                     ordinal_position: raw_str(&row, "ordinal_position")
                         .and_then(|s| s.parse().ok())
                         .unwrap_or(0),
-                    column_default: raw_str(&row, "column_default"),
+                    default_kind: DefaultKind::classify(column_default.as_deref(), is_identity),
+                    column_default,
+                    is_generated: raw_str(&row, "is_generated").as_deref() == Some("ALWAYS"),
                 })
             })
             .collect();
@@ -619,6 +1364,7 @@ pub async fn get_schema_columns(
     let columns = rows
         .into_iter()
         .filter_map(|row| {
+            let column_default = raw_str(&row, "column_default");
             Some(SchemaColumnInfo {
                 table_name: raw_str(&row, "table_name")?,
                 name: raw_str(&row, "column_name")?,
@@ -628,7 +1374,9 @@ pub async fn get_schema_columns(
                 ordinal_position: raw_str(&row, "ordinal_position")
                     .and_then(|s| s.parse().ok())
                     .unwrap_or(0),
-                column_default: raw_str(&row, "column_default"),
+                default_kind: DefaultKind::classify(column_default.as_deref(), false),
+                column_default,
+                is_generated: false,
             })
         })
         .collect();
@@ -636,6 +1384,55 @@ pub async fn get_schema_columns(
     Ok(columns)
 }
 
+/// Every user table, view and foreign table with its column names, for
+/// autocompletion. `include_details` adds each column's type and comment,
+/// which makes the query noticeably heavier on large catalogs.
+pub async fn get_completion_metadata(
+    pool: &PgPool,
+    include_details: bool,
+) -> Result<Vec<CompletionTable>, sqlx::Error> {
+    let details = if include_details {
+        ", pg_catalog.format_type(a.atttypid, a.atttypmod) AS data_type, \
+         pg_catalog.col_description(a.attrelid, a.attnum) AS comment"
+    } else {
+        ""
+    };
+    let sql = format!(
+        "SELECT n.nspname AS schema_name, c.relname AS table_name, a.attname AS column_name{} \
+         FROM pg_catalog.pg_attribute a \
+         JOIN pg_catalog.pg_class c ON c.oid = a.attrelid \
+         JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+         WHERE c.relkind IN ('r', 'v', 'm', 'f', 'p') \
+           AND a.attnum > 0 AND NOT a.attisdropped \
+           AND n.nspname NOT IN ('pg_catalog', 'information_schema') \
+           AND n.nspname NOT LIKE 'pg\\_toast%' AND n.nspname NOT LIKE 'pg\\_temp\\_%' \
+         ORDER BY n.nspname, c.relname, a.attnum",
+        details
+    );
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+
+    let mut tables: Vec<CompletionTable> = Vec::new();
+    for row in &rows {
+        let (Some(schema_name), Some(table_name), Some(column_name)) = (
+            raw_str(row, "schema_name"),
+            raw_str(row, "table_name"),
+            raw_str(row, "column_name"),
+        ) else {
+            continue;
+        };
+        let column = CompletionColumn {
+            name: column_name,
+            data_type: if include_details { raw_str(row, "data_type") } else { None },
+            comment: if include_details { raw_str(row, "comment") } else { None },
+        };
+        match tables.last_mut() {
+            Some(t) if t.schema_name == schema_name && t.name == table_name => t.columns.push(column),
+            _ => tables.push(CompletionTable { schema_name, name: table_name, columns: vec![column] }),
+        }
+    }
+    Ok(tables)
+}
+
 /// Get indexes for a table
 pub async fn get_table_indexes(
     pool: &PgPool,
@@ -761,6 +1558,102 @@ pub async fn get_table_constraints(
     Ok(constraints)
 }
 
+/// Get user-defined triggers for a table. Internal triggers backing foreign
+/// keys and other constraints are left out.
+pub async fn get_table_triggers(
+    pool: &PgPool,
+    schema_name: &str,
+    table_name: &str,
+) -> Result<Vec<TriggerInfo>, sqlx::Error> {
+    let escaped_schema = escape_sql_literal(schema_name);
+    let escaped_table = escape_sql_literal(table_name);
+
+    // tgtype bits: 1 ROW, 2 BEFORE, 4 INSERT, 8 DELETE, 16 UPDATE, 32 TRUNCATE, 64 INSTEAD.
+    let sql = format!(
+        "SELECT \
+            tg.tgname AS trigger_name, \
+            CASE \
+                WHEN tg.tgtype & 2 <> 0 THEN 'BEFORE' \
+                WHEN tg.tgtype & 64 <> 0 THEN 'INSTEAD OF' \
+                ELSE 'AFTER' \
+            END AS timing, \
+            ARRAY_REMOVE(ARRAY[ \
+                CASE WHEN tg.tgtype & 4 <> 0 THEN 'INSERT' END, \
+                CASE WHEN tg.tgtype & 16 <> 0 THEN 'UPDATE' END, \
+                CASE WHEN tg.tgtype & 8 <> 0 THEN 'DELETE' END, \
+                CASE WHEN tg.tgtype & 32 <> 0 THEN 'TRUNCATE' END \
+            ], NULL) AS events, \
+            CASE WHEN tg.tgtype & 1 <> 0 THEN 'ROW' ELSE 'STATEMENT' END AS level, \
+            pn.nspname || '.' || p.proname AS function_name, \
+            tg.tgenabled <> 'D' AS enabled, \
+            pg_get_triggerdef(tg.oid) AS definition \
+         FROM pg_trigger tg \
+         JOIN pg_class t ON t.oid = tg.tgrelid \
+         JOIN pg_namespace n ON n.oid = t.relnamespace \
+         JOIN pg_proc p ON p.oid = tg.tgfoid \
+         JOIN pg_namespace pn ON pn.oid = p.pronamespace \
+         WHERE n.nspname = '{}' AND t.relname = '{}' AND NOT tg.tgisinternal \
+         ORDER BY tg.tgname",
+        escaped_schema, escaped_table
+    );
+
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+
+    let triggers = rows
+        .into_iter()
+        .map(|row| TriggerInfo {
+            name: row.get("trigger_name"),
+            timing: row.get("timing"),
+            events: row.get("events"),
+            level: row.get("level"),
+            function_name: row.get("function_name"),
+            enabled: row.get("enabled"),
+            definition: row.get("definition"),
+        })
+        .collect();
+
+    Ok(triggers)
+}
+
+/// Reconstruct the CREATE VIEW (or CREATE MATERIALIZED VIEW) DDL for a view.
+/// Returns `None` if `view_name` is not a view or materialized view.
+pub async fn generate_view_ddl(
+    pool: &PgPool,
+    schema_name: &str,
+    view_name: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let sql = format!(
+        "SELECT c.relkind::text AS relkind, \
+                array_to_string(c.reloptions, ', ') AS options, \
+                pg_get_viewdef(c.oid, true) AS query, \
+                obj_description(c.oid, 'pg_class') AS comment, \
+                ARRAY( \
+                    SELECT pg_get_indexdef(ix.indexrelid) \
+                    FROM pg_index ix \
+                    JOIN pg_class i ON i.oid = ix.indexrelid \
+                    WHERE ix.indrelid = c.oid \
+                    ORDER BY i.relname \
+                ) AS index_defs \
+         FROM pg_class c \
+         JOIN pg_namespace n ON n.oid = c.relnamespace \
+         WHERE n.nspname = '{}' AND c.relname = '{}' AND c.relkind IN ('v', 'm')",
+        escape_sql_literal(schema_name),
+        escape_sql_literal(view_name)
+    );
+    let Some(row) = sqlx::raw_sql(&sql).fetch_all(pool).await?.into_iter().next() else {
+        return Ok(None);
+    };
+
+    let view = DdlView {
+        materialized: row.get::<String, _>("relkind") == "m",
+        options: row.get::<Option<String>, _>("options").filter(|o| !o.is_empty()),
+        query: row.get("query"),
+        index_defs: row.get("index_defs"),
+        comment: row.get("comment"),
+    };
+    Ok(Some(crate::commands::ddl::compose_view_ddl(schema_name, view_name, &view)))
+}
+
 /// Read the raw parts (columns, constraints, non-constraint indexes) needed to
 /// reconstruct a table's CREATE TABLE DDL.
 pub async fn get_table_ddl_parts(
@@ -780,7 +1673,8 @@ pub async fn get_table_ddl_parts(
             a.attnotnull AS not_null, \
             pg_get_expr(ad.adbin, ad.adrelid) AS default_expr, \
             a.attidentity::text AS identity, \
-            a.attgenerated::text AS generated \
+            a.attgenerated::text AS generated, \
+            col_description(a.attrelid, a.attnum) AS comment \
          FROM pg_attribute a \
          JOIN pg_class t ON t.oid = a.attrelid \
          JOIN pg_namespace n ON n.oid = t.relnamespace \
@@ -801,6 +1695,7 @@ pub async fn get_table_ddl_parts(
                 default_expr: raw_str(&row, "default_expr"),
                 identity: raw_str(&row, "identity").unwrap_or_default(),
                 generated: raw_str(&row, "generated").unwrap_or_default(),
+                comment: raw_str(&row, "comment"),
             })
         })
         .collect();
@@ -808,7 +1703,8 @@ pub async fn get_table_ddl_parts(
     // Constraints — full definitions via pg_get_constraintdef, ordered PK, UNIQUE, CHECK, FK.
     // EXCLUDE ('x') constraints are out of scope for reconstructed DDL.
     let con_sql = format!(
-        "SELECT con.conname AS name, pg_get_constraintdef(con.oid) AS def \
+        "SELECT con.conname AS name, pg_get_constraintdef(con.oid) AS def, \
+                obj_description(con.oid, 'pg_constraint') AS comment \
          FROM pg_constraint con \
          JOIN pg_class t ON t.oid = con.conrelid \
          JOIN pg_namespace n ON n.oid = t.relnamespace \
@@ -826,6 +1722,7 @@ pub async fn get_table_ddl_parts(
             Some(DdlConstraint {
                 name: raw_str(&row, "name")?,
                 definition: raw_str(&row, "def").unwrap_or_default(),
+                comment: raw_str(&row, "comment"),
             })
         })
         .collect();
@@ -850,25 +1747,76 @@ pub async fn get_table_ddl_parts(
         .filter_map(|row| raw_str(&row, "def"))
         .collect();
 
-    // Partition clause (NULL for non-partitioned tables).
+    // Partition clause (NULL for non-partitioned tables) and table comment.
     let part_sql = format!(
-        "SELECT pg_get_partkeydef(t.oid) AS def \
+        "SELECT pg_get_partkeydef(t.oid) AS def, obj_description(t.oid, 'pg_class') AS comment \
          FROM pg_class t \
          JOIN pg_namespace n ON n.oid = t.relnamespace \
          WHERE n.nspname = '{}' AND t.relname = '{}'",
         escaped_schema, escaped_table
     );
     let part_rows = sqlx::raw_sql(&part_sql).fetch_all(pool).await?;
-    let partition_by: Option<String> = part_rows.into_iter().next().and_then(|row| raw_str(&row, "def"));
+    let partition_by: Option<String> = part_rows.first().and_then(|row| raw_str(row, "def"));
+    let comment: Option<String> = part_rows.first().and_then(|row| raw_str(row, "comment"));
 
     Ok(TableDdlParts {
         columns,
         constraints,
         index_defs,
         partition_by,
+        comment,
     })
 }
 
+/// A schema's description from `obj_description`, if any.
+pub async fn get_schema_comment(pool: &PgPool, schema_name: &str) -> Result<Option<String>, sqlx::Error> {
+    let sql = format!(
+        "SELECT obj_description(n.oid, 'pg_namespace') AS comment \
+         FROM pg_catalog.pg_namespace n WHERE n.nspname = '{}'",
+        escape_sql_literal(schema_name)
+    );
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+    Ok(rows.first().and_then(|row| raw_str(row, "comment")))
+}
+
+/// Full definitions and comments of a schema's functions and procedures,
+/// skipping those that belong to an extension.
+pub async fn get_function_ddls(pool: &PgPool, schema_name: &str) -> Result<Vec<DdlFunction>, sqlx::Error> {
+    let sql = format!(
+        "SELECT p.proname AS name, \
+                pg_catalog.pg_get_function_identity_arguments(p.oid) AS args, \
+                p.prokind::text AS kind, \
+                pg_catalog.pg_get_functiondef(p.oid) AS def, \
+                obj_description(p.oid, 'pg_proc') AS comment \
+         FROM pg_catalog.pg_proc p \
+         JOIN pg_catalog.pg_namespace n ON n.oid = p.pronamespace \
+         WHERE n.nspname = '{}' AND p.prokind IN ('f', 'p') \
+           AND NOT EXISTS (SELECT 1 FROM pg_catalog.pg_depend d \
+                           WHERE d.classid = 'pg_catalog.pg_proc'::regclass \
+                             AND d.objid = p.oid AND d.deptype = 'e') \
+         ORDER BY p.proname, args",
+        escape_sql_literal(schema_name)
+    );
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let name = raw_str(row, "name")?;
+            Some(DdlFunction {
+                signature: format!(
+                    "\"{}\".\"{}\"({})",
+                    schema_name.replace('"', "\"\""),
+                    name.replace('"', "\"\""),
+                    raw_str(row, "args").unwrap_or_default()
+                ),
+                is_procedure: raw_str(row, "kind").as_deref() == Some("p"),
+                definition: raw_str(row, "def")?,
+                comment: raw_str(row, "comment"),
+            })
+        })
+        .collect())
+}
+
 /// Get functions and procedures in a schema
 pub async fn get_schema_functions(
     pool: &PgPool,
@@ -914,3 +1862,430 @@ pub async fn get_schema_functions(
 
     Ok(functions)
 }
+
+/// Get sequences in a schema, with the column each serial/identity sequence belongs to
+pub async fn get_sequences(
+    pool: &PgPool,
+    schema_name: &str,
+) -> Result<Vec<SequenceInfo>, sqlx::Error> {
+    let escaped_schema = escape_sql_literal(schema_name);
+
+    // deptype 'a' links a SERIAL sequence to its column, 'i' an identity one.
+    let sql = format!(
+        "SELECT \
+            s.sequencename AS seq_name, \
+            s.schemaname AS schema_name, \
+            pg_catalog.format_type(s.data_type, NULL) AS data_type, \
+            s.start_value, \
+            s.increment_by, \
+            s.min_value, \
+            s.max_value, \
+            s.last_value, \
+            s.cycle, \
+            CASE WHEN a.attname IS NOT NULL \
+                THEN pg_catalog.quote_ident(t.relname) || '.' || pg_catalog.quote_ident(a.attname) \
+            END AS owned_by \
+         FROM pg_catalog.pg_sequences s \
+         JOIN pg_catalog.pg_namespace n ON n.nspname = s.schemaname \
+         JOIN pg_catalog.pg_class c ON c.relnamespace = n.oid AND c.relname = s.sequencename \
+         LEFT JOIN pg_catalog.pg_depend d ON d.classid = 'pg_catalog.pg_class'::regclass \
+            AND d.objid = c.oid AND d.refclassid = 'pg_catalog.pg_class'::regclass AND d.deptype IN ('a', 'i') \
+         LEFT JOIN pg_catalog.pg_class t ON t.oid = d.refobjid \
+         LEFT JOIN pg_catalog.pg_attribute a ON a.attrelid = d.refobjid AND a.attnum = d.refobjsubid \
+         WHERE s.schemaname = '{}' \
+         ORDER BY s.sequencename",
+        escaped_schema
+    );
+
+    let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+    let int = |row: &sqlx::postgres::PgRow, col: &str| raw_str(row, col).and_then(|v| v.parse::<i64>().ok());
+
+    let sequences = rows
+        .iter()
+        .filter_map(|row| {
+            Some(SequenceInfo {
+                name: raw_str(row, "seq_name")?,
+                schema_name: raw_str(row, "schema_name")?,
+                data_type: raw_str(row, "data_type").unwrap_or_default(),
+                start_value: int(row, "start_value").unwrap_or(0),
+                increment: int(row, "increment_by").unwrap_or(1),
+                min_value: int(row, "min_value").unwrap_or(0),
+                max_value: int(row, "max_value").unwrap_or(0),
+                last_value: int(row, "last_value"),
+                cycle: matches!(raw_str(row, "cycle").as_deref(), Some("t" | "true")),
+                owned_by: raw_str(row, "owned_by"),
+            })
+        })
+        .collect();
+
+    Ok(sequences)
+}
+
+/// Estimated bloat of the tables and btree indexes in a schema, largest waste
+/// first. This is the widely used statistics-based estimate (ioguix's
+/// pgsql-bloat-estimation): expected pages are derived from `pg_stats` average
+/// widths and null fractions and compared with actual pages. Nothing is
+/// scanned, so it's cheap but only as good as the last ANALYZE.
+pub async fn get_bloat_estimates(pool: &PgPool, schema_name: &str) -> Result<Vec<BloatEstimate>, sqlx::Error> {
+    let schema = escape_sql_literal(schema_name);
+    let table_sql = format!(
+        "SELECT tblname AS table_name, tblname AS object_name, 'table' AS kind, \
+            (bs * tblpages)::bigint AS real_size, \
+            CASE WHEN tblpages > est_tblpages_ff THEN ((tblpages - est_tblpages_ff) * bs)::bigint ELSE 0 END AS bloat_size, \
+            CASE WHEN tblpages > est_tblpages_ff THEN (100 * (tblpages - est_tblpages_ff) / tblpages)::float8 ELSE 0 END AS bloat_pct, \
+            is_na \
+         FROM ( \
+            SELECT ceil(reltuples / ((bs - page_hdr) * fillfactor / (tpl_size * 100))) + ceil(toasttuples / 4) AS est_tblpages_ff, \
+                tblpages, bs, tblname, is_na \
+            FROM ( \
+                SELECT (4 + tpl_hdr_size + tpl_data_size + (2 * ma) \
+                        - CASE WHEN tpl_hdr_size % ma = 0 THEN ma ELSE tpl_hdr_size % ma END \
+                        - CASE WHEN ceil(tpl_data_size)::int % ma = 0 THEN ma ELSE ceil(tpl_data_size)::int % ma END \
+                    ) AS tpl_size, \
+                    (heappages + toastpages) AS tblpages, reltuples, toasttuples, bs, page_hdr, tblname, fillfactor, is_na \
+                FROM ( \
+                    SELECT tbl.oid AS tblid, tbl.relname AS tblname, tbl.reltuples, \
+                        tbl.relpages AS heappages, coalesce(toast.relpages, 0) AS toastpages, \
+                        coalesce(toast.reltuples, 0) AS toasttuples, \
+                        coalesce(substring(array_to_string(tbl.reloptions, ' ') FROM 'fillfactor=([0-9]+)')::smallint, 100) AS fillfactor, \
+                        current_setting('block_size')::numeric AS bs, \
+                        CASE WHEN version() ~ 'mingw32' OR version() ~ '64-bit|x86_64|ppc64|ia64|amd64|aarch64|arm64' THEN 8 ELSE 4 END AS ma, \
+                        24 AS page_hdr, \
+                        23 + CASE WHEN max(coalesce(s.null_frac, 0)) > 0 THEN (7 + count(s.attname)) / 8 ELSE 0::int END AS tpl_hdr_size, \
+                        sum((1 - coalesce(s.null_frac, 0)) * coalesce(s.avg_width, 0)) AS tpl_data_size, \
+                        bool_or(att.atttypid = 'pg_catalog.name'::regtype) \
+                            OR sum(CASE WHEN att.attnum > 0 THEN 1 ELSE 0 END) <> count(s.attname) AS is_na \
+                    FROM pg_catalog.pg_attribute att \
+                    JOIN pg_catalog.pg_class tbl ON att.attrelid = tbl.oid \
+                    JOIN pg_catalog.pg_namespace ns ON ns.oid = tbl.relnamespace \
+                    LEFT JOIN pg_catalog.pg_stats s ON s.schemaname = ns.nspname \
+                        AND s.tablename = tbl.relname AND s.inherited = false AND s.attname = att.attname \
+                    LEFT JOIN pg_catalog.pg_class toast ON tbl.reltoastrelid = toast.oid \
+                    WHERE NOT att.attisdropped AND att.attnum > 0 \
+                      AND tbl.relkind IN ('r', 'm') \
+                      AND ns.nspname = '{schema}' \
+                    GROUP BY 1, 2, 3, 4, 5, 6, 7, 8, 9, 10 \
+                ) AS s \
+            ) AS s2 \
+         ) AS s3"
+    );
+    let index_sql = format!(
+        "SELECT tblname AS table_name, idxname AS object_name, 'index' AS kind, \
+            (bs * relpages)::bigint AS real_size, \
+            CASE WHEN relpages > est_pages_ff THEN (bs * (relpages - est_pages_ff))::bigint ELSE 0 END AS bloat_size, \
+            CASE WHEN relpages > est_pages_ff THEN (100 * (relpages - est_pages_ff)::float8 / relpages) ELSE 0 END AS bloat_pct, \
+            is_na \
+         FROM ( \
+            SELECT coalesce(1 + ceil(reltuples / floor((bs - pageopqdata - pagehdr) * fillfactor / (100 * (4 + nulldatahdrwidth)::float8))), 0) AS est_pages_ff, \
+                bs, tblname, idxname, relpages, is_na \
+            FROM ( \
+                SELECT bs, tblname, idxname, reltuples, relpages, fillfactor, pagehdr, pageopqdata, is_na, \
+                    (index_tuple_hdr_bm \
+                        + maxalign - CASE WHEN index_tuple_hdr_bm % maxalign = 0 THEN maxalign ELSE index_tuple_hdr_bm % maxalign END \
+                        + nulldatawidth + maxalign - CASE \
+                            WHEN nulldatawidth = 0 THEN 0 \
+                            WHEN nulldatawidth::integer % maxalign = 0 THEN maxalign \
+                            ELSE nulldatawidth::integer % maxalign END \
+                    )::numeric AS nulldatahdrwidth \
+                FROM ( \
+                    SELECT i.tblname, i.idxname, i.reltuples, i.relpages, i.fillfactor, \
+                        current_setting('block_size')::numeric AS bs, \
+                        CASE WHEN version() ~ 'mingw32' OR version() ~ '64-bit|x86_64|ppc64|ia64|amd64|aarch64|arm64' THEN 8 ELSE 4 END AS maxalign, \
+                        24 AS pagehdr, \
+                        16 AS pageopqdata, \
+                        CASE WHEN max(coalesce(s.null_frac, 0)) = 0 THEN 8 ELSE 8 + ((32 + 8 - 1) / 8) END AS index_tuple_hdr_bm, \
+                        sum((1 - coalesce(s.null_frac, 0)) * coalesce(s.avg_width, 1024)) AS nulldatawidth, \
+                        bool_or(i.atttypid = 'pg_catalog.name'::regtype) OR count(s.attname) < count(*) AS is_na \
+                    FROM ( \
+                        SELECT ct.relname AS tblname, ct.relnamespace, ic.idxname, ic.reltuples, ic.relpages, ic.fillfactor, \
+                            coalesce(a1.attname, a2.attname) AS attname, \
+                            coalesce(a1.atttypid, a2.atttypid) AS atttypid, \
+                            CASE WHEN a1.attnum IS NULL THEN ic.idxname ELSE ct.relname END AS attrelname \
+                        FROM ( \
+                            SELECT ci.relname AS idxname, ci.reltuples, ci.relpages, i.indrelid AS tbloid, i.indexrelid AS idxoid, \
+                                coalesce(substring(array_to_string(ci.reloptions, ' ') FROM 'fillfactor=([0-9]+)')::smallint, 90) AS fillfactor, \
+                                i.indkey, \
+                                pg_catalog.generate_series(1, i.indnatts) AS attpos \
+                            FROM pg_catalog.pg_index i \
+                            JOIN pg_catalog.pg_class ci ON ci.oid = i.indexrelid \
+                            JOIN pg_catalog.pg_am am ON am.oid = ci.relam \
+                            WHERE am.amname = 'btree' AND ci.relpages > 0 \
+                        ) AS ic \
+                        JOIN pg_catalog.pg_class ct ON ct.oid = ic.tbloid \
+                        LEFT JOIN pg_catalog.pg_attribute a1 ON ic.indkey[ic.attpos - 1] <> 0 \
+                            AND a1.attrelid = ic.tbloid AND a1.attnum = ic.indkey[ic.attpos - 1] \
+                        LEFT JOIN pg_catalog.pg_attribute a2 ON ic.indkey[ic.attpos - 1] = 0 \
+                            AND a2.attrelid = ic.idxoid AND a2.attnum = ic.attpos \
+                    ) i \
+                    JOIN pg_catalog.pg_namespace n ON n.oid = i.relnamespace \
+                    LEFT JOIN pg_catalog.pg_stats s ON s.schemaname = n.nspname \
+                        AND s.tablename = i.attrelname AND s.attname = i.attname \
+                    WHERE n.nspname = '{schema}' \
+                    GROUP BY 1, 2, 3, 4, 5, 6, 7, 8 \
+                ) AS rows_data_stats \
+            ) AS rows_hdr_pdg_stats \
+         ) AS relation_stats"
+    );
+
+    let mut estimates = Vec::new();
+    for sql in [table_sql, index_sql] {
+        let rows = sqlx::raw_sql(&sql).fetch_all(pool).await?;
+        estimates.extend(rows.iter().filter_map(|row| {
+            Some(BloatEstimate {
+                kind: match raw_str(row, "kind")?.as_str() {
+                    "index" => BloatObjectKind::Index,
+                    _ => BloatObjectKind::Table,
+                },
+                schema_name: schema_name.to_string(),
+                name: raw_str(row, "object_name")?,
+                table_name: raw_str(row, "table_name")?,
+                real_size_bytes: raw_str(row, "real_size").and_then(|v| v.parse().ok()).unwrap_or(0),
+                bloat_bytes: raw_str(row, "bloat_size").and_then(|v| v.parse().ok()).unwrap_or(0),
+                bloat_percent: raw_str(row, "bloat_pct").and_then(|v| v.parse().ok()).unwrap_or(0.0),
+                reliable: !matches!(raw_str(row, "is_na").as_deref(), Some("t" | "true")),
+            })
+        }));
+    }
+    estimates.sort_by(|a, b| b.bloat_bytes.cmp(&a.bloat_bytes).then_with(|| a.name.cmp(&b.name)));
+    Ok(estimates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_pool;
+
+    #[test]
+    fn empty_password_is_looked_up_in_pgpass() {
+        use std::os::unix::fs::PermissionsExt;
+        let file = std::env::temp_dir().join(format!("pharos_pgpass_{}", uuid::Uuid::new_v4().simple()));
+        std::fs::write(&file, "db.example.com:6543:reporting:alice:from-pgpass\n").unwrap();
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o600)).unwrap();
+        std::env::set_var("PGPASSFILE", &file);
+
+        let mut config: ConnectionConfig = serde_json::from_value(serde_json::json!({
+            "id": "c1", "name": "c1", "host": "db.example.com", "port": 6543,
+            "database": "reporting", "username": "alice",
+        }))
+        .unwrap();
+        let from_pgpass = format!("{:?}", build_connect_options(&config));
+        config.password = "typed".to_string();
+        let typed = format!("{:?}", build_connect_options(&config));
+        std::env::remove_var("PGPASSFILE");
+        std::fs::remove_file(&file).unwrap();
+
+        assert!(from_pgpass.contains("from-pgpass"), "{}", from_pgpass);
+        assert!(typed.contains("typed") && !typed.contains("from-pgpass"), "{}", typed);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn view_ddl_uses_pretty_viewdef() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_view_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.t (id int, total numeric); \
+             CREATE VIEW {schema}.v AS SELECT id FROM {schema}.t WHERE total > 0; \
+             CREATE MATERIALIZED VIEW {schema}.mv AS SELECT id FROM {schema}.t; \
+             CREATE INDEX mv_id ON {schema}.mv (id);"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let view = generate_view_ddl(&pool, &schema, "v").await;
+        let matview = generate_view_ddl(&pool, &schema, "mv").await;
+        let table = generate_view_ddl(&pool, &schema, "t").await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        // Column qualification in the pretty output varies across server versions.
+        let view = view.unwrap().unwrap();
+        assert!(view.starts_with(&format!("CREATE OR REPLACE VIEW \"{schema}\".\"v\" AS\n SELECT ")));
+        assert!(view.contains(&format!("\n   FROM {schema}.t\n  WHERE ")));
+        assert!(view.ends_with("> 0::numeric;"));
+        let matview = matview.unwrap().unwrap();
+        assert!(matview.starts_with(&format!("CREATE MATERIALIZED VIEW \"{schema}\".\"mv\" AS\n")));
+        assert!(matview.ends_with(&format!("CREATE INDEX mv_id ON {schema}.mv USING btree (id);")));
+        assert!(table.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn completion_metadata_groups_columns_by_table() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_comp_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.a (id int, label text); \
+             ALTER TABLE {schema}.a DROP COLUMN label; \
+             ALTER TABLE {schema}.a ADD COLUMN name varchar(20); \
+             COMMENT ON COLUMN {schema}.a.name IS 'Display name'; \
+             CREATE VIEW {schema}.b AS SELECT id FROM {schema}.a;"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let light = get_completion_metadata(&pool, false).await;
+        let detailed = get_completion_metadata(&pool, true).await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        let ours = |tables: Vec<CompletionTable>| -> Vec<CompletionTable> {
+            tables.into_iter().filter(|t| t.schema_name == schema).collect()
+        };
+        let light = ours(light.unwrap());
+        assert_eq!(light.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        let names: Vec<&str> = light[0].columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["id", "name"]);
+        assert!(light[0].columns.iter().all(|c| c.data_type.is_none() && c.comment.is_none()));
+
+        let detailed = ours(detailed.unwrap());
+        let name = &detailed[0].columns[1];
+        assert_eq!(name.data_type.as_deref(), Some("character varying(20)"));
+        assert_eq!(name.comment.as_deref(), Some("Display name"));
+        assert_eq!(detailed[1].columns[0].data_type.as_deref(), Some("integer"));
+    }
+
+    #[test]
+    fn maintenance_percent_rounds_and_clamps() {
+        assert_eq!(maintenance_percent(1, 3), Some(33.3));
+        assert_eq!(maintenance_percent(12, 10), Some(100.0));
+        assert_eq!(maintenance_percent(0, 0), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn running_maintenance_query_is_valid() {
+        let pool = test_pool(1).await;
+        let running = get_running_maintenance(&pool, 150000).await.unwrap();
+        assert!(running.iter().all(|m| m.blocks_done <= m.blocks_total || m.blocks_total == 0));
+        assert!(get_running_maintenance(&pool, 90500).await.unwrap().is_empty());
+        assert!(!cancel_maintenance(&pool, 150000, i32::MAX).await.unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn row_count_estimate_tracks_analyze() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_est_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.t (id int); \
+             INSERT INTO {schema}.t SELECT generate_series(1, 1000);"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let before = get_row_count_estimate(&pool, &schema, "t").await;
+        sqlx::raw_sql(&format!(
+            "ANALYZE {schema}.t; INSERT INTO {schema}.t SELECT generate_series(1, 500);"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        let after = get_row_count_estimate(&pool, &schema, "t").await;
+        let missing = get_row_count_estimate(&pool, &schema, "nope").await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        let before = before.unwrap().unwrap();
+        assert_eq!(before.estimated_rows, None);
+        assert_eq!(before.last_analyzed_at, None);
+        let after = after.unwrap().unwrap();
+        assert_eq!(after.estimated_rows, Some(1000));
+        assert!(after.last_analyzed_at.is_some());
+        assert!(missing.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn sequences_include_serial_column_sequence() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_seq_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.orders (id serial PRIMARY KEY, note text); \
+             SELECT nextval('{schema}.orders_id_seq');"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let sequences = get_sequences(&pool, &schema).await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        let sequences = sequences.unwrap();
+        assert_eq!(sequences.len(), 1);
+        let seq = &sequences[0];
+        assert_eq!(seq.name, "orders_id_seq");
+        assert_eq!(seq.data_type, "integer");
+        assert_eq!((seq.start_value, seq.increment, seq.min_value), (1, 1, 1));
+        assert_eq!(seq.max_value, i32::MAX as i64);
+        assert_eq!(seq.last_value, Some(1));
+        assert!(!seq.cycle);
+        assert_eq!(seq.owned_by.as_deref(), Some("orders.id"));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn bloat_estimates_find_half_deleted_table() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_bloat_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.events (id int PRIMARY KEY, payload text); \
+             INSERT INTO {schema}.events SELECT g, repeat('x', 100) FROM generate_series(1, 20000) g; \
+             DELETE FROM {schema}.events WHERE id % 2 = 0; \
+             ANALYZE {schema}.events;"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let estimates = get_bloat_estimates(&pool, &schema).await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        let estimates = estimates.unwrap();
+        assert_eq!(estimates.len(), 2);
+        let table = estimates.iter().find(|e| e.kind == BloatObjectKind::Table).unwrap();
+        assert_eq!(table.name, "events");
+        assert!(table.reliable);
+        assert!((35.0..65.0).contains(&table.bloat_percent), "bloat {}%", table.bloat_percent);
+        let index = estimates.iter().find(|e| e.kind == BloatObjectKind::Index).unwrap();
+        assert_eq!((index.name.as_str(), index.table_name.as_str()), ("events_pkey", "events"));
+        assert!(estimates[0].bloat_bytes >= estimates[1].bloat_bytes);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs PHAROS_TEST_DATABASE_URL"]
+    async fn table_triggers_skip_internal_constraint_triggers() {
+        let pool = test_pool(1).await;
+        let schema = format!("pharos_trg_{}", uuid::Uuid::new_v4().simple());
+        sqlx::raw_sql(&format!(
+            "CREATE SCHEMA {schema}; \
+             CREATE TABLE {schema}.parent (id int PRIMARY KEY); \
+             CREATE TABLE {schema}.child (id int, parent_id int REFERENCES {schema}.parent); \
+             CREATE FUNCTION {schema}.touch() RETURNS trigger LANGUAGE plpgsql AS 'BEGIN RETURN NEW; END'; \
+             CREATE TRIGGER child_touch BEFORE INSERT OR UPDATE ON {schema}.child \
+                FOR EACH ROW EXECUTE FUNCTION {schema}.touch();"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let triggers = get_table_triggers(&pool, &schema, "child").await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        let triggers = triggers.unwrap();
+        assert_eq!(triggers.len(), 1);
+        let trigger = &triggers[0];
+        assert_eq!(trigger.name, "child_touch");
+        assert_eq!(trigger.timing, "BEFORE");
+        assert_eq!(trigger.events, vec!["INSERT", "UPDATE"]);
+        assert_eq!(trigger.level, "ROW");
+        assert_eq!(trigger.function_name, format!("{schema}.touch"));
+        assert!(trigger.enabled);
+        assert!(trigger.definition.starts_with("CREATE TRIGGER child_touch BEFORE INSERT OR UPDATE"));
+    }
+}
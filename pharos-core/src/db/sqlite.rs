@@ -1,7 +1,7 @@
 use rusqlite::{Connection, Result as SqliteResult};
 use std::path::Path;
 
-use crate::models::{AppSettings, ConnectionConfig, CreateSavedQuery, QueryHistoryEntry, SavedQuery, SslMode, UpdateSavedQuery};
+use crate::models::{AppSettings, ConnectionConfig, CreateSavedQuery, QueryHistoryEntry, SavedQuery, SshAuthMethod, SshTunnelConfig, SslMode, UpdateSavedQuery};
 
 // ==================== Compression Helpers ====================
 
@@ -210,6 +210,67 @@ pub fn init_database(app_data_dir: &Path) -> SqliteResult<Connection> {
         )?;
     }
 
+    // Migration: Add disable_history column if it doesn't exist
+    let has_disable_history: bool = conn
+        .prepare("SELECT COUNT(*) FROM pragma_table_info('connections') WHERE name = 'disable_history'")?
+        .query_row([], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_disable_history {
+        conn.execute(
+            "ALTER TABLE connections ADD COLUMN disable_history INTEGER NOT NULL DEFAULT 0",
+            [],
+        )?;
+    }
+
+    // Migration: Add masked_columns column (JSON array of globs) if it doesn't exist
+    let has_masked_columns: bool = conn
+        .prepare("SELECT COUNT(*) FROM pragma_table_info('connections') WHERE name = 'masked_columns'")?
+        .query_row([], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_masked_columns {
+        conn.execute(
+            "ALTER TABLE connections ADD COLUMN masked_columns TEXT",
+            [],
+        )?;
+    }
+
+    // Migration: Add labels column (JSON array of strings) if it doesn't exist
+    let has_labels: bool = conn
+        .prepare("SELECT COUNT(*) FROM pragma_table_info('connections') WHERE name = 'labels'")?
+        .query_row([], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_labels {
+        conn.execute(
+            "ALTER TABLE connections ADD COLUMN labels TEXT",
+            [],
+        )?;
+    }
+
+    // Migration: Add SSH tunnel columns if they don't exist (the SSH secret lives in the keychain)
+    let has_ssh_host: bool = conn
+        .prepare("SELECT COUNT(*) FROM pragma_table_info('connections') WHERE name = 'ssh_host'")?
+        .query_row([], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_ssh_host {
+        conn.execute_batch(
+            r#"
+            ALTER TABLE connections ADD COLUMN ssh_host TEXT;
+            ALTER TABLE connections ADD COLUMN ssh_port INTEGER;
+            ALTER TABLE connections ADD COLUMN ssh_username TEXT;
+            ALTER TABLE connections ADD COLUMN ssh_auth_method TEXT;
+            ALTER TABLE connections ADD COLUMN ssh_key_path TEXT;
+            "#,
+        )?;
+    }
+
     conn.execute_batch(
         r#"
 
@@ -436,6 +497,20 @@ pub fn init_database(app_data_dir: &Path) -> SqliteResult<Connection> {
         )?;
     }
 
+    // Migration: Flag data-modifying runs in query_history (DML, including
+    // `... RETURNING` statements that came back as a result set).
+    let has_is_mutation_col: bool = conn
+        .prepare("SELECT COUNT(*) FROM pragma_table_info('query_history') WHERE name = 'is_mutation'")?
+        .query_row([], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_is_mutation_col {
+        conn.execute_batch(
+            "ALTER TABLE query_history ADD COLUMN is_mutation INTEGER NOT NULL DEFAULT 0;"
+        )?;
+    }
+
     // Migration: Backfill FTS5 index if it's empty but history has data
     let fts_count: i64 = conn
         .query_row("SELECT COUNT(*) FROM query_history_fts", [], |row| row.get(0))
@@ -462,6 +537,17 @@ pub fn init_database(app_data_dir: &Path) -> SqliteResult<Connection> {
         conn.execute("ALTER TABLE saved_queries ADD COLUMN variables TEXT", [])?;
     }
 
+    // Migration: Add column_layout column to saved_queries if it doesn't exist
+    let has_column_layout_column: bool = conn
+        .prepare("SELECT COUNT(*) FROM pragma_table_info('saved_queries') WHERE name = 'column_layout'")?
+        .query_row([], |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_column_layout_column {
+        conn.execute("ALTER TABLE saved_queries ADD COLUMN column_layout TEXT", [])?;
+    }
+
     Ok(conn)
 }
 
@@ -476,10 +562,22 @@ pub fn save_connection(conn: &Connection, config: &ConnectionConfig) -> SqliteRe
         )
         .unwrap_or(0);
 
+    let masked_columns = if config.masked_columns.is_empty() {
+        None
+    } else {
+        serde_json::to_string(&config.masked_columns).ok()
+    };
+    let labels = if config.labels.is_empty() {
+        None
+    } else {
+        serde_json::to_string(&config.labels).ok()
+    };
+    let ssh = config.ssh_tunnel.as_ref();
+
     conn.execute(
         r#"
-        INSERT INTO connections (id, name, host, port, database, username, ssl_mode, sort_order, color, default_schema, updated_at)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, CURRENT_TIMESTAMP)
+        INSERT INTO connections (id, name, host, port, database, username, ssl_mode, sort_order, color, default_schema, disable_history, masked_columns, labels, ssh_host, ssh_port, ssh_username, ssh_auth_method, ssh_key_path, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, CURRENT_TIMESTAMP)
         ON CONFLICT(id) DO UPDATE SET
             name = excluded.name,
             host = excluded.host,
@@ -489,9 +587,17 @@ pub fn save_connection(conn: &Connection, config: &ConnectionConfig) -> SqliteRe
             ssl_mode = excluded.ssl_mode,
             color = excluded.color,
             default_schema = excluded.default_schema,
+            disable_history = excluded.disable_history,
+            masked_columns = excluded.masked_columns,
+            labels = excluded.labels,
+            ssh_host = excluded.ssh_host,
+            ssh_port = excluded.ssh_port,
+            ssh_username = excluded.ssh_username,
+            ssh_auth_method = excluded.ssh_auth_method,
+            ssh_key_path = excluded.ssh_key_path,
             updated_at = CURRENT_TIMESTAMP
         "#,
-        (
+        rusqlite::params![
             &config.id,
             &config.name,
             &config.host,
@@ -502,7 +608,15 @@ pub fn save_connection(conn: &Connection, config: &ConnectionConfig) -> SqliteRe
             next_order,
             &config.color,
             &config.default_schema,
-        ),
+            config.disable_history,
+            masked_columns,
+            labels,
+            ssh.map(|t| &t.host),
+            ssh.map(|t| t.port),
+            ssh.map(|t| &t.username),
+            ssh.map(|t| t.auth_method.to_string()),
+            ssh.and_then(|t| t.private_key_path.as_ref()),
+        ],
     )?;
     Ok(())
 }
@@ -510,7 +624,7 @@ pub fn save_connection(conn: &Connection, config: &ConnectionConfig) -> SqliteRe
 /// Load all connection configurations from the database (passwords loaded from keychain separately)
 pub fn load_connections(conn: &Connection) -> SqliteResult<Vec<ConnectionConfig>> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, host, port, database, username, COALESCE(ssl_mode, 'prefer') as ssl_mode, color, default_schema FROM connections ORDER BY sort_order, name",
+        "SELECT id, name, host, port, database, username, COALESCE(ssl_mode, 'prefer') as ssl_mode, color, default_schema, disable_history, masked_columns, labels, ssh_host, ssh_port, ssh_username, ssh_auth_method, ssh_key_path FROM connections ORDER BY sort_order, name",
     )?;
 
     let configs = stmt.query_map([], |row| {
@@ -520,6 +634,26 @@ pub fn load_connections(conn: &Connection) -> SqliteResult<Vec<ConnectionConfig>
             "require" => SslMode::Require,
             _ => SslMode::Prefer,
         };
+        let masked_columns: Option<String> = row.get(10)?;
+        let labels: Option<String> = row.get(11)?;
+        let ssh_host: Option<String> = row.get(12)?;
+        let ssh_tunnel = match ssh_host {
+            Some(host) => {
+                let auth_method: Option<String> = row.get(15)?;
+                Some(SshTunnelConfig {
+                    host,
+                    port: row.get::<_, Option<u16>>(13)?.unwrap_or(22),
+                    username: row.get::<_, Option<String>>(14)?.unwrap_or_default(),
+                    auth_method: match auth_method.as_deref() {
+                        Some("password") => SshAuthMethod::Password,
+                        _ => SshAuthMethod::PrivateKey,
+                    },
+                    private_key_path: row.get(16)?,
+                    password: String::new(), // Loaded from keychain separately
+                })
+            }
+            None => None,
+        };
         Ok(ConnectionConfig {
             id: row.get(0)?,
             name: row.get(1)?,
@@ -531,6 +665,14 @@ pub fn load_connections(conn: &Connection) -> SqliteResult<Vec<ConnectionConfig>
             ssl_mode,
             color: row.get(7)?,
             default_schema: row.get(8)?,
+            disable_history: row.get(9)?,
+            masked_columns: masked_columns
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            labels: labels
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            ssh_tunnel,
         })
     })?;
 
@@ -590,13 +732,14 @@ pub fn create_saved_query(conn: &Connection, id: &str, query: &CreateSavedQuery)
         variables: query.variables.clone(),
         created_at: now.clone(),
         updated_at: now,
+        column_layout: None,
     })
 }
 
 /// Load all saved queries
 pub fn load_saved_queries(conn: &Connection) -> SqliteResult<Vec<SavedQuery>> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, folder, sql, connection_id, created_at, updated_at, variables FROM saved_queries ORDER BY name",
+        "SELECT id, name, folder, sql, connection_id, created_at, updated_at, variables, column_layout FROM saved_queries ORDER BY name",
     )?;
 
     let queries = stmt.query_map([], |row| {
@@ -609,6 +752,7 @@ pub fn load_saved_queries(conn: &Connection) -> SqliteResult<Vec<SavedQuery>> {
             created_at: row.get(5)?,
             updated_at: row.get(6)?,
             variables: row.get(7)?,
+            column_layout: row.get(8)?,
         })
     })?;
 
@@ -618,7 +762,7 @@ pub fn load_saved_queries(conn: &Connection) -> SqliteResult<Vec<SavedQuery>> {
 /// Get a single saved query by ID
 pub fn get_saved_query(conn: &Connection, query_id: &str) -> SqliteResult<Option<SavedQuery>> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, folder, sql, connection_id, created_at, updated_at, variables FROM saved_queries WHERE id = ?1",
+        "SELECT id, name, folder, sql, connection_id, created_at, updated_at, variables, column_layout FROM saved_queries WHERE id = ?1",
     )?;
 
     let mut rows = stmt.query([query_id])?;
@@ -633,6 +777,7 @@ pub fn get_saved_query(conn: &Connection, query_id: &str) -> SqliteResult<Option
             created_at: row.get(5)?,
             updated_at: row.get(6)?,
             variables: row.get(7)?,
+            column_layout: row.get(8)?,
         }))
     } else {
         Ok(None)
@@ -693,6 +838,17 @@ pub fn update_saved_query(conn: &Connection, update: &UpdateSavedQuery) -> Sqlit
     get_saved_query(conn, &update.id)
 }
 
+/// Store (or clear, with `None`) a saved query's result grid layout. Leaves
+/// `updated_at` alone: the layout is a view preference, not an edit.
+/// Returns false if the query doesn't exist.
+pub fn set_saved_query_column_layout(conn: &Connection, query_id: &str, layout: Option<&str>) -> SqliteResult<bool> {
+    let rows_affected = conn.execute(
+        "UPDATE saved_queries SET column_layout = ?1 WHERE id = ?2",
+        (layout, query_id),
+    )?;
+    Ok(rows_affected > 0)
+}
+
 /// Delete a saved query
 pub fn delete_saved_query(conn: &Connection, query_id: &str) -> SqliteResult<bool> {
     let rows_affected = conn.execute("DELETE FROM saved_queries WHERE id = ?1", [query_id])?;
@@ -714,6 +870,45 @@ pub fn batch_delete_saved_queries(conn: &Connection, ids: &[String]) -> SqliteRe
     conn.execute(&sql, params.as_slice())
 }
 
+/// Rename a saved-query folder, including nested folders beneath it
+/// (`reports` -> `archive` turns `reports/monthly` into `archive/monthly`).
+/// Matching `empty_folders` entries in app settings are rewritten in the same
+/// transaction. Returns the number of saved queries moved.
+pub fn rename_saved_query_folder(conn: &mut Connection, old_folder: &str, new_folder: &str) -> SqliteResult<usize> {
+    let tx = conn.transaction()?;
+    let now = chrono::Utc::now().to_rfc3339();
+    // substr() rather than LIKE so `%`/`_` in folder names match literally.
+    let moved = tx.execute(
+        r#"
+        UPDATE saved_queries
+        SET folder = ?2 || substr(folder, length(?1) + 1), updated_at = ?3
+        WHERE folder = ?1 OR substr(folder, 1, length(?1) + 1) = ?1 || '/'
+        "#,
+        (old_folder, new_folder, &now),
+    )?;
+
+    let mut settings = load_settings(&tx)?;
+    let child_prefix = format!("{}/", old_folder);
+    let mut changed = false;
+    for folder in settings.empty_folders.iter_mut() {
+        if folder == old_folder {
+            *folder = new_folder.to_string();
+            changed = true;
+        } else if let Some(rest) = folder.strip_prefix(&child_prefix) {
+            *folder = format!("{}/{}", new_folder, rest);
+            changed = true;
+        }
+    }
+    if changed {
+        let mut seen = std::collections::HashSet::new();
+        settings.empty_folders.retain(|f| seen.insert(f.clone()));
+        save_settings(&tx, &settings)?;
+    }
+
+    tx.commit()?;
+    Ok(moved)
+}
+
 // ==================== App Settings ====================
 
 /// Load app settings from the database, returns default if none exist
@@ -766,8 +961,8 @@ pub fn save_query_history(
 
     conn.execute(
         r#"
-        INSERT INTO query_history (id, connection_id, connection_name, sql, row_count, execution_time_ms, executed_at, result_columns, result_rows, schema, column_count, table_names, source)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+        INSERT INTO query_history (id, connection_id, connection_name, sql, row_count, execution_time_ms, executed_at, result_columns, result_rows, schema, column_count, table_names, source, is_mutation)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
         "#,
         (
             &entry.id,
@@ -783,6 +978,7 @@ pub fn save_query_history(
             &entry.column_count,
             &entry.table_names,
             &entry.source,
+            entry.is_mutation,
         ),
     )?;
 
@@ -1139,7 +1335,7 @@ pub fn load_query_history(
     only_legacy: bool,
 ) -> SqliteResult<Vec<QueryHistoryEntry>> {
     let mut sql = String::from(
-        "SELECT id, connection_id, connection_name, sql, row_count, execution_time_ms, executed_at, (result_columns IS NOT NULL) as has_results, schema, column_count, table_names, source FROM query_history WHERE 1=1"
+        "SELECT id, connection_id, connection_name, sql, row_count, execution_time_ms, executed_at, (result_columns IS NOT NULL) as has_results, schema, column_count, table_names, source, is_mutation FROM query_history WHERE 1=1"
     );
     let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
     let mut param_idx = 1;
@@ -1187,6 +1383,7 @@ pub fn load_query_history(
             column_count: row.get(9)?,
             table_names: row.get(10)?,
             source: row.get(11)?,
+            is_mutation: row.get(12)?,
         })
     })?;
 
@@ -1214,6 +1411,54 @@ pub fn batch_delete_query_history_entries(conn: &Connection, ids: &[String]) ->
     conn.execute(&sql, params.as_slice())
 }
 
+/// Delete query history entries matching all the given filters: a connection,
+/// executed before a timestamp (anything SQLite's `datetime()` parses, e.g.
+/// RFC 3339), and/or a full-text search as in `load_query_history`.
+/// Returns the number of entries deleted.
+pub fn delete_query_history_filtered(
+    conn: &Connection,
+    connection_id: Option<&str>,
+    before_date: Option<&str>,
+    search: Option<&str>,
+) -> SqliteResult<usize> {
+    let mut sql = String::from("DELETE FROM query_history WHERE 1=1");
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+    let mut param_idx = 1;
+
+    if let Some(cid) = connection_id {
+        sql.push_str(&format!(" AND connection_id = ?{}", param_idx));
+        params.push(Box::new(cid.to_string()));
+        param_idx += 1;
+    }
+
+    if let Some(before) = before_date {
+        sql.push_str(&format!(" AND datetime(executed_at) < datetime(?{})", param_idx));
+        params.push(Box::new(before.to_string()));
+        param_idx += 1;
+    }
+
+    if let Some(q) = search.filter(|q| !q.is_empty()) {
+        sql.push_str(&format!(
+            " AND rowid IN (SELECT rowid FROM query_history_fts WHERE query_history_fts MATCH ?{})",
+            param_idx
+        ));
+        params.push(Box::new(escape_fts5_query(q)));
+    }
+
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+    conn.execute(&sql, params_refs.as_slice())
+}
+
+/// Full SQL text of a history entry, exactly as it was sent to the server
+pub fn get_query_history_sql(conn: &Connection, entry_id: &str) -> SqliteResult<Option<String>> {
+    let mut stmt = conn.prepare("SELECT sql FROM query_history WHERE id = ?1")?;
+    let mut rows = stmt.query([entry_id])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
 /// Load cached result data for a specific history entry (decompresses if gzip-compressed)
 pub fn get_query_history_result(conn: &Connection, entry_id: &str) -> SqliteResult<Option<(String, String)>> {
     let mut stmt = conn.prepare(
@@ -1233,6 +1478,12 @@ pub fn get_query_history_result(conn: &Connection, entry_id: &str) -> SqliteResu
     }
 }
 
+/// Unique temp dir per test so parallel `cargo test` runs never collide.
+#[cfg(test)]
+fn temp_db_dir(tag: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("pharos_test_{}_{}", tag, uuid::Uuid::new_v4()))
+}
+
 #[cfg(test)]
 mod workspace_name_tests {
     use super::resolve_workspace_name;
@@ -1299,12 +1550,6 @@ mod budget_tests {
 mod workspace_roundtrip_tests {
     use super::*;
     use crate::models::WorkspaceUpsert;
-    use std::path::PathBuf;
-
-    /// Unique temp dir per test so parallel `cargo test` runs never collide.
-    fn temp_db_dir(tag: &str) -> PathBuf {
-        std::env::temp_dir().join(format!("pharos_test_{}_{}", tag, uuid::Uuid::new_v4()))
-    }
 
     /// Real "now" (offset by a few seconds for ordering) so entries never trip
     /// the 90-day retention prune that save_query_history runs periodically.
@@ -1326,6 +1571,7 @@ mod workspace_roundtrip_tests {
             column_count: Some(2),
             table_names: Some("t".to_string()),
             source: None,
+            is_mutation: false,
         }
     }
 
@@ -1590,11 +1836,6 @@ mod workspace_roundtrip_tests {
 #[cfg(test)]
 mod history_source_tests {
     use super::*;
-    use std::path::PathBuf;
-
-    fn temp_db_dir(tag: &str) -> PathBuf {
-        std::env::temp_dir().join(format!("pharos_test_{}_{}", tag, uuid::Uuid::new_v4()))
-    }
 
     fn history_entry(id: &str, source: Option<&str>) -> QueryHistoryEntry {
         QueryHistoryEntry {
@@ -1610,9 +1851,30 @@ mod history_source_tests {
             column_count: Some(2),
             table_names: None,
             source: source.map(|s| s.to_string()),
+            is_mutation: false,
         }
     }
 
+    #[test]
+    fn history_sql_round_trips_exactly() {
+        let dir = temp_db_dir("history_sql");
+        let conn = init_database(&dir).expect("init_database");
+
+        let sql = "-- monthly report\r\nSELECT 'it''s', \"Weird \"\"Col\"\"\", $tag$a\\b\ttab$tag$\n\
+                   FROM t\n\n  WHERE note = E'line\\nbreak' /* ünïcode ✓ */;\n";
+        let mut entry = history_entry("h_sql", None);
+        entry.sql = sql.to_string();
+        save_query_history(&conn, &entry, None, None).expect("save entry");
+
+        assert_eq!(get_query_history_sql(&conn, "h_sql").unwrap().as_deref(), Some(sql));
+        assert_eq!(get_query_history_sql(&conn, "missing").unwrap(), None);
+        let loaded = load_query_history(&conn, Some("c1"), None, 10, 0, false).expect("load_query_history");
+        assert_eq!(loaded.iter().find(|e| e.id == "h_sql").unwrap().sql, sql);
+
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn source_tag_round_trips_and_defaults_to_none() {
         let dir = temp_db_dir("history_source");
@@ -1633,4 +1895,169 @@ mod history_source_tests {
         drop(conn);
         let _ = std::fs::remove_dir_all(&dir);
     }
+
+    #[test]
+    fn filtered_delete_only_removes_matching_entries() {
+        let dir = temp_db_dir("history_filtered_delete");
+        let conn = init_database(&dir).expect("init_database");
+
+        // Within the 90-day retention window so save_query_history's prune keeps it.
+        let mut old = history_entry("old", None);
+        old.executed_at = (chrono::Utc::now() - chrono::Duration::days(30)).to_rfc3339();
+        let mut other_conn = history_entry("other", None);
+        other_conn.connection_id = "c2".to_string();
+        let recent = history_entry("recent", None);
+        for e in [&old, &other_conn, &recent] {
+            save_query_history(&conn, e, None, None).expect("save");
+        }
+
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(1)).to_rfc3339();
+        let deleted = delete_query_history_filtered(&conn, Some("c1"), Some(&cutoff), None)
+            .expect("delete by connection + date");
+        assert_eq!(deleted, 1);
+
+        let deleted = delete_query_history_filtered(&conn, None, None, Some("t_recent"))
+            .expect("delete by search");
+        assert_eq!(deleted, 1);
+
+        let remaining = load_query_history(&conn, None, None, 10, 0, false).expect("load");
+        let ids: Vec<&str> = remaining.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["other"]);
+
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+/// Per-connection flags stored as columns on `connections` survive the
+/// save_connection -> load_connections round trip on a real on-disk DB.
+#[cfg(test)]
+mod connection_config_tests {
+    use super::*;
+
+    fn config(id: &str) -> ConnectionConfig {
+        ConnectionConfig {
+            id: id.to_string(),
+            name: format!("conn-{}", id),
+            host: "localhost".to_string(),
+            port: 5432,
+            database: "postgres".to_string(),
+            username: "postgres".to_string(),
+            password: String::new(),
+            ssl_mode: SslMode::Prefer,
+            color: None,
+            default_schema: None,
+            disable_history: false,
+            masked_columns: vec![],
+            labels: vec![],
+            ssh_tunnel: None,
+        }
+    }
+
+    #[test]
+    fn connection_flags_round_trip() {
+        let dir = temp_db_dir("connection_flags");
+        let conn = init_database(&dir).expect("init_database");
+
+        let mut prod = config("prod");
+        prod.disable_history = true;
+        prod.masked_columns = vec!["ssn".to_string(), "*password*".to_string()];
+        prod.labels = vec!["pii".to_string(), "eu-region".to_string()];
+        prod.ssh_tunnel = Some(SshTunnelConfig {
+            host: "bastion.example.com".to_string(),
+            port: 2222,
+            username: "deploy".to_string(),
+            auth_method: SshAuthMethod::Password,
+            private_key_path: None,
+            password: "hunter2".to_string(),
+        });
+        save_connection(&conn, &prod).expect("save prod");
+        save_connection(&conn, &config("dev")).expect("save dev");
+
+        let loaded = load_connections(&conn).expect("load_connections");
+        let prod = loaded.iter().find(|c| c.id == "prod").expect("prod present");
+        let dev = loaded.iter().find(|c| c.id == "dev").expect("dev present");
+        assert!(prod.disable_history);
+        assert!(!dev.disable_history);
+        assert_eq!(prod.masked_columns, vec!["ssn", "*password*"]);
+        assert!(dev.masked_columns.is_empty());
+        assert_eq!(prod.labels, vec!["pii", "eu-region"]);
+        assert!(dev.labels.is_empty());
+        let ssh = prod.ssh_tunnel.as_ref().expect("prod tunnel");
+        assert_eq!((ssh.host.as_str(), ssh.port, ssh.username.as_str()), ("bastion.example.com", 2222, "deploy"));
+        assert_eq!(ssh.auth_method, SshAuthMethod::Password);
+        assert!(ssh.password.is_empty(), "SSH secret must not be stored in SQLite");
+        assert!(dev.ssh_tunnel.is_none());
+
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
+
+#[cfg(test)]
+mod saved_query_folder_tests {
+    use super::*;
+
+    fn saved(conn: &Connection, id: &str, folder: Option<&str>) {
+        let query = CreateSavedQuery {
+            name: id.to_string(),
+            folder: folder.map(|f| f.to_string()),
+            sql: "SELECT 1".to_string(),
+            connection_id: None,
+            variables: None,
+        };
+        create_saved_query(conn, id, &query).expect("create_saved_query");
+    }
+
+    #[test]
+    fn column_layout_round_trips_without_touching_updated_at() {
+        let dir = temp_db_dir("column_layout");
+        let conn = init_database(&dir).expect("init_database");
+
+        saved(&conn, "q1", None);
+        let before = get_saved_query(&conn, "q1").unwrap().unwrap();
+        assert_eq!(before.column_layout, None);
+
+        let layout = r#"{"order":["b","a"],"hidden":["c"],"widths":{"a":120}}"#;
+        assert!(set_saved_query_column_layout(&conn, "q1", Some(layout)).expect("set layout"));
+        let after = get_saved_query(&conn, "q1").unwrap().unwrap();
+        assert_eq!(after.column_layout.as_deref(), Some(layout));
+        assert_eq!(after.updated_at, before.updated_at);
+
+        assert!(set_saved_query_column_layout(&conn, "q1", None).expect("clear layout"));
+        assert_eq!(load_saved_queries(&conn).unwrap()[0].column_layout, None);
+        assert!(!set_saved_query_column_layout(&conn, "missing", Some(layout)).expect("missing"));
+
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rename_rewrites_nested_folders_and_empty_folders() {
+        let dir = temp_db_dir("folder_rename");
+        let mut conn = init_database(&dir).expect("init_database");
+
+        saved(&conn, "q1", Some("reports"));
+        saved(&conn, "q2", Some("reports/monthly"));
+        saved(&conn, "q3", Some("reports-old"));
+        saved(&conn, "q4", None);
+        let settings = AppSettings {
+            empty_folders: vec!["reports/yearly".to_string(), "misc".to_string()],
+            ..AppSettings::default()
+        };
+        save_settings(&conn, &settings).expect("save_settings");
+
+        let moved = rename_saved_query_folder(&mut conn, "reports", "archive").expect("rename");
+        assert_eq!(moved, 2);
+
+        let folder_of = |id: &str| get_saved_query(&conn, id).unwrap().unwrap().folder;
+        assert_eq!(folder_of("q1").as_deref(), Some("archive"));
+        assert_eq!(folder_of("q2").as_deref(), Some("archive/monthly"));
+        assert_eq!(folder_of("q3").as_deref(), Some("reports-old"));
+        assert_eq!(folder_of("q4"), None);
+        assert_eq!(load_settings(&conn).unwrap().empty_folders, vec!["archive/yearly", "misc"]);
+
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,330 @@
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::os::fd::{AsRawFd, RawFd};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use ssh2::{CheckResult, Channel, HashType, KnownHostFileKind, Session};
+
+use crate::models::{SshAuthMethod, SshTunnelConfig};
+
+/// How long to wait for the bastion's TCP connect and SSH handshake.
+const SSH_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Keepalive interval so idle tunnels aren't dropped by NAT or the bastion.
+const SSH_KEEPALIVE_SECONDS: u32 = 30;
+/// Longest wait for socket activity between pump passes, so shutdown and
+/// keepalives are still noticed on an idle tunnel.
+const IDLE_WAIT_MS: i32 = 100;
+
+/// A local port forwarded through an SSH bastion to a database host.
+/// Each connection accepted on `127.0.0.1:<local_port>` gets its own
+/// direct-tcpip channel. Dropping the tunnel closes it.
+pub struct SshTunnel {
+    local_port: u16,
+    shutdown: Arc<AtomicBool>,
+    pump: Option<JoinHandle<()>>,
+}
+
+impl SshTunnel {
+    /// Connect and authenticate to the bastion, then forward a fresh local
+    /// port to `target_host:target_port` (as resolved by the bastion).
+    pub fn open(config: &SshTunnelConfig, target_host: &str, target_port: u16) -> io::Result<Self> {
+        let (session, session_fd) = connect_session(config)?;
+
+        let listener = TcpListener::bind(("127.0.0.1", 0))?;
+        listener.set_nonblocking(true)?;
+        let local_port = listener.local_addr()?.port();
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let pump = {
+            let shutdown = shutdown.clone();
+            let target_host = target_host.to_string();
+            std::thread::Builder::new()
+                .name(format!("ssh-tunnel-{}", local_port))
+                .spawn(move || pump(session, session_fd, listener, &target_host, target_port, &shutdown))?
+        };
+
+        Ok(SshTunnel { local_port, shutdown, pump: Some(pump) })
+    }
+
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(pump) = self.pump.take() {
+            let _ = pump.join();
+        }
+    }
+}
+
+fn ssh_error(context: &str, e: ssh2::Error) -> io::Error {
+    io::Error::other(format!("SSH {}: {}", context, e.message()))
+}
+
+/// Open an authenticated session to the bastion. Also returns the session's
+/// socket, for waiting on it.
+fn connect_session(config: &SshTunnelConfig) -> io::Result<(Session, RawFd)> {
+    let addr = (config.host.as_str(), config.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::other(format!("SSH host not found: {}", config.host)))?;
+    let tcp = TcpStream::connect_timeout(&addr, SSH_CONNECT_TIMEOUT)?;
+    let session_fd = tcp.as_raw_fd();
+
+    let mut session = Session::new().map_err(|e| ssh_error("session", e))?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(SSH_CONNECT_TIMEOUT.as_millis() as u32);
+    session.handshake().map_err(|e| ssh_error("handshake", e))?;
+    verify_host_key(&session, config)?;
+
+    let secret = Some(config.password.as_str()).filter(|p| !p.is_empty());
+    match config.auth_method {
+        SshAuthMethod::PrivateKey => {
+            let key_path = config
+                .private_key_path
+                .as_deref()
+                .filter(|p| !p.is_empty())
+                .ok_or_else(|| io::Error::other("SSH private key path is required"))?;
+            session
+                .userauth_pubkey_file(&config.username, None, Path::new(key_path), secret)
+                .map_err(|e| ssh_error("key authentication", e))?;
+        }
+        SshAuthMethod::Password => {
+            session
+                .userauth_password(&config.username, secret.unwrap_or(""))
+                .map_err(|e| ssh_error("password authentication", e))?;
+        }
+    }
+    if !session.authenticated() {
+        return Err(io::Error::other("SSH authentication failed"));
+    }
+
+    session.set_timeout(0);
+    session.set_keepalive(true, SSH_KEEPALIVE_SECONDS);
+    Ok((session, session_fd))
+}
+
+/// The user's OpenSSH `known_hosts` file.
+fn known_hosts_path() -> Option<std::path::PathBuf> {
+    std::env::var_os("HOME").map(|home| Path::new(&home).join(".ssh").join("known_hosts"))
+}
+
+/// Check the bastion's host key against `~/.ssh/known_hosts` before sending any
+/// credentials. An unknown or changed key fails the connection; the error
+/// carries the key's SHA256 fingerprint so it can be verified and added.
+fn verify_host_key(session: &Session, config: &SshTunnelConfig) -> io::Result<()> {
+    let (key, _) = session
+        .host_key()
+        .ok_or_else(|| io::Error::other("SSH server sent no host key"))?;
+    let fingerprint = session
+        .host_key_hash(HashType::Sha256)
+        .map(|hash| {
+            use base64::Engine;
+            format!("SHA256:{}", base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash))
+        })
+        .unwrap_or_default();
+
+    let mut known_hosts = session.known_hosts().map_err(|e| ssh_error("known hosts", e))?;
+    let path = known_hosts_path().filter(|p| p.exists());
+    if let Some(path) = &path {
+        known_hosts
+            .read_file(path, KnownHostFileKind::OpenSSH)
+            .map_err(|e| ssh_error("known hosts", e))?;
+    }
+
+    match known_hosts.check_port(&config.host, config.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(io::Error::other(format!(
+            "SSH host key for {} has changed ({}); it does not match known_hosts",
+            config.host, fingerprint
+        ))),
+        CheckResult::NotFound | CheckResult::Failure => Err(io::Error::other(format!(
+            "SSH host {} is not in {} ({}); connect once with ssh to verify and add it",
+            config.host,
+            path.or_else(known_hosts_path)
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "known_hosts".to_string()),
+            fingerprint
+        ))),
+    }
+}
+
+/// One local client connection and its channel, with bytes read from one
+/// side but not yet accepted by the other.
+struct Forward {
+    socket: TcpStream,
+    channel: Channel,
+    to_server: Vec<u8>,
+    to_client: Vec<u8>,
+    client_closed: bool,
+}
+
+impl Forward {
+    /// Move whatever data is ready in both directions. Returns whether any
+    /// bytes moved, or an error once either side is finished.
+    fn pump(&mut self, buf: &mut [u8]) -> io::Result<bool> {
+        let mut moved = false;
+
+        if self.to_server.is_empty() && !self.client_closed {
+            match self.socket.read(buf) {
+                Ok(0) => {
+                    self.client_closed = true;
+                    let _ = self.channel.send_eof();
+                }
+                Ok(n) => self.to_server.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !self.to_server.is_empty() {
+            match self.channel.write(&self.to_server) {
+                Ok(n) => {
+                    self.to_server.drain(..n);
+                    moved = true;
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if self.to_client.is_empty() {
+            match self.channel.read(buf) {
+                Ok(0) if self.channel.eof() => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => self.to_client.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !self.to_client.is_empty() {
+            match self.socket.write(&self.to_client) {
+                Ok(n) => {
+                    self.to_client.drain(..n);
+                    moved = true;
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(moved)
+    }
+}
+
+/// Accept local connections and shuttle bytes until `shutdown` is set. All
+/// channels share the session, so one thread drives them in non-blocking mode,
+/// sleeping in `poll` whenever a pass moves nothing.
+fn pump(
+    session: Session,
+    session_fd: RawFd,
+    listener: TcpListener,
+    target_host: &str,
+    target_port: u16,
+    shutdown: &AtomicBool,
+) {
+    let mut forwards: Vec<Forward> = Vec::new();
+    // Accepted connections whose channel isn't open yet. libssh2 opens one
+    // channel at a time; an open that would block resumes on a later pass.
+    let mut pending: VecDeque<TcpStream> = VecDeque::new();
+    let mut buf = vec![0u8; 32 * 1024];
+    session.set_blocking(false);
+
+    while !shutdown.load(Ordering::SeqCst) {
+        let mut moved = false;
+
+        while let Ok((socket, _)) = listener.accept() {
+            pending.push_back(socket);
+            moved = true;
+        }
+
+        while !pending.is_empty() {
+            match session.channel_direct_tcpip(target_host, target_port, None) {
+                Ok(channel) => {
+                    let socket = pending.pop_front().expect("pending is non-empty");
+                    if socket.set_nonblocking(true).is_ok() {
+                        forwards.push(Forward {
+                            socket,
+                            channel,
+                            to_server: Vec::new(),
+                            to_client: Vec::new(),
+                            client_closed: false,
+                        });
+                    }
+                    moved = true;
+                }
+                Err(e) => {
+                    let e = io::Error::from(e);
+                    if e.kind() == io::ErrorKind::WouldBlock {
+                        break;
+                    }
+                    pending.pop_front();
+                    log::warn!("SSH tunnel could not reach {}:{}: {}", target_host, target_port, e);
+                }
+            }
+        }
+
+        forwards.retain_mut(|forward| match forward.pump(&mut buf) {
+            Ok(progress) => {
+                moved |= progress;
+                true
+            }
+            Err(_) => {
+                let _ = forward.channel.close();
+                false
+            }
+        });
+
+        let _ = session.keepalive_send();
+        if !moved {
+            wait_for_io(&session, session_fd, &listener, &forwards);
+        }
+    }
+
+    for mut forward in forwards {
+        let _ = forward.channel.close();
+    }
+    let _ = session.disconnect(None, "tunnel closed", None);
+}
+
+/// Block until the listener, the session socket or a client socket can make
+/// progress, or `IDLE_WAIT_MS` passes. Returns at once if a channel already
+/// holds data libssh2 read while servicing another channel.
+fn wait_for_io(session: &Session, session_fd: RawFd, listener: &TcpListener, forwards: &[Forward]) {
+    if forwards.iter().any(|f| f.to_client.is_empty() && f.channel.read_window().available > 0) {
+        return;
+    }
+
+    let session_events = match session.block_directions() {
+        ssh2::BlockDirections::Outbound | ssh2::BlockDirections::Both => libc::POLLIN | libc::POLLOUT,
+        _ => libc::POLLIN,
+    };
+    let mut fds = vec![
+        libc::pollfd { fd: listener.as_raw_fd(), events: libc::POLLIN, revents: 0 },
+        libc::pollfd { fd: session_fd, events: session_events, revents: 0 },
+    ];
+    for forward in forwards {
+        let mut events = 0;
+        if forward.to_server.is_empty() && !forward.client_closed {
+            events |= libc::POLLIN;
+        }
+        if !forward.to_client.is_empty() {
+            events |= libc::POLLOUT;
+        }
+        if events != 0 {
+            fds.push(libc::pollfd { fd: forward.socket.as_raw_fd(), events, revents: 0 });
+        }
+    }
+
+    // SAFETY: `fds` is a valid, exclusively borrowed array of `fds.len()` pollfds
+    // whose descriptors stay open for the duration of the call.
+    unsafe {
+        libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, IDLE_WAIT_MS);
+    }
+}
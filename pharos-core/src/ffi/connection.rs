@@ -19,6 +19,20 @@ pub extern "C" fn pharos_load_connections() -> *mut c_char {
     })
 }
 
+/// Load saved connections that carry `label` (case-insensitive). Returns JSON array.
+#[no_mangle]
+pub extern "C" fn pharos_filter_connections_by_label(label: *const c_char) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let rt = runtime();
+        let label = unsafe { c_str_to_string(label) };
+        match rt.block_on(crate::commands::filter_connections_by_label(label, state)) {
+            Ok(configs) => to_json_c_string(&configs),
+            Err(e) => to_c_string(&serde_json::json!({"error": e.to_string()}).to_string()),
+        }
+    })
+}
+
 /// Save a connection config. `json` is a JSON-encoded ConnectionConfig.
 /// Returns NULL on success, or an error message string (caller must free).
 #[no_mangle]
@@ -93,6 +107,64 @@ pub extern "C" fn pharos_connect(
     });
 }
 
+/// Connect to another database on the server of `connection_id`. The derived
+/// connection is only saved when `persist` is true. Returns JSON ConnectionInfo via callback.
+#[no_mangle]
+pub extern "C" fn pharos_connect_to_database(
+    connection_id: *const c_char,
+    database: *const c_char,
+    persist: bool,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let id = unsafe { c_str_to_string(connection_id) };
+    let db = unsafe { c_str_to_string(database) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::connect_to_database(id, db, persist, state).await {
+            Ok(info) => {
+                let json = serde_json::to_string(&info).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Connections closed for inactivity since the last call, as a JSON array of
+/// connection IDs. Poll this to mirror idle disconnects in the UI. Caller must free.
+#[no_mangle]
+pub extern "C" fn pharos_take_idle_disconnected() -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        to_json_c_string(&crate::commands::take_idle_disconnected(state))
+    })
+}
+
+/// Get server capabilities. Returns JSON ServerCapabilities via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_server_capabilities(
+    connection_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let id = unsafe { c_str_to_string(connection_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_server_capabilities(id, state).await {
+            Ok(capabilities) => {
+                let json = serde_json::to_string(&capabilities).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
 /// Disconnect from PostgreSQL. Calls `callback` when done.
 #[no_mangle]
 pub extern "C" fn pharos_disconnect(
@@ -130,7 +202,7 @@ pub extern "C" fn pharos_test_connection(
                 return;
             }
         };
-        match crate::commands::test_connection(config).await {
+        match crate::commands::test_connection(config, app_state()).await {
             Ok(result) => {
                 let json = serde_json::to_string(&result).unwrap_or_default();
                 callback_ok(callback, ctx, &json);
@@ -139,3 +211,24 @@ pub extern "C" fn pharos_test_connection(
         }
     });
 }
+
+/// Test every saved connection, bounded by the connection-attempt limit.
+/// Returns a JSON array of ConnectionTestReport via callback.
+#[no_mangle]
+pub extern "C" fn pharos_test_all_connections(
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::test_all_connections(state).await {
+            Ok(reports) => {
+                let json = serde_json::to_string(&reports).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
@@ -10,6 +10,7 @@ use super::*;
 
 const SHUTDOWN_PER_POOL_BUDGET: Duration = Duration::from_secs(2);
 const SHUTDOWN_TOTAL_BUDGET: Duration = Duration::from_secs(4);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
 
 // ---------------------------------------------------------------------------
 // Lifecycle
@@ -20,8 +21,8 @@ const SHUTDOWN_TOTAL_BUDGET: Duration = Duration::from_secs(4);
 /// Returns true on success.
 #[no_mangle]
 pub extern "C" fn pharos_init(app_data_dir: *const c_char) -> bool {
-    // Initialize logger
-    let _ = env_logger::try_init();
+    // Initialize logger (also captures server notices for command results)
+    crate::db::notices::init_logger();
 
     let dir = unsafe { c_str_to_string(app_data_dir) };
     let path = std::path::PathBuf::from(&dir);
@@ -64,9 +65,22 @@ pub extern "C" fn pharos_init(app_data_dir: *const c_char) -> bool {
                 state.set_config(config);
             }
         }
+        if let Ok(settings) = crate::db::sqlite::load_settings(&db) {
+            state.set_connect_limit(settings.query.max_concurrent_connects as usize);
+        }
     }
 
     let _ = APP_STATE.set(state);
+
+    // Periodically close pools idle past the configured timeout.
+    runtime().spawn(async {
+        let mut tick = tokio::time::interval(IDLE_CHECK_INTERVAL);
+        loop {
+            tick.tick().await;
+            crate::commands::close_idle_connections(app_state()).await;
+        }
+    });
+
     true
 }
 
@@ -96,6 +110,11 @@ pub extern "C" fn pharos_shutdown() {
         let mut conns = state.connections.lock().unwrap_or_else(|e| e.into_inner());
         conns.drain().map(|(_, p)| p).collect()
     };
+    // In-memory connections from `connect_to_database` don't outlive the session.
+    {
+        let mut configs = state.connection_configs.lock().unwrap_or_else(|e| e.into_inner());
+        configs.retain(|id, _| !crate::commands::connection::is_derived_connection(id));
+    }
 
     let _ = runtime.block_on(async {
         tokio::time::timeout(SHUTDOWN_TOTAL_BUDGET, async {
@@ -25,7 +25,30 @@ pub extern "C" fn pharos_format_sql(sql: *const c_char) -> *mut c_char {
     })
 }
 
+/// Pretty-print a JSON/JSONB value with sorted keys. Returns NULL if the input
+/// isn't valid JSON. Caller must free.
+#[no_mangle]
+pub extern "C" fn pharos_format_json_value(json: *const c_char) -> *mut c_char {
+    ffi_sync!({
+        let json_str = unsafe { c_str_to_string(json) };
+        match crate::commands::format_json_value(&json_str) {
+            Ok(formatted) => to_c_string(&formatted),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Validate a jsonb cell edit. Returns JSON JsonbValidation. Caller must free.
+#[no_mangle]
+pub extern "C" fn pharos_validate_jsonb(json: *const c_char) -> *mut c_char {
+    ffi_sync!({
+        let json_str = unsafe { c_str_to_string(json) };
+        to_json_c_string(&crate::commands::validate_jsonb(&json_str))
+    })
+}
+
 /// Execute a SQL query. Returns JSON QueryResult via callback.
+/// `run_as_role` (nullable) runs the query under `SET ROLE`.
 #[no_mangle]
 pub extern "C" fn pharos_execute_query(
     connection_id: *const c_char,
@@ -34,6 +57,43 @@ pub extern "C" fn pharos_execute_query(
     limit: i32,
     schema: *const c_char,
     source: *const c_char,
+    run_as_role: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let sql_str = unsafe { c_str_to_string(sql) };
+    let qid = unsafe { c_str_to_option(query_id) };
+    let schema_str = unsafe { c_str_to_option(schema) };
+    let source_str = unsafe { c_str_to_option(source) };
+    let role = unsafe { c_str_to_option(run_as_role) };
+    let lim = if limit > 0 { Some(limit as u32) } else { None };
+
+    let ctx = context as usize;
+    ffi_spawn!(callback, context, async move {
+
+        match crate::commands::execute_query(conn_id, sql_str, qid, lim, schema_str, source_str, role, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Execute a SQL query like `pharos_execute_query`, returning JSON
+/// ColumnarQueryResult (values grouped per column) via callback.
+#[no_mangle]
+pub extern "C" fn pharos_execute_query_columnar(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    query_id: *const c_char,
+    limit: i32,
+    schema: *const c_char,
+    source: *const c_char,
+    run_as_role: *const c_char,
     callback: AsyncCallback,
     context: *mut std::ffi::c_void,
 ) {
@@ -43,12 +103,43 @@ pub extern "C" fn pharos_execute_query(
     let qid = unsafe { c_str_to_option(query_id) };
     let schema_str = unsafe { c_str_to_option(schema) };
     let source_str = unsafe { c_str_to_option(source) };
+    let role = unsafe { c_str_to_option(run_as_role) };
     let lim = if limit > 0 { Some(limit as u32) } else { None };
 
     let ctx = context as usize;
     ffi_spawn!(callback, context, async move {
+        match crate::commands::execute_query_columnar(conn_id, sql_str, qid, lim, schema_str, source_str, role, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
 
-        match crate::commands::execute_query(conn_id, sql_str, qid, lim, schema_str, source_str, state).await {
+/// Fetch the plan for a single statement, then execute it on the same connection.
+/// Returns JSON ExplainAndRunResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_explain_and_run(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    query_id: *const c_char,
+    limit: i32,
+    schema: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let sql_str = unsafe { c_str_to_string(sql) };
+    let qid = unsafe { c_str_to_option(query_id) };
+    let schema_str = unsafe { c_str_to_option(schema) };
+    let lim = if limit > 0 { Some(limit as u32) } else { None };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::explain_and_run(conn_id, sql_str, qid, lim, schema_str, state).await {
             Ok(result) => {
                 let json = serde_json::to_string(&result).unwrap_or_default();
                 callback_ok(callback, ctx, &json);
@@ -58,6 +149,177 @@ pub extern "C" fn pharos_execute_query(
     });
 }
 
+/// EXPLAIN one statement in `format` (json/text/xml/yaml); with `analyze` it
+/// runs inside a rolled-back transaction. Returns JSON ExplainResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_explain_query(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    analyze: bool,
+    format: *const c_char,
+    schema: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let sql_str = unsafe { c_str_to_string(sql) };
+    let format_str = unsafe { c_str_to_option(format) }.unwrap_or_else(|| "json".to_string());
+    let schema_str = unsafe { c_str_to_option(schema) };
+
+    let ctx = context as usize;
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::explain_query(conn_id, sql_str, analyze, format_str, schema_str, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Run one statement and report wall-clock time alongside its
+/// pg_stat_statements counters. `query_id` (nullable) lets `pharos_cancel_query`
+/// stop it. Returns JSON QueryProfile via callback.
+#[no_mangle]
+pub extern "C" fn pharos_profile_query(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    schema: *const c_char,
+    query_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let sql_str = unsafe { c_str_to_string(sql) };
+    let schema_str = unsafe { c_str_to_option(schema) };
+    let qid = unsafe { c_str_to_option(query_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::profile_query(conn_id, sql_str, schema_str, qid, state).await {
+            Ok(profile) => {
+                let json = serde_json::to_string(&profile).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Execute a query extracting jsonb paths from named columns server-side.
+/// `paths_json` is a JSON array of JsonPathExtraction. Returns JSON QueryResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_execute_query_with_json_paths(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    paths_json: *const c_char,
+    query_id: *const c_char,
+    limit: i32,
+    schema: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let sql_str = unsafe { c_str_to_string(sql) };
+    let paths_str = unsafe { c_str_to_string(paths_json) };
+    let qid = unsafe { c_str_to_option(query_id) };
+    let schema_str = unsafe { c_str_to_option(schema) };
+    let lim = if limit > 0 { Some(limit as u32) } else { None };
+
+    let ctx = context as usize;
+    ffi_spawn!(callback, context, async move {
+        let paths: Vec<crate::commands::JsonPathExtraction> = match serde_json::from_str(&paths_str) {
+            Ok(p) => p,
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::execute_query_with_json_paths(conn_id, sql_str, paths, qid, lim, schema_str, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Open a pagination cursor for a read query. Returns JSON CursorHandle via callback.
+#[no_mangle]
+pub extern "C" fn pharos_open_cursor(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    schema: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let sql_str = unsafe { c_str_to_string(sql) };
+    let schema_str = unsafe { c_str_to_option(schema) };
+
+    let ctx = context as usize;
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::open_cursor(conn_id, sql_str, schema_str, state).await {
+            Ok(handle) => {
+                let json = serde_json::to_string(&handle).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Fetch the next `count` rows from a cursor. `query_id` (nullable) lets
+/// `pharos_cancel_query` stop the fetch. Returns JSON QueryResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_fetch_cursor(
+    cursor_id: *const c_char,
+    count: u32,
+    query_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let id = unsafe { c_str_to_string(cursor_id) };
+    let qid = unsafe { c_str_to_option(query_id) };
+
+    let ctx = context as usize;
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::fetch_cursor(id, count, qid, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Close a cursor. Returns "true" (closed) or "false" (unknown id) via callback.
+#[no_mangle]
+pub extern "C" fn pharos_close_cursor(
+    cursor_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let id = unsafe { c_str_to_string(cursor_id) };
+
+    let ctx = context as usize;
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::close_cursor(id, state).await {
+            Ok(closed) => callback_ok(callback, ctx, if closed { "true" } else { "false" }),
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
 /// Execute a statement (INSERT/UPDATE/DELETE). Returns JSON ExecuteResult via callback.
 #[no_mangle]
 pub extern "C" fn pharos_execute_statement(
@@ -85,6 +347,37 @@ pub extern "C" fn pharos_execute_statement(
     });
 }
 
+/// Execute a DML statement only if EXPLAIN estimates it touches at most
+/// `max_rows` rows (<= 0 uses the setting), or `force` is set.
+/// Returns JSON GuardedExecuteResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_execute_with_rowcount_guard(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    schema: *const c_char,
+    max_rows: i64,
+    force: bool,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let sql_str = unsafe { c_str_to_string(sql) };
+    let schema_str = unsafe { c_str_to_option(schema) };
+    let max = if max_rows > 0 { Some(max_rows as u64) } else { None };
+
+    let ctx = context as usize;
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::execute_with_rowcount_guard(conn_id, sql_str, schema_str, max, force, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
 /// Fetch more rows. Returns JSON QueryResult via callback.
 #[no_mangle]
 pub extern "C" fn pharos_fetch_more_rows(
@@ -114,6 +407,20 @@ pub extern "C" fn pharos_fetch_more_rows(
     });
 }
 
+/// Preview the SQL `pharos_fetch_more_rows` would run for a page. Returns JSON
+/// `{"sql": ...}`, or `{"error": ...}` for a bad limit/offset. Caller must free.
+#[no_mangle]
+pub extern "C" fn pharos_preview_pagination_sql(sql: *const c_char, limit: i64, offset: i64) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let sql_str = unsafe { c_str_to_string(sql) };
+        match crate::commands::preview_pagination_sql(&sql_str, limit, offset, state) {
+            Ok(wrapped) => to_c_string(&serde_json::json!({"sql": wrapped}).to_string()),
+            Err(e) => to_c_string(&serde_json::json!({"error": e}).to_string()),
+        }
+    })
+}
+
 /// Cancel a running query. Returns immediately (synchronous).
 #[no_mangle]
 pub extern "C" fn pharos_cancel_query(
@@ -136,6 +443,72 @@ pub extern "C" fn pharos_cancel_query(
     });
 }
 
+/// Override a connection's statement timeout until disconnect; a negative
+/// `timeout_seconds` restores the settings' value and one too large to apply is
+/// an error. Returns JSON SessionTimeout via callback.
+#[no_mangle]
+pub extern "C" fn pharos_set_session_timeout(
+    connection_id: *const c_char,
+    timeout_seconds: i64,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+
+    let ctx = context as usize;
+    ffi_spawn!(callback, context, async move {
+        let seconds = match u32::try_from(timeout_seconds) {
+            Ok(s) => Some(s),
+            Err(_) if timeout_seconds < 0 => None,
+            Err(_) => {
+                callback_err(callback, ctx, &format!("Statement timeout too large: {}s", timeout_seconds));
+                return;
+            }
+        };
+        match crate::commands::set_session_timeout(conn_id, seconds, state).await {
+            Ok(timeout) => {
+                let json = serde_json::to_string(&timeout).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Get a connection's effective statement timeout. Returns JSON SessionTimeout via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_session_timeout(
+    connection_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+
+    let ctx = context as usize;
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_session_timeout(conn_id, state).await {
+            Ok(timeout) => {
+                let json = serde_json::to_string(&timeout).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// List running queries (optionally for one connection; nullable) as a JSON
+/// array of `{queryId, connectionId, backendPid, elapsedMs}`.
+#[no_mangle]
+pub extern "C" fn pharos_list_running_queries(connection_id: *const c_char) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let conn_id = unsafe { c_str_to_option(connection_id) };
+        to_json_c_string(&crate::commands::list_running_queries(conn_id, state))
+    })
+}
+
 /// Validate SQL syntax. Returns JSON ValidationResult via callback.
 #[no_mangle]
 pub extern "C" fn pharos_validate_sql(
@@ -162,3 +535,85 @@ pub extern "C" fn pharos_validate_sql(
         }
     });
 }
+
+/// Validate each statement of a multi-statement script. Returns a JSON array of
+/// StatementValidation (script-relative offsets and error positions) via callback.
+#[no_mangle]
+pub extern "C" fn pharos_validate_script(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    schema: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let sql_str = unsafe { c_str_to_string(sql) };
+    let schema_str = unsafe { c_str_to_option(schema) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::validate_script(conn_id, sql_str, schema_str, state).await {
+            Ok(results) => {
+                let json = serde_json::to_string(&results).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Store an EXPLAIN (FORMAT JSON) result for `sql` so later runs can be compared.
+/// Returns the StoredPlan JSON, or error JSON.
+#[no_mangle]
+pub extern "C" fn pharos_record_plan(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    plan_json: *const c_char,
+) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let conn_id = unsafe { c_str_to_string(connection_id) };
+        let sql_str = unsafe { c_str_to_string(sql) };
+        let plan = unsafe { c_str_to_string(plan_json) };
+        match crate::commands::record_plan(conn_id, sql_str, plan, state) {
+            Ok(stored) => to_json_c_string(&stored),
+            Err(e) => to_c_string(&serde_json::json!({"error": e}).to_string()),
+        }
+    })
+}
+
+/// Plans stored for `sql` on a connection, oldest first. Returns JSON array.
+#[no_mangle]
+pub extern "C" fn pharos_get_stored_plans(
+    connection_id: *const c_char,
+    sql: *const c_char,
+) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let conn_id = unsafe { c_str_to_string(connection_id) };
+        let sql_str = unsafe { c_str_to_string(sql) };
+        to_json_c_string(&crate::commands::get_stored_plans(conn_id, sql_str, state))
+    })
+}
+
+/// Compare two stored plans of `sql` by ID. Returns PlanComparison JSON, or error JSON.
+#[no_mangle]
+pub extern "C" fn pharos_compare_plans(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    base_id: *const c_char,
+    other_id: *const c_char,
+) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let conn_id = unsafe { c_str_to_string(connection_id) };
+        let sql_str = unsafe { c_str_to_string(sql) };
+        let base = unsafe { c_str_to_string(base_id) };
+        let other = unsafe { c_str_to_string(other_id) };
+        match crate::commands::compare_plans(conn_id, sql_str, base, other, state) {
+            Ok(comparison) => to_json_c_string(&comparison),
+            Err(e) => to_c_string(&serde_json::json!({"error": e}).to_string()),
+        }
+    })
+}
@@ -76,6 +76,55 @@ pub extern "C" fn pharos_get_query_history_result(entry_id: *const c_char) -> *m
     })
 }
 
+/// Get the full SQL of a history entry. Returns a JSON string, NULL if the entry
+/// doesn't exist, or error JSON.
+#[no_mangle]
+pub extern "C" fn pharos_get_history_entry_sql(entry_id: *const c_char) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let rt = runtime();
+        let id = unsafe { c_str_to_string(entry_id) };
+        match rt.block_on(crate::commands::get_history_entry_sql(id, state)) {
+            Ok(Some(sql)) => to_json_c_string(&sql),
+            Ok(None) => std::ptr::null_mut(),
+            Err(e) => to_c_string(&serde_json::json!({"error": e.to_string()}).to_string()),
+        }
+    })
+}
+
+/// Delete query history by filter. `json` is {connectionId?, beforeDate?, search?};
+/// at least one is required. Returns the count of deleted entries as a string, or error JSON.
+#[no_mangle]
+pub extern "C" fn pharos_delete_query_history_filtered(json: *const c_char) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let rt = runtime();
+        let json_str = unsafe { c_str_to_string(json) };
+
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct DeleteFilter {
+            connection_id: Option<String>,
+            before_date: Option<String>,
+            search: Option<String>,
+        }
+
+        let filter: DeleteFilter = match serde_json::from_str(&json_str) {
+            Ok(f) => f,
+            Err(e) => return to_c_string(&serde_json::json!({"error": e.to_string()}).to_string()),
+        };
+        match rt.block_on(crate::commands::delete_query_history_filtered(
+            filter.connection_id,
+            filter.before_date,
+            filter.search,
+            state,
+        )) {
+            Ok(count) => to_c_string(&format!("{}", count)),
+            Err(e) => to_c_string(&serde_json::json!({"error": e.to_string()}).to_string()),
+        }
+    })
+}
+
 /// Batch delete query history entries. `json` is a JSON array of ID strings.
 /// Returns the count of deleted entries as a string, or error JSON.
 #[no_mangle]
@@ -37,6 +37,20 @@ pub extern "C" fn pharos_create_saved_query(json: *const c_char) -> *mut c_char
     })
 }
 
+/// Duplicate a saved query as "<name> (copy)". Returns JSON SavedQuery. Caller must free.
+#[no_mangle]
+pub extern "C" fn pharos_duplicate_saved_query(query_id: *const c_char) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let rt = runtime();
+        let id = unsafe { c_str_to_string(query_id) };
+        match rt.block_on(crate::commands::duplicate_saved_query(state, id)) {
+            Ok(saved) => to_json_c_string(&saved),
+            Err(e) => to_c_string(&serde_json::json!({"error": e.to_string()}).to_string()),
+        }
+    })
+}
+
 /// Update a saved query. `json` is JSON-encoded UpdateSavedQuery. Returns JSON SavedQuery or null.
 #[no_mangle]
 pub extern "C" fn pharos_update_saved_query(json: *const c_char) -> *mut c_char {
@@ -87,6 +101,59 @@ pub extern "C" fn pharos_batch_delete_saved_queries(json: *const c_char) -> *mut
     })
 }
 
+/// Save a saved query's result grid layout. `layout_json` may be null to reset
+/// it. Returns "null" or an error object. Caller must free.
+#[no_mangle]
+pub extern "C" fn pharos_save_query_column_layout(
+    query_id: *const c_char,
+    layout_json: *const c_char,
+) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let rt = runtime();
+        let id = unsafe { c_str_to_string(query_id) };
+        let layout = unsafe { c_str_to_option(layout_json) };
+        match rt.block_on(crate::commands::save_query_column_layout(state, id, layout)) {
+            Ok(()) => to_c_string("null"),
+            Err(e) => to_c_string(&serde_json::json!({"error": e.to_string()}).to_string()),
+        }
+    })
+}
+
+/// Get a saved query's result grid layout JSON, or "null" if it has none.
+/// Caller must free.
+#[no_mangle]
+pub extern "C" fn pharos_get_query_column_layout(query_id: *const c_char) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let rt = runtime();
+        let id = unsafe { c_str_to_string(query_id) };
+        match rt.block_on(crate::commands::get_query_column_layout(state, id)) {
+            Ok(Some(layout)) => to_c_string(&layout),
+            Ok(None) => to_c_string("null"),
+            Err(e) => to_c_string(&serde_json::json!({"error": e.to_string()}).to_string()),
+        }
+    })
+}
+
+/// Rename a saved-query folder, including nested folders. Returns moved count as string.
+#[no_mangle]
+pub extern "C" fn pharos_rename_saved_query_folder(
+    old_folder: *const c_char,
+    new_folder: *const c_char,
+) -> *mut c_char {
+    ffi_sync!({
+        let state = app_state();
+        let rt = runtime();
+        let old_folder = unsafe { c_str_to_string(old_folder) };
+        let new_folder = unsafe { c_str_to_string(new_folder) };
+        match rt.block_on(crate::commands::rename_saved_query_folder(state, old_folder, new_folder)) {
+            Ok(count) => to_c_string(&count.to_string()),
+            Err(e) => to_c_string(&serde_json::json!({"error": e.to_string()}).to_string()),
+        }
+    })
+}
+
 /// Extract table names from SQL for display. Returns comma-separated names or NULL.
 #[no_mangle]
 pub extern "C" fn pharos_extract_table_names(sql: *const c_char) -> *mut c_char {
@@ -6,10 +6,11 @@ use super::*;
 // Schema introspection
 // ---------------------------------------------------------------------------
 
-/// Get schemas. Returns JSON array via callback.
+/// Get schemas, optionally including system schemas. Returns JSON array via callback.
 #[no_mangle]
 pub extern "C" fn pharos_get_schemas(
     connection_id: *const c_char,
+    include_system: bool,
     callback: AsyncCallback,
     context: *mut std::ffi::c_void,
 ) {
@@ -18,7 +19,7 @@ pub extern "C" fn pharos_get_schemas(
     let ctx = context as usize;
 
     ffi_spawn!(callback, context, async move {
-        match crate::commands::get_schemas(conn_id, state).await {
+        match crate::commands::get_schemas(conn_id, include_system, state).await {
             Ok(schemas) => {
                 let json = serde_json::to_string(&schemas).unwrap_or_default();
                 callback_ok(callback, ctx, &json);
@@ -28,6 +29,28 @@ pub extern "C" fn pharos_get_schemas(
     });
 }
 
+/// List connectable databases on the server. Returns JSON array of names via callback.
+#[no_mangle]
+pub extern "C" fn pharos_list_databases(
+    connection_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::list_databases(conn_id, state).await {
+            Ok(databases) => {
+                let json = serde_json::to_string(&databases).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
 /// Get tables for a schema. Returns JSON array via callback.
 #[no_mangle]
 pub extern "C" fn pharos_get_tables(
@@ -52,6 +75,33 @@ pub extern "C" fn pharos_get_tables(
     });
 }
 
+/// Search tables/views by name substring, optionally within one schema (nullable).
+/// Returns JSON array of TableInfo, best matches first, via callback.
+#[no_mangle]
+pub extern "C" fn pharos_search_tables(
+    connection_id: *const c_char,
+    term: *const c_char,
+    schema_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let term = unsafe { c_str_to_string(term) };
+    let schema = unsafe { c_str_to_option(schema_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::search_tables(conn_id, term, schema, state).await {
+            Ok(tables) => {
+                let json = serde_json::to_string(&tables).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
 /// Get direct child partitions of a partitioned parent. Returns JSON array via callback.
 #[no_mangle]
 pub extern "C" fn pharos_get_partitions(
@@ -78,6 +128,58 @@ pub extern "C" fn pharos_get_partitions(
     });
 }
 
+/// Get a table's RLS flags and policies. Returns TableRlsPolicies JSON via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_rls_policies(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_rls_policies(conn_id, schema, table, state).await {
+            Ok(policies) => {
+                let json = serde_json::to_string(&policies).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Get a table's inheritance parents and children. Returns TableInheritance JSON via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_inheritance(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_inheritance(conn_id, schema, table, state).await {
+            Ok(inheritance) => {
+                let json = serde_json::to_string(&inheritance).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
 /// Get parent→child partition name map for a schema. Returns JSON array via callback.
 #[no_mangle]
 pub extern "C" fn pharos_get_partition_map(
@@ -152,6 +254,30 @@ pub extern "C" fn pharos_get_schema_columns(
     });
 }
 
+/// Load a schema's tables with columns, indexes and constraints. Returns JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_load_schema_tree(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::load_schema_tree(conn_id, schema, state).await {
+            Ok(tree) => {
+                let json = serde_json::to_string(&tree).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
 /// Analyze a schema. Returns JSON AnalyzeResult via callback.
 #[no_mangle]
 pub extern "C" fn pharos_analyze_schema(
@@ -176,6 +302,64 @@ pub extern "C" fn pharos_analyze_schema(
     });
 }
 
+/// Refresh a materialized view. Returns the elapsed milliseconds via callback.
+#[no_mangle]
+pub extern "C" fn pharos_refresh_materialized_view(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    view_name: *const c_char,
+    concurrently: bool,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let view = unsafe { c_str_to_string(view_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::refresh_materialized_view(conn_id, schema, view, concurrently, state).await {
+            Ok(elapsed_ms) => callback_ok(callback, ctx, &elapsed_ms.to_string()),
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Get autocompletion metadata (tables with their columns; types and comments
+/// too when `include_details`). Returns JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_completion_metadata(
+    connection_id: *const c_char,
+    include_details: bool,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_completion_metadata(conn_id, include_details, state).await {
+            Ok(tables) => {
+                let json = serde_json::to_string(&tables).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Drop a connection's cached autocompletion metadata (both variants).
+#[no_mangle]
+pub extern "C" fn pharos_clear_completion_metadata(connection_id: *const c_char) {
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let state = app_state();
+        let conn_id = unsafe { c_str_to_string(connection_id) };
+        crate::commands::clear_completion_metadata(conn_id, state);
+    }));
+}
+
 /// Get schema functions. Returns JSON array via callback.
 #[no_mangle]
 pub extern "C" fn pharos_get_schema_functions(
@@ -199,3 +383,52 @@ pub extern "C" fn pharos_get_schema_functions(
         }
     });
 }
+
+/// Get schema sequences. Returns JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_sequences(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_sequences(conn_id, schema, state).await {
+            Ok(sequences) => {
+                let json = serde_json::to_string(&sequences).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Estimate table and index bloat in a schema. Returns JSON array of
+/// BloatEstimate, largest waste first, via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_bloat_estimate(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_bloat_estimate(conn_id, schema, state).await {
+            Ok(estimates) => {
+                let json = serde_json::to_string(&estimates).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
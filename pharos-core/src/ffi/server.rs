@@ -0,0 +1,217 @@
+use std::os::raw::c_char;
+
+use super::*;
+
+// ---------------------------------------------------------------------------
+// Server statistics
+// ---------------------------------------------------------------------------
+
+/// Get the most expensive statements from pg_stat_statements. `limit` <= 0
+/// uses the default. Returns JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_top_statements(
+    connection_id: *const c_char,
+    limit: i32,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let lim = if limit > 0 { Some(limit as u32) } else { None };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_top_statements(conn_id, lim, state).await {
+            Ok(statements) => {
+                let json = serde_json::to_string(&statements).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// List idle-in-transaction sessions in the current database.
+/// Returns JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_idle_in_transaction(
+    connection_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_idle_in_transaction(conn_id, state).await {
+            Ok(sessions) => {
+                let json = serde_json::to_string(&sessions).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Terminate idle-in-transaction sessions idle for at least `min_idle_seconds`.
+/// Returns a JSON array of terminated pids via callback.
+#[no_mangle]
+pub extern "C" fn pharos_terminate_idle_transactions(
+    connection_id: *const c_char,
+    min_idle_seconds: u32,
+    confirmed: bool,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::terminate_idle_transactions(conn_id, min_idle_seconds, confirmed, state).await {
+            Ok(pids) => {
+                let json = serde_json::to_string(&pids).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// List in-progress VACUUM / CREATE INDEX operations.
+/// Returns JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_running_maintenance(
+    connection_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_running_maintenance(conn_id, state).await {
+            Ok(operations) => {
+                let json = serde_json::to_string(&operations).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Cancel a running VACUUM / CREATE INDEX by backend pid.
+#[no_mangle]
+pub extern "C" fn pharos_cancel_maintenance(
+    connection_id: *const c_char,
+    pid: i32,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::cancel_maintenance(conn_id, pid, state).await {
+            Ok(()) => callback_ok(callback, ctx, "null"),
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// List prepared (two-phase) transactions in the current database.
+/// Returns JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_prepared_transactions(
+    connection_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_prepared_transactions(conn_id, state).await {
+            Ok(transactions) => {
+                let json = serde_json::to_string(&transactions).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Roll back a prepared transaction by gid.
+#[no_mangle]
+pub extern "C" fn pharos_rollback_prepared_transaction(
+    connection_id: *const c_char,
+    gid: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let gid = unsafe { c_str_to_string(gid) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::rollback_prepared_transaction(conn_id, gid, state).await {
+            Ok(()) => callback_ok(callback, ctx, "null"),
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// List the settings stored for the current database (`pg_db_role_setting`).
+/// Returns JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_database_settings(
+    connection_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_database_settings(conn_id, state).await {
+            Ok(settings) => {
+                let json = serde_json::to_string(&settings).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Set a whitelisted parameter on the current database; a null `value` resets
+/// it. Returns the database's settings afterwards as a JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_set_database_setting(
+    connection_id: *const c_char,
+    name: *const c_char,
+    value: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let name = unsafe { c_str_to_string(name) };
+    let value = unsafe { c_str_to_option(value) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::set_database_setting(conn_id, name, value, state).await {
+            Ok(settings) => {
+                let json = serde_json::to_string(&settings).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
@@ -36,3 +36,45 @@ pub extern "C" fn pharos_save_settings(json: *const c_char) -> *mut c_char {
         }
     })
 }
+
+/// Write the keyboard shortcuts to a JSON file. Returns the count via callback.
+#[no_mangle]
+pub extern "C" fn pharos_export_keymap(
+    file_path: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let path = unsafe { c_str_to_string(file_path) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::export_keymap(path, state).await {
+            Ok(count) => callback_ok(callback, ctx, &count.to_string()),
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Merge a keymap file over the current shortcuts and save them.
+/// Returns JSON KeymapImportResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_import_keymap(
+    file_path: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let path = unsafe { c_str_to_string(file_path) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::import_keymap(path, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
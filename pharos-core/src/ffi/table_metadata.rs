@@ -58,6 +58,58 @@ pub extern "C" fn pharos_get_table_constraints(
     });
 }
 
+/// Get table triggers (internal constraint triggers excluded). Returns JSON
+/// array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_table_triggers(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_table_triggers(conn_id, schema, table, state).await {
+            Ok(triggers) => {
+                let json = serde_json::to_string(&triggers).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Generate DDL for a whole schema, comments included. Returns the script
+/// as a JSON string via callback.
+#[no_mangle]
+pub extern "C" fn pharos_generate_schema_ddl(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::generate_schema_ddl(conn_id, schema, state).await {
+            Ok(ddl) => {
+                let json = serde_json::to_string(&ddl).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
 /// Generate table DDL (three detail variants). Returns JSON via callback.
 #[no_mangle]
 pub extern "C" fn pharos_generate_table_ddl(
@@ -83,3 +135,56 @@ pub extern "C" fn pharos_generate_table_ddl(
         }
     });
 }
+
+/// Generate CREATE VIEW / CREATE MATERIALIZED VIEW DDL. Returns the DDL as a
+/// JSON string via callback.
+#[no_mangle]
+pub extern "C" fn pharos_generate_view_ddl(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    view_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let view = unsafe { c_str_to_string(view_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::generate_view_ddl(conn_id, schema, view, state).await {
+            Ok(ddl) => {
+                let json = serde_json::to_string(&ddl).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Get a table's storage parameters. Returns JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_get_table_storage_params(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::get_table_storage_params(conn_id, schema, table, state).await {
+            Ok(params) => {
+                let json = serde_json::to_string(&params).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
@@ -37,6 +37,175 @@ pub extern "C" fn pharos_clone_table(
     });
 }
 
+/// Copy a table from one connection to another. `json` is JSON-encoded
+/// CloneTableOptions. Returns JSON CopyTableResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_copy_table_across_connections(
+    source_connection_id: *const c_char,
+    target_connection_id: *const c_char,
+    json: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let source_id = unsafe { c_str_to_string(source_connection_id) };
+    let target_id = unsafe { c_str_to_string(target_connection_id) };
+    let json_str = unsafe { c_str_to_string(json) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        let options: crate::commands::table::CloneTableOptions = match serde_json::from_str(&json_str) {
+            Ok(o) => o,
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::copy_table_across_connections(source_id, target_id, options, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Compute a content checksum of a table. `query_id` (nullable) lets
+/// `pharos_cancel_query` stop it. Returns JSON TableChecksum.
+#[no_mangle]
+pub extern "C" fn pharos_checksum_table(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    query_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let qid = unsafe { c_str_to_option(query_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::checksum_table(conn_id, schema, table, qid, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Compare a table's row estimate with its exact count (only counted when
+/// `exact`). `query_id` (nullable) lets `pharos_cancel_query` stop the count.
+/// Returns JSON CountAccuracy.
+#[no_mangle]
+pub extern "C" fn pharos_check_count_accuracy(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    exact: bool,
+    query_id: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let qid = unsafe { c_str_to_option(query_id) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::check_count_accuracy(conn_id, schema, table, exact, qid, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Preview the first and last `count` rows of a table. `key_columns_json` is a
+/// JSON array of ordering columns (normally the PK); empty orders by ctid.
+/// Returns JSON TableEndsPreview.
+#[no_mangle]
+pub extern "C" fn pharos_preview_table_ends(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    key_columns_json: *const c_char,
+    count: u32,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let keys_str = unsafe { c_str_to_option(key_columns_json) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        let key_columns: Vec<String> = match keys_str.as_deref().map(serde_json::from_str).transpose() {
+            Ok(keys) => keys.unwrap_or_default(),
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::preview_table_ends(conn_id, schema, table, key_columns, count, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Find duplicate value groups across `columns_json` (a JSON array of column
+/// names). Returns JSON QueryResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_find_duplicate_rows(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    columns_json: *const c_char,
+    limit: u32,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let columns_str = unsafe { c_str_to_string(columns_json) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        let columns: Vec<String> = match serde_json::from_str(&columns_str) {
+            Ok(c) => c,
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::find_duplicate_rows(conn_id, schema, table, columns, limit, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
 /// Export table data. `json` is JSON-encoded ExportTableOptions.
 #[no_mangle]
 pub extern "C" fn pharos_export_table(
@@ -68,6 +237,186 @@ pub extern "C" fn pharos_export_table(
     });
 }
 
+/// Write one cell's value to a file. `json` is JSON-encoded ExportCellOptions.
+/// Returns JSON ExportCellResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_export_cell_to_file(
+    connection_id: *const c_char,
+    json: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let json_str = unsafe { c_str_to_string(json) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        let options: crate::commands::table::ExportCellOptions = match serde_json::from_str(&json_str) {
+            Ok(o) => o,
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::export_cell_to_file(conn_id, options, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Export all rows of a query to a newline-delimited JSON file. `schema` may be
+/// null. Returns JSON ExportTableResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_export_ndjson(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    schema: *const c_char,
+    file_path: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let sql_str = unsafe { c_str_to_string(sql) };
+    let schema_str = unsafe { c_str_to_option(schema) };
+    let path = unsafe { c_str_to_string(file_path) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::export_ndjson(conn_id, sql_str, schema_str, path, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Export all rows of a query as a Markdown table. `schema` may be null; NULL
+/// cells are left empty when `null_as_empty`. Returns JSON ExportTableResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_export_markdown(
+    connection_id: *const c_char,
+    sql: *const c_char,
+    schema: *const c_char,
+    file_path: *const c_char,
+    null_as_empty: bool,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let sql_str = unsafe { c_str_to_string(sql) };
+    let schema_str = unsafe { c_str_to_option(schema) };
+    let path = unsafe { c_str_to_string(file_path) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::export_markdown(conn_id, sql_str, schema_str, path, null_as_empty, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Export in-memory query results to XLSX. `json` is JSON-encoded ExportResultsOptions.
+#[no_mangle]
+pub extern "C" fn pharos_export_results(
+    json: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let json_str = unsafe { c_str_to_string(json) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        let options: crate::commands::table::ExportResultsOptions = match serde_json::from_str(&json_str) {
+            Ok(o) => o,
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::export_results(options).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Export a table as INSERT statements. `options_json` is JSON-encoded
+/// ExportSqlOptions. Returns JSON ExportTableResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_export_sql(
+    connection_id: *const c_char,
+    options_json: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let json_str = unsafe { c_str_to_string(options_json) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        let options: crate::commands::table::ExportSqlOptions = match serde_json::from_str(&json_str) {
+            Ok(o) => o,
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::export_sql(conn_id, options, state).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Export in-memory query results to a JSON array file. `json` is
+/// JSON-encoded ExportResultsOptions. Returns JSON ExportTableResult via callback.
+#[no_mangle]
+pub extern "C" fn pharos_export_results_json(
+    json: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let json_str = unsafe { c_str_to_string(json) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        let options: crate::commands::table::ExportResultsOptions = match serde_json::from_str(&json_str) {
+            Ok(o) => o,
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::export_results_json(options).await {
+            Ok(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
 /// Get the live row count for an in-progress import.
 /// `key` is `"{connection_id}|{schema}|{table}"`.
 /// Returns the current row count, or `-1` if no import is active for that key.
@@ -114,3 +463,227 @@ pub extern "C" fn pharos_import_csv(
         }
     });
 }
+
+/// Render a fetched result set as INSERT statements for the clipboard.
+/// `result_json` is a JSON-encoded QueryResult; `schema_name` is nullable.
+/// Returns the statements as a JSON string, or error JSON.
+#[no_mangle]
+pub extern "C" fn pharos_results_to_insert_string(
+    result_json: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+) -> *mut c_char {
+    ffi_sync!({
+        let json_str = unsafe { c_str_to_string(result_json) };
+        let schema = unsafe { c_str_to_option(schema_name) };
+        let table = unsafe { c_str_to_string(table_name) };
+        let result: crate::commands::query::QueryResult = match serde_json::from_str(&json_str) {
+            Ok(r) => r,
+            Err(e) => return to_c_string(&serde_json::json!({"error": e.to_string()}).to_string()),
+        };
+        match crate::commands::table::results_to_insert_string(&result, schema.as_deref(), &table) {
+            Ok(sql) => to_json_c_string(&sql),
+            Err(e) => to_c_string(&serde_json::json!({"error": e}).to_string()),
+        }
+    })
+}
+
+/// Export a fetched result set as an HTML table. `result_json` is a JSON-encoded
+/// QueryResult; with `file_path` (nullable) the page is written there and the
+/// callback gets null, otherwise it gets the HTML as a JSON string.
+#[no_mangle]
+pub extern "C" fn pharos_export_html(
+    result_json: *const c_char,
+    file_path: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let json_str = unsafe { c_str_to_string(result_json) };
+    let path = unsafe { c_str_to_option(file_path) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        let result: crate::commands::query::QueryResult = match serde_json::from_str(&json_str) {
+            Ok(r) => r,
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::table::export_html(&result, path).await {
+            Ok(html) => {
+                let json = serde_json::to_string(&html).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Enable row-level security on a table. Calls back with null on success.
+#[no_mangle]
+pub extern "C" fn pharos_enable_rls(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::enable_rls(conn_id, schema, table, state).await {
+            Ok(()) => callback_ok(callback, ctx, "null"),
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Disable row-level security on a table; refused unless `confirmed`.
+/// Calls back with null on success.
+#[no_mangle]
+pub extern "C" fn pharos_disable_rls(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    confirmed: bool,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::disable_rls(conn_id, schema, table, confirmed, state).await {
+            Ok(()) => callback_ok(callback, ctx, "null"),
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Drop an RLS policy from a table. Calls back with null on success.
+#[no_mangle]
+pub extern "C" fn pharos_drop_policy(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    policy_name: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let policy = unsafe { c_str_to_string(policy_name) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::drop_policy(conn_id, schema, table, policy, state).await {
+            Ok(()) => callback_ok(callback, ctx, "null"),
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Set one whitelisted storage parameter on a table. Returns the table's
+/// storage parameters afterwards as a JSON array via callback.
+#[no_mangle]
+pub extern "C" fn pharos_set_table_storage_param(
+    connection_id: *const c_char,
+    schema_name: *const c_char,
+    table_name: *const c_char,
+    name: *const c_char,
+    value: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let schema = unsafe { c_str_to_string(schema_name) };
+    let table = unsafe { c_str_to_string(table_name) };
+    let param = unsafe { c_str_to_string(name) };
+    let param_value = unsafe { c_str_to_string(value) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        match crate::commands::set_table_storage_param(conn_id, schema, table, param, param_value, state).await {
+            Ok(params) => {
+                let json = serde_json::to_string(&params).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Dry-run a column type change in a rolled-back transaction. `json` is
+/// JSON-encoded AlterColumnTypeOptions. Returns JSON AlterColumnTypePreview via callback.
+#[no_mangle]
+pub extern "C" fn pharos_preview_alter_column_type(
+    connection_id: *const c_char,
+    json: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let json_str = unsafe { c_str_to_string(json) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        let options: crate::commands::table::AlterColumnTypeOptions = match serde_json::from_str(&json_str) {
+            Ok(o) => o,
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::preview_alter_column_type(conn_id, options, state).await {
+            Ok(preview) => {
+                let json = serde_json::to_string(&preview).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
+
+/// Change a column's type. `json` is JSON-encoded AlterColumnTypeOptions.
+/// Returns the executed statement as a JSON string via callback.
+#[no_mangle]
+pub extern "C" fn pharos_alter_column_type(
+    connection_id: *const c_char,
+    json: *const c_char,
+    callback: AsyncCallback,
+    context: *mut std::ffi::c_void,
+) {
+    let state = app_state();
+    let conn_id = unsafe { c_str_to_string(connection_id) };
+    let json_str = unsafe { c_str_to_string(json) };
+    let ctx = context as usize;
+
+    ffi_spawn!(callback, context, async move {
+        let options: crate::commands::table::AlterColumnTypeOptions = match serde_json::from_str(&json_str) {
+            Ok(o) => o,
+            Err(e) => {
+                callback_err(callback, ctx, &e.to_string());
+                return;
+            }
+        };
+        match crate::commands::alter_column_type(conn_id, options, state).await {
+            Ok(sql) => {
+                let json = serde_json::to_string(&sql).unwrap_or_default();
+                callback_ok(callback, ctx, &json);
+            }
+            Err(e) => callback_err(callback, ctx, &e),
+        }
+    });
+}
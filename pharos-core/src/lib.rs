@@ -4,3 +4,5 @@ pub mod db;
 pub mod ffi;
 pub mod models;
 pub mod state;
+#[cfg(test)]
+mod test_support;
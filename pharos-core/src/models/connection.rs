@@ -19,6 +19,45 @@ impl std::fmt::Display for SslMode {
     }
 }
 
+/// How `SshTunnelConfig` authenticates to the bastion.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SshAuthMethod {
+    #[default]
+    PrivateKey,
+    Password,
+}
+
+impl std::fmt::Display for SshAuthMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SshAuthMethod::PrivateKey => write!(f, "privateKey"),
+            SshAuthMethod::Password => write!(f, "password"),
+        }
+    }
+}
+
+/// An SSH bastion the database connection is forwarded through.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshTunnelConfig {
+    pub host: String,
+    #[serde(default = "default_ssh_port")]
+    pub port: u16,
+    pub username: String,
+    #[serde(default)]
+    pub auth_method: SshAuthMethod,
+    /// Private key file for `PrivateKey` auth (OpenSSH or PEM format).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key_path: Option<String>,
+    /// SSH password, or the private key's passphrase. Stored in the OS
+    /// keychain like the database password; only set in transit.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub password: String,
+}
+
+fn default_ssh_port() -> u16 { 22 }
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConnectionConfig {
@@ -38,6 +77,20 @@ pub struct ConnectionConfig {
     pub color: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub default_schema: Option<String>,
+    /// Never record queries run on this connection in history.
+    #[serde(default)]
+    pub disable_history: bool,
+    /// Column-name globs (`*`, `?`, case-insensitive) whose values are shown
+    /// as `****` in results and history, e.g. for screen-sharing.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub masked_columns: Vec<String>,
+    /// Free-form labels (e.g. "pii", "replica") for filtering the connection list.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Reach the server through an SSH bastion; `host`/`port` are then
+    /// resolved on the bastion's side.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_tunnel: Option<SshTunnelConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -59,6 +112,10 @@ pub struct ConnectionInfo {
     pub status: ConnectionStatus,
     pub error: Option<String>,
     pub latency_ms: Option<u64>,
+    /// `server_version` (e.g. "16.2"), read once at connect. None if the
+    /// server didn't report one.
+    #[serde(default)]
+    pub server_version: Option<String>,
 }
 
 impl From<&ConnectionConfig> for ConnectionInfo {
@@ -72,6 +129,7 @@ impl From<&ConnectionConfig> for ConnectionInfo {
             status: ConnectionStatus::Disconnected,
             error: None,
             latency_ms: None,
+            server_version: None,
         }
     }
 }
@@ -81,4 +139,39 @@ pub struct TestConnectionResult {
     pub success: bool,
     pub latency_ms: Option<u64>,
     pub error: Option<String>,
+    #[serde(default)]
+    pub server_version: Option<String>,
+}
+
+/// One saved connection's outcome from `test_all_connections`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionTestReport {
+    pub connection_id: String,
+    pub result: TestConnectionResult,
+}
+
+/// What the connected server supports, so the UI can hide features that
+/// would only error (extension-backed views, newer syntax, superuser tools).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerCapabilities {
+    /// `server_version`, e.g. "16.2 (Debian 16.2-1.pgdg120+2)".
+    pub server_version: String,
+    /// `server_version_num`, e.g. 160002.
+    pub server_version_num: i32,
+    /// Major version (16 for 16.x, 9 for 9.6).
+    pub major_version: i32,
+    pub is_superuser: bool,
+    /// Installed extension versions; `None` when not installed in this database.
+    pub postgis_version: Option<String>,
+    pub pgvector_version: Option<String>,
+    pub pg_stat_statements_version: Option<String>,
+    /// Extension installed *and* its view is readable (it also needs to be in
+    /// shared_preload_libraries).
+    pub pg_stat_statements_available: bool,
+    /// TABLESAMPLE (9.5+).
+    pub supports_tablesample: bool,
+    /// REFRESH MATERIALIZED VIEW CONCURRENTLY (9.4+).
+    pub supports_refresh_concurrently: bool,
 }
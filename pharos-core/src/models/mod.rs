@@ -2,6 +2,7 @@ pub mod connection;
 pub mod query_history;
 pub mod saved_query;
 pub mod schema;
+pub mod server;
 pub mod settings;
 pub mod workspace;
 
@@ -9,5 +10,6 @@ pub use connection::*;
 pub use query_history::*;
 pub use saved_query::*;
 pub use schema::*;
+pub use server::*;
 pub use settings::*;
 pub use workspace::{WorkspaceDetail, WorkspaceResultMeta, WorkspaceSummary, WorkspaceUpsert};
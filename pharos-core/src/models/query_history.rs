@@ -6,6 +6,7 @@ pub struct QueryHistoryEntry {
     pub id: String,
     pub connection_id: String,
     pub connection_name: String,
+    /// Complete statement text as sent to the server, never truncated.
     pub sql: String,
     pub row_count: Option<i64>,
     pub execution_time_ms: i64,
@@ -21,4 +22,8 @@ pub struct QueryHistoryEntry {
     /// server-aggregation query). `None` for normal, untagged runs.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
+    /// True when the statement modified data (INSERT/UPDATE/DELETE/MERGE,
+    /// including `... RETURNING` run as a query and data-modifying CTEs).
+    #[serde(default)]
+    pub is_mutation: bool,
 }
@@ -11,6 +11,10 @@ pub struct SavedQuery {
     pub variables: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Result grid layout (column order, hidden columns, widths) as
+    /// frontend-defined JSON. `None` shows the default layout.
+    #[serde(default)]
+    pub column_layout: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
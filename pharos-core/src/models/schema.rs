@@ -38,6 +38,29 @@ impl PartitionStrategy {
     }
 }
 
+/// Durability of a relation, from `pg_class.relpersistence`. Unlogged tables
+/// skip the WAL and are truncated after a crash; temporary tables live only
+/// for their session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TablePersistence {
+    #[default]
+    Logged,
+    Unlogged,
+    Temporary,
+}
+
+impl TablePersistence {
+    /// Map `pg_class.relpersistence` ('p' | 'u' | 't') to a persistence.
+    pub fn from_pg_char(c: char) -> TablePersistence {
+        match c {
+            'u' => TablePersistence::Unlogged,
+            't' => TablePersistence::Temporary,
+            _ => TablePersistence::Logged,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TableInfo {
@@ -64,6 +87,8 @@ pub struct TableInfo {
     /// Number of direct child partitions. Present when `is_partitioned`.
     #[serde(default)]
     pub partition_count: Option<i64>,
+    #[serde(default)]
+    pub persistence: TablePersistence,
 }
 
 /// Minimal parent→child pairing used to populate the sidebar filter index
@@ -75,6 +100,56 @@ pub struct PartitionRef {
     pub name: String,
 }
 
+/// A table on either side of an inheritance link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InheritedTable {
+    pub schema_name: String,
+    pub name: String,
+    /// The link is declarative partitioning rather than `INHERITS`.
+    pub is_partition: bool,
+}
+
+/// One row-level security policy (`pg_policies`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RlsPolicyInfo {
+    pub name: String,
+    /// ALL, SELECT, INSERT, UPDATE or DELETE.
+    pub command: String,
+    /// False for RESTRICTIVE policies.
+    pub permissive: bool,
+    pub roles: Vec<String>,
+    pub using_expression: Option<String>,
+    pub with_check_expression: Option<String>,
+}
+
+/// A table's RLS switches and policies.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRlsPolicies {
+    pub rls_enabled: bool,
+    /// RLS applies to the table owner too (`FORCE ROW LEVEL SECURITY`).
+    pub rls_forced: bool,
+    pub policies: Vec<RlsPolicyInfo>,
+}
+
+/// One storage parameter set on a table (an entry of `pg_class.reloptions`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStorageParam {
+    pub name: String,
+    pub value: String,
+}
+
+/// Direct parents and children of a table via `pg_inherits`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableInheritance {
+    pub parents: Vec<InheritedTable>,
+    pub children: Vec<InheritedTable>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AnalyzeResult {
@@ -86,6 +161,88 @@ pub struct AnalyzeResult {
     pub tables: Vec<TableInfo>,
 }
 
+/// What a column's default does, so a new-row editor knows which columns to
+/// leave blank for the server to fill and which to prompt for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DefaultKind {
+    /// No default: the value must be supplied (or is NULL).
+    #[default]
+    None,
+    /// Sequence-backed (`nextval(...)`, serial or identity column).
+    Sequence,
+    /// Computed by the server at insert time (`now()`, `gen_random_uuid()`, ...).
+    Function,
+    /// A constant value (`0`, `'draft'::text`, `true`).
+    Literal,
+}
+
+impl DefaultKind {
+    /// Classify a column from its `column_default` expression and
+    /// information_schema `is_identity` flag.
+    pub fn classify(column_default: Option<&str>, is_identity: bool) -> DefaultKind {
+        if is_identity {
+            return DefaultKind::Sequence;
+        }
+        let expr = match column_default.map(str::trim) {
+            Some(e) if !e.is_empty() => e,
+            _ => return DefaultKind::None,
+        };
+        if expr.to_ascii_lowercase().starts_with("nextval(") {
+            DefaultKind::Sequence
+        } else if is_literal_expression(expr) {
+            DefaultKind::Literal
+        } else {
+            DefaultKind::Function
+        }
+    }
+}
+
+/// Whether a default expression is a constant, optionally parenthesized and
+/// cast: `'x'::text`, `(-1)`, `0.5`, `true`, `NULL::integer`, `'{}'::jsonb`.
+fn is_literal_expression(expr: &str) -> bool {
+    let mut expr = expr.trim();
+    while expr.starts_with('(') && expr.ends_with(')') {
+        expr = expr[1..expr.len() - 1].trim();
+    }
+    if let Some(rest) = expr.strip_prefix('\'') {
+        // Quoted literal: find its closing quote ('' is an escaped quote),
+        // after which only casts may follow.
+        let mut chars = rest.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c == '\'' {
+                if chars.peek().map(|&(_, n)| n) == Some('\'') {
+                    chars.next();
+                    continue;
+                }
+                let tail = rest[i + 1..].trim();
+                return tail.is_empty()
+                    || (tail.starts_with("::")
+                        && tail.chars().all(|c| c.is_alphanumeric() || " _:[](),\".".contains(c)));
+            }
+        }
+        return false;
+    }
+    let value = expr.split("::").next().unwrap_or("").trim();
+    let value = value.trim_start_matches('(').trim_end_matches(')').trim();
+    value.parse::<f64>().is_ok()
+        || value.eq_ignore_ascii_case("true")
+        || value.eq_ignore_ascii_case("false")
+        || value.eq_ignore_ascii_case("null")
+}
+
+/// The planner's row count estimate for a table and how fresh its statistics are.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowCountEstimate {
+    /// `pg_class.reltuples`; `None` if the table has never been analyzed.
+    pub estimated_rows: Option<i64>,
+    /// Latest manual or automatic ANALYZE.
+    pub last_analyzed_at: Option<String>,
+    /// Rows changed since that ANALYZE (`n_mod_since_analyze`).
+    pub modifications_since_analyze: Option<i64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ColumnInfo {
@@ -95,6 +252,11 @@ pub struct ColumnInfo {
     pub is_primary_key: bool,
     pub ordinal_position: i32,
     pub column_default: Option<String>,
+    #[serde(default)]
+    pub default_kind: DefaultKind,
+    /// A `GENERATED ALWAYS AS (...) STORED` column, which can't be written to.
+    #[serde(default)]
+    pub is_generated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -107,6 +269,11 @@ pub struct SchemaColumnInfo {
     pub is_primary_key: bool,
     pub ordinal_position: i32,
     pub column_default: Option<String>,
+    #[serde(default)]
+    pub default_kind: DefaultKind,
+    /// A `GENERATED ALWAYS AS (...) STORED` column, which can't be written to.
+    #[serde(default)]
+    pub is_generated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -131,6 +298,54 @@ pub struct ConstraintInfo {
     pub check_clause: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerInfo {
+    pub name: String,
+    /// BEFORE, AFTER or INSTEAD OF.
+    pub timing: String,
+    /// Some of INSERT, UPDATE, DELETE, TRUNCATE.
+    pub events: Vec<String>,
+    /// ROW or STATEMENT.
+    pub level: String,
+    /// Schema-qualified trigger function.
+    pub function_name: String,
+    pub enabled: bool,
+    /// Full CREATE TRIGGER statement from `pg_get_triggerdef`.
+    pub definition: String,
+}
+
+/// A table, view or foreign table and its columns, for editor autocompletion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionTable {
+    pub schema_name: String,
+    pub name: String,
+    /// In ordinal order.
+    pub columns: Vec<CompletionColumn>,
+}
+
+/// A column name, plus its type and comment when details were requested.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionColumn {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// A table with its full per-table metadata, as returned by `load_schema_tree`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableTreeNode {
+    pub table: TableInfo,
+    pub columns: Vec<ColumnInfo>,
+    pub indexes: Vec<IndexInfo>,
+    pub constraints: Vec<ConstraintInfo>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FunctionInfo {
@@ -142,6 +357,48 @@ pub struct FunctionInfo {
     pub language: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceInfo {
+    pub name: String,
+    pub schema_name: String,
+    /// smallint, integer or bigint.
+    pub data_type: String,
+    pub start_value: i64,
+    pub increment: i64,
+    pub min_value: i64,
+    pub max_value: i64,
+    /// None until `nextval` has been called, or without USAGE/SELECT on it.
+    pub last_value: Option<i64>,
+    pub cycle: bool,
+    /// `table.column` whose serial/identity default the sequence backs.
+    pub owned_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BloatObjectKind {
+    Table,
+    Index,
+}
+
+/// Estimated wasted space in one table or btree index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BloatEstimate {
+    pub kind: BloatObjectKind,
+    pub schema_name: String,
+    pub name: String,
+    /// The table itself, or the table an index belongs to.
+    pub table_name: String,
+    pub real_size_bytes: i64,
+    pub bloat_bytes: i64,
+    pub bloat_percent: f64,
+    /// False when statistics are missing or the estimate can't account for
+    /// some column (e.g. `name` columns); run ANALYZE or treat it as a guess.
+    pub reliable: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,4 +410,24 @@ mod tests {
         assert_eq!(PartitionStrategy::from_pg_char('h'), Some(PartitionStrategy::Hash));
         assert_eq!(PartitionStrategy::from_pg_char('x'), None);
     }
+
+    #[test]
+    fn default_kind_classification() {
+        use DefaultKind::{Function, Literal, Sequence};
+        assert_eq!(DefaultKind::classify(None, false), DefaultKind::None);
+        assert_eq!(DefaultKind::classify(None, true), Sequence);
+        assert_eq!(DefaultKind::classify(Some("nextval('orders_id_seq'::regclass)"), false), Sequence);
+        assert_eq!(DefaultKind::classify(Some("now()"), false), Function);
+        assert_eq!(DefaultKind::classify(Some("CURRENT_TIMESTAMP"), false), Function);
+        assert_eq!(DefaultKind::classify(Some("gen_random_uuid()"), false), Function);
+        // Evaluated per insert, unlike a plain 'now'::date constant.
+        assert_eq!(DefaultKind::classify(Some("('now'::text)::date"), false), Function);
+        assert_eq!(DefaultKind::classify(Some("'draft'::character varying"), false), Literal);
+        assert_eq!(DefaultKind::classify(Some("'it''s'::text"), false), Literal);
+        assert_eq!(DefaultKind::classify(Some("'a'::text || 'b'::text"), false), Function);
+        assert_eq!(DefaultKind::classify(Some("(-1)"), false), Literal);
+        assert_eq!(DefaultKind::classify(Some("0.5"), false), Literal);
+        assert_eq!(DefaultKind::classify(Some("true"), false), Literal);
+        assert_eq!(DefaultKind::classify(Some("NULL::integer"), false), Literal);
+    }
 }
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+/// One row of `pg_stat_statements`, aggregated per normalized query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopStatement {
+    pub query: String,
+    pub database: Option<String>,
+    pub role: Option<String>,
+    pub calls: i64,
+    pub total_exec_time_ms: f64,
+    pub mean_exec_time_ms: f64,
+    pub rows: i64,
+}
+
+/// A two-phase-commit transaction awaiting COMMIT/ROLLBACK PREPARED (`pg_prepared_xacts`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreparedTransaction {
+    pub gid: String,
+    pub transaction: Option<String>,
+    pub prepared_at: Option<String>,
+    pub owner: Option<String>,
+    pub database: Option<String>,
+}
+
+/// A session sitting idle inside an open transaction (`pg_stat_activity`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleTransactionSession {
+    pub pid: i32,
+    pub username: Option<String>,
+    pub application_name: Option<String>,
+    pub client_addr: Option<String>,
+    /// "idle in transaction" or "idle in transaction (aborted)".
+    pub state: String,
+    pub transaction_started_at: Option<String>,
+    /// Seconds since the session last changed state, i.e. went idle.
+    pub idle_seconds: f64,
+    /// The last statement the session ran.
+    pub query: Option<String>,
+}
+
+/// Kind of in-progress maintenance operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MaintenanceKind {
+    Vacuum,
+    CreateIndex,
+}
+
+/// A VACUUM or CREATE INDEX in progress (`pg_stat_progress_*`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceProgress {
+    pub pid: i32,
+    pub kind: MaintenanceKind,
+    /// e.g. "VACUUM", "CREATE INDEX CONCURRENTLY", "REINDEX".
+    pub command: String,
+    /// Started by an autovacuum worker rather than a user session.
+    pub is_autovacuum: bool,
+    pub database: Option<String>,
+    /// Schema-qualified table; `None` if it isn't in the current database.
+    pub relation: Option<String>,
+    pub index_name: Option<String>,
+    pub phase: String,
+    pub blocks_total: i64,
+    pub blocks_done: i64,
+    /// Progress of the current phase; `None` when the phase has no block count.
+    pub percent_complete: Option<f64>,
+    pub started_at: Option<String>,
+    pub elapsed_seconds: f64,
+}
+
+/// A configuration parameter stored for the current database (`pg_db_role_setting`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseSetting {
+    pub name: String,
+    pub value: String,
+    /// Set with `ALTER ROLE ... IN DATABASE` for this role only; `None` for
+    /// database-wide settings.
+    pub role_name: Option<String>,
+}
+
+/// Counters for one `pg_stat_statements` entry, or the change in them across a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementCounters {
+    pub query_id: i64,
+    pub query: String,
+    pub calls: i64,
+    pub rows: i64,
+    /// `None` before PostgreSQL 13; zero unless `pg_stat_statements.track_planning` is on.
+    pub planning_time_ms: Option<f64>,
+    pub execution_time_ms: f64,
+    pub shared_blks_hit: i64,
+    pub shared_blks_read: i64,
+    pub temp_blks_read: i64,
+    pub temp_blks_written: i64,
+}
+
+/// Result of `profile_query`: Pharos's wall-clock time plus the server's own
+/// measurements for the run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryProfile {
+    pub wall_clock_ms: u64,
+    /// Counter deltas attributed to this run; `None` when pg_stat_statements
+    /// is unavailable or no entry could be matched.
+    pub server_stats: Option<StatementCounters>,
+    /// Why `server_stats` is missing, or a caveat about it.
+    pub note: Option<String>,
+}
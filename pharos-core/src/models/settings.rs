@@ -95,11 +95,69 @@ pub struct QuerySettings {
     pub notify_when_background_tab: bool,
     #[serde(default = "default_notify_min_duration_seconds")]
     pub notify_min_duration_seconds: u32,
+    /// Hard ceiling on rows fetched by a single execute/fetch-more call,
+    /// independent of `default_limit`. Requested limits above it are clamped.
+    #[serde(default = "default_max_result_rows")]
+    pub max_result_rows: u32,
+    /// Decode PostGIS geometry/geography columns into GeoJSON text. Off by
+    /// default since it only makes sense with the extension installed.
+    #[serde(default)]
+    pub decode_postgis_geometry: bool,
+    /// Record executed queries in history. Connections can also opt out
+    /// individually via `ConnectionConfig::disable_history`.
+    #[serde(default = "default_history_enabled")]
+    pub history_enabled: bool,
+    /// Rows per server round trip for SELECT-like queries. 0 (the default)
+    /// streams the result directly; a positive value reads it through a
+    /// cursor in batches of this size, stopping once `limit` + 1 rows (the
+    /// extra row detects `has_more`) have been read.
+    #[serde(default)]
+    pub fetch_size: u32,
+    /// Close a connection's pool after this many minutes without activity,
+    /// freeing its server connections. 0 (the default) never closes it.
+    #[serde(default)]
+    pub idle_disconnect_minutes: u32,
+    /// Largest planner row estimate `execute_with_rowcount_guard` lets through
+    /// without `force`.
+    #[serde(default = "default_rowcount_guard_max_rows")]
+    pub rowcount_guard_max_rows: u64,
+    /// How timestamp/timestamptz/date values are shown in results and
+    /// exports: unset keeps the built-in formats, "iso8601" uses ISO-8601,
+    /// anything else is a strftime pattern.
+    #[serde(default)]
+    pub datetime_format: Option<String>,
+    /// Text written for NULL in CSV/TSV and Markdown exports (e.g. "(null)").
+    /// Unset keeps "NULL" for table exports and empty fields for query exports;
+    /// "null as empty" still wins. JSON output is unaffected.
+    #[serde(default)]
+    pub export_null_display: Option<String>,
+    /// Most connection attempts (connects and connection tests) in flight at
+    /// once, so testing many saved connections can't exhaust sockets.
+    #[serde(default = "default_max_concurrent_connects")]
+    pub max_concurrent_connects: u32,
+    /// Skip `execute_query`'s `SET search_path` when the pooled session already
+    /// has that schema's path (checked alongside the backend PID, so no extra
+    /// round trip). A connection's default schema is only what the UI passes
+    /// as `schema`; sessions start with the server's default path, so the first
+    /// query on each pooled session still pays for the SET.
+    #[serde(default = "default_skip_redundant_search_path")]
+    pub skip_redundant_search_path: bool,
+    /// Extra attempts `execute_query` makes to get a pooled connection when
+    /// every connection stayed busy for the pool's acquire timeout, backing
+    /// off between attempts. Errors other than pool exhaustion aren't retried.
+    #[serde(default = "default_acquire_retries")]
+    pub acquire_retries: u32,
 }
 
 fn default_notify_when_app_inactive() -> bool { true }
 fn default_notify_when_background_tab() -> bool { true }
 fn default_notify_min_duration_seconds() -> u32 { 5 }
+fn default_max_result_rows() -> u32 { 1_000_000 }
+fn default_history_enabled() -> bool { true }
+fn default_rowcount_guard_max_rows() -> u64 { 1000 }
+pub(crate) fn default_max_concurrent_connects() -> u32 { 8 }
+fn default_skip_redundant_search_path() -> bool { true }
+fn default_acquire_retries() -> u32 { 2 }
 
 impl Default for QuerySettings {
     fn default() -> Self {
@@ -111,6 +169,17 @@ impl Default for QuerySettings {
             notify_when_app_inactive: default_notify_when_app_inactive(),
             notify_when_background_tab: default_notify_when_background_tab(),
             notify_min_duration_seconds: default_notify_min_duration_seconds(),
+            max_result_rows: default_max_result_rows(),
+            decode_postgis_geometry: false,
+            history_enabled: default_history_enabled(),
+            fetch_size: 0,
+            idle_disconnect_minutes: 0,
+            rowcount_guard_max_rows: default_rowcount_guard_max_rows(),
+            datetime_format: None,
+            export_null_display: None,
+            max_concurrent_connects: default_max_concurrent_connects(),
+            skip_redundant_search_path: default_skip_redundant_search_path(),
+            acquire_retries: default_acquire_retries(),
         }
     }
 }
@@ -1,20 +1,91 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Mutex;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use sqlx::PgPool;
+use std::time::{Duration, Instant};
+use sqlx::pool::PoolConnection;
+use sqlx::{PgPool, Postgres};
 use rusqlite::Connection as SqliteConnection;
+use serde::{Deserialize, Serialize};
 
-use crate::models::ConnectionConfig;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::db::ssh_tunnel::SshTunnel;
+use crate::models::{default_max_concurrent_connects, CompletionTable, ConnectionConfig};
 
 /// Represents a running query that can be cancelled
 pub struct RunningQuery {
+    /// The connection the query runs on
+    pub connection_id: String,
     /// The PostgreSQL backend PID for this query
     pub backend_pid: i32,
+    /// When the query was registered
+    pub started_at: Instant,
     /// Flag to signal cancellation
     pub cancelled: Arc<AtomicBool>,
 }
 
+/// A `running_queries` entry that is removed when dropped, so no return path
+/// leaves a finished query listed.
+pub struct QueryRegistration<'a> {
+    state: &'a AppState,
+    query_id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl QueryRegistration<'_> {
+    /// Set once `cancel_query` has been called for this query.
+    pub fn cancelled(&self) -> &AtomicBool {
+        &self.cancelled
+    }
+
+    /// Record the backend the query runs on, so `cancel_query` can interrupt it.
+    pub fn set_backend_pid(&self, backend_pid: i32) {
+        let mut queries = self.state.running_queries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(query) = queries.get_mut(&self.query_id) {
+            query.backend_pid = backend_pid;
+        }
+    }
+}
+
+impl Drop for QueryRegistration<'_> {
+    fn drop(&mut self) {
+        self.state.unregister_query(&self.query_id);
+    }
+}
+
+/// Read-only view of a `RunningQuery`, for listing queries whose ID the UI lost.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunningQueryInfo {
+    pub query_id: String,
+    pub connection_id: String,
+    pub backend_pid: i32,
+    pub elapsed_ms: u64,
+}
+
+/// A server-side cursor opened by `open_cursor`, pinned to its connection
+/// (and transaction) until closed.
+pub struct OpenCursor {
+    pub connection_id: String,
+    pub conn: PoolConnection<Postgres>,
+    /// Backend holding the cursor, so a FETCH can be cancelled.
+    pub backend_pid: i32,
+}
+
+/// An EXPLAIN (FORMAT JSON) result kept for comparison with later runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredPlan {
+    pub id: String,
+    pub captured_at: String,
+    /// The plan object (the single element of EXPLAIN's JSON array).
+    pub plan: serde_json::Value,
+}
+
+/// How many plans are kept per (connection, query).
+pub const MAX_STORED_PLANS: usize = 5;
+
 /// Application state managed by Tauri
 pub struct AppState {
     /// Active PostgreSQL connection pools, keyed by connection ID
@@ -40,6 +111,60 @@ pub struct AppState {
     /// Live row counters for in-progress CSV imports.
     /// Keyed by `"{connection_id}|{schema}|{table}"`.
     pub import_progress: Mutex<HashMap<String, Arc<AtomicU64>>>,
+
+    /// When each connected pool was last handed out, for idle disconnects.
+    pub last_activity: Mutex<HashMap<String, Instant>>,
+
+    /// Connections closed for inactivity that the frontend hasn't collected yet.
+    pub idle_disconnected: Mutex<Vec<String>>,
+
+    /// Open pagination cursors, keyed by cursor ID. A cursor is taken out of
+    /// the map while a FETCH runs on it and put back afterwards.
+    pub open_cursors: Mutex<HashMap<String, OpenCursor>>,
+
+    /// Recent plans per (connection_id, normalized SQL), oldest first.
+    pub plan_history: Mutex<HashMap<(String, String), VecDeque<StoredPlan>>>,
+
+    /// SSH tunnels of connected pools. Dropped after their pool is closed.
+    pub ssh_tunnels: Mutex<HashMap<String, SshTunnel>>,
+
+    /// `server_version` and `server_version_num` of each connected server,
+    /// captured at connect. Missing for servers that don't report one (non-PostgreSQL).
+    pub server_versions: Mutex<HashMap<String, (String, Option<i32>)>>,
+
+    /// Statement timeouts (seconds) set for the rest of a connection's session,
+    /// overriding the query settings' default. Cleared on disconnect.
+    pub session_timeouts: Mutex<HashMap<String, u32>>,
+
+    /// Autocompletion metadata per (connection_id, include_details), loaded on
+    /// first request. Cleared on disconnect or by `clear_completion_metadata`.
+    pub completion_metadata: Mutex<HashMap<(String, bool), Vec<CompletionTable>>>,
+
+    /// Bounds concurrent connection attempts (connects and tests) app-wide.
+    /// `connect_limit` is the configured limit; `connect_permits_owed` counts
+    /// permits a lowered limit couldn't remove yet because they were in use,
+    /// retired as they are released.
+    pub connect_semaphore: Semaphore,
+    pub connect_limit: Mutex<usize>,
+    pub connect_permits_owed: Mutex<usize>,
+}
+
+/// A connection-attempt slot. Released on drop, unless the limit was lowered
+/// while it was held, in which case it is retired instead.
+pub struct ConnectPermit<'a> {
+    permit: Option<SemaphorePermit<'a>>,
+    owed: &'a Mutex<usize>,
+}
+
+impl Drop for ConnectPermit<'_> {
+    fn drop(&mut self) {
+        let Some(permit) = self.permit.take() else { return };
+        let mut owed = self.owed.lock().unwrap_or_else(|e| e.into_inner());
+        if *owed > 0 {
+            *owed -= 1;
+            permit.forget();
+        }
+    }
 }
 
 impl AppState {
@@ -52,6 +177,17 @@ impl AppState {
             password_cache: Mutex::new(HashMap::new()),
             analyze_denied: Mutex::new(HashMap::new()),
             import_progress: Mutex::new(HashMap::new()),
+            last_activity: Mutex::new(HashMap::new()),
+            idle_disconnected: Mutex::new(Vec::new()),
+            open_cursors: Mutex::new(HashMap::new()),
+            plan_history: Mutex::new(HashMap::new()),
+            ssh_tunnels: Mutex::new(HashMap::new()),
+            server_versions: Mutex::new(HashMap::new()),
+            session_timeouts: Mutex::new(HashMap::new()),
+            completion_metadata: Mutex::new(HashMap::new()),
+            connect_semaphore: Semaphore::new(default_max_concurrent_connects() as usize),
+            connect_limit: Mutex::new(default_max_concurrent_connects() as usize),
+            connect_permits_owed: Mutex::new(0),
         }
     }
 
@@ -87,24 +223,149 @@ impl AppState {
         cache.get(connection_id).cloned()
     }
 
-    /// Get a connection pool by ID
+    /// Get a connection pool by ID. Every command goes through here, so it
+    /// also counts as activity for the idle-disconnect timer.
     pub fn get_pool(&self, connection_id: &str) -> Option<PgPool> {
-        let connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
-        connections.get(connection_id).cloned()
+        let pool = {
+            let connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
+            connections.get(connection_id).cloned()
+        };
+        if pool.is_some() {
+            self.touch_connection(connection_id);
+        }
+        pool
     }
 
     /// Add a connection pool
-    pub fn add_pool(&self, connection_id: String, pool: PgPool) {
+    pub fn add_pool(&self, connection_id: String, pool: PgPool, tunnel: Option<SshTunnel>) {
+        self.touch_connection(&connection_id);
+        if let Some(tunnel) = tunnel {
+            let mut tunnels = self.ssh_tunnels.lock().unwrap_or_else(|e| e.into_inner());
+            tunnels.insert(connection_id.clone(), tunnel);
+        }
         let mut connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
         connections.insert(connection_id, pool);
     }
 
     /// Remove a connection pool
     pub fn remove_pool(&self, connection_id: &str) -> Option<PgPool> {
+        {
+            let mut activity = self.last_activity.lock().unwrap_or_else(|e| e.into_inner());
+            activity.remove(connection_id);
+        }
+        {
+            let mut versions = self.server_versions.lock().unwrap_or_else(|e| e.into_inner());
+            versions.remove(connection_id);
+        }
+        {
+            let mut timeouts = self.session_timeouts.lock().unwrap_or_else(|e| e.into_inner());
+            timeouts.remove(connection_id);
+        }
+        {
+            let mut history = self.plan_history.lock().unwrap_or_else(|e| e.into_inner());
+            history.retain(|(id, _), _| id != connection_id);
+        }
+        self.clear_completion_metadata(connection_id);
         let mut connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
         connections.remove(connection_id)
     }
 
+    /// Close a connection's SSH tunnel, if it has one. Call after its pool is closed.
+    pub fn close_tunnel(&self, connection_id: &str) {
+        let tunnel = {
+            let mut tunnels = self.ssh_tunnels.lock().unwrap_or_else(|e| e.into_inner());
+            tunnels.remove(connection_id)
+        };
+        drop(tunnel);
+    }
+
+    /// Remember the server version of a connected pool
+    pub fn set_server_version(&self, connection_id: &str, server_version: String, server_version_num: Option<i32>) {
+        let mut versions = self.server_versions.lock().unwrap_or_else(|e| e.into_inner());
+        versions.insert(connection_id.to_string(), (server_version, server_version_num));
+    }
+
+    /// `server_version_num` captured when the connection was opened
+    pub fn server_version(&self, connection_id: &str) -> Option<i32> {
+        let versions = self.server_versions.lock().unwrap_or_else(|e| e.into_inner());
+        versions.get(connection_id).and_then(|(_, num)| *num)
+    }
+
+    /// `server_version` text (e.g. "16.2") captured when the connection was opened
+    pub fn server_version_text(&self, connection_id: &str) -> Option<String> {
+        let versions = self.server_versions.lock().unwrap_or_else(|e| e.into_inner());
+        versions.get(connection_id).map(|(text, _)| text.clone())
+    }
+
+    /// Override (`Some`) or restore (`None`) a connection's statement timeout
+    pub fn set_session_timeout(&self, connection_id: &str, timeout_seconds: Option<u32>) {
+        let mut timeouts = self.session_timeouts.lock().unwrap_or_else(|e| e.into_inner());
+        match timeout_seconds {
+            Some(seconds) => timeouts.insert(connection_id.to_string(), seconds),
+            None => timeouts.remove(connection_id),
+        };
+    }
+
+    /// The connection's statement timeout override, if one is set
+    pub fn session_timeout(&self, connection_id: &str) -> Option<u32> {
+        let timeouts = self.session_timeouts.lock().unwrap_or_else(|e| e.into_inner());
+        timeouts.get(connection_id).copied()
+    }
+
+    /// Cached autocompletion metadata for a connection, if loaded
+    pub fn completion_metadata(&self, connection_id: &str, include_details: bool) -> Option<Vec<CompletionTable>> {
+        let cache = self.completion_metadata.lock().unwrap_or_else(|e| e.into_inner());
+        cache.get(&(connection_id.to_string(), include_details)).cloned()
+    }
+
+    /// Cache autocompletion metadata for a connection
+    pub fn set_completion_metadata(&self, connection_id: &str, include_details: bool, tables: Vec<CompletionTable>) {
+        let mut cache = self.completion_metadata.lock().unwrap_or_else(|e| e.into_inner());
+        cache.insert((connection_id.to_string(), include_details), tables);
+    }
+
+    /// Drop both cached autocompletion variants for a connection
+    pub fn clear_completion_metadata(&self, connection_id: &str) {
+        let mut cache = self.completion_metadata.lock().unwrap_or_else(|e| e.into_inner());
+        cache.retain(|(id, _), _| id != connection_id);
+    }
+
+    /// Record activity on a connection (resets its idle timer)
+    pub fn touch_connection(&self, connection_id: &str) {
+        let mut activity = self.last_activity.lock().unwrap_or_else(|e| e.into_inner());
+        activity.insert(connection_id.to_string(), Instant::now());
+    }
+
+    /// Connected pools with no activity for at least `idle_for` and no
+    /// connection currently checked out (so a long-running query isn't cut off).
+    /// Connections pinned by open cursors don't count as checked out.
+    pub fn idle_connection_ids(&self, idle_for: Duration) -> Vec<String> {
+        let activity = self.last_activity.lock().unwrap_or_else(|e| e.into_inner());
+        let cursors = self.open_cursors.lock().unwrap_or_else(|e| e.into_inner());
+        let connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        connections
+            .iter()
+            .filter(|(id, pool)| {
+                let idle = activity.get(*id).map(|t| t.elapsed() >= idle_for).unwrap_or(true);
+                let pinned = cursors.values().filter(|c| &c.connection_id == *id).count();
+                idle && (pool.size() as usize).saturating_sub(pool.num_idle()) == pinned
+            })
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Record connections closed for inactivity
+    pub fn push_idle_disconnected(&self, connection_ids: &[String]) {
+        let mut pending = self.idle_disconnected.lock().unwrap_or_else(|e| e.into_inner());
+        pending.extend(connection_ids.iter().cloned());
+    }
+
+    /// Take (and clear) the connections closed for inactivity since the last call
+    pub fn take_idle_disconnected(&self) -> Vec<String> {
+        let mut pending = self.idle_disconnected.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *pending)
+    }
+
     /// Check if a connection pool exists
     pub fn has_pool(&self, connection_id: &str) -> bool {
         let connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
@@ -130,10 +391,12 @@ impl AppState {
     }
 
     /// Register a running query
-    pub fn register_query(&self, query_id: String, backend_pid: i32) -> Arc<AtomicBool> {
+    pub fn register_query(&self, query_id: String, connection_id: &str, backend_pid: i32) -> Arc<AtomicBool> {
         let cancelled = Arc::new(AtomicBool::new(false));
         let running_query = RunningQuery {
+            connection_id: connection_id.to_string(),
             backend_pid,
+            started_at: Instant::now(),
             cancelled: cancelled.clone(),
         };
         let mut queries = self.running_queries.lock().unwrap_or_else(|e| e.into_inner());
@@ -141,6 +404,13 @@ impl AppState {
         cancelled
     }
 
+    /// Register a running query until the returned registration is dropped. Its
+    /// backend PID is 0 until one is set, so a cancel before then only sets the flag.
+    pub fn track_query(&self, query_id: String, connection_id: &str) -> QueryRegistration<'_> {
+        let cancelled = self.register_query(query_id.clone(), connection_id, 0);
+        QueryRegistration { state: self, query_id, cancelled }
+    }
+
     /// Unregister a running query
     pub fn unregister_query(&self, query_id: &str) {
         let mut queries = self.running_queries.lock().unwrap_or_else(|e| e.into_inner());
@@ -153,6 +423,23 @@ impl AppState {
         queries.get(query_id).map(|q| q.backend_pid)
     }
 
+    /// Registered running queries, optionally only those on one connection, longest-running first
+    pub fn running_queries(&self, connection_id: Option<&str>) -> Vec<RunningQueryInfo> {
+        let queries = self.running_queries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<RunningQueryInfo> = queries
+            .iter()
+            .filter(|(_, q)| connection_id.map_or(true, |id| q.connection_id == id))
+            .map(|(query_id, q)| RunningQueryInfo {
+                query_id: query_id.clone(),
+                connection_id: q.connection_id.clone(),
+                backend_pid: q.backend_pid,
+                elapsed_ms: q.started_at.elapsed().as_millis() as u64,
+            })
+            .collect();
+        list.sort_by_key(|q| std::cmp::Reverse(q.elapsed_ms));
+        list
+    }
+
     /// Get the set of tables denied ANALYZE for a connection+schema
     pub fn get_analyze_denied(&self, connection_id: &str, schema_name: &str) -> HashSet<String> {
         let cache = self.analyze_denied.lock().unwrap_or_else(|e| e.into_inner());
@@ -182,6 +469,73 @@ impl AppState {
         cache.remove(connection_id);
     }
 
+    /// Keep a plan for a query, dropping the oldest beyond `MAX_STORED_PLANS`
+    pub fn push_plan(&self, connection_id: &str, normalized_sql: String, plan: StoredPlan) {
+        let mut history = self.plan_history.lock().unwrap_or_else(|e| e.into_inner());
+        let plans = history.entry((connection_id.to_string(), normalized_sql)).or_default();
+        plans.push_back(plan);
+        while plans.len() > MAX_STORED_PLANS {
+            plans.pop_front();
+        }
+    }
+
+    /// Stored plans for a query, oldest first
+    pub fn get_plans(&self, connection_id: &str, normalized_sql: &str) -> Vec<StoredPlan> {
+        let history = self.plan_history.lock().unwrap_or_else(|e| e.into_inner());
+        history
+            .get(&(connection_id.to_string(), normalized_sql.to_string()))
+            .map(|plans| plans.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Wait for a free connection-attempt slot; hold the permit while connecting
+    pub async fn acquire_connect_permit(&self) -> Option<ConnectPermit<'_>> {
+        let permit = self.connect_semaphore.acquire().await.ok()?;
+        Some(ConnectPermit { permit: Some(permit), owed: &self.connect_permits_owed })
+    }
+
+    /// Resize the connection-attempt limit (minimum 1). Lowering it removes idle
+    /// permits at once; permits in use are retired as their attempts finish.
+    pub fn set_connect_limit(&self, limit: usize) {
+        let limit = limit.max(1);
+        let mut current = self.connect_limit.lock().unwrap_or_else(|e| e.into_inner());
+        let mut owed = self.connect_permits_owed.lock().unwrap_or_else(|e| e.into_inner());
+        if limit > *current {
+            // Cancel retirements still pending before adding new permits.
+            let raise = limit - *current;
+            let cancelled = raise.min(*owed);
+            *owed -= cancelled;
+            self.connect_semaphore.add_permits(raise - cancelled);
+        } else if limit < *current {
+            let cut = *current - limit;
+            *owed += cut - self.connect_semaphore.forget_permits(cut);
+        }
+        *current = limit;
+    }
+
+    /// Store an open cursor (new, or returned after a fetch)
+    pub fn put_cursor(&self, cursor_id: String, cursor: OpenCursor) {
+        let mut cursors = self.open_cursors.lock().unwrap_or_else(|e| e.into_inner());
+        cursors.insert(cursor_id, cursor);
+    }
+
+    /// Take an open cursor out of the map for exclusive use
+    pub fn take_cursor(&self, cursor_id: &str) -> Option<OpenCursor> {
+        let mut cursors = self.open_cursors.lock().unwrap_or_else(|e| e.into_inner());
+        cursors.remove(cursor_id)
+    }
+
+    /// Take all open cursors for a connection (e.g. before closing its pool)
+    pub fn take_cursors_for_connection(&self, connection_id: &str) -> Vec<OpenCursor> {
+        let mut cursors = self.open_cursors.lock().unwrap_or_else(|e| e.into_inner());
+        let ids: Vec<String> = cursors
+            .iter()
+            .filter(|(_, c)| c.connection_id == connection_id)
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.iter().filter_map(|id| cursors.remove(id)).collect()
+    }
+
     /// Mark a query as cancelled
     pub fn mark_query_cancelled(&self, query_id: &str) -> bool {
         let queries = self.running_queries.lock().unwrap_or_else(|e| e.into_inner());
@@ -0,0 +1,84 @@
+//! Fixtures for tests against a live PostgreSQL server. Such tests are marked
+//! `#[ignore = "needs PHAROS_TEST_DATABASE_URL"]`; run them with
+//! `cargo test -- --ignored` once that variable points at a disposable
+//! database whose user may create databases and roles.
+
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use sqlx::Executor;
+
+use crate::state::AppState;
+
+/// Connect options for the test server. Panics without `PHAROS_TEST_DATABASE_URL`,
+/// so a live test that was asked to run can't pass by doing nothing.
+pub fn test_options() -> PgConnectOptions {
+    let url = std::env::var("PHAROS_TEST_DATABASE_URL").expect("PHAROS_TEST_DATABASE_URL must point at a test database");
+    url.parse().expect("PHAROS_TEST_DATABASE_URL is not a valid connection URL")
+}
+
+/// A pool on the test database.
+pub async fn test_pool(max_connections: u32) -> PgPool {
+    connect(test_options(), max_connections).await
+}
+
+/// A fresh `AppState` with `pool` registered as `connection_id`.
+pub fn state_with_pool(connection_id: &str, pool: PgPool) -> AppState {
+    let state = AppState::new(rusqlite::Connection::open_in_memory().unwrap());
+    state.add_pool(connection_id.to_string(), pool, None);
+    state
+}
+
+async fn connect(options: PgConnectOptions, max_connections: u32) -> PgPool {
+    PgPoolOptions::new()
+        .max_connections(max_connections)
+        .connect_with(options)
+        .await
+        .expect("connect to test database")
+}
+
+/// A database created for one test, for changes that would otherwise outlive
+/// it (database-level settings, a second database to copy into). It is
+/// dropped with the value, even when the test panics, closing any connections
+/// still open to it.
+pub struct ScratchDatabase {
+    pub name: String,
+}
+
+impl ScratchDatabase {
+    pub async fn create() -> Self {
+        let name = format!("pharos_test_{}", uuid::Uuid::new_v4().simple());
+        let admin = test_pool(1).await;
+        (&admin)
+            .execute(sqlx::raw_sql(&format!("CREATE DATABASE {}", name)))
+            .await
+            .expect("create scratch database");
+        admin.close().await;
+        ScratchDatabase { name }
+    }
+
+    pub fn options(&self) -> PgConnectOptions {
+        test_options().database(&self.name)
+    }
+
+    pub async fn pool(&self, max_connections: u32) -> PgPool {
+        connect(self.options(), max_connections).await
+    }
+}
+
+impl Drop for ScratchDatabase {
+    fn drop(&mut self) {
+        // Drop can't await and usually runs on the test's own runtime, so the
+        // database is dropped from a thread with a runtime of its own.
+        let sql = format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", self.name);
+        let dropped = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            runtime.block_on(async {
+                let admin = test_pool(1).await;
+                (&admin).execute(sqlx::raw_sql(&sql)).await.map(|_| ())
+            })
+        })
+        .join();
+        if !matches!(dropped, Ok(Ok(()))) && !std::thread::panicking() {
+            panic!("failed to drop scratch database {}", self.name);
+        }
+    }
+}